@@ -0,0 +1,24 @@
+DO $$ BEGIN
+    CREATE TYPE DOCUMENT_KIND AS ENUM (
+        'passport',
+        'migration_card',
+        'visa'
+    );
+EXCEPTION WHEN duplicate_object THEN NULL; END $$;
+
+CREATE TABLE documents (
+    user_id     BIGINT        NOT NULL,
+    slot_start  TIMESTAMPTZ   NOT NULL,
+    kind        DOCUMENT_KIND NOT NULL,
+    file_name   VARCHAR       NOT NULL,
+    mime_type   VARCHAR(64)   NOT NULL,
+    size        BIGINT        NOT NULL,
+    uploaded_at TIMESTAMPTZ   NOT NULL,
+
+    PRIMARY KEY (user_id, slot_start, kind),
+
+    CONSTRAINT fk_user
+        FOREIGN KEY (user_id)
+        REFERENCES  users (id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS notifications;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS visa_expiry,
+    DROP COLUMN IF EXISTS registration_expiry;
@@ -0,0 +1,16 @@
+ALTER TABLE users
+    ADD COLUMN visa_expiry         DATE NULL,
+    ADD COLUMN registration_expiry DATE NULL;
+
+CREATE TABLE notifications (
+    user_id BIGINT      NOT NULL,
+    key     VARCHAR     NOT NULL,
+    sent_at TIMESTAMPTZ NOT NULL,
+
+    PRIMARY KEY (user_id, key),
+
+    CONSTRAINT fk_user
+        FOREIGN KEY (user_id)
+        REFERENCES  users (id)
+        ON DELETE CASCADE
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS deadline_overrides;
+DROP TYPE IF EXISTS override_status;
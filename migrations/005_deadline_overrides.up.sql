@@ -0,0 +1,24 @@
+CREATE TYPE OVERRIDE_STATUS AS ENUM (
+    'pending',
+    'approved',
+    'rejected',
+    'used'
+);
+
+CREATE TABLE deadline_overrides (
+    id           BIGSERIAL       PRIMARY KEY,
+    user_id      BIGINT          NOT NULL,
+    service      SERVICE         NOT NULL,
+    status       OVERRIDE_STATUS NOT NULL,
+    requested_at TIMESTAMPTZ     NOT NULL,
+    resolved_by  BIGINT          NULL,
+    resolved_at  TIMESTAMPTZ     NULL,
+    used_at      TIMESTAMPTZ     NULL,
+
+    CONSTRAINT fk_user
+        FOREIGN KEY (user_id)
+        REFERENCES  users (id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX deadline_overrides_user_service_idx ON deadline_overrides (user_id, service);
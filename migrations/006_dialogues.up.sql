@@ -0,0 +1,8 @@
+CREATE TABLE dialogues (
+    chat_id    BIGINT      NOT NULL,
+    name       VARCHAR(32) NOT NULL,
+    state      BYTEA       NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL,
+
+    PRIMARY KEY (chat_id, name)
+);
@@ -0,0 +1,12 @@
+CREATE TYPE STAFF_ROLE AS ENUM (
+    'viewer',
+    'operator',
+    'owner'
+);
+
+CREATE TABLE staff (
+    user_id    BIGINT      PRIMARY KEY,
+    role       STAFF_ROLE  NOT NULL,
+    granted_by BIGINT      NULL,
+    granted_at TIMESTAMPTZ NOT NULL
+);
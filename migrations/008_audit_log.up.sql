@@ -0,0 +1,23 @@
+CREATE TABLE audit_log (
+    id      BIGSERIAL   PRIMARY KEY,
+    at      TIMESTAMPTZ NOT NULL,
+    actor   BIGINT      NOT NULL,
+    action  VARCHAR(64) NOT NULL,
+    target  BIGINT      NULL,
+    changes JSONB       NOT NULL
+);
+
+CREATE INDEX audit_log_actor_idx ON audit_log (actor, at);
+CREATE INDEX audit_log_target_idx ON audit_log (target, at);
+CREATE INDEX audit_log_at_idx ON audit_log (at);
+
+-- Журнал аудита только дополняется: изменить или удалить запись нельзя
+CREATE FUNCTION audit_log_append_only() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'audit_log is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_log_append_only
+    BEFORE UPDATE OR DELETE ON audit_log
+    FOR EACH ROW EXECUTE FUNCTION audit_log_append_only();
@@ -0,0 +1,4 @@
+ALTER TABLE users
+    DROP COLUMN IF EXISTS anonymised_at;
+
+DROP TABLE IF EXISTS consents;
@@ -0,0 +1,12 @@
+-- Согласие хранится и после удаления данных пользователя, поэтому без внешнего ключа.
+CREATE TABLE consents (
+    user_id    BIGINT      NOT NULL,
+    version    INTEGER     NOT NULL,
+    given_at   TIMESTAMPTZ NOT NULL,
+    revoked_at TIMESTAMPTZ NULL,
+
+    PRIMARY KEY (user_id, version)
+);
+
+ALTER TABLE users
+    ADD COLUMN anonymised_at TIMESTAMPTZ NULL;
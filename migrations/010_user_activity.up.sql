@@ -0,0 +1,3 @@
+-- Время последнего изменения анкеты. По нему и по записям определяются неактивные пользователи.
+ALTER TABLE users
+    ADD COLUMN updated_at TIMESTAMPTZ NOT NULL DEFAULT now();
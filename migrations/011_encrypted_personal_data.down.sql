@@ -0,0 +1,11 @@
+-- Откат возможен только для незашифрованных данных: шифротекст не приводится к DATE.
+DROP INDEX IF EXISTS users_full_name_cyr_idx;
+DROP INDEX IF EXISTS users_full_name_lat_idx;
+
+ALTER TABLE users
+    DROP COLUMN IF EXISTS full_name_cyr_idx,
+    DROP COLUMN IF EXISTS full_name_lat_idx,
+    ALTER COLUMN arrival_date  TYPE DATE USING arrival_date::DATE,
+    ALTER COLUMN citizenship   TYPE VARCHAR(32),
+    ALTER COLUMN full_name_cyr TYPE VARCHAR,
+    ALTER COLUMN full_name_lat TYPE VARCHAR;
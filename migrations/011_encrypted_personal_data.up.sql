@@ -0,0 +1,12 @@
+-- Персональные данные шифруются в приложении, поэтому в столбцах хранится
+-- текст произвольной длины, а для поиска по ФИО используется blind index.
+ALTER TABLE users
+    ALTER COLUMN full_name_lat TYPE TEXT,
+    ALTER COLUMN full_name_cyr TYPE TEXT,
+    ALTER COLUMN citizenship   TYPE TEXT,
+    ALTER COLUMN arrival_date  TYPE TEXT USING to_char(arrival_date, 'YYYY-MM-DD'),
+    ADD COLUMN full_name_lat_idx TEXT NULL,
+    ADD COLUMN full_name_cyr_idx TEXT NULL;
+
+CREATE INDEX users_full_name_lat_idx ON users (full_name_lat_idx);
+CREATE INDEX users_full_name_cyr_idx ON users (full_name_cyr_idx);
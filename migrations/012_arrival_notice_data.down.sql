@@ -0,0 +1,5 @@
+ALTER TABLE users
+    DROP COLUMN IF EXISTS place_of_stay,
+    DROP COLUMN IF EXISTS passport,
+    DROP COLUMN IF EXISTS sex,
+    DROP COLUMN IF EXISTS birth_date;
@@ -0,0 +1,6 @@
+-- Данные для уведомления о прибытии. Хранятся зашифрованными, как и остальные персональные данные.
+ALTER TABLE users
+    ADD COLUMN birth_date    TEXT NULL,
+    ADD COLUMN sex           TEXT NULL,
+    ADD COLUMN passport      TEXT NULL,
+    ADD COLUMN place_of_stay TEXT NULL;
@@ -0,0 +1,6 @@
+-- Слоты, отражённые в календаре сотрудников, и записи, с которыми событие было отправлено.
+CREATE TABLE calendar_events (
+    slot_start      TIMESTAMPTZ PRIMARY KEY,
+    reservation_ids TEXT[]      NOT NULL,
+    synced_at       TIMESTAMPTZ NOT NULL
+);
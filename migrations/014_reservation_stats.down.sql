@@ -0,0 +1,4 @@
+DROP TABLE IF EXISTS cancelled_reservations;
+ALTER TABLE reservations
+    DROP COLUMN IF EXISTS attended_at,
+    DROP COLUMN IF EXISTS reserved_at;
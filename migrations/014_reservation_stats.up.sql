@@ -0,0 +1,22 @@
+-- Время записи и отметка о приходе студента. Для старых записей время записи неизвестно.
+ALTER TABLE reservations
+    ADD COLUMN reserved_at TIMESTAMPTZ NULL,
+    ADD COLUMN attended_at TIMESTAMPTZ NULL;
+ALTER TABLE reservations
+    ALTER COLUMN reserved_at SET DEFAULT now();
+
+-- Отменённые записи хранятся для статистики.
+CREATE TABLE cancelled_reservations (
+    slot_start   TIMESTAMPTZ NOT NULL,
+    service      SERVICE     NOT NULL,
+    user_id      BIGINT      NOT NULL,
+    reserved_at  TIMESTAMPTZ NULL,
+    cancelled_at TIMESTAMPTZ NOT NULL,
+
+    CONSTRAINT fk_user
+        FOREIGN KEY (user_id)
+        REFERENCES  users (id)
+        ON DELETE CASCADE
+);
+
+CREATE INDEX cancelled_reservations_slot_start_idx ON cancelled_reservations (slot_start);
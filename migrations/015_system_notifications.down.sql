@@ -0,0 +1,4 @@
+DELETE FROM notifications WHERE user_id IS NULL;
+ALTER TABLE notifications DROP CONSTRAINT notifications_user_id_key_key;
+ALTER TABLE notifications ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE notifications ADD PRIMARY KEY (user_id, key);
@@ -0,0 +1,6 @@
+-- Рассылки, не относящиеся к одному пользователю (например, сводки для сотрудников),
+-- хранятся с пустым user_id.
+ALTER TABLE notifications DROP CONSTRAINT notifications_pkey;
+ALTER TABLE notifications ALTER COLUMN user_id DROP NOT NULL;
+ALTER TABLE notifications
+    ADD CONSTRAINT notifications_user_id_key_key UNIQUE NULLS NOT DISTINCT (user_id, key);
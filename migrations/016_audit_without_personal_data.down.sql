@@ -0,0 +1,2 @@
+-- Стёртые значения восстановить нельзя.
+SELECT 1;
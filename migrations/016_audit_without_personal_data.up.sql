@@ -0,0 +1,23 @@
+-- Журнал аудита хранится вечно, поэтому в нём остаются только названия изменённых полей
+-- пользователя, а не их значения. Имя загруженного файла тоже стирается: его задаёт студент.
+ALTER TABLE audit_log DISABLE TRIGGER audit_log_append_only;
+
+UPDATE audit_log
+SET changes = (
+    SELECT coalesce(
+        jsonb_agg(jsonb_build_object('field', c->>'field', 'before', NULL, 'after', NULL)),
+        '[]'::jsonb
+    )
+    FROM jsonb_array_elements(changes) AS c
+)
+WHERE action IN ('user_registered', 'user_updated', 'user_anonymised');
+
+UPDATE audit_log
+SET changes = (
+    SELECT coalesce(jsonb_agg(c), '[]'::jsonb)
+    FROM jsonb_array_elements(changes) AS c
+    WHERE c->>'field' <> 'file_name'
+)
+WHERE action = 'document_uploaded';
+
+ALTER TABLE audit_log ENABLE TRIGGER audit_log_append_only;
@@ -0,0 +1,7 @@
+DELETE FROM cancelled_reservations WHERE user_id IS NULL;
+ALTER TABLE cancelled_reservations ALTER COLUMN user_id SET NOT NULL;
+
+DELETE FROM reservations WHERE user_id IS NULL;
+ALTER TABLE reservations DROP CONSTRAINT reservations_slot_start_user_id_key;
+ALTER TABLE reservations ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE reservations ADD PRIMARY KEY (slot_start, user_id);
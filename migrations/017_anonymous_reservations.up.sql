@@ -0,0 +1,8 @@
+-- Записи старше срока хранения отвязываются от пользователей: они остаются только для
+-- статистики с пустым user_id.
+ALTER TABLE reservations DROP CONSTRAINT reservations_pkey;
+ALTER TABLE reservations ALTER COLUMN user_id DROP NOT NULL;
+ALTER TABLE reservations
+    ADD CONSTRAINT reservations_slot_start_user_id_key UNIQUE (slot_start, user_id);
+
+ALTER TABLE cancelled_reservations ALTER COLUMN user_id DROP NOT NULL;
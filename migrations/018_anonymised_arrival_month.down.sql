@@ -0,0 +1,2 @@
+-- Стёртые дни восстановить нельзя.
+SELECT 1;
@@ -0,0 +1,5 @@
+-- У обезличенных пользователей от даты въезда остаётся только месяц. Зашифрованные даты
+-- в SQL не прочитать, поэтому здесь огрубляются только незашифрованные.
+UPDATE users
+SET arrival_date = to_char(date_trunc('month', arrival_date::date), 'YYYY-MM-DD')
+WHERE anonymised_at IS NOT NULL AND arrival_date ~ '^\d{4}-\d{2}-\d{2}$';
@@ -0,0 +1,3 @@
+ALTER TABLE calendar_events DROP COLUMN event_hash;
+ALTER TABLE calendar_events ADD COLUMN reservation_ids TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE calendar_events ALTER COLUMN reservation_ids DROP DEFAULT;
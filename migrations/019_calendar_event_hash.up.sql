@@ -0,0 +1,5 @@
+-- Событие слота сравнивается по отпечатку содержимого, а не по набору записей, чтобы
+-- в календарь попадали и изменения имён и услуг. Уже отражённые слоты отправятся заново.
+ALTER TABLE calendar_events DROP COLUMN reservation_ids;
+ALTER TABLE calendar_events ADD COLUMN event_hash TEXT NOT NULL DEFAULT '';
+ALTER TABLE calendar_events ALTER COLUMN event_hash DROP DEFAULT;
@@ -0,0 +1,11 @@
+-- Время последнего обновления от чата. Отметки удаляются, когда брошенные диалоги чата
+-- сбрасываются, поэтому таблица не растёт вместе с числом пользователей.
+CREATE TABLE chat_activity (
+    chat_id   BIGINT      PRIMARY KEY,
+    active_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX chat_activity_active_at_idx ON chat_activity (active_at);
+
+-- Раньше активность хранилась как диалог "activity" и не удалялась.
+DELETE FROM dialogues WHERE name = 'activity';
@@ -0,0 +1,3 @@
+-- Ключ файла в хранилище. При переносе записи документы переходят к новой записи, а файлы
+-- остаются под прежними ключами. NULL - документ загружен раньше и ключ выводится из записи.
+ALTER TABLE documents ADD COLUMN storage_key VARCHAR NULL;
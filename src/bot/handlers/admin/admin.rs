@@ -1,33 +1,92 @@
-use chrono::NaiveDate;
-use csv::Writer;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use teloxide::dispatching::dialogue::InMemStorage;
+use teloxide::dispatching::dialogue::ErasedStorage;
 use teloxide::dispatching::{dialogue, UpdateHandler};
 use teloxide::macros::BotCommands;
 use teloxide::prelude::*;
 use teloxide::types::{InputFile, ParseMode};
+use teloxide::utils::html;
 
+use super::export::{ExportFormat, arrival_notices_zip, schedule_pdf};
 use crate::bot::handlers::fsm::HandlerResult;
-use crate::bot::handlers::keyboards::service_to_str;
+use crate::bot::handlers::keyboards::{
+    TABLE_CITIZENSHIP_BTN, TABLE_EXPORT_BTN, TABLE_FORMAT_BTN, TABLE_SERVICE_BTN, TABLE_STATUS_BTN,
+    document_kind_to_str, make_table_filters_keyboard,
+};
 use crate::domain::Error;
-use crate::domain::models::UserID;
-use crate::usecases::{CheckAdminUseCase, ReservationDTO, ReservationsUseCase};
+use crate::domain::models::{
+    Citizenship, ReservationStatus, ReservationsFilter, Service, StaffRole, UserID,
+};
+use crate::usecases::{
+    CheckAdminUseCase, DocumentFileDTO, DocumentsUseCase, MAX_EXPORT_DAYS, ReservationsUseCase,
+};
 
 #[derive(BotCommands, Clone)]
 #[command(description = "Команды записи")]
 enum AdminCommand {
-    #[command(rename = "table", description = "получить таблицу с записями на день")]
+    #[command(rename = "table", description = "получить таблицу с записями на день или за период")]
     Table,
+
+    #[command(rename = "docs", description = "получить документы записавшихся на день")]
+    Documents,
+
+    #[command(
+        rename = "schedule",
+        description = "расписание на день для печати: /schedule [ДД.ММ.ГГГГ]"
+    )]
+    Schedule(String),
+
+    #[command(
+        rename = "notices",
+        description = "уведомления о прибытии записавшихся на регистрацию: /notices [ДД.ММ.ГГГГ]"
+    )]
+    Notices(String),
+
+    #[command(
+        rename = "checkin",
+        description = "отметить приход студента по коду записи: /checkin <код>"
+    )]
+    Checkin(String),
 }
 
+/// Префикс параметра ссылки t.me/<bot>?start=docs_<id>_<timestamp> на документы записи.
+pub(super) const DOCUMENTS_LINK_PREFIX: &str = "docs_";
+
 #[derive(Default, Clone, Serialize, Deserialize)]
 pub enum AdminState {
     #[default]
     Start,
     AwaitingDate,
+    AwaitingFilters {
+        from: NaiveDate,
+        to: NaiveDate,
+        filter: ReservationsFilter,
+        format: ExportFormat,
+    },
+    AwaitingDocumentsDate,
 }
 
-pub type AdminDialogue = Dialogue<AdminState, InMemStorage<AdminState>>;
+pub type AdminDialogue = Dialogue<AdminState, ErasedStorage<AdminState>>;
+
+/// Проверяет, что у отправителя есть роль не ниже `required`, и иначе сообщает об отказе.
+pub(super) async fn check_role(
+    bot: &Bot,
+    msg: &Message,
+    use_case: &CheckAdminUseCase,
+    required: StaffRole,
+) -> Result<bool, Error> {
+    let user_id = UserID::new(msg.chat.id.0);
+    if !use_case.has_role(user_id, required).await? {
+        bot.send_message(
+            msg.chat.id,
+            "⛔ <b>Доступ запрещен</b>"
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(false);
+    }
+    Ok(true)
+}
 
 async fn handle_table_command(
     bot: Bot,
@@ -35,15 +94,317 @@ async fn handle_table_command(
     dialogue: AdminDialogue,
     use_case: CheckAdminUseCase,
 ) -> HandlerResult {
-    let user_id = UserID::new(msg.chat.id.0);
-    if !use_case.is_admin(user_id).await? {
+    if !check_role(&bot, &msg, &use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    bot.send_message(
+        msg.chat.id,
+        "📅 <b>Введите дату или период</b>\n\
+        В формате ДД.ММ.ГГГГ или ДД.ММ.ГГГГ-ДД.ММ.ГГГГ",
+    )
+        .parse_mode(ParseMode::Html)
+        .await?;
+    dialogue.update(AdminState::AwaitingDate).await?;
+    Ok(())
+}
+
+/// Разбирает дату ДД.ММ.ГГГГ или период ДД.ММ.ГГГГ-ДД.ММ.ГГГГ.
+pub(super) fn parse_period(s: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let parse = |s: &str| NaiveDate::parse_from_str(s.trim(), "%d.%m.%Y").ok();
+    match s.split_once('-') {
+        Some((from, to)) => Some((parse(from)?, parse(to)?)),
+        None => parse(s).map(|date| (date, date)),
+    }
+}
+
+pub(super) fn format_period(from: NaiveDate, to: NaiveDate) -> String {
+    if from == to {
+        from.format("%d.%m.%Y").to_string()
+    } else {
+        format!("{} – {}", from.format("%d.%m.%Y"), to.format("%d.%m.%Y"))
+    }
+}
+
+fn filters_text(from: NaiveDate, to: NaiveDate) -> String {
+    format!(
+        "📋 <b>Записи за {}</b>\n\
+        Нажимайте на фильтры, чтобы изменить их, затем «Выгрузить»",
+        format_period(from, to),
+    )
+}
+
+async fn receive_date(
+    bot: Bot,
+    msg: Message,
+    dialogue: AdminDialogue,
+) -> HandlerResult {
+    let Some(text) = msg.text() else {
+        bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+            .await?;
+        return Ok(());
+    };
+    let Some((from, to)) = parse_period(text) else {
         bot.send_message(
             msg.chat.id,
-            "⛔ <b>Доступ запрещен</b>"
+            "❌ <b>Неверный формат</b>\n\
+            Введите дату в формате ДД.ММ.ГГГГ или период в формате ДД.ММ.ГГГГ-ДД.ММ.ГГГГ.",
         )
             .parse_mode(ParseMode::Html)
             .await?;
         return Ok(());
+    };
+    if from > to || (to - from).num_days() >= MAX_EXPORT_DAYS {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ <b>Неверный период</b>\n\
+                Начало должно быть не позже конца, а период - не длиннее {} дней.",
+                MAX_EXPORT_DAYS,
+            ),
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    }
+
+    let filter = ReservationsFilter::default();
+    let format = ExportFormat::default();
+    bot.send_message(msg.chat.id, filters_text(from, to))
+        .parse_mode(ParseMode::Html)
+        .reply_markup(make_table_filters_keyboard(&filter, format))
+        .await?;
+    dialogue.update(AdminState::AwaitingFilters { from, to, filter, format }).await?;
+    Ok(())
+}
+
+/// Следующее значение фильтра: «все», затем каждое значение по очереди и снова «все».
+fn next_value<T: Clone + PartialEq>(current: &Option<T>, values: &[T]) -> Option<T> {
+    match current {
+        None => values.first().cloned(),
+        Some(value) => values
+            .iter()
+            .position(|v| v == value)
+            .and_then(|i| values.get(i + 1))
+            .cloned(),
+    }
+}
+
+fn next_citizenship(current: &Option<Citizenship>) -> Option<Citizenship> {
+    let mut values = Citizenship::known().to_vec();
+    values.push(Citizenship::Other(String::new()));
+    let current = match current {
+        Some(Citizenship::Other(_)) => Some(Citizenship::Other(String::new())),
+        other => other.clone(),
+    };
+    next_value(&current, &values)
+}
+
+async fn receive_filter(
+    bot: Bot,
+    q: CallbackQuery,
+    dialogue: AdminDialogue,
+    (from, to, mut filter, mut format): (NaiveDate, NaiveDate, ReservationsFilter, ExportFormat),
+    use_case: ReservationsUseCase,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id.clone()).await?;
+    let Some(msg) = q.message.as_ref() else {
+        return Ok(());
+    };
+    match q.data.as_deref() {
+        Some(TABLE_SERVICE_BTN) => filter.service = next_value(&filter.service, Service::all()),
+        Some(TABLE_CITIZENSHIP_BTN) => filter.citizenship = next_citizenship(&filter.citizenship),
+        Some(TABLE_STATUS_BTN) => {
+            filter.status = next_value(
+                &filter.status,
+                &[ReservationStatus::Upcoming, ReservationStatus::Past],
+            )
+        }
+        Some(TABLE_FORMAT_BTN) => {
+            format = next_value(&Some(format), ExportFormat::all()).unwrap_or_default()
+        }
+        Some(TABLE_EXPORT_BTN) => {
+            bot.edit_message_reply_markup(msg.chat().id, msg.id())
+                .await?;
+            let actor = UserID::new(q.from.id.0 as i64);
+            let days = use_case.reservations_between(actor, from, to, &filter).await?;
+            if days.is_empty() {
+                bot.send_message(msg.chat().id, "📭 Нет записей, подходящих под фильтры")
+                    .await?;
+            } else {
+                let me = bot.get_me().await?;
+                let exporter = format.exporter();
+                let data = exporter.export(&days, me.username())?;
+                let period = if from == to {
+                    from.format("%Y-%m-%d").to_string()
+                } else {
+                    format!("{}_{}", from.format("%Y-%m-%d"), to.format("%Y-%m-%d"))
+                };
+                let file_name = format!("slots_{}.{}", period, exporter.extension());
+                let input_file = InputFile::memory(data).file_name(file_name);
+                bot.send_document(msg.chat().id, input_file).await?;
+            }
+            dialogue.exit().await?;
+            return Ok(());
+        }
+        _ => return Ok(()),
+    }
+
+    bot.edit_message_reply_markup(msg.chat().id, msg.id())
+        .reply_markup(make_table_filters_keyboard(&filter, format))
+        .await?;
+    dialogue.update(AdminState::AwaitingFilters { from, to, filter, format }).await?;
+    Ok(())
+}
+
+/// Разбирает необязательную дату ДД.ММ.ГГГГ из аргумента команды, по умолчанию - сегодня.
+fn parse_day(args: &str) -> Option<NaiveDate> {
+    let args = args.trim();
+    if args.is_empty() {
+        return Some(Utc::now().date_naive());
+    }
+    NaiveDate::parse_from_str(args, "%d.%m.%Y").ok()
+}
+
+async fn handle_schedule_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    r_use_case: ReservationsUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let Some(date) = parse_day(&args) else {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Неверный формат</b>\n\
+            Используйте /schedule или /schedule &lt;ДД.ММ.ГГГГ&gt;",
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    };
+
+    let actor = UserID::new(msg.chat.id.0);
+    let slots = r_use_case.schedule(actor, date).await?;
+    if slots.is_empty() {
+        bot.send_message(msg.chat.id, "📭 В этот день нет приёма")
+            .await?;
+        return Ok(());
+    }
+    let file_name = format!("schedule_{}.pdf", date.format("%Y-%m-%d"));
+    let input_file = InputFile::memory(schedule_pdf(date, &slots)?).file_name(file_name);
+    bot.send_document(msg.chat.id, input_file)
+        .caption(format!("🖨 Расписание на {}", date.format("%d.%m.%Y")))
+        .await?;
+    Ok(())
+}
+
+async fn handle_notices_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    r_use_case: ReservationsUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let Some(date) = parse_day(&args) else {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Неверный формат</b>\n\
+            Используйте /notices или /notices &lt;ДД.ММ.ГГГГ&gt;",
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    };
+
+    let actor = UserID::new(msg.chat.id.0);
+    let notices = r_use_case.arrival_notices(actor, date).await?;
+    if notices.is_empty() {
+        bot.send_message(msg.chat.id, "📭 В этот день никто не записан на регистрацию")
+            .await?;
+        return Ok(());
+    }
+    let incomplete = notices.iter().filter(|n| !n.missing_fields.is_empty()).count();
+    let mut caption = format!(
+        "📄 Уведомления о прибытии на {}: {}",
+        date.format("%d.%m.%Y"),
+        notices.len(),
+    );
+    if incomplete > 0 {
+        caption.push_str(&format!(
+            "\n⚠️ Не все данные указаны у {}, пустые поля нужно заполнить от руки",
+            incomplete,
+        ));
+    }
+    let zip = arrival_notices_zip(&notices)?;
+    if zip.overflowing > 0 {
+        caption.push_str(&format!(
+            "\n⚠️ У {} данные не поместились в клетки бланка, такие поля подписаны «от руки»",
+            zip.overflowing,
+        ));
+    }
+    let file_name = format!("notices_{}.zip", date.format("%Y-%m-%d"));
+    let input_file = InputFile::memory(zip.data).file_name(file_name);
+    bot.send_document(msg.chat.id, input_file)
+        .caption(caption)
+        .await?;
+    Ok(())
+}
+
+async fn handle_checkin_command(
+    bot: Bot,
+    msg: Message,
+    code: String,
+    ca_use_case: CheckAdminUseCase,
+    r_use_case: ReservationsUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Operator).await? {
+        return Ok(());
+    }
+    if code.trim().is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Не указан код записи</b>\n\
+            Используйте /checkin &lt;код&gt;",
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    }
+
+    let actor = UserID::new(msg.chat.id.0);
+    let Some(r) = r_use_case.check_in(actor, &code).await? else {
+        bot.send_message(msg.chat.id, "🔍 Сегодня нет записи с таким кодом")
+            .await?;
+        return Ok(());
+    };
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "✅ Приход отмечен: {} ({}), {}",
+            html::escape(&r.user_name_cyr),
+            html::escape(&r.user_name_lat),
+            r.slot_start.format("%H:%M"),
+        ),
+    )
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+async fn handle_documents_command(
+    bot: Bot,
+    msg: Message,
+    dialogue: AdminDialogue,
+    use_case: CheckAdminUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &use_case, StaffRole::Viewer).await? {
+        return Ok(());
     }
     bot.send_message(
         msg.chat.id,
@@ -52,99 +413,128 @@ async fn handle_table_command(
     )
         .parse_mode(ParseMode::Html)
         .await?;
-    dialogue.update(AdminState::AwaitingDate).await?;
+    dialogue.update(AdminState::AwaitingDocumentsDate).await?;
     Ok(())
 }
 
-async fn receive_date(
+async fn receive_documents_date(
     bot: Bot,
     msg: Message,
     dialogue: AdminDialogue,
-    use_case: ReservationsUseCase,
+    r_use_case: ReservationsUseCase,
+    d_use_case: DocumentsUseCase,
 ) -> HandlerResult {
-    match msg.text() {
-        Some(text) => {
-            match NaiveDate::parse_from_str(text, "%d.%m.%Y") {
-                Ok(date) => {
-                    let reservations = use_case.reservations(date).await?;
-                    let csv_data = generate_csv(&reservations)?;
-                    let file_name = format!("slots_{}.csv", date.format("%Y-%m-%d"));
-                    let input_file = InputFile::memory(csv_data).file_name(file_name);
-                    bot.send_document(msg.chat.id, input_file).await?;
-                    dialogue.exit().await?;
-                }
-                Err(_) => {
-                    bot.send_message(
-                        msg.chat.id,
-                        "❌ <b>Неверный формат</b>\n\
-                        Введите дату в формате ДД.ММ.ГГГГ.",
-                    )
-                        .parse_mode(ParseMode::Html)
-                        .await?;
-                }
-            }
-        }
+    let text = match msg.text() {
+        Some(text) => text,
         None => {
             bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
                 .await?;
+            return Ok(());
         }
+    };
+    let date = match NaiveDate::parse_from_str(text, "%d.%m.%Y") {
+        Ok(date) => date,
+        Err(_) => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Введите дату в формате ДД.ММ.ГГГГ.",
+            )
+                .parse_mode(ParseMode::Html)
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let actor = UserID::new(msg.chat.id.0);
+    let mut reservations = r_use_case.reservations(actor, date).await?;
+    reservations.sort_by_key(|r| r.slot_start);
+    let mut sent = 0;
+    for r in reservations.iter() {
+        let documents = d_use_case.documents(actor, r.user_id, r.slot_start).await?;
+        let caption = format!(
+            "{} {} ({})",
+            r.slot_start.format("%H:%M"),
+            r.user_name_cyr,
+            r.user_name_lat,
+        );
+        sent += documents.len();
+        send_documents(&bot, msg.chat.id, &caption, documents).await?;
     }
+    if sent == 0 {
+        bot.send_message(msg.chat.id, "📭 На этот день документы не загружены")
+            .await?;
+    }
+    dialogue.exit().await?;
     Ok(())
 }
 
-fn generate_csv(rs: &[ReservationDTO]) -> Result<Vec<u8>, Error> {
-    let mut buffer = Vec::new();
-    // UTF-8 BOM
-    buffer.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
-
-    let mut writer = Writer::from_writer(buffer);
-
-    writer.write_record(&[
-        "#",
-        "Начало",
-        "Конец",
-        "Услуга",
-        "Telegram",
-        "ФИО (лат)",
-        "ФИО (кир)",
-        "Гражданство",
-        "Дата прибытия",
-    ])
-        .map_err(|err| Error::Other(err.into()))?;
-
-    for (i, r) in rs.into_iter().enumerate() {
-        writer.write_record(&[
-            format!("{}", i + 1),
-            r.slot_start.format("%H:%M").to_string(),
-            r.slot_end.format("%H:%M").to_string(),
-            service_to_str(&r.service).to_string(),
-            format!("t.me/{}/", r.username),
-            r.user_name_lat.clone(),
-            r.user_name_cyr.clone(),
-            r.citizenship.clone().into(),
-            r.arrival_date.format("%d.%m.%Y").to_string(),
-        ])
-            .map_err(|err| Error::Other(err.into()))?;
-    }
-
-    writer.flush()
-        .map_err(|err| Error::Other(err.into()))?;
-    let res = writer
-        .into_inner()
-        .map_err(|err| Error::Other(err.into()))?;
-    Ok(res)
+fn parse_documents_link(msg: Message) -> Option<(UserID, DateTime<Utc>)> {
+    let payload = msg.text()?.strip_prefix("/start ")?;
+    let (id, timestamp) = payload
+        .strip_prefix(DOCUMENTS_LINK_PREFIX)?
+        .split_once('_')?;
+    let id = UserID::new(id.parse::<i64>().ok()?);
+    let slot_start = DateTime::from_timestamp(timestamp.parse::<i64>().ok()?, 0)?;
+    Some((id, slot_start))
+}
+
+async fn handle_documents_link(
+    bot: Bot,
+    msg: Message,
+    (user_id, slot_start): (UserID, DateTime<Utc>),
+    ca_use_case: CheckAdminUseCase,
+    d_use_case: DocumentsUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let actor = UserID::new(msg.chat.id.0);
+    let documents = d_use_case.documents(actor, user_id, slot_start).await?;
+    if documents.is_empty() {
+        bot.send_message(msg.chat.id, "📭 К этой записи документы не загружены")
+            .await?;
+        return Ok(());
+    }
+    let caption = format!("{} #{}", slot_start.format("%d.%m.%Y %H:%M"), user_id);
+    send_documents(&bot, msg.chat.id, &caption, documents).await
+}
+
+async fn send_documents(
+    bot: &Bot,
+    chat_id: ChatId,
+    caption: &str,
+    documents: Vec<DocumentFileDTO>,
+) -> HandlerResult {
+    for d in documents {
+        let input_file = InputFile::memory(d.data).file_name(d.file_name);
+        bot.send_document(chat_id, input_file)
+            .caption(format!("{}: {}", caption, document_kind_to_str(&d.kind)))
+            .await?;
+    }
+    Ok(())
 }
 
 pub fn admin_schema() -> UpdateHandler<Error> {
     use dptree::case;
 
     let command_handler = teloxide::filter_command::<AdminCommand, _>()
-        .branch(case![AdminCommand::Table].endpoint(handle_table_command));
+        .branch(case![AdminCommand::Table].endpoint(handle_table_command))
+        .branch(case![AdminCommand::Documents].endpoint(handle_documents_command))
+        .branch(case![AdminCommand::Schedule(args)].endpoint(handle_schedule_command))
+        .branch(case![AdminCommand::Notices(args)].endpoint(handle_notices_command))
+        .branch(case![AdminCommand::Checkin(code)].endpoint(handle_checkin_command));
 
     let message_handler = Update::filter_message()
+        .branch(dptree::filter_map(parse_documents_link).endpoint(handle_documents_link))
         .branch(command_handler)
-        .branch(case![AdminState::AwaitingDate].endpoint(receive_date));
+        .branch(case![AdminState::AwaitingDate].endpoint(receive_date))
+        .branch(case![AdminState::AwaitingDocumentsDate].endpoint(receive_documents_date));
+
+    let callback_handler = Update::filter_callback_query()
+        .branch(case![AdminState::AwaitingFilters { from, to, filter, format }].endpoint(receive_filter));
 
-    dialogue::enter::<Update, InMemStorage<AdminState>, AdminState, _>()
+    dialogue::enter::<Update, ErasedStorage<AdminState>, AdminState, _>()
         .branch(message_handler)
+        .branch(callback_handler)
 }
@@ -0,0 +1,125 @@
+use chrono::NaiveDate;
+use csv::Writer;
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::{InputFile, ParseMode};
+
+use super::admin::check_role;
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::domain::Error;
+use crate::domain::models::{StaffRole, UserID};
+use crate::usecases::{AuditEntryDTO, AuditUseCase, CheckAdminUseCase};
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды журнала аудита")]
+enum AuditCommand {
+    #[command(rename = "audit", description = "журнал аудита: /audit <id> или /audit <ДД.ММ.ГГГГ>")]
+    Audit(String),
+}
+
+/// Запрос к журналу: по пользователю или за день.
+enum AuditQuery {
+    User(UserID),
+    Date(NaiveDate),
+}
+
+fn parse_query(s: &str) -> Option<AuditQuery> {
+    let s = s.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(s, "%d.%m.%Y") {
+        return Some(AuditQuery::Date(date));
+    }
+    s.parse::<i64>().ok().map(|id| AuditQuery::User(UserID::new(id)))
+}
+
+async fn handle_audit_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    a_use_case: AuditUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Owner).await? {
+        return Ok(());
+    }
+    let (entries, file_name) = match parse_query(&args) {
+        Some(AuditQuery::User(id)) => {
+            (a_use_case.user_entries(id).await?, format!("audit_{}.csv", id))
+        }
+        Some(AuditQuery::Date(date)) => (
+            a_use_case.entries_on(date).await?,
+            format!("audit_{}.csv", date.format("%Y-%m-%d")),
+        ),
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Используйте /audit &lt;id&gt; или /audit &lt;ДД.ММ.ГГГГ&gt;",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+    };
+
+    if entries.is_empty() {
+        bot.send_message(msg.chat.id, "📭 В журнале нет записей")
+            .await?;
+        return Ok(());
+    }
+    let input_file = InputFile::memory(generate_csv(&entries)?).file_name(file_name);
+    bot.send_document(msg.chat.id, input_file)
+        .caption(format!("🗂 Записей: {}", entries.len()))
+        .await?;
+    Ok(())
+}
+
+fn generate_csv(entries: &[AuditEntryDTO]) -> Result<Vec<u8>, Error> {
+    let mut buffer = Vec::new();
+    // UTF-8 BOM
+    buffer.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+
+    let mut writer = Writer::from_writer(buffer);
+
+    writer.write_record(["Время (UTC)", "Кто", "Действие", "Чьи данные", "Поле", "Было", "Стало"])
+        .map_err(|err| Error::Other(err.into()))?;
+
+    for e in entries {
+        let head = [
+            e.at.format("%d.%m.%Y %H:%M:%S").to_string(),
+            e.actor.to_string(),
+            e.action.as_str().to_string(),
+            e.target.map(|id| id.to_string()).unwrap_or_default(),
+        ];
+        if e.changes.is_empty() {
+            writer.write_record(head.iter().chain(["", "", ""].map(String::from).iter()))
+                .map_err(|err| Error::Other(err.into()))?;
+        }
+        // Каждое изменённое поле - отдельная строка, чтобы таблицу было удобно фильтровать.
+        for c in e.changes.iter() {
+            let change = [
+                c.field.clone(),
+                c.before.clone().unwrap_or_default(),
+                c.after.clone().unwrap_or_default(),
+            ];
+            writer.write_record(head.iter().chain(change.iter()))
+                .map_err(|err| Error::Other(err.into()))?;
+        }
+    }
+
+    writer.flush()
+        .map_err(|err| Error::Other(err.into()))?;
+    let res = writer
+        .into_inner()
+        .map_err(|err| Error::Other(err.into()))?;
+    Ok(res)
+}
+
+pub fn audit_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<AuditCommand, _>()
+        .branch(case![AuditCommand::Audit(args)].endpoint(handle_audit_command));
+
+    Update::filter_message().branch(command_handler)
+}
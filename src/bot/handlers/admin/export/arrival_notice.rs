@@ -0,0 +1,305 @@
+use std::io::{Cursor, Write};
+
+use chrono::{Datelike, NaiveDate};
+use printpdf::{Mm, PdfDocument, PdfLayerReference, Rect};
+use zip::ZipWriter;
+use zip::write::SimpleFileOptions;
+
+use super::pdf::{Fonts, MARGIN, PAGE_HEIGHT, PAGE_WIDTH, pdf_error};
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::domain::Error;
+use crate::domain::models::{Sex, reservation_code};
+use crate::usecases::ArrivalNoticeDTO;
+
+/// Размер клетки, в которую вписывается одна буква, как на бланке.
+const CELL_WIDTH: f32 = 5.0;
+const CELL_HEIGHT: f32 = 6.5;
+/// Клеток в строке между полями листа.
+const CELLS_PER_LINE: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / CELL_WIDTH) as usize;
+/// Принимающая сторона одна для всех студентов.
+const HOST_NAME: &str = "МГТУ им. Н.Э. Баумана";
+
+/// Архив уведомлений о прибытии.
+pub struct ArrivalNoticesZip {
+    pub data: Vec<u8>,
+    /// Сколько бланков с полями, в клетки которых не поместились данные студента.
+    pub overflowing: usize,
+}
+
+/// Бланк уведомления и названия полей, в клетки которых не поместились данные.
+struct ArrivalNoticePdf {
+    data: Vec<u8>,
+    overflow: Vec<String>,
+}
+
+/// Формирует бланк уведомления о прибытии иностранного гражданина в место пребывания,
+/// заполненный данными студента. Поля, которые студент не заполнил, остаются пустыми
+/// клетками для заполнения от руки. Значение, которое не помещается в клетки, не обрезается:
+/// поле тоже остаётся пустым, а в подписи к нему просят заполнить его от руки.
+fn arrival_notice_pdf(notice: &ArrivalNoticeDTO) -> Result<ArrivalNoticePdf, Error> {
+    let user = &notice.user;
+    let (doc, page, layer) = PdfDocument::new(
+        format!("Уведомление о прибытии: {}", user.full_name_cyr.as_str()),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Уведомление",
+    );
+    let fonts = Fonts::add(&doc)?;
+    let mut form = Form {
+        layer: doc.get_page(page).get_layer(layer),
+        fonts: &fonts,
+        y: PAGE_HEIGHT - MARGIN,
+        overflow: Vec::new(),
+    };
+
+    form.title("УВЕДОМЛЕНИЕ О ПРИБЫТИИ ИНОСТРАННОГО ГРАЖДАНИНА");
+    form.title("ИЛИ ЛИЦА БЕЗ ГРАЖДАНСТВА В МЕСТО ПРЕБЫВАНИЯ");
+
+    form.section("1. Сведения об иностранном гражданине");
+    let (surname, name, patronymic) = split_full_name(user.full_name_cyr.as_str());
+    form.text_field("Фамилия", surname, 1);
+    form.text_field("Имя", name, 1);
+    form.text_field("Отчество (при наличии)", patronymic, 1);
+    form.text_field("Гражданство", user.citizenship.as_str(), 1);
+    form.birth_date_and_sex(user.birth_date, user.sex);
+    form.passport(user.passport.as_ref().map(|p| (p.series(), p.number())));
+    form.dates(user.arrival_date, user.visa_expiry);
+
+    form.section("2. Место пребывания");
+    form.text_field("Адрес", user.place_of_stay.as_deref().unwrap_or_default(), 3);
+
+    form.section("3. Принимающая сторона");
+    form.text_field("Наименование организации", HOST_NAME, 1);
+
+    form.footer(&format!(
+        "Запись {} · {} · код {}",
+        notice.slot_start.format("%d.%m.%Y %H:%M"),
+        service_to_str(&notice.service),
+        reservation_code(user.id, notice.slot_start),
+    ));
+
+    let overflow = form.overflow;
+    Ok(ArrivalNoticePdf {
+        data: doc.save_to_bytes().map_err(pdf_error)?,
+        overflow,
+    })
+}
+
+/// Упаковывает уведомления в zip-архив, по файлу на запись в порядке записи.
+pub fn arrival_notices_zip(notices: &[ArrivalNoticeDTO]) -> Result<ArrivalNoticesZip, Error> {
+    let zip_error = |err: zip::result::ZipError| Error::Other(err.into());
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let mut overflowing = 0;
+    for (i, notice) in notices.iter().enumerate() {
+        let file_name = format!(
+            "{:02}_{}_{}.pdf",
+            i + 1,
+            notice.slot_start.format("%H%M"),
+            notice.user.full_name_lat.as_str().replace(' ', "_"),
+        );
+        zip.start_file(file_name, SimpleFileOptions::default())
+            .map_err(zip_error)?;
+        let pdf = arrival_notice_pdf(notice)?;
+        if !pdf.overflow.is_empty() {
+            log::warn!(
+                "Arrival notice for {} does not fit fields: {:?}",
+                notice.user.id,
+                pdf.overflow
+            );
+            overflowing += 1;
+        }
+        zip.write_all(&pdf.data)
+            .map_err(|err| Error::Other(err.into()))?;
+    }
+    Ok(ArrivalNoticesZip {
+        data: zip.finish().map_err(zip_error)?.into_inner(),
+        overflowing,
+    })
+}
+
+/// Делит ФИО на фамилию, имя и отчество. Всё после имени считается отчеством.
+fn split_full_name(full_name: &str) -> (&str, &str, &str) {
+    let full_name = full_name.trim();
+    let (surname, rest) = full_name.split_once(' ').unwrap_or((full_name, ""));
+    let rest = rest.trim_start();
+    let (name, patronymic) = rest.split_once(' ').unwrap_or((rest, ""));
+    (surname, name, patronymic.trim_start())
+}
+
+/// Бланк уведомления: поля идут сверху вниз, `y` - верхняя граница следующего поля.
+struct Form<'a> {
+    layer: PdfLayerReference,
+    fonts: &'a Fonts,
+    y: f32,
+    /// Поля, в клетки которых не поместилось значение.
+    overflow: Vec<String>,
+}
+
+impl Form<'_> {
+    fn title(&mut self, text: &str) {
+        self.y -= 6.0;
+        self.layer
+            .use_text(text, 12.0, Mm(MARGIN), Mm(self.y), &self.fonts.bold);
+    }
+
+    fn section(&mut self, text: &str) {
+        self.y -= 10.0;
+        self.layer
+            .use_text(text, 10.0, Mm(MARGIN), Mm(self.y), &self.fonts.bold);
+        self.y -= 1.0;
+    }
+
+    fn label(&self, text: &str, x: f32) {
+        self.layer
+            .use_text(text, 7.0, Mm(x), Mm(self.y - 3.0), &self.fonts.regular);
+    }
+
+    /// Подпись поля. Если значение длиннее `count` клеток, поле запоминается как
+    /// непоместившееся, а значение заменяется пустым, чтобы не вписать его обрезанным.
+    fn checked_label<'v>(&mut self, text: &str, x: f32, count: usize, value: &'v str) -> &'v str {
+        if value.chars().count() <= count {
+            self.label(text, x);
+            return value;
+        }
+        // Подпись короткая: у узких полей рядом следующее поле.
+        self.label(&format!("{}: от руки", text), x);
+        self.overflow.push(text.to_string());
+        ""
+    }
+
+    /// Рисует `count` клеток от `x` по текущей строке и вписывает в них `value` по букве.
+    /// Значение должно помещаться в клетки, см. `checked_label`.
+    fn cells(&self, x: f32, count: usize, value: &str) {
+        let top = self.y - 4.0;
+        self.layer.set_outline_thickness(0.3);
+        for i in 0..count {
+            let left = x + i as f32 * CELL_WIDTH;
+            self.layer.add_rect(
+                Rect::new(
+                    Mm(left),
+                    Mm(top - CELL_HEIGHT),
+                    Mm(left + CELL_WIDTH),
+                    Mm(top),
+                )
+                .with_mode(printpdf::path::PaintMode::Stroke),
+            );
+        }
+        debug_assert!(value.chars().count() <= count, "{} does not fit {} cells", value, count);
+        for (i, c) in value.chars().enumerate() {
+            self.layer.use_text(
+                c.to_string(),
+                10.0,
+                Mm(x + i as f32 * CELL_WIDTH + 1.2),
+                Mm(top - CELL_HEIGHT + 1.8),
+                &self.fonts.regular,
+            );
+        }
+    }
+
+    fn next_line(&mut self) {
+        self.y -= 4.0 + CELL_HEIGHT + 1.5;
+    }
+
+    /// Поле во всю ширину листа. Длинное значение переносится на следующие `lines` строк.
+    fn text_field(&mut self, label: &str, value: &str, lines: usize) {
+        let value = value.to_uppercase();
+        let value = self.checked_label(label, MARGIN, lines * CELLS_PER_LINE, &value);
+        let chars: Vec<char> = value.chars().collect();
+        for line in 0..lines {
+            let chunk: String = chars
+                .iter()
+                .skip(line * CELLS_PER_LINE)
+                .take(CELLS_PER_LINE)
+                .collect();
+            self.cells(MARGIN, CELLS_PER_LINE, &chunk);
+            if line + 1 < lines {
+                self.y -= CELL_HEIGHT + 1.0;
+            }
+        }
+        self.next_line();
+    }
+
+    /// Дата клетками ДД.ММ.ГГГГ.
+    fn date_cells(&self, x: f32, date: Option<NaiveDate>) {
+        let (day, month, year) = match date {
+            Some(d) => (
+                format!("{:02}", d.day()),
+                format!("{:02}", d.month()),
+                format!("{:04}", d.year()),
+            ),
+            None => Default::default(),
+        };
+        self.cells(x, 2, &day);
+        self.cells(x + 2.0 * CELL_WIDTH + 2.0, 2, &month);
+        self.cells(x + 4.0 * CELL_WIDTH + 4.0, 4, &year);
+    }
+
+    fn birth_date_and_sex(&mut self, birth_date: Option<NaiveDate>, sex: Option<Sex>) {
+        self.label("Дата рождения", MARGIN);
+        self.date_cells(MARGIN, birth_date);
+
+        let x = MARGIN + 70.0;
+        self.label("Пол", x);
+        for (i, (option, title)) in [(Sex::Male, "мужской"), (Sex::Female, "женский")]
+            .into_iter()
+            .enumerate()
+        {
+            let left = x + i as f32 * 30.0;
+            self.cells(left, 1, if sex == Some(option) { "X" } else { "" });
+            self.layer.use_text(
+                title,
+                9.0,
+                Mm(left + CELL_WIDTH + 2.0),
+                Mm(self.y - 4.0 - CELL_HEIGHT + 1.8),
+                &self.fonts.regular,
+            );
+        }
+        self.next_line();
+    }
+
+    fn passport(&mut self, passport: Option<(&str, &str)>) {
+        let (series, number) = passport.unwrap_or_default();
+        self.label("Документ, удостоверяющий личность: вид", MARGIN);
+        self.cells(MARGIN, 7, "ПАСПОРТ");
+        let x = MARGIN + 7.0 * CELL_WIDTH + 5.0;
+        let series = self.checked_label("Серия", x, 6, series);
+        self.cells(x, 6, series);
+        let x = x + 6.0 * CELL_WIDTH + 5.0;
+        let number = self.checked_label("№", x, 12, number);
+        self.cells(x, 12, number);
+        self.next_line();
+    }
+
+    fn dates(&mut self, arrival_date: NaiveDate, stay_until: Option<NaiveDate>) {
+        self.label("Дата въезда", MARGIN);
+        self.date_cells(MARGIN, Some(arrival_date));
+        let x = MARGIN + 70.0;
+        self.label("Срок пребывания до", x);
+        self.date_cells(x, stay_until);
+        self.next_line();
+    }
+
+    fn footer(&self, text: &str) {
+        self.layer
+            .use_text(text, 8.0, Mm(MARGIN), Mm(MARGIN), &self.fonts.regular);
+    }
+}
+
+#[cfg(test)]
+mod arrival_notice_tests {
+    use super::*;
+
+    #[test]
+    fn test_split_full_name() {
+        assert_eq!(
+            split_full_name("Иванов Иван Иванович"),
+            ("Иванов", "Иван", "Иванович")
+        );
+        assert_eq!(split_full_name("Петросян  Анна"), ("Петросян", "Анна", ""));
+        assert_eq!(split_full_name("Ли"), ("Ли", "", ""));
+        assert_eq!(
+            split_full_name("Абдуллаев Бахтиёр Рустам угли"),
+            ("Абдуллаев", "Бахтиёр", "Рустам угли")
+        );
+    }
+}
@@ -0,0 +1,78 @@
+use csv::Writer;
+
+use super::{HEADER, ReservationsExporter, documents_link};
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::domain::Error;
+use crate::domain::models::reservation_code;
+use crate::usecases::DayReservationsDTO;
+
+/// CsvExporter выгружает записи в CSV с UTF-8 BOM, чтобы Excel правильно открывал кириллицу.
+/// Записи каждого дня идут под строкой с датой и заканчиваются строкой итога.
+pub struct CsvExporter;
+
+/// Строка-заголовок дня или строка итога: подпись и значение, остальные столбцы пустые.
+fn summary_row(label: String, value: String) -> Vec<String> {
+    let mut row = vec![String::new(); HEADER.len()];
+    row[0] = label;
+    row[1] = value;
+    row
+}
+
+impl ReservationsExporter for CsvExporter {
+    fn extension(&self) -> &'static str {
+        "csv"
+    }
+
+    fn export(&self, days: &[DayReservationsDTO], bot_username: &str) -> Result<Vec<u8>, Error> {
+        let mut buffer = Vec::new();
+        // UTF-8 BOM
+        buffer.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+
+        let mut writer = Writer::from_writer(buffer);
+
+        writer.write_record(HEADER)
+            .map_err(|err| Error::Other(err.into()))?;
+
+        let mut total = 0;
+        for day in days {
+            let date = day.date.format("%d.%m.%Y").to_string();
+            writer.write_record(summary_row(date.clone(), String::new()))
+                .map_err(|err| Error::Other(err.into()))?;
+
+            for (i, r) in day.reservations.iter().enumerate() {
+                writer.write_record(&[
+                    format!("{}", i + 1),
+                    reservation_code(r.user_id, r.slot_start),
+                    r.slot_start.format("%H:%M").to_string(),
+                    r.slot_end.format("%H:%M").to_string(),
+                    service_to_str(&r.service).to_string(),
+                    format!("t.me/{}/", r.username),
+                    r.user_name_lat.clone(),
+                    r.user_name_cyr.clone(),
+                    r.citizenship.clone().into(),
+                    r.arrival_date.format("%d.%m.%Y").to_string(),
+                    documents_link(bot_username, r),
+                ])
+                    .map_err(|err| Error::Other(err.into()))?;
+            }
+
+            writer.write_record(summary_row(
+                format!("Итого за {}", date),
+                day.reservations.len().to_string(),
+            ))
+                .map_err(|err| Error::Other(err.into()))?;
+            total += day.reservations.len();
+        }
+        if days.len() > 1 {
+            writer.write_record(summary_row("Всего".to_string(), total.to_string()))
+                .map_err(|err| Error::Other(err.into()))?;
+        }
+
+        writer.flush()
+            .map_err(|err| Error::Other(err.into()))?;
+        let res = writer
+            .into_inner()
+            .map_err(|err| Error::Other(err.into()))?;
+        Ok(res)
+    }
+}
@@ -0,0 +1,267 @@
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont, point};
+use chrono::Weekday;
+
+use super::pdf::{FONT, FONT_BOLD};
+use crate::domain::Error;
+use crate::usecases::HeatmapDTO;
+
+/// Размеры в пикселях.
+const MARGIN: u32 = 20;
+const TITLE_HEIGHT: u32 = 44;
+const HEADER_HEIGHT: u32 = 30;
+const TIME_COLUMN_WIDTH: u32 = 64;
+const CELL_WIDTH: u32 = 92;
+const CELL_HEIGHT: u32 = 28;
+const LEGEND_HEIGHT: u32 = 56;
+const LEGEND_WIDTH: u32 = 240;
+const MIN_WIDTH: u32 = 520;
+
+const TITLE_SIZE: f32 = 20.0;
+const TEXT_SIZE: f32 = 15.0;
+
+const WHITE: [u8; 3] = [255, 255, 255];
+const TEXT: [u8; 3] = [33, 33, 33];
+/// Цвет ячеек, в которые нет приёма.
+const NO_SLOT: [u8; 3] = [224, 224, 224];
+/// Цвета пустого, наполовину и полностью занятого слота.
+const EMPTY: [u8; 3] = [99, 190, 123];
+const HALF: [u8; 3] = [255, 235, 132];
+const FULL: [u8; 3] = [248, 105, 107];
+
+/// Рисует тепловую карту заполненности в PNG: столбцы - дни недели, строки - время начала
+/// слотов, цвет ячейки - средняя доля занятых мест от зелёного (пусто) до красного (занято всё).
+pub fn heatmap_png(heatmap: &HeatmapDTO) -> Result<Vec<u8>, Error> {
+    let font_error = |err: ab_glyph::InvalidFont| Error::Other(err.into());
+    let regular = FontRef::try_from_slice(FONT).map_err(font_error)?;
+    let bold = FontRef::try_from_slice(FONT_BOLD).map_err(font_error)?;
+
+    let title = format!(
+        "Заполненность слотов {} – {}",
+        heatmap.from.format("%d.%m.%Y"),
+        heatmap.to.format("%d.%m.%Y"),
+    );
+    let grid_width = TIME_COLUMN_WIDTH + CELL_WIDTH * heatmap.weekdays.len() as u32;
+    let width = (grid_width + 2 * MARGIN)
+        .max(text_width(&bold, TITLE_SIZE, &title).ceil() as u32 + 2 * MARGIN)
+        .max(MIN_WIDTH);
+    let grid_top = MARGIN + TITLE_HEIGHT + HEADER_HEIGHT;
+    let height = grid_top + CELL_HEIGHT * heatmap.times.len() as u32 + LEGEND_HEIGHT + MARGIN;
+    let mut canvas = Canvas::new(width, height);
+
+    canvas.draw_text(&bold, TITLE_SIZE, MARGIN as f32, (MARGIN + 24) as f32, &title, TEXT);
+
+    let grid_left = MARGIN + TIME_COLUMN_WIDTH;
+    for (i, weekday) in heatmap.weekdays.iter().enumerate() {
+        let name = weekday_short_name(*weekday);
+        let x = grid_left + CELL_WIDTH * i as u32;
+        let offset = (CELL_WIDTH as f32 - text_width(&bold, TEXT_SIZE, name)) / 2.0;
+        canvas.draw_text(&bold, TEXT_SIZE, x as f32 + offset, (grid_top - 10) as f32, name, TEXT);
+    }
+    for (j, time) in heatmap.times.iter().enumerate() {
+        let y = grid_top + CELL_HEIGHT * j as u32;
+        let label = time.format("%H:%M").to_string();
+        canvas.draw_text(&regular, TEXT_SIZE, MARGIN as f32, (y + 19) as f32, &label, TEXT);
+    }
+
+    for (i, row) in heatmap.fill.iter().enumerate() {
+        for (j, fill) in row.iter().enumerate() {
+            let (x, y) = (grid_left + CELL_WIDTH * i as u32, grid_top + CELL_HEIGHT * j as u32);
+            let color = fill.map_or(NO_SLOT, fill_color);
+            // Белая рамка в 1 пиксель разделяет ячейки.
+            canvas.fill_rect(x + 1, y + 1, CELL_WIDTH - 2, CELL_HEIGHT - 2, color);
+            if let Some(fill) = fill {
+                let label = format!("{:.0}%", fill * 100.0);
+                let offset = (CELL_WIDTH as f32 - text_width(&regular, TEXT_SIZE, &label)) / 2.0;
+                canvas.draw_text(&regular, TEXT_SIZE, x as f32 + offset, (y + 19) as f32, &label, TEXT);
+            }
+        }
+    }
+
+    // Шкала цветов под таблицей.
+    let legend_top = grid_top + CELL_HEIGHT * heatmap.times.len() as u32 + 16;
+    for dx in 0..LEGEND_WIDTH {
+        let color = fill_color(dx as f64 / (LEGEND_WIDTH - 1) as f64);
+        canvas.fill_rect(MARGIN + dx, legend_top, 1, 12, color);
+    }
+    let labels_y = (legend_top + 30) as f32;
+    canvas.draw_text(&regular, TEXT_SIZE, MARGIN as f32, labels_y, "0%", TEXT);
+    let half = (MARGIN + LEGEND_WIDTH / 2) as f32 - text_width(&regular, TEXT_SIZE, "50%") / 2.0;
+    canvas.draw_text(&regular, TEXT_SIZE, half, labels_y, "50%", TEXT);
+    let full = (MARGIN + LEGEND_WIDTH) as f32 - text_width(&regular, TEXT_SIZE, "100%");
+    canvas.draw_text(&regular, TEXT_SIZE, full, labels_y, "100%", TEXT);
+    let no_slot_left = MARGIN + LEGEND_WIDTH + 24;
+    canvas.fill_rect(no_slot_left, legend_top, 12, 12, NO_SLOT);
+    canvas.draw_text(
+        &regular,
+        TEXT_SIZE,
+        (no_slot_left + 18) as f32,
+        (legend_top + 12) as f32,
+        "нет приёма",
+        TEXT,
+    );
+
+    canvas.encode()
+}
+
+pub fn weekday_short_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Пн",
+        Weekday::Tue => "Вт",
+        Weekday::Wed => "Ср",
+        Weekday::Thu => "Чт",
+        Weekday::Fri => "Пт",
+        Weekday::Sat => "Сб",
+        Weekday::Sun => "Вс",
+    }
+}
+
+/// Цвет доли занятых мест: от зелёного через жёлтый к красному. Запись сверх мест - красный.
+fn fill_color(fill: f64) -> [u8; 3] {
+    let fill = fill.clamp(0.0, 1.0);
+    let (from, to, t) = if fill < 0.5 {
+        (EMPTY, HALF, fill * 2.0)
+    } else {
+        (HALF, FULL, (fill - 0.5) * 2.0)
+    };
+    [0, 1, 2].map(|c| (from[c] as f64 + (to[c] as f64 - from[c] as f64) * t).round() as u8)
+}
+
+fn text_width(font: &FontRef, size: f32, text: &str) -> f32 {
+    let font = font.as_scaled(PxScale::from(size));
+    let mut width = 0.0;
+    let mut previous = None;
+    for c in text.chars() {
+        let id = font.glyph_id(c);
+        if let Some(previous) = previous {
+            width += font.kern(previous, id);
+        }
+        width += font.h_advance(id);
+        previous = Some(id);
+    }
+    width
+}
+
+/// Изображение RGB в памяти.
+struct Canvas {
+    width: u32,
+    height: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            height,
+            pixels: WHITE.repeat((width * height) as usize),
+        }
+    }
+
+    fn blend(&mut self, x: u32, y: u32, color: [u8; 3], alpha: f32) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let alpha = alpha.clamp(0.0, 1.0);
+        let i = ((y * self.width + x) * 3) as usize;
+        for (pixel, color) in self.pixels[i..i + 3].iter_mut().zip(color) {
+            let background = *pixel as f32;
+            *pixel = (background + (color as f32 - background) * alpha).round() as u8;
+        }
+    }
+
+    fn fill_rect(&mut self, x: u32, y: u32, width: u32, height: u32, color: [u8; 3]) {
+        for y in y..y + height {
+            for x in x..x + width {
+                self.blend(x, y, color, 1.0);
+            }
+        }
+    }
+
+    /// Рисует строку начиная с `x`, `baseline` - координата базовой линии.
+    fn draw_text(&mut self, font: &FontRef, size: f32, x: f32, baseline: f32, text: &str, color: [u8; 3]) {
+        let scale = PxScale::from(size);
+        let scaled = font.as_scaled(scale);
+        let mut caret = x;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = scaled.glyph_id(c);
+            if let Some(previous) = previous {
+                caret += scaled.kern(previous, id);
+            }
+            let glyph = id.with_scale_and_position(scale, point(caret, baseline));
+            caret += scaled.h_advance(id);
+            previous = Some(id);
+            let Some(outline) = font.outline_glyph(glyph) else {
+                // Пробел
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let (px, py) = (bounds.min.x + gx as f32, bounds.min.y + gy as f32);
+                if px >= 0.0 && py >= 0.0 {
+                    self.blend(px as u32, py as u32, color, coverage);
+                }
+            });
+        }
+    }
+
+    fn encode(self) -> Result<Vec<u8>, Error> {
+        let png_error = |err: png::EncodingError| Error::Other(err.into());
+        let mut data = Vec::new();
+        let mut encoder = png::Encoder::new(&mut data, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(png_error)?;
+        writer.write_image_data(&self.pixels).map_err(png_error)?;
+        writer.finish().map_err(png_error)?;
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod heatmap_png_tests {
+    use super::*;
+    use chrono::{NaiveDate, NaiveTime};
+
+    #[test]
+    fn test_heatmap_colours_cells_by_fill() {
+        // GIVEN два дня недели и три слота, в пятницу последнего слота нет
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let heatmap = HeatmapDTO {
+            from: NaiveDate::from_ymd_opt(2025, 9, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2025, 9, 30).unwrap(),
+            weekdays: vec![Weekday::Mon, Weekday::Fri],
+            times: vec![time(10, 0), time(10, 20), time(10, 40)],
+            fill: vec![
+                vec![Some(0.0), Some(0.5), Some(1.2)],
+                vec![Some(1.0), Some(0.25), None],
+            ],
+        };
+
+        // WHEN рисуется тепловая карта
+        let data = heatmap_png(&heatmap).unwrap();
+
+        // THEN получается PNG нужного размера
+        let decoder = png::Decoder::new(data.as_slice());
+        let mut reader = decoder.read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        let grid_top = MARGIN + TITLE_HEIGHT + HEADER_HEIGHT;
+        assert_eq!(info.height, grid_top + 3 * CELL_HEIGHT + LEGEND_HEIGHT + MARGIN);
+        assert!(info.width >= MIN_WIDTH);
+
+        // AND ячейки окрашены по заполненности (угол ячейки, где нет текста)
+        let pixel = |i: u32, j: u32| {
+            let x = MARGIN + TIME_COLUMN_WIDTH + CELL_WIDTH * i + 3;
+            let y = grid_top + CELL_HEIGHT * j + 3;
+            let k = ((y * info.width + x) * 3) as usize;
+            [pixels[k], pixels[k + 1], pixels[k + 2]]
+        };
+        assert_eq!(pixel(0, 0), EMPTY);
+        assert_eq!(pixel(0, 1), HALF);
+        // Запись сверх мест окрашена как полная.
+        assert_eq!(pixel(0, 2), FULL);
+        assert_eq!(pixel(1, 0), FULL);
+        assert_eq!(pixel(1, 2), NO_SLOT);
+    }
+}
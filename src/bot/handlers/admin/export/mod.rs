@@ -0,0 +1,299 @@
+mod arrival_notice;
+mod csv_exporter;
+mod heatmap_png;
+mod pdf;
+mod schedule_pdf;
+mod stats_csv;
+mod xlsx_exporter;
+
+use serde::{Deserialize, Serialize};
+
+use super::admin::DOCUMENTS_LINK_PREFIX;
+use crate::domain::Error;
+use crate::usecases::{DayReservationsDTO, ReservationDTO};
+
+pub use arrival_notice::arrival_notices_zip;
+pub use csv_exporter::CsvExporter;
+pub use heatmap_png::{heatmap_png, weekday_short_name};
+pub use schedule_pdf::schedule_pdf;
+pub use stats_csv::{citizenship_label, stats_csv};
+pub use xlsx_exporter::XlsxExporter;
+
+/// ReservationsExporter формирует файл выгрузки записей, сгруппированных по дням.
+pub trait ReservationsExporter: Send + Sync {
+    /// Расширение файла без точки.
+    fn extension(&self) -> &'static str;
+
+    fn export(&self, days: &[DayReservationsDTO], bot_username: &str) -> Result<Vec<u8>, Error>;
+}
+
+/// Формат выгрузки, который сотрудник выбирает в диалоге /table.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    #[default]
+    Csv,
+    Xlsx,
+}
+
+impl ExportFormat {
+    pub fn all() -> &'static [ExportFormat] {
+        &[ExportFormat::Csv, ExportFormat::Xlsx]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Xlsx => "Excel (XLSX)",
+        }
+    }
+
+    pub fn exporter(&self) -> &'static dyn ReservationsExporter {
+        match self {
+            ExportFormat::Csv => &CsvExporter,
+            ExportFormat::Xlsx => &XlsxExporter,
+        }
+    }
+}
+
+const HEADER: [&str; 11] = [
+    "#",
+    "Код",
+    "Начало",
+    "Конец",
+    "Услуга",
+    "Telegram",
+    "ФИО (лат)",
+    "ФИО (кир)",
+    "Гражданство",
+    "Дата прибытия",
+    "Документы",
+];
+
+fn documents_link(bot_username: &str, r: &ReservationDTO) -> String {
+    format!(
+        "https://t.me/{}?start={}{}_{}",
+        bot_username,
+        DOCUMENTS_LINK_PREFIX,
+        r.user_id,
+        r.slot_start.timestamp(),
+    )
+}
+
+#[cfg(test)]
+mod export_tests {
+    use super::*;
+    use crate::domain::models::{Citizenship, Service, UserID};
+    use crate::domain::models::{OnlyCyrillic, OnlyLatin, Passport, Sex, Username};
+    use crate::usecases::{ArrivalNoticeDTO, DayStatsDTO, ScheduleSlotDTO, StatsDTO, UserDTO};
+    use chrono::{DateTime, Duration, NaiveDate, TimeZone, Utc};
+
+    fn reservation(start: DateTime<Utc>, id: i64) -> ReservationDTO {
+        ReservationDTO {
+            slot_start: start,
+            slot_end: start + Duration::minutes(20),
+            service: Service::Visa,
+            user_id: UserID::new(id),
+            username: format!("user{}", id),
+            user_name_lat: "Ivanov".to_string(),
+            user_name_cyr: "Иванов".to_string(),
+            citizenship: Citizenship::Armenia,
+            arrival_date: NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+        }
+    }
+
+    fn day(date: NaiveDate, users: &[i64]) -> DayReservationsDTO {
+        let start = Utc.from_utc_datetime(&date.and_hms_opt(10, 0, 0).unwrap());
+        DayReservationsDTO {
+            date,
+            reservations: users.iter().map(|id| reservation(start, *id)).collect(),
+        }
+    }
+
+    fn schedule(date: NaiveDate, slots: usize) -> Vec<ScheduleSlotDTO> {
+        let start = Utc.from_utc_datetime(&date.and_hms_opt(9, 0, 0).unwrap());
+        (0..slots)
+            .map(|i| {
+                let start = start + Duration::minutes(20 * i as i64);
+                ScheduleSlotDTO {
+                    start,
+                    end: start + Duration::minutes(20),
+                    max_size: 3,
+                    reservations: if i == 0 { vec![reservation(start, 1)] } else { vec![] },
+                }
+            })
+            .collect()
+    }
+
+    fn pdf_pages(data: &[u8]) -> usize {
+        let pdf = String::from_utf8_lossy(data);
+        let (_, count) = pdf.split_once("/Type/Pages/Count ").unwrap();
+        count[..count.find('/').unwrap()].parse().unwrap()
+    }
+
+    fn days() -> Vec<DayReservationsDTO> {
+        vec![
+            day(NaiveDate::from_ymd_opt(2025, 7, 14).unwrap(), &[1, 2]),
+            day(NaiveDate::from_ymd_opt(2025, 7, 15).unwrap(), &[3]),
+        ]
+    }
+
+    #[test]
+    fn test_csv_groups_rows_by_day() {
+        // GIVEN записи за два дня
+        // WHEN они выгружаются в CSV
+        let data = ExportFormat::Csv.exporter().export(&days(), "umd_bot").unwrap();
+        let text = String::from_utf8(data).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        // THEN у каждого дня есть заголовок и итог, а в конце - общий итог
+        assert!(lines[0].starts_with('\u{feff}'));
+        assert!(lines[1].starts_with("14.07.2025,"));
+        assert!(lines[2].starts_with("1,"));
+        assert!(lines[3].starts_with("2,"));
+        assert!(lines[4].starts_with("Итого за 14.07.2025,2,"));
+        assert!(lines[5].starts_with("15.07.2025,"));
+        assert!(lines[7].starts_with("Итого за 15.07.2025,1,"));
+        assert!(lines[8].starts_with("Всего,3,"));
+        assert!(lines[2].contains("https://t.me/umd_bot?start=docs_1_"));
+    }
+
+    #[test]
+    fn test_xlsx_has_sheet_per_day() {
+        // GIVEN записи за два дня
+        // WHEN они выгружаются в XLSX
+        let data = ExportFormat::Xlsx.exporter().export(&days(), "umd_bot").unwrap();
+
+        // THEN получается zip-архив книги с листом на каждый день и листом итогов
+        assert!(data.starts_with(b"PK"));
+        let workbook = String::from_utf8_lossy(&data);
+        assert!(workbook.contains("xl/worksheets/sheet3.xml"));
+        assert!(!workbook.contains("xl/worksheets/sheet4.xml"));
+    }
+
+    #[test]
+    fn test_schedule_pdf_includes_free_seats() {
+        // GIVEN расписание из двух слотов по 3 места, занято одно место
+        let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
+
+        // WHEN оно выгружается в PDF
+        let data = schedule_pdf(date, &schedule(date, 2)).unwrap();
+
+        // THEN получается одностраничный PDF
+        assert!(data.starts_with(b"%PDF"));
+        assert_eq!(pdf_pages(&data), 1);
+    }
+
+    #[test]
+    fn test_schedule_pdf_breaks_pages() {
+        // GIVEN полный рабочий день: 24 слота по 3 места
+        let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
+
+        // WHEN расписание выгружается в PDF
+        let data = schedule_pdf(date, &schedule(date, 24)).unwrap();
+
+        // THEN 72 строки не помещаются на один лист A4
+        assert!(pdf_pages(&data) > 1);
+    }
+
+    fn notice(id: i64, full_name_lat: &str, full_name_cyr: &str) -> ArrivalNoticeDTO {
+        let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
+        ArrivalNoticeDTO {
+            slot_start: Utc.from_utc_datetime(&date.and_hms_opt(10, 20, 0).unwrap()),
+            service: Service::InitialRegistration,
+            user: UserDTO {
+                id: UserID::new(id),
+                username: Username::new(""),
+                full_name_lat: OnlyLatin::new(full_name_lat).unwrap(),
+                full_name_cyr: OnlyCyrillic::new(full_name_cyr).unwrap(),
+                citizenship: Citizenship::Armenia,
+                arrival_date: NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+                visa_expiry: None,
+                registration_expiry: None,
+                birth_date: NaiveDate::from_ymd_opt(2005, 3, 1),
+                sex: Some(Sex::Female),
+                passport: Some(Passport::parse("AB 1234567").unwrap()),
+                place_of_stay: None,
+            },
+            missing_fields: vec!["place_of_stay"],
+        }
+    }
+
+    #[test]
+    fn test_arrival_notices_zip_has_file_per_notice() {
+        // GIVEN две записи на регистрацию
+        let notices = vec![
+            notice(1, "Petrosyan Anna", "Петросян Анна"),
+            notice(2, "Ivanov Ivan Ivanovich", "Иванов Иван Иванович"),
+        ];
+
+        // WHEN уведомления упаковываются в архив
+        let zip = arrival_notices_zip(&notices).unwrap();
+
+        // THEN в архиве по PDF на каждую запись в порядке записи
+        assert!(zip.data.starts_with(b"PK"));
+        let archive = String::from_utf8_lossy(&zip.data);
+        assert!(archive.contains("01_1020_Petrosyan_Anna.pdf"));
+        assert!(archive.contains("02_1020_Ivanov_Ivan_Ivanovich.pdf"));
+        assert_eq!(zip.overflowing, 0);
+    }
+
+    #[test]
+    fn test_arrival_notice_flags_values_longer_than_cells() {
+        // GIVEN у одного студента адрес не помещается в три строки клеток бланка
+        let mut long = notice(2, "Ivanov Ivan", "Иванов Иван");
+        long.user.place_of_stay = Some("г. Москва, ул. Бауманская 2-я, ".repeat(10));
+        let notices = vec![notice(1, "Petrosyan Anna", "Петросян Анна"), long];
+
+        // WHEN уведомления упаковываются в архив
+        let zip = arrival_notices_zip(&notices).unwrap();
+
+        // THEN бланк с длинным адресом отмечен, а не обрезан молча
+        assert_eq!(zip.overflowing, 1);
+    }
+
+    #[test]
+    fn test_stats_csv_has_days_and_breakdowns() {
+        // GIVEN статистика за два дня
+        let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
+        let stats = StatsDTO {
+            from: date,
+            to: date + Duration::days(1),
+            days: vec![
+                DayStatsDTO {
+                    date,
+                    capacity: 40,
+                    reserved: 10,
+                    attended: 8,
+                    cancelled: 1,
+                    no_shows: Some(2),
+                },
+                DayStatsDTO {
+                    date: date + Duration::days(1),
+                    capacity: 40,
+                    reserved: 20,
+                    attended: 0,
+                    cancelled: 3,
+                    no_shows: None,
+                },
+            ],
+            by_service: vec![(Service::Visa, 30)],
+            by_citizenship: vec![(Citizenship::Armenia, 25), (Citizenship::Other(String::new()), 5)],
+            average_lead_time: Some(Duration::hours(30)),
+        };
+
+        // WHEN она выгружается в CSV
+        let data = stats_csv(&stats).unwrap();
+        let text = String::from_utf8(data).unwrap();
+        let lines: Vec<&str> = text.lines().collect();
+
+        // THEN по строке на день с итогом, затем разбивки и время от записи до приёма
+        assert!(lines[0].starts_with("\u{feff}Дата,"));
+        assert_eq!(lines[1], "14.07.2025,40,10,25.0,8,2,1");
+        assert_eq!(lines[2], "15.07.2025,40,20,50.0,0,,3");
+        assert_eq!(lines[3], "Всего,80,30,37.5,8,2,4");
+        assert_eq!(lines[6], "Получение визы,30");
+        assert_eq!(lines[9], "Армения,25");
+        assert_eq!(lines[10], "не указано,5");
+        assert_eq!(lines[12], "\"Среднее время от записи до приёма, ч\",30.0");
+    }
+}
@@ -0,0 +1,30 @@
+use printpdf::{IndirectFontRef, PdfDocumentReference};
+
+use crate::domain::Error;
+
+/// Шрифт с кириллицей встраивается в бинарник: в образе бота системных шрифтов нет.
+pub(super) const FONT: &[u8] = include_bytes!("../../../../../assets/fonts/DejaVuSans.ttf");
+pub(super) const FONT_BOLD: &[u8] = include_bytes!("../../../../../assets/fonts/DejaVuSans-Bold.ttf");
+
+/// Размер листа A4 в миллиметрах.
+pub(super) const PAGE_WIDTH: f32 = 210.0;
+pub(super) const PAGE_HEIGHT: f32 = 297.0;
+pub(super) const MARGIN: f32 = 15.0;
+
+pub(super) struct Fonts {
+    pub regular: IndirectFontRef,
+    pub bold: IndirectFontRef,
+}
+
+impl Fonts {
+    pub fn add(doc: &PdfDocumentReference) -> Result<Self, Error> {
+        Ok(Self {
+            regular: doc.add_external_font(FONT).map_err(pdf_error)?,
+            bold: doc.add_external_font(FONT_BOLD).map_err(pdf_error)?,
+        })
+    }
+}
+
+pub(super) fn pdf_error(err: printpdf::Error) -> Error {
+    Error::Other(err.into())
+}
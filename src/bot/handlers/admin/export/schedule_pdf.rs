@@ -0,0 +1,200 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use printpdf::{Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point, Rect};
+
+use super::pdf::{Fonts, MARGIN, PAGE_HEIGHT, PAGE_WIDTH, pdf_error};
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::domain::Error;
+use crate::domain::models::reservation_code;
+use crate::usecases::{ReservationDTO, ScheduleSlotDTO};
+
+const HEADER_HEIGHT: f32 = 8.0;
+const ROW_HEIGHT: f32 = 11.0;
+const CHECKBOX_SIZE: f32 = 5.0;
+
+/// Левые границы столбцов «Время», «Код», «ФИО», «Услуга», «Пришёл» и правая граница таблицы.
+const COLUMNS: [f32; 6] = [MARGIN, 37.0, 55.0, 135.0, 178.0, PAGE_WIDTH - MARGIN];
+const HEADER: [&str; 5] = ["Время", "Код", "ФИО", "Услуга", "Пришёл"];
+
+/// Сколько символов помещается в столбец ФИО кириллицей (10 pt) и латиницей (8 pt).
+const NAME_CYR_CHARS: usize = 40;
+const NAME_LAT_CHARS: usize = 50;
+
+/// Формирует расписание на день для печати на листе A4: все слоты по порядку, под каждое
+/// место в слоте — строка, свободные места остаются пустыми строками для записи от руки.
+pub fn schedule_pdf(date: NaiveDate, slots: &[ScheduleSlotDTO]) -> Result<Vec<u8>, Error> {
+    let title = format!(
+        "Расписание на {} ({})",
+        date.format("%d.%m.%Y"),
+        weekday_name(date.weekday())
+    );
+    let (doc, page, layer) = PdfDocument::new(
+        title.as_str(),
+        Mm(PAGE_WIDTH),
+        Mm(PAGE_HEIGHT),
+        "Расписание",
+    );
+    let fonts = Fonts::add(&doc)?;
+
+    let seats: usize = slots.iter().map(|slot| slot.max_size).sum();
+    let reserved: usize = slots.iter().map(|slot| slot.reservations.len()).sum();
+    let layer = doc.get_page(page).get_layer(layer);
+    layer.use_text(
+        &title,
+        14.0,
+        Mm(MARGIN),
+        Mm(PAGE_HEIGHT - MARGIN - 5.0),
+        &fonts.bold,
+    );
+    layer.use_text(
+        format!("Записано: {} из {} мест", reserved, seats),
+        10.0,
+        Mm(MARGIN),
+        Mm(PAGE_HEIGHT - MARGIN - 11.0),
+        &fonts.regular,
+    );
+
+    let mut sheet = Sheet {
+        doc: &doc,
+        fonts: &fonts,
+        layer,
+        y: PAGE_HEIGHT - MARGIN - 16.0,
+    };
+    sheet.header();
+    for slot in slots {
+        // Слот целиком переносится на новую страницу, чтобы время не отрывалось от мест.
+        sheet.ensure_space(ROW_HEIGHT * slot.max_size as f32);
+        for seat in 0..slot.max_size {
+            sheet.ensure_space(ROW_HEIGHT);
+            let time = (seat == 0).then(|| {
+                format!(
+                    "{}–{}",
+                    slot.start.format("%H:%M"),
+                    slot.end.format("%H:%M")
+                )
+            });
+            sheet.row(time.as_deref(), slot.reservations.get(seat));
+        }
+        sheet.separator(0.8);
+    }
+
+    doc.save_to_bytes().map_err(pdf_error)
+}
+
+/// Текущая страница и позиция, с которой продолжается таблица.
+struct Sheet<'a> {
+    doc: &'a PdfDocumentReference,
+    fonts: &'a Fonts,
+    layer: PdfLayerReference,
+    y: f32,
+}
+
+impl Sheet<'_> {
+    /// Переносит таблицу на новую страницу, если строка высотой `height` не помещается.
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height >= MARGIN {
+            return;
+        }
+        let (page, layer) = self
+            .doc
+            .add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "Расписание");
+        self.layer = self.doc.get_page(page).get_layer(layer);
+        self.y = PAGE_HEIGHT - MARGIN;
+        self.header();
+    }
+
+    fn header(&mut self) {
+        for (title, x) in HEADER.iter().zip(COLUMNS) {
+            self.layer
+                .use_text(*title, 9.0, Mm(x + 1.0), Mm(self.y - 5.5), &self.fonts.bold);
+        }
+        self.y -= HEADER_HEIGHT;
+        self.separator(0.8);
+    }
+
+    fn row(&mut self, time: Option<&str>, r: Option<&ReservationDTO>) {
+        let top = self.y;
+        if let Some(time) = time {
+            self.layer.use_text(
+                time,
+                9.0,
+                Mm(COLUMNS[0] + 1.0),
+                Mm(top - 6.5),
+                &self.fonts.bold,
+            );
+        }
+        if let Some(r) = r {
+            self.layer.use_text(
+                reservation_code(r.user_id, r.slot_start),
+                9.0,
+                Mm(COLUMNS[1] + 1.0),
+                Mm(top - 6.5),
+                &self.fonts.regular,
+            );
+            self.layer.use_text(
+                truncate(&r.user_name_cyr, NAME_CYR_CHARS),
+                10.0,
+                Mm(COLUMNS[2] + 1.0),
+                Mm(top - 4.8),
+                &self.fonts.regular,
+            );
+            self.layer.use_text(
+                truncate(&r.user_name_lat, NAME_LAT_CHARS),
+                8.0,
+                Mm(COLUMNS[2] + 1.0),
+                Mm(top - 9.0),
+                &self.fonts.regular,
+            );
+            self.layer.use_text(
+                service_to_str(&r.service),
+                9.0,
+                Mm(COLUMNS[3] + 1.0),
+                Mm(top - 6.5),
+                &self.fonts.regular,
+            );
+        }
+
+        let x = (COLUMNS[4] + COLUMNS[5] - CHECKBOX_SIZE) / 2.0;
+        let y = top - (ROW_HEIGHT + CHECKBOX_SIZE) / 2.0;
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_rect(
+            Rect::new(Mm(x), Mm(y), Mm(x + CHECKBOX_SIZE), Mm(y + CHECKBOX_SIZE))
+                .with_mode(printpdf::path::PaintMode::Stroke),
+        );
+
+        self.y -= ROW_HEIGHT;
+        self.separator(0.2);
+    }
+
+    /// Проводит горизонтальную линию по текущей позиции: толстую между слотами, тонкую между местами.
+    fn separator(&self, thickness: f32) {
+        self.layer.set_outline_thickness(thickness);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(COLUMNS[0]), Mm(self.y)), false),
+                (Point::new(Mm(COLUMNS[5]), Mm(self.y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+}
+
+fn truncate(s: &str, max_chars: usize) -> String {
+    if s.chars().count() <= max_chars {
+        return s.to_string();
+    }
+    let mut truncated: String = s.chars().take(max_chars - 1).collect();
+    truncated.push('…');
+    truncated
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "понедельник",
+        Weekday::Tue => "вторник",
+        Weekday::Wed => "среда",
+        Weekday::Thu => "четверг",
+        Weekday::Fri => "пятница",
+        Weekday::Sat => "суббота",
+        Weekday::Sun => "воскресенье",
+    }
+}
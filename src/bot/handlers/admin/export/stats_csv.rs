@@ -0,0 +1,100 @@
+use csv::WriterBuilder;
+
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::domain::Error;
+use crate::usecases::StatsDTO;
+
+const DAYS_HEADER: [&str; 7] = [
+    "Дата",
+    "Мест",
+    "Записей",
+    "Заполненность, %",
+    "Пришли",
+    "Неявки (если отмечали приход)",
+    "Отмены",
+];
+
+/// Выгружает статистику в CSV с UTF-8 BOM: таблица по дням с итогом, затем разбивки по
+/// услугам и гражданству и среднее время от записи до приёма. Таблицы разделены пустой строкой.
+pub fn stats_csv(stats: &StatsDTO) -> Result<Vec<u8>, Error> {
+    let csv_error = |err: csv::Error| Error::Other(err.into());
+    let mut buffer = Vec::new();
+    // UTF-8 BOM
+    buffer.extend_from_slice(&[0xEF, 0xBB, 0xBF]);
+
+    // Таблицы разной ширины
+    let mut writer = WriterBuilder::new().flexible(true).from_writer(buffer);
+
+    writer.write_record(DAYS_HEADER).map_err(csv_error)?;
+    for day in &stats.days {
+        writer
+            .write_record([
+                day.date.format("%d.%m.%Y").to_string(),
+                day.capacity.to_string(),
+                day.reserved.to_string(),
+                percent(day.reserved, day.capacity),
+                day.attended.to_string(),
+                day.no_shows.map(|n| n.to_string()).unwrap_or_default(),
+                day.cancelled.to_string(),
+            ])
+            .map_err(csv_error)?;
+    }
+    let (no_shows, _) = stats.no_shows();
+    writer
+        .write_record([
+            "Всего".to_string(),
+            stats.capacity().to_string(),
+            stats.reserved().to_string(),
+            percent(stats.reserved(), stats.capacity()),
+            stats.days.iter().map(|d| d.attended).sum::<usize>().to_string(),
+            no_shows.to_string(),
+            stats.cancelled().to_string(),
+        ])
+        .map_err(csv_error)?;
+
+    writer.write_record([""]).map_err(csv_error)?;
+    writer.write_record(["Услуга", "Записей"]).map_err(csv_error)?;
+    for (service, reserved) in &stats.by_service {
+        writer
+            .write_record([service_to_str(service).to_string(), reserved.to_string()])
+            .map_err(csv_error)?;
+    }
+
+    writer.write_record([""]).map_err(csv_error)?;
+    writer.write_record(["Гражданство", "Записей"]).map_err(csv_error)?;
+    for (citizenship, reserved) in &stats.by_citizenship {
+        writer
+            .write_record([citizenship_label(citizenship.as_str()), reserved.to_string()])
+            .map_err(csv_error)?;
+    }
+
+    writer.write_record([""]).map_err(csv_error)?;
+    writer
+        .write_record([
+            "Среднее время от записи до приёма, ч".to_string(),
+            stats
+                .average_lead_time
+                .map(|t| format!("{:.1}", t.num_minutes() as f64 / 60.0))
+                .unwrap_or_default(),
+        ])
+        .map_err(csv_error)?;
+
+    writer.flush().map_err(|err| Error::Other(err.into()))?;
+    writer.into_inner().map_err(|err| Error::Other(err.to_string().into()))
+}
+
+fn percent(part: usize, total: usize) -> String {
+    if total == 0 {
+        return String::new();
+    }
+    format!("{:.1}", part as f64 * 100.0 / total as f64)
+}
+
+/// У обезличенных пользователей гражданство стёрто.
+pub fn citizenship_label(citizenship: &str) -> String {
+    if citizenship.is_empty() {
+        "не указано".to_string()
+    } else {
+        citizenship.to_string()
+    }
+}
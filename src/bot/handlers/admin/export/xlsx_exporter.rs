@@ -0,0 +1,143 @@
+use rust_xlsxwriter::{Color, Format, FormatBorder, Url, Workbook, Worksheet, XlsxError};
+
+use super::{HEADER, ReservationsExporter, documents_link};
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::domain::Error;
+use crate::domain::models::reservation_code;
+use crate::usecases::{DayReservationsDTO, ReservationDTO};
+
+/// Ширина столбцов в символах, в порядке `HEADER`.
+const COLUMN_WIDTHS: [f64; 11] = [4.0, 9.0, 8.0, 8.0, 24.0, 22.0, 28.0, 28.0, 16.0, 14.0, 12.0];
+
+/// XlsxExporter выгружает записи в книгу Excel: по листу на каждый день, а для периода
+/// ещё и первый лист с итогами по дням. Время и даты записываются как значения Excel,
+/// поэтому по ним работают сортировка и фильтры.
+pub struct XlsxExporter;
+
+struct Formats {
+    header: Format,
+    time: Format,
+    date: Format,
+    total: Format,
+}
+
+impl Formats {
+    fn new() -> Self {
+        Self {
+            header: Format::new()
+                .set_bold()
+                .set_background_color(Color::RGB(0xD9E1F2))
+                .set_border_bottom(FormatBorder::Thin),
+            time: Format::new().set_num_format("hh:mm"),
+            date: Format::new().set_num_format("dd.mm.yyyy"),
+            total: Format::new().set_bold(),
+        }
+    }
+}
+
+fn write_header(
+    sheet: &mut Worksheet,
+    header: &[&str],
+    widths: &[f64],
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    for (col, (title, width)) in header.iter().zip(widths).enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, &formats.header)?;
+        sheet.set_column_width(col as u16, *width)?;
+    }
+    sheet.set_freeze_panes(1, 0)?;
+    Ok(())
+}
+
+fn write_reservation(
+    sheet: &mut Worksheet,
+    row: u32,
+    n: usize,
+    r: &ReservationDTO,
+    bot_username: &str,
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    sheet.write_number(row, 0, n as f64)?;
+    sheet.write_string(row, 1, reservation_code(r.user_id, r.slot_start))?;
+    sheet.write_datetime_with_format(row, 2, r.slot_start.naive_utc(), &formats.time)?;
+    sheet.write_datetime_with_format(row, 3, r.slot_end.naive_utc(), &formats.time)?;
+    sheet.write_string(row, 4, service_to_str(&r.service))?;
+    if !r.username.is_empty() {
+        sheet.write_url_with_text(
+            row,
+            5,
+            Url::new(format!("https://t.me/{}", r.username)),
+            format!("@{}", r.username),
+        )?;
+    }
+    sheet.write_string(row, 6, &r.user_name_lat)?;
+    sheet.write_string(row, 7, &r.user_name_cyr)?;
+    sheet.write_string(row, 8, r.citizenship.as_str())?;
+    sheet.write_datetime_with_format(row, 9, r.arrival_date, &formats.date)?;
+    sheet.write_url_with_text(row, 10, Url::new(documents_link(bot_username, r)), "Документы")?;
+    Ok(())
+}
+
+fn write_day(
+    workbook: &mut Workbook,
+    day: &DayReservationsDTO,
+    bot_username: &str,
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name(day.date.format("%d.%m.%Y").to_string())?;
+    write_header(sheet, &HEADER, &COLUMN_WIDTHS, formats)?;
+
+    for (i, r) in day.reservations.iter().enumerate() {
+        write_reservation(sheet, i as u32 + 1, i + 1, r, bot_username, formats)?;
+    }
+
+    let row = day.reservations.len() as u32 + 1;
+    sheet.write_string_with_format(row, 0, "Итого", &formats.total)?;
+    sheet.write_number_with_format(row, 1, day.reservations.len() as f64, &formats.total)?;
+    Ok(())
+}
+
+fn write_totals(
+    workbook: &mut Workbook,
+    days: &[DayReservationsDTO],
+    formats: &Formats,
+) -> Result<(), XlsxError> {
+    let sheet = workbook.add_worksheet();
+    sheet.set_name("Итого")?;
+    write_header(sheet, &["Дата", "Записей"], &[14.0, 10.0], formats)?;
+
+    for (i, day) in days.iter().enumerate() {
+        let row = i as u32 + 1;
+        sheet.write_datetime_with_format(row, 0, day.date, &formats.date)?;
+        sheet.write_number(row, 1, day.reservations.len() as f64)?;
+    }
+
+    let row = days.len() as u32 + 1;
+    let total: usize = days.iter().map(|day| day.reservations.len()).sum();
+    sheet.write_string_with_format(row, 0, "Всего", &formats.total)?;
+    sheet.write_number_with_format(row, 1, total as f64, &formats.total)?;
+    Ok(())
+}
+
+impl ReservationsExporter for XlsxExporter {
+    fn extension(&self) -> &'static str {
+        "xlsx"
+    }
+
+    fn export(&self, days: &[DayReservationsDTO], bot_username: &str) -> Result<Vec<u8>, Error> {
+        let formats = Formats::new();
+        let mut workbook = Workbook::new();
+
+        let mut write = || -> Result<Vec<u8>, XlsxError> {
+            if days.len() > 1 {
+                write_totals(&mut workbook, days, &formats)?;
+            }
+            for day in days {
+                write_day(&mut workbook, day, bot_username, &formats)?;
+            }
+            workbook.save_to_buffer()
+        };
+        write().map_err(|err| Error::Other(err.into()))
+    }
+}
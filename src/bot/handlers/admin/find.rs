@@ -0,0 +1,93 @@
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+use teloxide::utils::html;
+
+use super::admin::check_role;
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::domain::Error;
+use crate::domain::models::{StaffRole, UserID};
+use crate::usecases::{CheckAdminUseCase, SearchUsersUseCase, UserDTO};
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды поиска студентов")]
+enum FindCommand {
+    #[command(rename = "find", description = "поиск студента по ФИО: /find <ФИО>")]
+    Find(String),
+}
+
+fn format_user(u: &UserDTO) -> String {
+    let username = u.username.as_str();
+    format!(
+        "👤 <a href=\"tg://user?id={}\">{}</a> ({}){}\n\
+        ID: <code>{}</code>\n\
+        Гражданство: {}\n\
+        Дата прибытия: {}",
+        u.id,
+        html::escape(u.full_name_cyr.as_str()),
+        html::escape(u.full_name_lat.as_str()),
+        if username.is_empty() {
+            String::new()
+        } else {
+            format!(", @{}", html::escape(username))
+        },
+        u.id,
+        u.citizenship.as_str(),
+        u.arrival_date.format("%d.%m.%Y"),
+    )
+}
+
+async fn handle_find_command(
+    bot: Bot,
+    msg: Message,
+    full_name: String,
+    ca_use_case: CheckAdminUseCase,
+    su_use_case: SearchUsersUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    if full_name.trim().is_empty() {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Не указано ФИО</b>\n\
+            Используйте /find &lt;ФИО&gt;, например /find Иванов Иван",
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+
+    let actor = UserID::new(msg.chat.id.0);
+    let users = su_use_case.search(actor, &full_name).await?;
+    if users.is_empty() {
+        // Персональные данные зашифрованы, поэтому поиск возможен только по полному ФИО.
+        bot.send_message(
+            msg.chat.id,
+            "🔍 Никого не найдено\n\
+            Поиск идёт по полному ФИО латиницей или кириллицей, как при регистрации",
+        )
+        .await?;
+        return Ok(());
+    }
+
+    let text = users
+        .iter()
+        .map(format_user)
+        .collect::<Vec<_>>()
+        .join("\n\n");
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+pub fn find_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<FindCommand, _>()
+        .branch(case![FindCommand::Find(full_name)].endpoint(handle_find_command));
+
+    Update::filter_message().branch(command_handler)
+}
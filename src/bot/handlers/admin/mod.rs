@@ -1,3 +1,15 @@
 mod admin;
+mod audit;
+mod export;
+mod find;
+mod overrides;
+mod staff;
+mod stats;
 
 pub use admin::*;
+pub use audit::*;
+pub use export::*;
+pub use find::*;
+pub use overrides::*;
+pub use staff::*;
+pub use stats::*;
@@ -0,0 +1,206 @@
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use super::admin::check_role;
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::{
+    OVERRIDE_APPROVE_PREFIX, OVERRIDE_REJECT_PREFIX, make_override_resolve_keyboard,
+    make_reserve_link_keyboard, service_to_str,
+};
+use crate::domain::Error;
+use crate::domain::models::{StaffRole, UserID};
+use crate::usecases::{CheckAdminUseCase, DeadlineOverrideDTO, DeadlineOverridesUseCase};
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды записи после срока")]
+enum OverridesCommand {
+    #[command(rename = "overrides", description = "запросы на запись после срока")]
+    Overrides,
+}
+
+#[derive(Clone, Copy)]
+enum Decision {
+    Approve,
+    Reject,
+}
+
+fn format_override(o: &DeadlineOverrideDTO) -> String {
+    let username = o.user.username.as_str();
+    format!(
+        "📨 <b>Запрос на запись после срока #{}</b>\n\
+        Студент: <a href=\"tg://user?id={}\">{}</a> ({}){}\n\
+        Гражданство: {}\n\
+        Дата прибытия: {}\n\
+        Услуга: «{}»\n\
+        Срок истёк: {}",
+        o.id,
+        o.user.id,
+        o.user.full_name_cyr.as_str(),
+        o.user.full_name_lat.as_str(),
+        if username.is_empty() {
+            String::new()
+        } else {
+            format!(", @{}", username)
+        },
+        o.user.citizenship.as_str(),
+        o.user.arrival_date.format("%d.%m.%Y"),
+        service_to_str(&o.service),
+        o.deadline.format("%d.%m.%Y"),
+    )
+}
+
+/// Рассылает сотрудникам, которые могут его рассмотреть, новый запрос на запись после срока
+/// с кнопками решения.
+pub async fn notify_admins_about_override(
+    bot: &Bot,
+    use_case: &CheckAdminUseCase,
+    o: &DeadlineOverrideDTO,
+) -> HandlerResult {
+    for admin in use_case.admins_with_role(StaffRole::Operator).await? {
+        let res = bot
+            .send_message(ChatId(admin.as_i64()), format_override(o))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_override_resolve_keyboard(o.id))
+            .await;
+        if let Err(err) = res {
+            log::warn!("Failed to send deadline override {} to {}: {}", o.id, admin, err);
+        }
+    }
+    Ok(())
+}
+
+async fn handle_overrides_command(
+    bot: Bot,
+    msg: Message,
+    ca_use_case: CheckAdminUseCase,
+    do_use_case: DeadlineOverridesUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let pending = do_use_case.pending().await?;
+    if pending.is_empty() {
+        bot.send_message(msg.chat.id, "📭 Нет нерассмотренных запросов")
+            .await?;
+        return Ok(());
+    }
+    for o in pending.iter() {
+        bot.send_message(msg.chat.id, format_override(o))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_override_resolve_keyboard(o.id))
+            .await?;
+    }
+    Ok(())
+}
+
+fn parse_decision(q: CallbackQuery) -> Option<(Decision, i64)> {
+    let data = q.data.as_ref()?;
+    let (decision, id) = if let Some(id) = data.strip_prefix(OVERRIDE_APPROVE_PREFIX) {
+        (Decision::Approve, id)
+    } else {
+        (Decision::Reject, data.strip_prefix(OVERRIDE_REJECT_PREFIX)?)
+    };
+    Some((decision, id.parse::<i64>().ok()?))
+}
+
+async fn handle_decision(
+    bot: Bot,
+    q: CallbackQuery,
+    (decision, id): (Decision, i64),
+    ca_use_case: CheckAdminUseCase,
+    do_use_case: DeadlineOverridesUseCase,
+) -> HandlerResult {
+    let admin_id = UserID::new(q.from.id.0 as i64);
+    if !ca_use_case.has_role(admin_id, StaffRole::Operator).await? {
+        bot.answer_callback_query(q.id)
+            .text("⛔ Доступ запрещен")
+            .await?;
+        return Ok(());
+    }
+
+    let res = match decision {
+        Decision::Approve => do_use_case.approve(id, admin_id).await,
+        Decision::Reject => do_use_case.reject(id, admin_id).await,
+    };
+    let o = match res {
+        Ok(o) => o,
+        Err(Error::OverrideAlreadyResolved(_)) => {
+            bot.answer_callback_query(q.id)
+                .text("Запрос уже рассмотрен")
+                .await?;
+            if let Some(msg) = q.message {
+                bot.edit_message_reply_markup(msg.chat().id, msg.id())
+                    .await?;
+            }
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    bot.answer_callback_query(q.id).await?;
+
+    let verdict = match decision {
+        Decision::Approve => "✅ Разрешено",
+        Decision::Reject => "❌ Отказано",
+    };
+    if let Some(msg) = q.message {
+        bot.edit_message_text(
+            msg.chat().id,
+            msg.id(),
+            format!("{}\n\n<b>{}</b>", format_override(&o), verdict),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+    }
+
+    let student = ChatId(o.user.id.as_i64());
+    let res = match decision {
+        Decision::Approve => {
+            let me = bot.get_me().await?;
+            bot.send_message(
+                student,
+                format!(
+                    "✅ <b>Сотрудники УМД разрешили запись после срока</b>\n\
+                    Услуга: «{}». Разрешение действует на одну запись.",
+                    service_to_str(&o.service),
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_reserve_link_keyboard(&me, o.service))
+            .await
+        }
+        Decision::Reject => {
+            bot.send_message(
+                student,
+                format!(
+                    "❌ <b>Запрос на запись после срока отклонён</b>\n\
+                    Услуга: «{}». Обратитесь в УМД лично в часы приёма.",
+                    service_to_str(&o.service),
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await
+        }
+    };
+    if let Err(err) = res {
+        log::warn!("Failed to notify {} about deadline override: {}", o.user.id, err);
+    }
+    Ok(())
+}
+
+pub fn overrides_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<OverridesCommand, _>()
+        .branch(case![OverridesCommand::Overrides].endpoint(handle_overrides_command));
+
+    let message_handler = Update::filter_message().branch(command_handler);
+
+    let callback_handler = Update::filter_callback_query()
+        .branch(dptree::filter_map(parse_decision).endpoint(handle_decision));
+
+    dptree::entry()
+        .branch(message_handler)
+        .branch(callback_handler)
+}
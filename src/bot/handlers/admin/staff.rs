@@ -0,0 +1,137 @@
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::{ChatMemberUpdated, ParseMode};
+
+use super::admin::check_role;
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::staff_role_to_str;
+use crate::domain::Error;
+use crate::domain::models::{StaffRole, UserID};
+use crate::usecases::{CheckAdminUseCase, StaffUseCase};
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды управления сотрудниками")]
+enum StaffCommand {
+    #[command(rename = "staff", description = "список сотрудников и их ролей")]
+    Staff,
+
+    #[command(rename = "grant", description = "назначить роль: /grant <id> <viewer|operator|owner>")]
+    Grant(String),
+
+    #[command(rename = "revoke", description = "снять роль: /revoke <id>")]
+    Revoke(String),
+}
+
+fn parse_user_id(s: &str) -> Option<UserID> {
+    s.parse::<i64>().ok().map(UserID::new)
+}
+
+async fn handle_staff_command(
+    bot: Bot,
+    msg: Message,
+    ca_use_case: CheckAdminUseCase,
+    s_use_case: StaffUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Owner).await? {
+        return Ok(());
+    }
+    let staff = s_use_case.staff().await?;
+    let mut text = String::from("👥 <b>Сотрудники</b>\n");
+    for m in staff.iter() {
+        text.push_str(&format!(
+            "\n• <a href=\"tg://user?id={}\">{}</a>: {} ({})",
+            m.id,
+            m.id,
+            staff_role_to_str(&m.role),
+            m.role.as_str(),
+        ));
+    }
+    bot.send_message(msg.chat.id, text)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+async fn handle_grant_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    s_use_case: StaffUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Owner).await? {
+        return Ok(());
+    }
+    let parsed = args.split_once(' ').and_then(|(id, role)| {
+        Some((parse_user_id(id.trim())?, StaffRole::try_from(role.trim()).ok()?))
+    });
+    let Some((id, role)) = parsed else {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Неверный формат</b>\n\
+            Используйте /grant &lt;id&gt; &lt;viewer|operator|owner&gt;",
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        return Ok(());
+    };
+
+    s_use_case.grant(UserID::new(msg.chat.id.0), id, role).await?;
+    bot.send_message(
+        msg.chat.id,
+        format!("✅ Пользователю {} назначена роль «{}»", id, staff_role_to_str(&role)),
+    )
+    .await?;
+    Ok(())
+}
+
+async fn handle_revoke_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    s_use_case: StaffUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Owner).await? {
+        return Ok(());
+    }
+    let Some(id) = parse_user_id(args.trim()) else {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Неверный формат</b>\n\
+            Используйте /revoke &lt;id&gt;",
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        return Ok(());
+    };
+
+    let text = if s_use_case.revoke(UserID::new(msg.chat.id.0), id).await? {
+        format!("✅ Роль, назначенная через бота, снята с пользователя {}", id)
+    } else {
+        format!("📭 Пользователь {} не является сотрудником", id)
+    };
+    bot.send_message(msg.chat.id, text).await?;
+    Ok(())
+}
+
+/// Сбрасывает закэшированную роль участника, статус которого изменился в одной из групп бота.
+async fn handle_chat_member(update: ChatMemberUpdated, ca_use_case: CheckAdminUseCase) -> HandlerResult {
+    let user_id = UserID::new(update.new_chat_member.user.id.0 as i64);
+    ca_use_case.membership_changed(update.chat.id.0, user_id).await;
+    Ok(())
+}
+
+pub fn staff_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<StaffCommand, _>()
+        .branch(case![StaffCommand::Staff].endpoint(handle_staff_command))
+        .branch(case![StaffCommand::Grant(args)].endpoint(handle_grant_command))
+        .branch(case![StaffCommand::Revoke(args)].endpoint(handle_revoke_command));
+
+    dptree::entry()
+        .branch(Update::filter_message().branch(command_handler))
+        .branch(Update::filter_chat_member().endpoint(handle_chat_member))
+}
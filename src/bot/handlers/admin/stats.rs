@@ -0,0 +1,348 @@
+use chrono::{Datelike, Duration, NaiveDate, Utc};
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::{InputFile, ParseMode};
+
+use super::admin::{check_role, format_period, parse_period};
+use super::export::{citizenship_label, heatmap_png, stats_csv, weekday_short_name};
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::domain::Error;
+use crate::domain::models::{StaffRole, slot_today};
+use crate::usecases::{
+    CheckAdminUseCase, ForecastDTO, ForecastUseCase, MAX_FORECAST_DAYS, MAX_STATS_DAYS,
+    MISSED_DEADLINE_DAYS, StatsDTO, StatsUseCase,
+};
+
+/// Период статистики по умолчанию: последние 30 дней, включая сегодня.
+const DEFAULT_STATS_DAYS: i64 = 30;
+/// Длина прогноза по умолчанию в неделях.
+const DEFAULT_FORECAST_WEEKS: u64 = 4;
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды статистики")]
+enum StatsCommand {
+    #[command(
+        rename = "stats",
+        description = "статистика записей: /stats [ДД.ММ.ГГГГ-ДД.ММ.ГГГГ]"
+    )]
+    Stats(String),
+
+    #[command(
+        rename = "heatmap",
+        description = "заполненность по дням недели и времени: /heatmap [ДД.ММ.ГГГГ-ДД.ММ.ГГГГ]"
+    )]
+    Heatmap(String),
+
+    #[command(
+        rename = "forecast",
+        description = "прогноз: хватит ли мест студентам со сроком подачи документов: /forecast [недель]"
+    )]
+    Forecast(String),
+}
+
+/// Разбирает необязательный период из аргумента команды, по умолчанию - последние 30 дней.
+/// Если период неверный, сообщает об этом и возвращает None.
+async fn receive_period(
+    bot: &Bot,
+    msg: &Message,
+    command: &str,
+    args: &str,
+) -> Result<Option<(NaiveDate, NaiveDate)>, Error> {
+    let Some((from, to)) = parse_stats_period(args) else {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ <b>Неверный формат</b>\n\
+                Используйте /{0} или /{0} &lt;ДД.ММ.ГГГГ-ДД.ММ.ГГГГ&gt;",
+                command,
+            ),
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(None);
+    };
+    if from > to || (to - from).num_days() >= MAX_STATS_DAYS {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ <b>Неверный период</b>\n\
+                Начало должно быть не позже конца, а период - не длиннее {} дней.",
+                MAX_STATS_DAYS,
+            ),
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(None);
+    }
+    Ok(Some((from, to)))
+}
+
+fn parse_stats_period(args: &str) -> Option<(NaiveDate, NaiveDate)> {
+    let args = args.trim();
+    if args.is_empty() {
+        let today = slot_today(Utc::now());
+        return Some((today - Duration::days(DEFAULT_STATS_DAYS - 1), today));
+    }
+    parse_period(args)
+}
+
+fn format_percent(part: usize, total: usize) -> String {
+    if total == 0 {
+        return "—".to_string();
+    }
+    format!("{:.0}%", part as f64 * 100.0 / total as f64)
+}
+
+fn format_lead_time(lead_time: Duration) -> String {
+    if lead_time < Duration::days(1) {
+        format!("{} ч", lead_time.num_hours())
+    } else {
+        format!("{:.1} дн", lead_time.num_hours() as f64 / 24.0)
+    }
+}
+
+fn stats_text(stats: &StatsDTO) -> String {
+    let reserved = stats.reserved();
+    let working_days = stats.days.iter().filter(|d| d.capacity > 0).count();
+    let (no_shows, known_days) = stats.no_shows();
+    let attended: usize = stats
+        .days
+        .iter()
+        .filter(|d| d.no_shows.is_some())
+        .map(|d| d.attended)
+        .sum();
+
+    let mut text = format!(
+        "📊 <b>Статистика за {}</b>\n\n\
+        Дней приёма: {}\n\
+        Записей: {} из {} мест ({}), в среднем {:.1} в день приёма\n\
+        Отмен: {}\n",
+        format_period(stats.from, stats.to),
+        working_days,
+        reserved,
+        stats.capacity(),
+        stats
+            .utilisation()
+            .map(|u| format!("{:.0}%", u * 100.0))
+            .unwrap_or_else(|| "—".to_string()),
+        if working_days == 0 {
+            0.0
+        } else {
+            reserved as f64 / working_days as f64
+        },
+        stats.cancelled(),
+    );
+    if known_days == 0 {
+        text.push_str("Неявки: приход через /checkin не отмечали, неявки не посчитать\n");
+    } else {
+        text.push_str(&format!(
+            "Неявки: {} из {} записей ({}) за {} дн. с отметками прихода; \
+            дни без отметок через /checkin не учитываются\n",
+            no_shows,
+            no_shows + attended,
+            format_percent(no_shows, no_shows + attended),
+            known_days,
+        ));
+    }
+    if let Some(lead_time) = stats.average_lead_time {
+        text.push_str(&format!(
+            "Записываются в среднем за {} до приёма\n",
+            format_lead_time(lead_time),
+        ));
+    }
+
+    if !stats.by_service.is_empty() {
+        text.push_str("\n<b>По услугам</b>\n");
+        for (service, count) in &stats.by_service {
+            text.push_str(&format!(
+                "• {}: {} ({})\n",
+                service_to_str(service),
+                count,
+                format_percent(*count, reserved),
+            ));
+        }
+    }
+    if !stats.by_citizenship.is_empty() {
+        text.push_str("\n<b>По гражданству</b>\n");
+        for (citizenship, count) in &stats.by_citizenship {
+            text.push_str(&format!(
+                "• {}: {} ({})\n",
+                citizenship_label(citizenship.as_str()),
+                count,
+                format_percent(*count, reserved),
+            ));
+        }
+    }
+    text
+}
+
+async fn handle_stats_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    stats_use_case: StatsUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let Some((from, to)) = receive_period(&bot, &msg, "stats", &args).await? else {
+        return Ok(());
+    };
+
+    let stats = stats_use_case.stats(from, to).await?;
+    if stats.days.is_empty() {
+        bot.send_message(msg.chat.id, "📭 В этот период не было приёма и записей")
+            .await?;
+        return Ok(());
+    }
+    bot.send_message(msg.chat.id, stats_text(&stats))
+        .parse_mode(ParseMode::Html)
+        .await?;
+    let file_name = format!(
+        "stats_{}_{}.csv",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d"),
+    );
+    let input_file = InputFile::memory(stats_csv(&stats)?).file_name(file_name);
+    bot.send_document(msg.chat.id, input_file).await?;
+    Ok(())
+}
+
+async fn handle_heatmap_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    stats_use_case: StatsUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let Some((from, to)) = receive_period(&bot, &msg, "heatmap", &args).await? else {
+        return Ok(());
+    };
+
+    let heatmap = stats_use_case.heatmap(from, to).await?;
+    if heatmap.weekdays.is_empty() {
+        bot.send_message(msg.chat.id, "📭 В этот период нет приёма")
+            .await?;
+        return Ok(());
+    }
+    let file_name = format!(
+        "heatmap_{}_{}.png",
+        from.format("%Y-%m-%d"),
+        to.format("%Y-%m-%d"),
+    );
+    let input_file = InputFile::memory(heatmap_png(&heatmap)?).file_name(file_name);
+    bot.send_photo(msg.chat.id, input_file)
+        .caption(format!(
+            "🌡 Средняя заполненность слотов за {}",
+            format_period(from, to),
+        ))
+        .await?;
+    Ok(())
+}
+
+fn forecast_text(forecast: &ForecastDTO) -> String {
+    let (Some(first), Some(last)) = (forecast.days.first(), forecast.days.last()) else {
+        return "📭 Нет дней для прогноза".to_string();
+    };
+    let mut text = format!(
+        "📈 <b>Прогноз на {}</b>\n\
+        Не записались, срок подачи документов в эти дни: {}\n\
+        Срок истёк за последние {} дн.: {}\n\
+        Срок позже: {}\n\n\
+        <pre>Дата     Срок  Мест  Нужно/Есть\n",
+        format_period(first.date, last.date),
+        last.demand,
+        MISSED_DEADLINE_DAYS,
+        forecast.overdue,
+        forecast.later,
+    );
+    // Выходные без сроков не показываются.
+    for day in forecast
+        .days
+        .iter()
+        .filter(|d| d.free > 0 || d.due > 0 || d.shortage() > 0)
+    {
+        text.push_str(&format!(
+            "{} {} {:>4} {:>5} {:>6}/{}{}\n",
+            weekday_short_name(day.date.weekday()),
+            day.date.format("%d.%m"),
+            day.due,
+            day.free,
+            day.demand,
+            day.supply,
+            if day.shortage() > 0 { " ⚠️" } else { "" },
+        ));
+    }
+    text.push_str("</pre>\n");
+
+    let first_shortage = forecast.shortage_days().next();
+    let worst = forecast.shortage_days().max_by_key(|d| d.shortage());
+    match (first_shortage, worst) {
+        (Some(first), Some(worst)) => text.push_str(&format!(
+            "⚠️ <b>Мест не хватит к {}</b>: нужно принять {}, свободно {}.\n\
+            Больше всего не хватает к {}: {} мест. Откройте дополнительные часы заранее.",
+            first.date.format("%d.%m.%Y"),
+            first.demand,
+            first.supply,
+            worst.date.format("%d.%m.%Y"),
+            worst.shortage(),
+        )),
+        _ => text.push_str("✅ Мест хватает всем, у кого истекает срок"),
+    }
+    text
+}
+
+async fn handle_forecast_command(
+    bot: Bot,
+    msg: Message,
+    args: String,
+    ca_use_case: CheckAdminUseCase,
+    f_use_case: ForecastUseCase,
+) -> HandlerResult {
+    if !check_role(&bot, &msg, &ca_use_case, StaffRole::Viewer).await? {
+        return Ok(());
+    }
+    let args = args.trim();
+    let weeks = if args.is_empty() {
+        Some(DEFAULT_FORECAST_WEEKS)
+    } else {
+        args.parse::<u64>().ok()
+    };
+    let max_weeks = MAX_FORECAST_DAYS / 7;
+    let Some(weeks) = weeks.filter(|weeks| (1..=max_weeks).contains(weeks)) else {
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "❌ <b>Неверное число недель</b>\n\
+                Используйте /forecast или /forecast &lt;недель&gt;, не больше {}",
+                max_weeks,
+            ),
+        )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        return Ok(());
+    };
+
+    let forecast = f_use_case.forecast(weeks * 7).await?;
+    bot.send_message(msg.chat.id, forecast_text(&forecast))
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+pub fn stats_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<StatsCommand, _>()
+        .branch(case![StatsCommand::Stats(args)].endpoint(handle_stats_command))
+        .branch(case![StatsCommand::Heatmap(args)].endpoint(handle_heatmap_command))
+        .branch(case![StatsCommand::Forecast(args)].endpoint(handle_forecast_command));
+
+    Update::filter_message().branch(command_handler)
+}
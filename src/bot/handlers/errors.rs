@@ -0,0 +1,278 @@
+use chrono::{DateTime, Duration, Utc};
+use std::collections::VecDeque;
+use std::ops::ControlFlow;
+use std::sync::{Arc, Mutex};
+use teloxide::dispatching::dialogue::GetChatId;
+use teloxide::dispatching::{DpHandlerDescription, UpdateHandler};
+use teloxide::dptree::HandlerDescription;
+use teloxide::dptree::di::{DependencyMap, DependencySupplier};
+use teloxide::prelude::*;
+use teloxide::types::{ChatId, ParseMode, UpdateKind};
+use teloxide::utils::html;
+
+use crate::dispatcher::DialogueStorages;
+use crate::domain::Error;
+
+/// Не больше `ALERT_LIMIT` уведомлений администраторам за `ALERT_WINDOW_MINUTES` минут.
+const ALERT_LIMIT: usize = 5;
+const ALERT_WINDOW_MINUTES: i64 = 10;
+
+/// Текст, который увидит пользователь, если обработчик вернул ошибку.
+pub fn user_message(err: &Error) -> String {
+    match err {
+        Error::InvalidValue(_) => {
+            "❌ Некорректное значение. Проверьте введённые данные и попробуйте ещё раз.".to_string()
+        }
+        Error::MaxCapacityExceeded(_) => {
+            "❌ На это время больше нет свободных мест. Выберите другое время: /reserve".to_string()
+        }
+        Error::UserNotFound(_) => {
+            "❌ Вы еще не зарегистрированы. Используйте /start для регистрации.".to_string()
+        }
+        Error::UserNotReserved(_) => {
+            "❌ У вас нет активной записи. Записаться: /reserve".to_string()
+        }
+        Error::SlotNotFoundError => {
+            "❌ Выбранное время недоступно. Выберите другое: /reserve".to_string()
+        }
+        Error::SlotAlreadyReserved(_) => {
+            "❌ Вы уже записаны. Посмотреть запись: /status".to_string()
+        }
+        Error::DocumentTooLarge(size) => format!(
+            "❌ Файл слишком большой. Максимальный размер: {} МБ",
+            size / (1024 * 1024)
+        ),
+        Error::UnsupportedDocumentType(kind) => {
+            format!("❌ Формат файла не поддерживается: {}", kind)
+        }
+        Error::DeadlineExpired(deadline) => format!(
+            "❌ Срок подачи документов истёк {}. Запросите запись у сотрудников: /reserve",
+            deadline.format("%d.%m.%Y")
+        ),
+        Error::OverrideNotFound(_) => "❌ Запрос не найден".to_string(),
+        Error::OverrideAlreadyRequested => {
+            "❌ Запрос уже отправлен, дождитесь решения сотрудников".to_string()
+        }
+        Error::OverrideAlreadyResolved(_) => "❌ Запрос уже рассмотрен".to_string(),
+        Error::OverrideNotApproved(_) => "❌ Запрос ещё не одобрен сотрудниками".to_string(),
+        Error::LastOwner(_) => {
+            "❌ Нельзя понизить или лишить роли последнего владельца. Сначала назначьте другого."
+                .to_string()
+        }
+        Error::RoleFromGroup(_) => {
+            "❌ Роль выдана группой сотрудников. Чтобы снять её, исключите пользователя из группы \
+            или снимите с него права администратора."
+                .to_string()
+        }
+        Error::Conflict(_) | Error::SerializationFailure(_) => {
+            "⚠️ Данные изменились, пока вы их вводили. Попробуйте ещё раз.".to_string()
+        }
+        Error::Unavailable(_) => {
+            "⚠️ Сервис временно недоступен. Попробуйте через несколько минут.".to_string()
+        }
+        Error::Other(_) => {
+            "⚠️ Что-то пошло не так. Попробуйте позже или начните заново: /cancel".to_string()
+        }
+    }
+}
+
+/// Непредвиденные ошибки, о которых нужно сообщить администраторам.
+fn is_unexpected(err: &Error) -> bool {
+    matches!(
+        err,
+        Error::Unavailable(_) | Error::SerializationFailure(_) | Error::Other(_)
+    )
+}
+
+/// Скользящее окно, ограничивающее число уведомлений, чтобы, например, недоступность БД
+/// не завалила чат администраторов.
+struct AlertRateLimiter {
+    limit: usize,
+    window: Duration,
+    sent: VecDeque<DateTime<Utc>>,
+    suppressed: usize,
+}
+
+impl AlertRateLimiter {
+    fn new(limit: usize, window: Duration) -> Self {
+        Self {
+            limit,
+            window,
+            sent: VecDeque::new(),
+            suppressed: 0,
+        }
+    }
+
+    /// Возвращает число подавленных с прошлой отправки уведомлений, если отправить можно.
+    fn allow(&mut self, now: DateTime<Utc>) -> Option<usize> {
+        while self.sent.front().is_some_and(|&t| now - t >= self.window) {
+            self.sent.pop_front();
+        }
+        if self.sent.len() >= self.limit {
+            self.suppressed += 1;
+            return None;
+        }
+        self.sent.push_back(now);
+        Some(std::mem::take(&mut self.suppressed))
+    }
+}
+
+/// ErrorReporter пересылает непредвиденные ошибки в чат администраторов.
+#[derive(Clone)]
+pub struct ErrorReporter {
+    admin_chat: Option<ChatId>,
+    limiter: Arc<Mutex<AlertRateLimiter>>,
+}
+
+impl ErrorReporter {
+    pub fn new(admin_chat: Option<ChatId>) -> Self {
+        Self {
+            admin_chat,
+            limiter: Arc::new(Mutex::new(AlertRateLimiter::new(
+                ALERT_LIMIT,
+                Duration::minutes(ALERT_WINDOW_MINUTES),
+            ))),
+        }
+    }
+
+    async fn report(&self, bot: &Bot, err: &Error, upd: &Update, storages: &DialogueStorages) {
+        let Some(admin_chat) = self.admin_chat else {
+            return;
+        };
+        let Some(suppressed) = self.limiter.lock().unwrap().allow(Utc::now()) else {
+            return;
+        };
+
+        let chat = upd
+            .chat_id()
+            .map(|id| id.to_string())
+            .unwrap_or("—".to_string());
+        let state = match upd.chat_id() {
+            Some(chat_id) => match storages.describe(chat_id).await {
+                Ok(states) if states.is_empty() => "нет активных диалогов".to_string(),
+                Ok(states) => states.join("; "),
+                Err(err) => format!("не удалось получить: {}", err),
+            },
+            None => "—".to_string(),
+        };
+        let mut text = format!(
+            "🚨 <b>Ошибка при обработке обновления</b>\n\
+            Чат: <code>{}</code>\n\
+            Обновление: <code>{}</code>\n\
+            Состояние: <code>{}</code>\n\
+            Ошибка: <code>{}</code>",
+            chat,
+            html::escape(&describe_update(upd)),
+            html::escape(&state),
+            html::escape(&err.to_string()),
+        );
+        if suppressed > 0 {
+            text.push_str(&format!("\n\nПропущено уведомлений: {}", suppressed));
+        }
+
+        if let Err(err) = bot
+            .send_message(admin_chat, text)
+            .parse_mode(ParseMode::Html)
+            .await
+        {
+            log::error!("Failed to send error alert to {}: {}", admin_chat, err);
+        }
+    }
+}
+
+/// Вид обновления: по нему и состоянию диалога видно, какой обработчик его получил. Текст
+/// сообщений и данные кнопок не указываются: в них бывают персональные данные.
+fn describe_update(upd: &Update) -> String {
+    match &upd.kind {
+        UpdateKind::Message(msg) if msg.text().is_some() => "сообщение".to_string(),
+        UpdateKind::Message(msg) if msg.document().is_some() || msg.photo().is_some() => {
+            "файл".to_string()
+        }
+        UpdateKind::Message(_) => "сообщение без текста".to_string(),
+        UpdateKind::CallbackQuery(_) => "кнопка".to_string(),
+        kind => format!("{:?}", kind)
+            .split(|c: char| !c.is_alphanumeric())
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+    }
+}
+
+async fn handle_error(
+    err: Error,
+    bot: &Bot,
+    upd: &Update,
+    reporter: &ErrorReporter,
+    storages: &DialogueStorages,
+) {
+    if is_unexpected(&err) {
+        log::error!("Failed to handle update {}: {}", upd.id.0, err);
+        reporter.report(bot, &err, upd, storages).await;
+    } else {
+        log::warn!("Handler returned {} for update {}", err, upd.id.0);
+    }
+
+    let Some(chat_id) = upd.chat_id() else {
+        return;
+    };
+    if let Err(err) = bot.send_message(chat_id, user_message(&err)).await {
+        log::error!("Failed to notify {} about error: {}", chat_id, err);
+    }
+}
+
+/// Перехватывает ошибки обработчиков схемы `schema`: сообщает пользователю, что пошло не так,
+/// а о непредвиденных ошибках — администраторам.
+pub fn with_error_handling(schema: UpdateHandler<Error>) -> UpdateHandler<Error> {
+    let catch = |deps: DependencyMap, cont: dptree::Cont<'static, _, _>| async move {
+        let bot: Arc<Bot> = deps.get();
+        let upd: Arc<Update> = deps.get();
+        let reporter: Arc<ErrorReporter> = deps.get();
+        let storages: Arc<DialogueStorages> = deps.get();
+        match cont(deps).await {
+            ControlFlow::Break(Err(err)) => {
+                handle_error(err, &bot, &upd, &reporter, &storages).await;
+                ControlFlow::Break(Ok(()))
+            }
+            res => res,
+        }
+    };
+    dptree::from_fn_with_description(DpHandlerDescription::entry(), catch).chain(schema)
+}
+
+#[cfg(test)]
+mod errors_tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_alerts_are_rate_limited() {
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+        let mut limiter = AlertRateLimiter::new(2, Duration::minutes(10));
+
+        // GIVEN лимит исчерпан
+        assert_eq!(limiter.allow(start), Some(0));
+        assert_eq!(limiter.allow(start + Duration::minutes(1)), Some(0));
+
+        // WHEN ошибки продолжаются в том же окне
+        // THEN уведомления подавляются
+        assert_eq!(limiter.allow(start + Duration::minutes(2)), None);
+        assert_eq!(limiter.allow(start + Duration::minutes(3)), None);
+
+        // AND после окна первое уведомление сообщает о пропущенных
+        assert_eq!(limiter.allow(start + Duration::minutes(10)), Some(2));
+        assert_eq!(limiter.allow(start + Duration::minutes(11)), Some(0));
+    }
+
+    #[test]
+    fn test_only_infrastructure_errors_are_unexpected() {
+        // GIVEN ошибка инфраструктуры и ошибка предметной области
+        let other = Error::Other("connection refused".into());
+        let domain = Error::SlotNotFoundError;
+
+        // THEN администраторам сообщается только об ошибке инфраструктуры
+        assert!(is_unexpected(&other));
+        assert!(!is_unexpected(&domain));
+        // AND пользователь не видит подробностей ошибки
+        assert!(!user_message(&other).contains("connection refused"));
+    }
+}
@@ -1,5 +1,5 @@
 use serde::{Deserialize, Serialize};
-use teloxide::RequestError;
+use teloxide::{DownloadError, RequestError};
 use teloxide::dispatching::dialogue::InMemStorageError;
 
 use crate::domain::Error;
@@ -24,3 +24,9 @@ impl From<InMemStorageError> for Error {
         Self::Other(value.into())
     }
 }
+
+impl From<DownloadError> for Error {
+    fn from(value: DownloadError) -> Self {
+        Self::Other(value.into())
+    }
+}
@@ -1,8 +1,13 @@
-use crate::domain::models::Service;
+use crate::bot::handlers::admin::ExportFormat;
+use crate::domain::models::{
+    Citizenship, DocumentKind, ReservationStatus, ReservationsFilter, Service, Sex, StaffRole,
+};
 use crate::usecases::FreeSlotDTO;
 use chrono::{DateTime, NaiveDate, Utc};
 use std::collections::HashMap;
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup};
+use teloxide::types::{
+    InlineKeyboardButton, InlineKeyboardMarkup, KeyboardButton, KeyboardMarkup, Me,
+};
 
 pub const AGREEMENT_BTN: &'static str = "Подтверждаю";
 
@@ -47,10 +52,32 @@ pub fn make_citizenship_keyboard() -> KeyboardMarkup {
         .one_time_keyboard()
 }
 
+pub const SKIP_BTN: &str = "Пропустить";
+
+pub fn make_skip_keyboard() -> KeyboardMarkup {
+    KeyboardMarkup::new(vec![vec![KeyboardButton::new(SKIP_BTN)]])
+        .resize_keyboard()
+        .one_time_keyboard()
+}
+
+/// Разбирает необязательную дату в формате ДД.ММ.ГГГГ, кнопка «Пропустить» означает её отсутствие.
+pub fn parse_optional_date(text: &str) -> Option<Option<NaiveDate>> {
+    if text == SKIP_BTN {
+        return Some(None);
+    }
+    NaiveDate::parse_from_str(text, "%d.%m.%Y").ok().map(Some)
+}
+
 pub const FIELD_NAME_LAT_BTN: &'static str = "Имя на латинице";
 pub const FIELD_NAME_CYR_BTN: &'static str = "Имя на кириллицe";
 pub const FIELD_CITIZENSHIP_BTN: &'static str = "Гражданство";
 pub const FIELD_ARRIVAL_DATE_BTN: &'static str = "Дата прибытия";
+pub const FIELD_VISA_EXPIRY_BTN: &str = "Срок визы";
+pub const FIELD_REGISTRATION_EXPIRY_BTN: &str = "Срок регистрации";
+pub const FIELD_BIRTH_DATE_BTN: &str = "Дата рождения";
+pub const FIELD_SEX_BTN: &str = "Пол";
+pub const FIELD_PASSPORT_BTN: &str = "Паспорт";
+pub const FIELD_PLACE_OF_STAY_BTN: &str = "Место пребывания";
 
 pub fn make_field_selection_keyboard() -> KeyboardMarkup {
     let buttons = vec![
@@ -62,6 +89,18 @@ pub fn make_field_selection_keyboard() -> KeyboardMarkup {
             KeyboardButton::new(FIELD_CITIZENSHIP_BTN),
             KeyboardButton::new(FIELD_ARRIVAL_DATE_BTN),
         ],
+        vec![
+            KeyboardButton::new(FIELD_VISA_EXPIRY_BTN),
+            KeyboardButton::new(FIELD_REGISTRATION_EXPIRY_BTN),
+        ],
+        vec![
+            KeyboardButton::new(FIELD_BIRTH_DATE_BTN),
+            KeyboardButton::new(FIELD_SEX_BTN),
+        ],
+        vec![
+            KeyboardButton::new(FIELD_PASSPORT_BTN),
+            KeyboardButton::new(FIELD_PLACE_OF_STAY_BTN),
+        ],
     ];
 
     KeyboardMarkup::new(buttons)
@@ -69,6 +108,33 @@ pub fn make_field_selection_keyboard() -> KeyboardMarkup {
         .one_time_keyboard()
 }
 
+pub const SEX_MALE_BTN: &str = "Мужской";
+pub const SEX_FEMALE_BTN: &str = "Женский";
+
+pub fn make_sex_keyboard() -> KeyboardMarkup {
+    KeyboardMarkup::new(vec![vec![
+        KeyboardButton::new(SEX_MALE_BTN),
+        KeyboardButton::new(SEX_FEMALE_BTN),
+    ]])
+    .resize_keyboard()
+    .one_time_keyboard()
+}
+
+pub fn sex_to_str(s: &Sex) -> &'static str {
+    match s {
+        Sex::Male => SEX_MALE_BTN,
+        Sex::Female => SEX_FEMALE_BTN,
+    }
+}
+
+pub fn sex_from_str(s: &str) -> Option<Sex> {
+    match s {
+        SEX_MALE_BTN => Some(Sex::Male),
+        SEX_FEMALE_BTN => Some(Sex::Female),
+        _ => None,
+    }
+}
+
 pub fn service_to_str(s: &Service) -> &'static str {
     match s {
         Service::InitialRegistration => "Первичная регистрация",
@@ -106,6 +172,46 @@ pub fn make_service_keyboard() -> KeyboardMarkup {
     .one_time_keyboard()
 }
 
+pub fn document_kind_to_str(k: &DocumentKind) -> &'static str {
+    match k {
+        DocumentKind::Passport => "Паспорт",
+        DocumentKind::MigrationCard => "Миграционная карта",
+        DocumentKind::Visa => "Виза",
+    }
+}
+
+pub fn staff_role_to_str(r: &StaffRole) -> &'static str {
+    match r {
+        StaffRole::Viewer => "Наблюдатель",
+        StaffRole::Operator => "Оператор",
+        StaffRole::Owner => "Владелец",
+    }
+}
+
+pub fn document_kind_from_str(s: &str) -> Option<DocumentKind> {
+    match s {
+        "Паспорт" => Some(DocumentKind::Passport),
+        "Миграционная карта" => Some(DocumentKind::MigrationCard),
+        "Виза" => Some(DocumentKind::Visa),
+        _ => None,
+    }
+}
+
+pub const DONE_BTN: &str = "Готово";
+
+pub fn make_document_kind_keyboard() -> KeyboardMarkup {
+    let mut buttons = vec![
+        DocumentKind::all()
+            .iter()
+            .map(|k| KeyboardButton::new(document_kind_to_str(k)))
+            .collect::<Vec<KeyboardButton>>(),
+    ];
+    buttons.push(vec![KeyboardButton::new(DONE_BTN)]);
+    KeyboardMarkup::new(buttons)
+        .resize_keyboard()
+        .one_time_keyboard()
+}
+
 pub fn make_days_keyboard_with_back(days: &[NaiveDate]) -> KeyboardMarkup {
     let mut buttons = days
         .chunks(4)
@@ -147,3 +253,98 @@ pub fn make_cancel_inline_keyboard(slot_start: DateTime<Utc>) -> InlineKeyboardM
         slot_start.to_string(),
     )]])
 }
+
+/// Префикс параметра ссылки t.me/<bot>?start=reserve_<service> на запись на конкретную услугу.
+pub const RESERVE_LINK_PREFIX: &str = "reserve_";
+
+pub fn make_reserve_link_keyboard(me: &Me, service: Service) -> InlineKeyboardMarkup {
+    let service_str: String = service.into();
+    let mut url = me.tme_url();
+    url.query_pairs_mut()
+        .append_pair("start", &format!("{}{}", RESERVE_LINK_PREFIX, service_str));
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::url(
+        format!("Записаться: {}", service_to_str(&service)),
+        url,
+    )]])
+}
+
+/// Префикс данных кнопки запроса на запись после окончания срока: ovr_req_<service>.
+pub const OVERRIDE_REQUEST_PREFIX: &str = "ovr_req_";
+/// Префиксы данных кнопок решения по запросу: ovr_ok_<id> и ovr_no_<id>.
+pub const OVERRIDE_APPROVE_PREFIX: &str = "ovr_ok_";
+pub const OVERRIDE_REJECT_PREFIX: &str = "ovr_no_";
+
+pub fn make_override_request_keyboard(service: Service) -> InlineKeyboardMarkup {
+    let service_str: String = service.into();
+    InlineKeyboardMarkup::new(vec![vec![InlineKeyboardButton::callback(
+        "Отправить запрос сотрудникам",
+        format!("{}{}", OVERRIDE_REQUEST_PREFIX, service_str),
+    )]])
+}
+
+pub fn make_override_resolve_keyboard(id: i64) -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("✅ Разрешить", format!("{}{}", OVERRIDE_APPROVE_PREFIX, id)),
+        InlineKeyboardButton::callback("❌ Отказать", format!("{}{}", OVERRIDE_REJECT_PREFIX, id)),
+    ]])
+}
+
+pub const REVOKE_CONSENT_CONFIRM: &str = "consent_revoke";
+pub const REVOKE_CONSENT_ABORT: &str = "consent_keep";
+
+pub fn make_revoke_consent_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("🗑 Отозвать", REVOKE_CONSENT_CONFIRM),
+        InlineKeyboardButton::callback("Оставить", REVOKE_CONSENT_ABORT),
+    ]])
+}
+
+pub const DELETE_ME_CONFIRM: &str = "delete_me";
+pub const DELETE_ME_ABORT: &str = "delete_me_keep";
+
+pub fn make_delete_me_keyboard() -> InlineKeyboardMarkup {
+    InlineKeyboardMarkup::new(vec![vec![
+        InlineKeyboardButton::callback("🗑 Удалить", DELETE_ME_CONFIRM),
+        InlineKeyboardButton::callback("Оставить", DELETE_ME_ABORT),
+    ]])
+}
+
+pub fn reservation_status_to_str(s: &ReservationStatus) -> &'static str {
+    match s {
+        ReservationStatus::Upcoming => "Предстоящие",
+        ReservationStatus::Past => "Прошедшие",
+    }
+}
+
+/// Данные кнопок фильтров выгрузки /table: нажатие переключает фильтр на следующее значение.
+pub const TABLE_SERVICE_BTN: &str = "table_service";
+pub const TABLE_CITIZENSHIP_BTN: &str = "table_citizenship";
+pub const TABLE_STATUS_BTN: &str = "table_status";
+pub const TABLE_FORMAT_BTN: &str = "table_format";
+pub const TABLE_EXPORT_BTN: &str = "table_export";
+
+pub fn make_table_filters_keyboard(
+    filter: &ReservationsFilter,
+    format: ExportFormat,
+) -> InlineKeyboardMarkup {
+    let service = filter.service.as_ref().map(service_to_str).unwrap_or("все");
+    let citizenship = match &filter.citizenship {
+        None => "все",
+        Some(Citizenship::Other(_)) => "Другое",
+        Some(c) => c.as_str(),
+    };
+    let status = filter.status.as_ref().map(reservation_status_to_str).unwrap_or("все");
+    InlineKeyboardMarkup::new(vec![
+        vec![InlineKeyboardButton::callback(format!("Услуга: {}", service), TABLE_SERVICE_BTN)],
+        vec![InlineKeyboardButton::callback(
+            format!("Гражданство: {}", citizenship),
+            TABLE_CITIZENSHIP_BTN,
+        )],
+        vec![InlineKeyboardButton::callback(format!("Статус: {}", status), TABLE_STATUS_BTN)],
+        vec![InlineKeyboardButton::callback(
+            format!("Формат: {}", format.as_str()),
+            TABLE_FORMAT_BTN,
+        )],
+        vec![InlineKeyboardButton::callback("📥 Выгрузить", TABLE_EXPORT_BTN)],
+    ])
+}
@@ -1,5 +1,7 @@
 mod fsm;
-mod keyboards;
+pub mod keyboards;
 
 pub mod admin;
+pub mod errors;
+pub mod session;
 pub mod user;
@@ -0,0 +1,51 @@
+use teloxide::dispatching::UpdateHandler;
+use teloxide::dispatching::dialogue::GetChatId;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::KeyboardRemove;
+
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::dispatcher::DialogueStorages;
+use crate::domain::Error;
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Общие команды")]
+enum SessionCommand {
+    #[command(rename = "cancel", description = "отменить текущую операцию")]
+    Cancel,
+}
+
+async fn handle_cancel_command(
+    bot: Bot,
+    msg: Message,
+    storages: DialogueStorages,
+) -> HandlerResult {
+    let text = if storages.reset(msg.chat.id).await? {
+        "❌ Текущая операция отменена"
+    } else {
+        "🔹 Нет операции для отмены"
+    };
+    bot.send_message(msg.chat.id, text)
+        .reply_markup(KeyboardRemove::new())
+        .await?;
+    Ok(())
+}
+
+/// Отмечает активность чата, по которой периодически сбрасываются брошенные диалоги.
+pub async fn track_activity(upd: Update, storages: DialogueStorages) {
+    let Some(chat_id) = upd.chat_id() else {
+        return;
+    };
+    if let Err(err) = storages.touch(chat_id).await {
+        log::error!("Failed to track activity of {}: {}", chat_id, err);
+    }
+}
+
+pub fn session_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<SessionCommand, _>()
+        .branch(case![SessionCommand::Cancel].endpoint(handle_cancel_command));
+
+    Update::filter_message().branch(command_handler)
+}
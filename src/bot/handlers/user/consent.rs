@@ -0,0 +1,134 @@
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::{KeyboardRemove, ParseMode};
+
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::{
+    AGREEMENT_BTN, REVOKE_CONSENT_ABORT, REVOKE_CONSENT_CONFIRM, make_agreement_keyboard,
+    make_revoke_consent_keyboard,
+};
+use crate::dispatcher::DialogueStorages;
+use crate::domain::Error;
+use crate::domain::models::UserID;
+use crate::usecases::{ConsentUseCase, RevokeConsentUseCase};
+
+/// Редакция текста согласия [`CONSENT_TEXT`]. При изменении текста её нужно увеличить, чтобы
+/// пользователи дали согласие заново.
+pub const CONSENT_VERSION: u32 = 1;
+
+pub const CONSENT_TEXT: &str =
+    "Для продолжения работы необходимо дать согласие на обработку персональных данных, \
+    согласно с Федеральным законом РФ от 27.07.2006 №152-ФЗ «О персональных данных».\n\
+    Согласие можно отозвать командой /revoke_consent.";
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Согласие на обработку персональных данных")]
+enum ConsentCommand {
+    #[command(rename = "revoke_consent", description = "отозвать согласие и удалить свои данные")]
+    RevokeConsent,
+}
+
+async fn needs_consent(msg: Message, use_case: ConsentUseCase) -> bool {
+    let id = UserID::new(msg.chat.id.0);
+    match use_case.needs_consent(id).await {
+        Ok(needs) => needs,
+        Err(err) => {
+            log::error!("Failed to check consent of {}: {}", id, err);
+            false
+        }
+    }
+}
+
+/// Пока зарегистрированный пользователь не согласится с действующей редакцией текста,
+/// остальные команды ему недоступны.
+async fn receive_consent(bot: Bot, msg: Message, use_case: ConsentUseCase) -> HandlerResult {
+    if msg.text() == Some(AGREEMENT_BTN) {
+        use_case.give(UserID::new(msg.chat.id.0)).await?;
+        bot.send_message(msg.chat.id, "✅ Спасибо! Можно продолжать работу.")
+            .reply_markup(KeyboardRemove::new())
+            .await?;
+        return Ok(());
+    }
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "📄 <b>Обновлён текст согласия</b>\n{}",
+            CONSENT_TEXT
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(make_agreement_keyboard())
+    .await?;
+    Ok(())
+}
+
+async fn handle_revoke_consent_command(bot: Bot, msg: Message) -> HandlerResult {
+    bot.send_message(
+        msg.chat.id,
+        "⚠️ <b>Отозвать согласие на обработку персональных данных?</b>\n\
+        Будущие записи будут отменены, загруженные документы удалены, \
+        а ваши данные обезличены. Пользоваться ботом можно будет только \
+        после повторной регистрации: /start",
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(make_revoke_consent_keyboard())
+    .await?;
+    Ok(())
+}
+
+async fn handle_revoke_consent(
+    bot: Bot,
+    q: CallbackQuery,
+    use_case: RevokeConsentUseCase,
+    storages: DialogueStorages,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id).await?;
+    let Some(msg) = q.message else {
+        return Ok(());
+    };
+    let cancelled = use_case.revoke(UserID::new(q.from.id.0 as i64)).await?;
+    storages.reset(msg.chat().id).await?;
+    let mut text = String::from("✅ <b>Согласие отозвано</b>\nВаши данные обезличены.");
+    if cancelled > 0 {
+        text.push_str(&format!("\nОтменено записей: {}.", cancelled));
+    }
+    bot.edit_message_text(msg.chat().id, msg.id(), text)
+        .parse_mode(ParseMode::Html)
+        .await?;
+    Ok(())
+}
+
+async fn handle_keep_consent(bot: Bot, q: CallbackQuery) -> HandlerResult {
+    bot.answer_callback_query(q.id).await?;
+    if let Some(msg) = q.message {
+        bot.edit_message_text(msg.chat().id, msg.id(), "🔹 Согласие сохранено")
+            .await?;
+    }
+    Ok(())
+}
+
+pub fn consent_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<ConsentCommand, _>()
+        .branch(case![ConsentCommand::RevokeConsent].endpoint(handle_revoke_consent_command));
+
+    let message_handler = Update::filter_message()
+        .branch(command_handler)
+        .branch(dptree::filter_async(needs_consent).endpoint(receive_consent));
+
+    let callback_handler = Update::filter_callback_query()
+        .branch(
+            dptree::filter(|q: CallbackQuery| q.data.as_deref() == Some(REVOKE_CONSENT_CONFIRM))
+                .endpoint(handle_revoke_consent),
+        )
+        .branch(
+            dptree::filter(|q: CallbackQuery| q.data.as_deref() == Some(REVOKE_CONSENT_ABORT))
+                .endpoint(handle_keep_consent),
+        );
+
+    dptree::entry()
+        .branch(message_handler)
+        .branch(callback_handler)
+}
@@ -0,0 +1,238 @@
+use serde::{Deserialize, Serialize};
+use teloxide::dispatching::dialogue::ErasedStorage;
+use teloxide::dispatching::{UpdateHandler, dialogue};
+use teloxide::macros::BotCommands;
+use teloxide::net::Download;
+use teloxide::prelude::*;
+use teloxide::types::{KeyboardRemove, ParseMode};
+
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::{
+    DONE_BTN, document_kind_from_str, document_kind_to_str, make_document_kind_keyboard,
+};
+use crate::domain::Error;
+use crate::domain::models::{Document, DocumentKind, MAX_DOCUMENT_SIZE, UserID};
+use crate::usecases::{CheckRegisteredUseCase, UploadDocumentRequest, UploadDocumentUseCase};
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды документов")]
+enum DocumentsCommand {
+    #[command(rename = "documents", description = "загрузить документы для проверки")]
+    Documents,
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub enum DocumentsState {
+    #[default]
+    Start,
+    AwaitingKind,
+    AwaitingFile(DocumentKind),
+}
+
+pub type DocumentsDialogue = Dialogue<DocumentsState, ErasedStorage<DocumentsState>>;
+
+async fn handle_documents_command(
+    bot: Bot,
+    msg: Message,
+    dialogue: DocumentsDialogue,
+    cr_use_case: CheckRegisteredUseCase,
+    ud_use_case: UploadDocumentUseCase,
+) -> HandlerResult {
+    let user_id = UserID::new(msg.chat.id.0);
+    if !cr_use_case.is_registered(user_id).await? {
+        bot.send_message(
+            msg.chat.id,
+            "⚠️ <b>Сначала зарегистрируйтесь!</b>\n\
+             Введите /start для начала.",
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+    let slot_start = match ud_use_case.next_reservation(user_id).await? {
+        Some(slot_start) => slot_start,
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "⚠️ <b>У вас нет предстоящих записей</b>\n\
+                 Сначала запишитесь на приём: /reserve",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+    };
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "📎 <b>Загрузка документов</b>\n\
+            Документы будут приложены к записи на {} и проверены сотрудником УМД до приёма.\n\
+            Принимаются фото и PDF размером до {} МБ. После приёма документы удаляются.\n\
+            Выберите документ:",
+            slot_start.format("%m.%d %H:%M"),
+            MAX_DOCUMENT_SIZE / 1024 / 1024,
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(make_document_kind_keyboard())
+    .await?;
+    dialogue.update(DocumentsState::AwaitingKind).await?;
+    Ok(())
+}
+
+async fn receive_kind(bot: Bot, msg: Message, dialogue: DocumentsDialogue) -> HandlerResult {
+    match msg.text() {
+        Some(DONE_BTN) => {
+            bot.send_message(msg.chat.id, "✅ Загрузка документов завершена")
+                .reply_markup(KeyboardRemove::new())
+                .await?;
+            dialogue.exit().await?;
+        }
+        Some(text) => match document_kind_from_str(text) {
+            Some(kind) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "📷 <b>Отправьте {}</b>\n\
+                        Фотографией или PDF-файлом.",
+                        document_kind_to_str(&kind).to_lowercase(),
+                    ),
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(KeyboardRemove::new())
+                .await?;
+                dialogue.update(DocumentsState::AwaitingFile(kind)).await?;
+            }
+            None => {
+                bot.send_message(
+                    msg.chat.id,
+                    "❌ <b>Ошибка ввода</b>\n\
+                    Используйте клавиатуру для ввода.",
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(make_document_kind_keyboard())
+                .await?;
+            }
+        },
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .reply_markup(make_document_kind_keyboard())
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn receive_file(
+    bot: Bot,
+    msg: Message,
+    dialogue: DocumentsDialogue,
+    kind: DocumentKind,
+    use_case: UploadDocumentUseCase,
+) -> HandlerResult {
+    let (file, file_name, mime_type) = if let Some(doc) = msg.document() {
+        (
+            doc.file.clone(),
+            doc.file_name.clone().unwrap_or_default(),
+            doc.mime_type
+                .as_ref()
+                .map(|m| m.essence_str().to_string())
+                .unwrap_or_default(),
+        )
+    } else if let Some(photo) = msg.photo().and_then(|sizes| sizes.last()) {
+        (
+            photo.file.clone(),
+            "photo.jpg".to_string(),
+            "image/jpeg".to_string(),
+        )
+    } else {
+        bot.send_message(msg.chat.id, "📷 Отправьте фотографию или PDF-файл")
+            .await?;
+        return Ok(());
+    };
+
+    match Document::check(&mime_type, file.size as usize) {
+        Ok(_) => {}
+        Err(Error::UnsupportedDocumentType(_)) => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неподдерживаемый формат</b>\n\
+                Отправьте фотографию, JPEG, PNG или PDF-файл.",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+        Err(Error::DocumentTooLarge(max_size)) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "❌ <b>Файл слишком большой</b>\n\
+                    Максимальный размер - {} МБ.",
+                    max_size / 1024 / 1024,
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    }
+
+    let tg_file = bot.get_file(file.id).await?;
+    let mut data = Vec::with_capacity(tg_file.meta.size as usize);
+    bot.download_file(&tg_file.path, &mut data).await?;
+
+    let req = UploadDocumentRequest {
+        user_id: UserID::new(msg.chat.id.0),
+        kind,
+        file_name,
+        mime_type,
+        data,
+    };
+    match use_case.upload(req).await {
+        Ok(slot_start) => {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "✅ <b>{} загружен(а)</b>\n\
+                    Документ приложен к записи на {}. Выберите следующий документ или нажмите «{}».",
+                    document_kind_to_str(&kind),
+                    slot_start.format("%m.%d %H:%M"),
+                    DONE_BTN,
+                ),
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_document_kind_keyboard())
+            .await?;
+            dialogue.update(DocumentsState::AwaitingKind).await?;
+        }
+        Err(Error::UserNotReserved(_)) => {
+            bot.send_message(
+                msg.chat.id,
+                "⚠️ <b>У вас нет предстоящих записей</b>\n\
+                 Сначала запишитесь на приём: /reserve",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            dialogue.exit().await?;
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+pub fn documents_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<DocumentsCommand, _>()
+        .branch(case![DocumentsCommand::Documents].endpoint(handle_documents_command));
+
+    let message_handler = Update::filter_message()
+        .branch(command_handler)
+        .branch(case![DocumentsState::AwaitingKind].endpoint(receive_kind))
+        .branch(case![DocumentsState::AwaitingFile(kind)].endpoint(receive_file));
+
+    dialogue::enter::<Update, ErasedStorage<DocumentsState>, DocumentsState, _>()
+        .branch(message_handler)
+}
@@ -1,9 +1,17 @@
+mod consent;
+mod documents;
+mod my_data;
 mod registration;
 mod slots;
+mod status;
 mod update;
 mod view;
 
+pub use consent::*;
+pub use documents::*;
+pub use my_data::*;
 pub use registration::*;
 pub use slots::*;
+pub use status::*;
 pub use update::*;
 pub use view::*;
@@ -0,0 +1,159 @@
+use serde_json::{Value, json};
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::{InputFile, ParseMode};
+
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::{
+    DELETE_ME_ABORT, DELETE_ME_CONFIRM, document_kind_to_str, make_delete_me_keyboard,
+    service_to_str,
+};
+use crate::dispatcher::DialogueStorages;
+use crate::domain::Error;
+use crate::domain::models::UserID;
+use crate::usecases::{DeleteUserUseCase, ExportUserDataUseCase, UserDataDTO};
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Мои данные")]
+enum MyDataCommand {
+    #[command(rename = "mydata", description = "получить все свои данные файлом")]
+    MyData,
+
+    #[command(rename = "delete_me", description = "удалить все свои данные")]
+    DeleteMe,
+}
+
+fn to_json(data: &UserDataDTO) -> Value {
+    let user = &data.user;
+    json!({
+        "profile": {
+            "id": user.id.as_i64(),
+            "username": user.username.as_str(),
+            "full_name_lat": user.full_name_lat.as_str(),
+            "full_name_cyr": user.full_name_cyr.as_str(),
+            "citizenship": user.citizenship.as_str(),
+            "arrival_date": user.arrival_date,
+            "visa_expiry": user.visa_expiry,
+            "registration_expiry": user.registration_expiry,
+            "birth_date": user.birth_date,
+            "sex": user.sex.map(|s| s.as_str()),
+            "passport": user.passport.as_ref().map(|p| p.to_string()),
+            "place_of_stay": user.place_of_stay,
+        },
+        "reservations": data.reservations.iter().map(|(start, service)| json!({
+            "slot_start": start,
+            "service": service_to_str(service),
+        })).collect::<Vec<_>>(),
+        "cancelled_reservations": data.cancellations.iter().map(|(start, service, cancelled_at)| json!({
+            "slot_start": start,
+            "service": service_to_str(service),
+            "cancelled_at": cancelled_at,
+        })).collect::<Vec<_>>(),
+        "documents": data.documents.iter().map(|d| json!({
+            "slot_start": d.slot_start,
+            "kind": document_kind_to_str(&d.kind),
+            "file_name": d.file_name,
+            "size": d.size,
+            "uploaded_at": d.uploaded_at,
+        })).collect::<Vec<_>>(),
+        "consent": data.consent.as_ref().map(|c| json!({
+            "version": c.version,
+            "given_at": c.given_at,
+            "revoked_at": c.revoked_at,
+        })),
+        "audit": data.audit.iter().map(|e| json!({
+            "at": e.at,
+            "actor": e.actor.as_i64(),
+            "action": e.action.as_str(),
+            "target": e.target.map(|id| id.as_i64()),
+            "changes": e.changes,
+        })).collect::<Vec<_>>(),
+    })
+}
+
+async fn handle_my_data_command(
+    bot: Bot,
+    msg: Message,
+    use_case: ExportUserDataUseCase,
+) -> HandlerResult {
+    let data = use_case.export(UserID::new(msg.chat.id.0)).await?;
+    let json = serde_json::to_vec_pretty(&to_json(&data))
+        .map_err(|err| Error::Other(err.into()))?;
+    let input_file = InputFile::memory(json).file_name(format!("mydata_{}.json", msg.chat.id));
+    bot.send_document(msg.chat.id, input_file)
+        .caption("📦 Все данные, которые бот хранит о вас")
+        .await?;
+    Ok(())
+}
+
+async fn handle_delete_me_command(bot: Bot, msg: Message) -> HandlerResult {
+    bot.send_message(
+        msg.chat.id,
+        "⚠️ <b>Удалить все ваши данные?</b>\n\
+        Анкета, записи и отмены, загруженные документы и запросы будут удалены без возможности \
+        восстановления. В журнале действий останутся ваш Telegram ID, время и вид действий, время слотов \
+        и названия изменённых полей анкеты - без самих данных. \
+        Скачать данные перед удалением можно командой /mydata",
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(make_delete_me_keyboard())
+    .await?;
+    Ok(())
+}
+
+async fn handle_delete_me(
+    bot: Bot,
+    q: CallbackQuery,
+    use_case: DeleteUserUseCase,
+    storages: DialogueStorages,
+) -> HandlerResult {
+    bot.answer_callback_query(q.id).await?;
+    let Some(msg) = q.message else {
+        return Ok(());
+    };
+    use_case.delete(UserID::new(q.from.id.0 as i64)).await?;
+    storages.reset(msg.chat().id).await?;
+    bot.edit_message_text(
+        msg.chat().id,
+        msg.id(),
+        "✅ <b>Ваши данные удалены</b>\n\
+        Чтобы снова пользоваться ботом, зарегистрируйтесь: /start",
+    )
+    .parse_mode(ParseMode::Html)
+    .await?;
+    Ok(())
+}
+
+async fn handle_keep_data(bot: Bot, q: CallbackQuery) -> HandlerResult {
+    bot.answer_callback_query(q.id).await?;
+    if let Some(msg) = q.message {
+        bot.edit_message_text(msg.chat().id, msg.id(), "🔹 Данные сохранены")
+            .await?;
+    }
+    Ok(())
+}
+
+pub fn my_data_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<MyDataCommand, _>()
+        .branch(case![MyDataCommand::MyData].endpoint(handle_my_data_command))
+        .branch(case![MyDataCommand::DeleteMe].endpoint(handle_delete_me_command));
+
+    let message_handler = Update::filter_message().branch(command_handler);
+
+    let callback_handler = Update::filter_callback_query()
+        .branch(
+            dptree::filter(|q: CallbackQuery| q.data.as_deref() == Some(DELETE_ME_CONFIRM))
+                .endpoint(handle_delete_me),
+        )
+        .branch(
+            dptree::filter(|q: CallbackQuery| q.data.as_deref() == Some(DELETE_ME_ABORT))
+                .endpoint(handle_keep_data),
+        );
+
+    dptree::entry()
+        .branch(message_handler)
+        .branch(callback_handler)
+}
@@ -1,13 +1,17 @@
 use crate::bot::handlers::fsm::HandlerResult;
 use crate::bot::handlers::keyboards::{
-    AGREEMENT_BTN, make_agreement_keyboard, make_citizenship_keyboard,
+    AGREEMENT_BTN, make_agreement_keyboard, make_citizenship_keyboard, make_skip_keyboard,
+    parse_optional_date,
 };
 use crate::domain::Error;
 use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, UserID, Username};
-use crate::usecases::{CheckRegisteredUseCase, RegisterUserRequest, RegisterUserUseCase};
+use crate::bot::handlers::user::CONSENT_TEXT;
+use crate::usecases::{
+    CheckRegisteredUseCase, ConsentUseCase, RegisterUserRequest, RegisterUserUseCase,
+};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use teloxide::dispatching::dialogue::InMemStorage;
+use teloxide::dispatching::dialogue::ErasedStorage;
 use teloxide::dispatching::{UpdateHandler, dialogue};
 use teloxide::macros::BotCommands;
 use teloxide::prelude::*;
@@ -18,9 +22,6 @@ use teloxide::types::{KeyboardRemove, ParseMode};
 enum RegistrationCommand {
     #[command(rename = "start", description = "начать регистрацию")]
     Start,
-
-    #[command(rename = "cancel", description = "отменить регистрацию")]
-    Cancel,
 }
 
 #[derive(Default, Clone, Serialize, Deserialize)]
@@ -33,9 +34,17 @@ pub enum RegistrationState {
     AwaitingCitizenship(OnlyLatin, OnlyCyrillic),
     AwaitingOtherCitizenship(OnlyLatin, OnlyCyrillic),
     AwaitingArrivalDate(OnlyLatin, OnlyCyrillic, Citizenship),
+    AwaitingVisaExpiry(OnlyLatin, OnlyCyrillic, Citizenship, NaiveDate),
+    AwaitingRegistrationExpiry(
+        OnlyLatin,
+        OnlyCyrillic,
+        Citizenship,
+        NaiveDate,
+        Option<NaiveDate>,
+    ),
 }
 
-pub type RegistrationDialogue = Dialogue<RegistrationState, InMemStorage<RegistrationState>>;
+pub type RegistrationDialogue = Dialogue<RegistrationState, ErasedStorage<RegistrationState>>;
 
 async fn handle_start_command(
     bot: Bot,
@@ -50,6 +59,7 @@ async fn handle_start_command(
             "🔹 <b>Уже зарегистрированы!</b>\n\
             Доступные команды:\n\
             /view – посмотреть свои данные;\n\
+            /status – сроки подачи документов и ближайшая запись;\n\
             /update – изменить данные;\n\
             /reserve – записаться на услугу.",
         )
@@ -58,11 +68,13 @@ async fn handle_start_command(
         return Ok(());
     }
 
-    bot.send_message(msg.chat.id,
-         "🌟 <b>Добро пожаловать</b>!\n\
-          Вас приветствует бот для записи на приём для получения услуг в кабинете 401аю ГУК.\n\
-          Для продолжения работы необходимо дать согласие на обработку персональных данных, \
-          согласно с Федеральным законом РФ от 27.07.2006 №152-ФЗ «О персональных данных»"
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "🌟 <b>Добро пожаловать</b>!\n\
+            Вас приветствует бот для записи на приём для получения услуг в кабинете 401аю ГУК.\n{}",
+            CONSENT_TEXT
+        ),
     )
         .parse_mode(ParseMode::Html)
         .reply_markup(make_agreement_keyboard())
@@ -77,10 +89,12 @@ async fn receive_pd_agreement(
     bot: Bot,
     msg: Message,
     dialogue: RegistrationDialogue,
+    use_case: ConsentUseCase,
 ) -> HandlerResult {
     match msg.text() {
         Some(text) => {
             if text == AGREEMENT_BTN {
+                use_case.give(UserID::new(msg.chat.id.0)).await?;
                 bot.send_message(
                     msg.chat.id,
                     "✏️ <b>Введите ФИО латиницей</b>\n\
@@ -293,7 +307,6 @@ async fn receive_arrival_date(
     msg: Message,
     dialogue: RegistrationDialogue,
     (full_name_lat, full_name_cyr, citizenship): (OnlyLatin, OnlyCyrillic, Citizenship),
-    use_case: RegisterUserUseCase,
 ) -> HandlerResult {
     let date_str = match msg.text() {
         Some(t) => t,
@@ -306,6 +319,116 @@ async fn receive_arrival_date(
 
     match NaiveDate::parse_from_str(date_str, "%d.%m.%Y") {
         Ok(arrival_date) => {
+            bot.send_message(
+                msg.chat.id,
+                "🛂 <b>Введите дату окончания визы</b>\n\
+                В формате ДД.ММ.ГГГГ. Если визы нет, нажмите «Пропустить».",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_skip_keyboard())
+            .await?;
+            dialogue
+                .update(RegistrationState::AwaitingVisaExpiry(
+                    full_name_lat,
+                    full_name_cyr,
+                    citizenship,
+                    arrival_date,
+                ))
+                .await?;
+        }
+        Err(_) => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Введите дату в формате ДД.ММ.ГГГГ.",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn receive_visa_expiry(
+    bot: Bot,
+    msg: Message,
+    dialogue: RegistrationDialogue,
+    (full_name_lat, full_name_cyr, citizenship, arrival_date): (
+        OnlyLatin,
+        OnlyCyrillic,
+        Citizenship,
+        NaiveDate,
+    ),
+) -> HandlerResult {
+    let date_str = match msg.text() {
+        Some(t) => t,
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .reply_markup(make_skip_keyboard())
+                .await?;
+            return Ok(());
+        }
+    };
+
+    match parse_optional_date(date_str) {
+        Some(visa_expiry) => {
+            bot.send_message(
+                msg.chat.id,
+                "🏠 <b>Введите дату окончания регистрации</b>\n\
+                В формате ДД.ММ.ГГГГ. Если регистрации ещё нет, нажмите «Пропустить».",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_skip_keyboard())
+            .await?;
+            dialogue
+                .update(RegistrationState::AwaitingRegistrationExpiry(
+                    full_name_lat,
+                    full_name_cyr,
+                    citizenship,
+                    arrival_date,
+                    visa_expiry,
+                ))
+                .await?;
+        }
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Введите дату в формате ДД.ММ.ГГГГ.",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_skip_keyboard())
+            .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn receive_registration_expiry(
+    bot: Bot,
+    msg: Message,
+    dialogue: RegistrationDialogue,
+    (full_name_lat, full_name_cyr, citizenship, arrival_date, visa_expiry): (
+        OnlyLatin,
+        OnlyCyrillic,
+        Citizenship,
+        NaiveDate,
+        Option<NaiveDate>,
+    ),
+    use_case: RegisterUserUseCase,
+) -> HandlerResult {
+    let date_str = match msg.text() {
+        Some(t) => t,
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .reply_markup(make_skip_keyboard())
+                .await?;
+            return Ok(());
+        }
+    };
+
+    match parse_optional_date(date_str) {
+        Some(registration_expiry) => {
             let user = RegisterUserRequest {
                 id: UserID::new(msg.chat.id.0),
                 username: Username::new(msg.chat.username().unwrap_or_default().to_string()),
@@ -313,6 +436,8 @@ async fn receive_arrival_date(
                 full_name_cyr,
                 citizenship,
                 arrival_date,
+                visa_expiry,
+                registration_expiry,
             };
             match use_case.register(user).await {
                 Ok(_) => {
@@ -321,10 +446,12 @@ async fn receive_arrival_date(
                         "🎉 <b>Регистрация завершена!</b>\n\
                         Доступные команды:\n\
                         /view – посмотреть свои данные\n\
+                        /status – сроки подачи документов и ближайшая запись;\n\
                         /update – изменить данные;\n\
                         /reserve – записаться на услугу.",
                     )
                     .parse_mode(ParseMode::Html)
+                    .reply_markup(KeyboardRemove::new())
                     .await?;
                 }
                 Err(e) => {
@@ -342,37 +469,25 @@ async fn receive_arrival_date(
             }
             dialogue.exit().await?;
         }
-        Err(_) => {
+        None => {
             bot.send_message(
                 msg.chat.id,
                 "❌ <b>Неверный формат</b>\n\
                 Введите дату в формате ДД.ММ.ГГГГ.",
             )
             .parse_mode(ParseMode::Html)
+            .reply_markup(make_skip_keyboard())
             .await?;
         }
     }
     Ok(())
 }
 
-async fn handle_cancel_command(
-    bot: Bot,
-    msg: Message,
-    dialogue: RegistrationDialogue,
-) -> HandlerResult {
-    bot.send_message(msg.chat.id, "🚫 Операция отменена")
-        .reply_markup(KeyboardRemove::new())
-        .await?;
-    dialogue.exit().await?;
-    Ok(())
-}
-
 pub fn registration_schema() -> UpdateHandler<Error> {
     use dptree::case;
 
     let command_handler = teloxide::filter_command::<RegistrationCommand, _>()
-        .branch(case![RegistrationCommand::Start].endpoint(handle_start_command))
-        .branch(case![RegistrationCommand::Cancel].endpoint(handle_cancel_command));
+        .branch(case![RegistrationCommand::Start].endpoint(handle_start_command));
 
     let message_handler = Update::filter_message()
         .branch(command_handler)
@@ -403,8 +518,27 @@ pub fn registration_schema() -> UpdateHandler<Error> {
                 citizenship
             )]
             .endpoint(receive_arrival_date),
+        )
+        .branch(
+            case![RegistrationState::AwaitingVisaExpiry(
+                full_name_lat,
+                full_name_cyr,
+                citizenship,
+                arrival_date
+            )]
+            .endpoint(receive_visa_expiry),
+        )
+        .branch(
+            case![RegistrationState::AwaitingRegistrationExpiry(
+                full_name_lat,
+                full_name_cyr,
+                citizenship,
+                arrival_date,
+                visa_expiry
+            )]
+            .endpoint(receive_registration_expiry),
         );
 
-    dialogue::enter::<Update, InMemStorage<RegistrationState>, RegistrationState, _>()
+    dialogue::enter::<Update, ErasedStorage<RegistrationState>, RegistrationState, _>()
         .branch(message_handler)
 }
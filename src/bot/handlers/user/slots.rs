@@ -1,24 +1,27 @@
+use crate::bot::handlers::admin::notify_admins_about_override;
 use crate::bot::handlers::fsm::HandlerResult;
 use crate::bot::handlers::keyboards::{
-    BACK_BTN, YES_BTN, make_cancel_inline_keyboard, make_days_keyboard_with_back,
-    make_service_keyboard, make_slots_keyboard_with_back, make_yes_back_keyboard, service_from_str,
-    service_to_str,
+    BACK_BTN, OVERRIDE_REQUEST_PREFIX, RESERVE_LINK_PREFIX, YES_BTN, make_cancel_inline_keyboard,
+    make_days_keyboard_with_back, make_override_request_keyboard, make_service_keyboard,
+    make_slots_keyboard_with_back, make_yes_back_keyboard, service_from_str, service_to_str,
 };
 use crate::domain::Error;
-use crate::domain::models::{Service, UserID};
+use crate::domain::models::{Service, UserID, reservation_code};
+use crate::infra::{IcsMethod, reservation_event, to_ics};
 use crate::usecases::{
-    CancelReservationUseCase, CheckDeadlineUseCase, CheckRegisteredUseCase,
-    DaysWithFreeSlotsUseCase, FreeSlotDTO, FreeSlotsUseCase, ReserveSlotUseCase,
+    CancelReservationUseCase, CheckAdminUseCase, CheckDeadlineUseCase, CheckRegisteredUseCase,
+    DaysWithFreeSlotsUseCase, FreeSlotDTO, FreeSlotsUseCase, RequestDeadlineOverrideUseCase,
+    ReserveSlotUseCase,
 };
-use chrono::{DateTime, Datelike, NaiveDate};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::str::FromStr;
-use teloxide::dispatching::dialogue::InMemStorage;
+use teloxide::dispatching::dialogue::ErasedStorage;
 use teloxide::dispatching::{UpdateHandler, dialogue};
 use teloxide::macros::BotCommands;
 use teloxide::prelude::*;
-use teloxide::types::ParseMode;
+use teloxide::types::{InputFile, ParseMode};
 
 #[derive(BotCommands, Clone)]
 #[command(description = "Команды записи")]
@@ -43,7 +46,7 @@ pub enum SlotsState {
     AwaitingApprovalOfCancel,
 }
 
-pub type SlotsDialogue = Dialogue<SlotsState, InMemStorage<SlotsState>>;
+pub type SlotsDialogue = Dialogue<SlotsState, ErasedStorage<SlotsState>>;
 
 async fn handle_reserve_command(
     bot: Bot,
@@ -80,6 +83,75 @@ async fn handle_reserve_command(
     Ok(())
 }
 
+async fn offer_days(
+    bot: &Bot,
+    chat_id: ChatId,
+    dialogue: &SlotsDialogue,
+    service: Service,
+    cd_use_case: &CheckDeadlineUseCase,
+    dfs_use_case: &DaysWithFreeSlotsUseCase,
+) -> HandlerResult {
+    let user_id = UserID::new(chat_id.0);
+    let ok = cd_use_case.check_deadline(user_id, service).await?;
+    if !ok {
+        let deadline = cd_use_case.deadline(user_id, service).await?;
+        send_deadline_expired(bot, chat_id, service, deadline).await?;
+        dialogue.exit().await?;
+        return Ok(());
+    }
+    match dfs_use_case.days_with_free_slots(user_id, service).await {
+        Ok(days) if days.is_empty() => {
+            bot.send_message(chat_id, "😔 <b>Нет доступных дней для записи</b>")
+                .parse_mode(ParseMode::Html)
+                .await?;
+            dialogue.exit().await?;
+        }
+        Ok(days) => {
+            bot.send_message(chat_id, "📅 <b>Выберите удобный день</b>")
+                .parse_mode(ParseMode::Html)
+                .reply_markup(make_days_keyboard_with_back(&days))
+                .await?;
+            dialogue
+                .update(SlotsState::AwaitingDay(service, days))
+                .await?;
+        }
+        Err(Error::DeadlineExpired(deadline)) => {
+            send_deadline_expired(bot, chat_id, service, Some(deadline)).await?;
+            dialogue.exit().await?;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+async fn send_deadline_expired(
+    bot: &Bot,
+    chat_id: ChatId,
+    service: Service,
+    deadline: Option<NaiveDate>,
+) -> HandlerResult {
+    let deadline = deadline
+        .map(|d| format!(" {}", d.format("%d.%m.%Y")))
+        .unwrap_or_default();
+    bot.send_message(
+        chat_id,
+        format!(
+            "⏳ <b>Срок подачи документов на услугу «{}» истёк{}</b>\n\
+            Записаться через бота после окончания срока можно только с разрешения сотрудников УМД.\n\
+            Что делать дальше:\n\
+            1. Отправьте запрос сотрудникам кнопкой ниже. Когда запрос рассмотрят, бот пришлёт \
+            уведомление.\n\
+            2. Если вопрос срочный, обратитесь в УМД лично в часы приёма.",
+            service_to_str(&service),
+            deadline,
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .reply_markup(make_override_request_keyboard(service))
+    .await?;
+    Ok(())
+}
+
 async fn receive_service_type(
     bot: Bot,
     msg: Message,
@@ -90,29 +162,15 @@ async fn receive_service_type(
     match msg.text() {
         Some(text) => match service_from_str(text) {
             Some(service) => {
-                let user_id = UserID::new(msg.chat.id.0);
-                let ok = cd_use_case.check_deadline(user_id, service).await?;
-                if ok {
-                    let days = dfs_use_case.days_with_free_slots(user_id, service).await?;
-                    if days.is_empty() {
-                        bot.send_message(msg.chat.id, "😔 <b>Нет доступных дней для записи</b>")
-                            .parse_mode(ParseMode::Html)
-                            .await?;
-                        dialogue.exit().await?;
-                    } else {
-                        bot.send_message(msg.chat.id, "📅 <b>Выберите удобный день</b>")
-                            .parse_mode(ParseMode::Html)
-                            .reply_markup(make_days_keyboard_with_back(&days))
-                            .await?;
-                        dialogue
-                            .update(SlotsState::AwaitingDay(service, days))
-                            .await?;
-                    }
-                } else {
-                    bot.send_message(msg.chat.id, "⏳ <b>Срок подачи заявки истек</b>")
-                        .parse_mode(ParseMode::Html)
-                        .await?;
-                }
+                offer_days(
+                    &bot,
+                    msg.chat.id,
+                    &dialogue,
+                    service,
+                    &cd_use_case,
+                    &dfs_use_case,
+                )
+                .await?;
             }
             None => {
                 bot.send_message(
@@ -134,6 +192,49 @@ async fn receive_service_type(
     Ok(())
 }
 
+fn parse_reserve_link(msg: Message) -> Option<Service> {
+    let payload = msg.text()?.strip_prefix("/start ")?;
+    let service = payload.strip_prefix(RESERVE_LINK_PREFIX)?;
+    Service::try_from(service.to_string()).ok()
+}
+
+async fn handle_reserve_link(
+    bot: Bot,
+    msg: Message,
+    dialogue: SlotsDialogue,
+    service: Service,
+    cr_use_case: CheckRegisteredUseCase,
+    cd_use_case: CheckDeadlineUseCase,
+    dfs_use_case: DaysWithFreeSlotsUseCase,
+) -> HandlerResult {
+    let registered = cr_use_case.is_registered(UserID::new(msg.chat.id.0)).await?;
+    if !registered {
+        bot.send_message(
+            msg.chat.id,
+            "⚠️ <b>Сначала зарегистрируйтесь!</b>\n\
+             Введите /start для начала.",
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        return Ok(());
+    }
+    bot.send_message(
+        msg.chat.id,
+        format!("🔹 <b>Запись на услугу «{}»</b>", service_to_str(&service)),
+    )
+    .parse_mode(ParseMode::Html)
+    .await?;
+    offer_days(
+        &bot,
+        msg.chat.id,
+        &dialogue,
+        service,
+        &cd_use_case,
+        &dfs_use_case,
+    )
+    .await
+}
+
 fn fetch_month_and_date(s: &str) -> Option<(u32, u32)> {
     let v = s
         .splitn(2, '.')
@@ -308,14 +409,22 @@ async fn receive_approval(
                         format!(
                             "✅ <b>Запись успешно создана!</b>\n\
                             Услуга: «{}»\n\
-                            Время: {}",
+                            Время: {}\n\
+                            Код записи: <code>{}</code>",
                             service_to_str(&service),
                             slot.start.format("%m.%d %H:%M"),
+                            reservation_code(user_id, slot.start),
                         ),
                     )
                     .parse_mode(ParseMode::Html)
                     .reply_markup(make_cancel_inline_keyboard(slot.start))
                     .await?;
+                    bot.send_document(
+                        msg.chat.id,
+                        reservation_ics(user_id, &slot, service, IcsMethod::Publish),
+                    )
+                    .caption("📅 Добавьте запись в календарь телефона")
+                    .await?;
                     dialogue.exit().await?;
                 }
                 Err(Error::SlotNotFoundError) => {
@@ -338,6 +447,10 @@ async fn receive_approval(
                         .await?;
                     dialogue.exit().await?;
                 }
+                Err(Error::DeadlineExpired(deadline)) => {
+                    send_deadline_expired(&bot, msg.chat.id, service, Some(deadline)).await?;
+                    dialogue.exit().await?;
+                }
                 Err(e) => return Err(e.into()),
             }
         }
@@ -360,30 +473,98 @@ async fn receive_approval(
     Ok(())
 }
 
+fn parse_cancel_callback(q: CallbackQuery) -> Option<DateTime<Utc>> {
+    DateTime::from_str(q.data.as_ref()?).ok()
+}
+
 async fn handle_cancel_callback(
     bot: Bot,
     q: CallbackQuery,
+    date: DateTime<Utc>,
     use_case: CancelReservationUseCase,
 ) -> HandlerResult {
     let user_id = UserID::new(q.from.id.0 as i64);
-    if let Some(data) = q.data {
-        if let Ok(date) = DateTime::from_str(&data) {
-            use_case.cancel_reservation(user_id, date).await?;
-            bot.answer_callback_query(q.id).await?;
-            if let Some(msg) = q.message {
-                bot.edit_message_reply_markup(msg.chat().id, msg.id())
-                    .await?;
-                bot.send_message(
-                    msg.chat().id,
-                    format!(
-                        "🚫 <b>Запись на {} отменена</b>",
-                        date.format("%m.%d %H:%M"),
-                    ),
-                )
-                .parse_mode(ParseMode::Html)
-                .await?;
-            }
+    let cancelled = use_case.cancel_reservation(user_id, date).await?;
+    bot.answer_callback_query(q.id).await?;
+    if let Some(msg) = q.message {
+        bot.edit_message_reply_markup(msg.chat().id, msg.id())
+            .await?;
+        bot.send_message(
+            msg.chat().id,
+            format!(
+                "🚫 <b>Запись на {} отменена</b>",
+                date.format("%m.%d %H:%M"),
+            ),
+        )
+        .parse_mode(ParseMode::Html)
+        .await?;
+        let slot = FreeSlotDTO {
+            start: cancelled.slot_start,
+            end: cancelled.slot_end,
+        };
+        bot.send_document(
+            msg.chat().id,
+            reservation_ics(user_id, &slot, cancelled.service, IcsMethod::Cancel),
+        )
+        .caption("📅 Откройте файл, чтобы убрать запись из календаря")
+        .await?;
+    }
+    Ok(())
+}
+
+/// Файл календаря с записью студента или её отменой.
+fn reservation_ics(
+    user_id: UserID,
+    slot: &FreeSlotDTO,
+    service: Service,
+    method: IcsMethod,
+) -> InputFile {
+    let event = reservation_event(user_id, slot.start, slot.end, service_to_str(&service), method);
+    let ics = to_ics(method, &[event], Utc::now());
+    InputFile::memory(ics.into_bytes())
+        .file_name(format!("reservation_{}.ics", slot.start.format("%Y-%m-%d_%H%M")))
+}
+
+fn parse_override_request(q: CallbackQuery) -> Option<Service> {
+    let service = q.data.as_ref()?.strip_prefix(OVERRIDE_REQUEST_PREFIX)?;
+    Service::try_from(service.to_string()).ok()
+}
+
+async fn handle_override_request(
+    bot: Bot,
+    q: CallbackQuery,
+    service: Service,
+    ro_use_case: RequestDeadlineOverrideUseCase,
+    ca_use_case: CheckAdminUseCase,
+) -> HandlerResult {
+    let user_id = UserID::new(q.from.id.0 as i64);
+    bot.answer_callback_query(q.id).await?;
+    if let Some(msg) = q.message.as_ref() {
+        bot.edit_message_reply_markup(msg.chat().id, msg.id())
+            .await?;
+    }
+    let chat_id = ChatId(user_id.as_i64());
+    match ro_use_case.request(user_id, service).await {
+        Ok(request) => {
+            notify_admins_about_override(&bot, &ca_use_case, &request).await?;
+            bot.send_message(
+                chat_id,
+                "📨 <b>Запрос отправлен сотрудникам УМД</b>\n\
+                Бот пришлёт уведомление, когда запрос будет рассмотрен.",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+        }
+        Err(Error::OverrideAlreadyRequested) => {
+            bot.send_message(
+                chat_id,
+                "⏳ <b>Запрос уже отправлен</b>\n\
+                Дождитесь решения сотрудников УМД.",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
         }
+        Err(e) => return Err(e),
     }
     Ok(())
 }
@@ -395,6 +576,7 @@ pub fn slots_schema() -> UpdateHandler<Error> {
         .branch(case![SlotsCommand::Reserve].endpoint(handle_reserve_command));
 
     let message_handler = Update::filter_message()
+        .branch(dptree::filter_map(parse_reserve_link).endpoint(handle_reserve_link))
         .branch(command_handler)
         .branch(case![SlotsState::AwaitingServiceType].endpoint(receive_service_type))
         .branch(case![SlotsState::AwaitingDay(service, days)].endpoint(receive_day))
@@ -404,9 +586,11 @@ pub fn slots_schema() -> UpdateHandler<Error> {
                 .endpoint(receive_approval),
         );
 
-    let callback_handler = Update::filter_callback_query().endpoint(handle_cancel_callback);
+    let callback_handler = Update::filter_callback_query()
+        .branch(dptree::filter_map(parse_override_request).endpoint(handle_override_request))
+        .branch(dptree::filter_map(parse_cancel_callback).endpoint(handle_cancel_callback));
 
-    dialogue::enter::<Update, InMemStorage<SlotsState>, SlotsState, _>()
+    dialogue::enter::<Update, ErasedStorage<SlotsState>, SlotsState, _>()
         .branch(message_handler)
         .branch(callback_handler)
 }
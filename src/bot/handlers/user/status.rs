@@ -0,0 +1,134 @@
+use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::{document_kind_to_str, service_to_str};
+use crate::domain::Error;
+use crate::domain::models::UserID;
+use crate::usecases::{CalendarFeedUseCase, DeadlineStatusDTO, NextReservationDTO, StatusUseCase};
+use teloxide::dispatching::UpdateHandler;
+use teloxide::macros::BotCommands;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+#[derive(BotCommands, Clone)]
+#[command(description = "Команды статуса")]
+enum StatusCommand {
+    #[command(rename = "status", description = "Показать сроки, ближайшую запись и документы")]
+    Status,
+
+    #[command(rename = "calendar", description = "Ссылка на календарь с вашими записями")]
+    Calendar,
+}
+
+async fn handle_status_command(bot: Bot, msg: Message, use_case: StatusUseCase) -> HandlerResult {
+    match use_case.status(UserID::new(msg.chat.id.0)).await {
+        Ok(status) => {
+            let mut text = String::from("📊 <b>Ваш статус</b>\n\n⏳ <b>Сроки подачи документов</b>\n");
+            for d in status.deadlines.iter() {
+                text.push_str(&format_deadline(d));
+                text.push('\n');
+            }
+            text.push('\n');
+            match status.next_reservation {
+                Some(r) => text.push_str(&format_next_reservation(&r)),
+                None => text.push_str(
+                    "📅 <b>Ближайшая запись</b>\n\
+                    У вас нет предстоящих записей. Записаться: /reserve",
+                ),
+            }
+            bot.send_message(msg.chat.id, text)
+                .parse_mode(ParseMode::Html)
+                .await?;
+        }
+        Err(Error::UserNotFound(_)) => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ Вы еще не зарегистрированы. Используйте /start для регистрации.",
+            )
+            .await?;
+        }
+        Err(e) => return Err(e),
+    }
+    Ok(())
+}
+
+async fn handle_calendar_command(
+    bot: Bot,
+    msg: Message,
+    use_case: CalendarFeedUseCase,
+) -> HandlerResult {
+    let Some(link) = use_case.link(UserID::new(msg.chat.id.0)) else {
+        bot.send_message(
+            msg.chat.id,
+            "📅 Календарь по ссылке не настроен. Файл календаря приходит после каждой записи.",
+        )
+        .await?;
+        return Ok(());
+    };
+    bot.send_message(
+        msg.chat.id,
+        format!(
+            "📅 <b>Ваш календарь записей</b>\n\
+            <code>{}</code>\n\n\
+            Добавьте ссылку в календарь телефона как подписку: новые записи появятся, \
+            отменённые исчезнут сами.\n\
+            • iPhone: Настройки → Календарь → Учётные записи → Новая → Другое → Подписной календарь\n\
+            • Google Календарь: Другие календари → Добавить по URL\n\n\
+            ⚠️ Не пересылайте ссылку: по ней видны ваши записи.",
+            link,
+        ),
+    )
+    .parse_mode(ParseMode::Html)
+    .await?;
+    Ok(())
+}
+
+fn format_deadline(d: &DeadlineStatusDTO) -> String {
+    let left = if d.days_left < 0 {
+        "срок истёк".to_string()
+    } else if d.days_left == 0 {
+        "последний день".to_string()
+    } else {
+        format!("осталось {} дн.", d.days_left)
+    };
+    format!(
+        "• «{}»: до {} ({})",
+        service_to_str(&d.service),
+        d.deadline.format("%d.%m.%Y"),
+        left,
+    )
+}
+
+fn format_next_reservation(r: &NextReservationDTO) -> String {
+    let documents: Vec<String> = r
+        .documents
+        .iter()
+        .map(|(kind, uploaded)| {
+            format!(
+                "{} {}",
+                if *uploaded { "✅" } else { "⬜" },
+                document_kind_to_str(kind)
+            )
+        })
+        .collect();
+    format!(
+        "📅 <b>Ближайшая запись</b>\n\
+        Услуга: «{}»\n\
+        Время: {}\n\
+        Код записи: <code>{}</code>\n\n\
+        📎 <b>Документы</b> (загрузить: /documents)\n\
+        {}",
+        service_to_str(&r.service),
+        r.slot_start.format("%d.%m.%Y %H:%M"),
+        r.code,
+        documents.join("\n"),
+    )
+}
+
+pub fn status_schema() -> UpdateHandler<Error> {
+    use dptree::case;
+
+    let command_handler = teloxide::filter_command::<StatusCommand, _>()
+        .branch(case![StatusCommand::Status].endpoint(handle_status_command))
+        .branch(case![StatusCommand::Calendar].endpoint(handle_calendar_command));
+
+    Update::filter_message().branch(command_handler)
+}
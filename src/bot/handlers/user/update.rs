@@ -1,6 +1,6 @@
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
-use teloxide::dispatching::dialogue::InMemStorage;
+use teloxide::dispatching::dialogue::ErasedStorage;
 use teloxide::dispatching::{UpdateHandler, dialogue};
 use teloxide::macros::BotCommands;
 use teloxide::prelude::*;
@@ -8,10 +8,11 @@ use teloxide::types::{KeyboardRemove, ParseMode};
 
 use crate::bot::handlers::fsm::HandlerResult;
 use crate::bot::handlers::keyboards::{
-    self, make_citizenship_keyboard, make_field_selection_keyboard,
+    self, make_citizenship_keyboard, make_field_selection_keyboard, make_sex_keyboard,
+    make_skip_keyboard, parse_optional_date, sex_from_str,
 };
 use crate::domain::Error;
-use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, UserID};
+use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, Passport, UserID};
 use crate::usecases::{CheckRegisteredUseCase, UpdateUserUseCase};
 
 #[derive(BotCommands, Clone)]
@@ -19,9 +20,6 @@ use crate::usecases::{CheckRegisteredUseCase, UpdateUserUseCase};
 enum UpdateCommand {
     #[command(rename = "update", description = "обновить данные")]
     Update,
-
-    #[command(rename = "cancel", description = "отменить операцию")]
-    Cancel,
 }
 
 #[derive(Default, Clone, Serialize, Deserialize)]
@@ -34,9 +32,15 @@ pub enum UpdateState {
     AwaitingCitizenship,
     AwaitingOtherCitizenship,
     AwaitingArrivalDate,
+    AwaitingVisaExpiry,
+    AwaitingRegistrationExpiry,
+    AwaitingBirthDate,
+    AwaitingSex,
+    AwaitingPassport,
+    AwaitingPlaceOfStay,
 }
 
-pub type UpdateDialogue = Dialogue<UpdateState, InMemStorage<UpdateState>>;
+pub type UpdateDialogue = Dialogue<UpdateState, ErasedStorage<UpdateState>>;
 
 async fn handle_update_command(
     bot: Bot,
@@ -107,6 +111,66 @@ async fn receive_field_selection(
                 .await?;
                 dialogue.update(UpdateState::AwaitingArrivalDate).await?;
             }
+            keyboards::FIELD_VISA_EXPIRY_BTN => {
+                bot.send_message(
+                    msg.chat.id,
+                    "🛂 <b>Введите дату окончания визы</b>\n\
+                    В формате ДД.ММ.ГГГГ. Если визы нет, нажмите «Пропустить».",
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(make_skip_keyboard())
+                .await?;
+                dialogue.update(UpdateState::AwaitingVisaExpiry).await?;
+            }
+            keyboards::FIELD_REGISTRATION_EXPIRY_BTN => {
+                bot.send_message(
+                    msg.chat.id,
+                    "🏠 <b>Введите дату окончания регистрации</b>\n\
+                    В формате ДД.ММ.ГГГГ. Если регистрации нет, нажмите «Пропустить».",
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(make_skip_keyboard())
+                .await?;
+                dialogue.update(UpdateState::AwaitingRegistrationExpiry).await?;
+            }
+            keyboards::FIELD_BIRTH_DATE_BTN => {
+                bot.send_message(
+                    msg.chat.id,
+                    "🎂 <b>Введите дату рождения</b>\n\
+                    В формате ДД.ММ.ГГГГ",
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+                dialogue.update(UpdateState::AwaitingBirthDate).await?;
+            }
+            keyboards::FIELD_SEX_BTN => {
+                bot.send_message(msg.chat.id, "👤 <b>Выберите пол</b>")
+                    .parse_mode(ParseMode::Html)
+                    .reply_markup(make_sex_keyboard())
+                    .await?;
+                dialogue.update(UpdateState::AwaitingSex).await?;
+            }
+            keyboards::FIELD_PASSPORT_BTN => {
+                bot.send_message(
+                    msg.chat.id,
+                    "🛂 <b>Введите серию и номер паспорта</b>\n\
+                    Латинскими буквами и цифрами через пробел, например <i>AB 1234567</i>. \
+                    Если серии нет, введите только номер.",
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+                dialogue.update(UpdateState::AwaitingPassport).await?;
+            }
+            keyboards::FIELD_PLACE_OF_STAY_BTN => {
+                bot.send_message(
+                    msg.chat.id,
+                    "🏠 <b>Введите адрес места пребывания</b>\n\
+                    Пример: <i>г. Москва, ул. Бауманская, д. 1, кв. 1</i>",
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+                dialogue.update(UpdateState::AwaitingPlaceOfStay).await?;
+            }
             _ => {
                 bot.send_message(
                     msg.chat.id,
@@ -308,20 +372,206 @@ async fn receive_arrival_date(
     Ok(())
 }
 
-async fn handle_cancel_command(bot: Bot, msg: Message, dialogue: UpdateDialogue) -> HandlerResult {
-    bot.send_message(msg.chat.id, "❌ Текущая операция отменена")
+async fn receive_visa_expiry(
+    bot: Bot,
+    msg: Message,
+    dialogue: UpdateDialogue,
+    use_case: UpdateUserUseCase,
+) -> HandlerResult {
+    let date_str = match msg.text() {
+        Some(t) => t,
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .reply_markup(make_skip_keyboard())
+                .await?;
+            return Ok(());
+        }
+    };
+    let visa_expiry = match parse_optional_date(date_str) {
+        Some(visa_expiry) => visa_expiry,
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Введите дату в формате ДД.ММ.ГГГГ.",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_skip_keyboard())
+            .await?;
+            return Ok(());
+        }
+    };
+    use_case
+        .update_visa_expiry(msg.chat.id.0, visa_expiry)
+        .await?;
+    bot.send_message(msg.chat.id, "✅ Срок действия визы изменён!")
+        .reply_markup(KeyboardRemove::new())
+        .await?;
+    dialogue.exit().await?;
+    Ok(())
+}
+
+async fn receive_registration_expiry(
+    bot: Bot,
+    msg: Message,
+    dialogue: UpdateDialogue,
+    use_case: UpdateUserUseCase,
+) -> HandlerResult {
+    let date_str = match msg.text() {
+        Some(t) => t,
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .reply_markup(make_skip_keyboard())
+                .await?;
+            return Ok(());
+        }
+    };
+    let registration_expiry = match parse_optional_date(date_str) {
+        Some(registration_expiry) => registration_expiry,
+        None => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Введите дату в формате ДД.ММ.ГГГГ.",
+            )
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_skip_keyboard())
+            .await?;
+            return Ok(());
+        }
+    };
+    use_case
+        .update_registration_expiry(msg.chat.id.0, registration_expiry)
+        .await?;
+    bot.send_message(msg.chat.id, "✅ Срок действия регистрации изменён!")
         .reply_markup(KeyboardRemove::new())
         .await?;
     dialogue.exit().await?;
     Ok(())
 }
 
+async fn receive_birth_date(
+    bot: Bot,
+    msg: Message,
+    dialogue: UpdateDialogue,
+    use_case: UpdateUserUseCase,
+) -> HandlerResult {
+    let date_str = match msg.text() {
+        Some(t) => t,
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .await?;
+            return Ok(());
+        }
+    };
+    let birth_date = match NaiveDate::parse_from_str(date_str, "%d.%m.%Y") {
+        Ok(birth_date) => birth_date,
+        Err(_) => {
+            bot.send_message(
+                msg.chat.id,
+                "❌ <b>Неверный формат</b>\n\
+                Введите дату в формате ДД.ММ.ГГГГ.",
+            )
+            .parse_mode(ParseMode::Html)
+            .await?;
+            return Ok(());
+        }
+    };
+    use_case
+        .update_birth_date(msg.chat.id.0, birth_date)
+        .await?;
+    bot.send_message(msg.chat.id, "✅ Дата рождения изменена!")
+        .await?;
+    dialogue.exit().await?;
+    Ok(())
+}
+
+async fn receive_sex(
+    bot: Bot,
+    msg: Message,
+    dialogue: UpdateDialogue,
+    use_case: UpdateUserUseCase,
+) -> HandlerResult {
+    let Some(sex) = msg.text().and_then(sex_from_str) else {
+        bot.send_message(
+            msg.chat.id,
+            "❌ <b>Ошибка ввода</b>\n\
+            Используйте клавиатуру для ввода.",
+        )
+        .parse_mode(ParseMode::Html)
+        .reply_markup(make_sex_keyboard())
+        .await?;
+        return Ok(());
+    };
+    use_case.update_sex(msg.chat.id.0, sex).await?;
+    bot.send_message(msg.chat.id, "✅ Пол изменён!")
+        .reply_markup(KeyboardRemove::new())
+        .await?;
+    dialogue.exit().await?;
+    Ok(())
+}
+
+async fn receive_passport(
+    bot: Bot,
+    msg: Message,
+    dialogue: UpdateDialogue,
+    use_case: UpdateUserUseCase,
+) -> HandlerResult {
+    match msg.text() {
+        Some(text) => match Passport::parse(text) {
+            Ok(passport) => {
+                use_case.update_passport(msg.chat.id.0, passport).await?;
+                bot.send_message(msg.chat.id, "✅ Паспортные данные изменены!")
+                    .await?;
+                dialogue.exit().await?;
+            }
+            Err(_) => {
+                bot.send_message(
+                    msg.chat.id,
+                    "❌ <b>Ошибка ввода</b>\n\
+                    Введите серию и номер латинскими буквами и цифрами через пробел \
+                    или только номер. Попробуйте еще раз.",
+                )
+                .parse_mode(ParseMode::Html)
+                .await?;
+            }
+        },
+        None => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+async fn receive_place_of_stay(
+    bot: Bot,
+    msg: Message,
+    dialogue: UpdateDialogue,
+    use_case: UpdateUserUseCase,
+) -> HandlerResult {
+    let place_of_stay = match msg.text().map(str::trim) {
+        Some(t) if !t.is_empty() => t,
+        _ => {
+            bot.send_message(msg.chat.id, "📝 Введите текстовое сообщение")
+                .await?;
+            return Ok(());
+        }
+    };
+    use_case
+        .update_place_of_stay(msg.chat.id.0, place_of_stay.to_string())
+        .await?;
+    bot.send_message(msg.chat.id, "✅ Место пребывания изменено!")
+        .await?;
+    dialogue.exit().await?;
+    Ok(())
+}
+
 pub fn update_schema() -> UpdateHandler<Error> {
     use dptree::case;
 
     let command_handler = teloxide::filter_command::<UpdateCommand, _>()
-        .branch(case![UpdateCommand::Update].endpoint(handle_update_command))
-        .branch(case![UpdateCommand::Cancel].endpoint(handle_cancel_command));
+        .branch(case![UpdateCommand::Update].endpoint(handle_update_command));
 
     let message_handler = Update::filter_message()
         .branch(command_handler)
@@ -330,7 +580,15 @@ pub fn update_schema() -> UpdateHandler<Error> {
         .branch(case![UpdateState::AwaitingFullNameCyr].endpoint(receive_full_name_cyr))
         .branch(case![UpdateState::AwaitingCitizenship].endpoint(receive_citizenship))
         .branch(case![UpdateState::AwaitingOtherCitizenship].endpoint(receive_other_citizenship))
-        .branch(case![UpdateState::AwaitingArrivalDate].endpoint(receive_arrival_date));
+        .branch(case![UpdateState::AwaitingArrivalDate].endpoint(receive_arrival_date))
+        .branch(case![UpdateState::AwaitingVisaExpiry].endpoint(receive_visa_expiry))
+        .branch(
+            case![UpdateState::AwaitingRegistrationExpiry].endpoint(receive_registration_expiry),
+        )
+        .branch(case![UpdateState::AwaitingBirthDate].endpoint(receive_birth_date))
+        .branch(case![UpdateState::AwaitingSex].endpoint(receive_sex))
+        .branch(case![UpdateState::AwaitingPassport].endpoint(receive_passport))
+        .branch(case![UpdateState::AwaitingPlaceOfStay].endpoint(receive_place_of_stay));
 
-    dialogue::enter::<Update, InMemStorage<UpdateState>, UpdateState, _>().branch(message_handler)
+    dialogue::enter::<Update, ErasedStorage<UpdateState>, UpdateState, _>().branch(message_handler)
 }
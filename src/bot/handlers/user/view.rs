@@ -1,11 +1,14 @@
 use crate::bot::handlers::fsm::HandlerResult;
+use crate::bot::handlers::keyboards::sex_to_str;
 use crate::domain::Error;
 use crate::domain::models::UserID;
 use crate::usecases::GetUserUseCase;
+use chrono::NaiveDate;
 use teloxide::dispatching::UpdateHandler;
 use teloxide::macros::BotCommands;
 use teloxide::prelude::*;
 use teloxide::types::ParseMode;
+use teloxide::utils::html;
 
 #[derive(BotCommands, Clone)]
 #[command(description = "Команды профиля")]
@@ -26,11 +29,29 @@ pub async fn handle_view_command(
                 👤 Имя (лат): {}\n\
                 👤 Имя (кир): {}\n\
                 🌍 Гражданство: {}\n\
-                📅 Дата прибытия: {}",
+                📅 Дата прибытия: {}\n\
+                🛂 Виза действует до: {}\n\
+                🏠 Регистрация действует до: {}\n\
+                🎂 Дата рождения: {}\n\
+                👤 Пол: {}\n\
+                🛂 Паспорт: {}\n\
+                🏠 Место пребывания: {}",
                 user.full_name_lat.as_str(),
                 user.full_name_cyr.as_str(),
                 user.citizenship.as_str(),
                 user.arrival_date.format("%d.%m.%Y"),
+                format_optional_date(user.visa_expiry),
+                format_optional_date(user.registration_expiry),
+                format_optional_date(user.birth_date),
+                user.sex.as_ref().map(sex_to_str).unwrap_or("не указано"),
+                user.passport
+                    .as_ref()
+                    .map(|p| p.to_string())
+                    .unwrap_or_else(|| "не указано".to_string()),
+                user.place_of_stay
+                    .as_deref()
+                    .map(html::escape)
+                    .unwrap_or_else(|| "не указано".to_string()),
             );
             bot.send_message(msg.chat.id, text)
                 .parse_mode(ParseMode::Html)
@@ -48,6 +69,13 @@ pub async fn handle_view_command(
     Ok(())
 }
 
+fn format_optional_date(date: Option<NaiveDate>) -> String {
+    match date {
+        Some(date) => date.format("%d.%m.%Y").to_string(),
+        None => "не указано".to_string(),
+    }
+}
+
 pub fn view_schema() -> UpdateHandler<Error> {
     use dptree::case;
 
@@ -0,0 +1,96 @@
+use crate::domain::Error;
+use crate::infra::PostgresRepository;
+use crate::usecases::RetentionUseCase;
+
+pub const USAGE: &str = "usage: umd-bot [retention [--dry-run] | encrypt-users [--dry-run]]";
+
+/// Command - режим запуска, заданный аргументами командной строки.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// Запуск бота.
+    Bot,
+    /// Однократное применение политики хранения данных.
+    Retention { dry_run: bool },
+    /// Шифрование персональных данных существующих пользователей текущим ключом.
+    EncryptUsers { dry_run: bool },
+}
+
+impl Command {
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let args: Vec<String> = args.into_iter().collect();
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        match args.as_slice() {
+            [] => Ok(Self::Bot),
+            ["retention"] => Ok(Self::Retention { dry_run: false }),
+            ["retention", "--dry-run"] => Ok(Self::Retention { dry_run: true }),
+            ["encrypt-users"] => Ok(Self::EncryptUsers { dry_run: false }),
+            ["encrypt-users", "--dry-run"] => Ok(Self::EncryptUsers { dry_run: true }),
+            _ => Err(USAGE.to_string()),
+        }
+    }
+}
+
+/// Применяет политику хранения и печатает, чьи данные обезличены и удалены.
+pub async fn retention(use_case: &RetentionUseCase, dry_run: bool) -> Result<(), Error> {
+    let report = use_case.apply(dry_run).await?;
+    let (anonymise, delete) = if report.dry_run {
+        ("would anonymise", "would delete")
+    } else {
+        ("anonymised", "deleted")
+    };
+    for id in report.anonymised.iter() {
+        println!("{} user {}", anonymise, id);
+    }
+    for id in report.deleted.iter() {
+        println!("{} user {}", delete, id);
+    }
+    println!(
+        "{} {} users, {} {} users, {} {} reservations",
+        anonymise,
+        report.anonymised.len(),
+        delete,
+        report.deleted.len(),
+        anonymise,
+        report.reservations,
+    );
+    Ok(())
+}
+
+/// Перешифровывает персональные данные пользователей в базе текущим ключом.
+pub async fn encrypt_users(repos: &PostgresRepository, dry_run: bool) -> Result<(), Error> {
+    let changed = repos.reencrypt_users(dry_run).await?;
+    let verb = if dry_run { "would re-encrypt" } else { "re-encrypted" };
+    println!("{} {} users", verb, changed);
+    Ok(())
+}
+
+#[cfg(test)]
+mod cli_tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_parse_command() {
+        assert_eq!(Command::parse(args("")), Ok(Command::Bot));
+        assert_eq!(
+            Command::parse(args("retention --dry-run")),
+            Ok(Command::Retention { dry_run: true })
+        );
+        assert_eq!(
+            Command::parse(args("retention")),
+            Ok(Command::Retention { dry_run: false })
+        );
+        assert!(Command::parse(args("retention --force")).is_err());
+        assert_eq!(
+            Command::parse(args("encrypt-users --dry-run")),
+            Ok(Command::EncryptUsers { dry_run: true })
+        );
+        assert_eq!(
+            Command::parse(args("encrypt-users")),
+            Ok(Command::EncryptUsers { dry_run: false })
+        );
+    }
+}
@@ -1,37 +1,216 @@
-use teloxide::dispatching::dialogue::InMemStorage;
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use deadpool_postgres::Pool;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use teloxide::dispatching::dialogue::serializer::Json;
+use teloxide::dispatching::dialogue::{ErasedStorage, InMemStorage, Storage};
 use teloxide::dispatching::{DefaultKey, UpdateHandler};
 use teloxide::dptree::entry;
 use teloxide::prelude::Dispatcher;
+use teloxide::types::ChatId;
 use teloxide::{Bot, dptree};
-use crate::bot::handlers::admin::{admin_schema, AdminState};
+use crate::bot::handlers::admin::{admin_schema, audit_schema, find_schema, overrides_schema, staff_schema, stats_schema, AdminState};
+use crate::bot::handlers::errors::{ErrorReporter, with_error_handling};
+use crate::bot::handlers::session::{session_schema, track_activity};
 use crate::bot::handlers::user::{
-    RegistrationState, SlotsState, UpdateState, registration_schema, slots_schema, update_schema,
-    view_schema,
+    DocumentsState, RegistrationState, SlotsState, UpdateState, consent_schema, documents_schema, my_data_schema,
+    registration_schema, slots_schema, status_schema, update_schema, view_schema,
 };
 use crate::domain::Error;
+use crate::domain::interfaces::ChatActivity;
+use crate::infra::{FieldCipher, PostgresChatActivity, PostgresDialogueStorage};
 use crate::usecases::App;
 
+/// Хранилища состояний диалогов. В памяти состояния теряются при перезапуске бота.
+#[derive(Clone)]
+pub struct DialogueStorages {
+    pub registration: Arc<ErasedStorage<RegistrationState>>,
+    pub update: Arc<ErasedStorage<UpdateState>>,
+    pub slots: Arc<ErasedStorage<SlotsState>>,
+    pub admin: Arc<ErasedStorage<AdminState>>,
+    pub documents: Arc<ErasedStorage<DocumentsState>>,
+    /// Время последнего обновления от чата, по которому определяются брошенные диалоги.
+    activity: Arc<dyn ChatActivity>,
+    idle_timeout: Duration,
+}
+
+impl DialogueStorages {
+    pub fn in_memory(idle_timeout: Duration) -> Self {
+        Self {
+            registration: InMemStorage::new().erase(),
+            update: InMemStorage::new().erase(),
+            slots: InMemStorage::new().erase(),
+            admin: InMemStorage::new().erase(),
+            documents: InMemStorage::new().erase(),
+            activity: Arc::new(InMemChatActivity::default()),
+            idle_timeout,
+        }
+    }
+
+    pub fn postgres(pool: Pool, cipher: Arc<FieldCipher>, idle_timeout: Duration) -> Self {
+        let storage = |name| PostgresDialogueStorage::new(pool.clone(), cipher.clone(), name, Json);
+        Self {
+            registration: storage("registration").erase(),
+            update: storage("update").erase(),
+            slots: storage("slots").erase(),
+            admin: storage("admin").erase(),
+            documents: storage("documents").erase(),
+            activity: Arc::new(PostgresChatActivity::new(pool)),
+            idle_timeout,
+        }
+    }
+
+    /// Сбрасывает все диалоги чата. Возвращает `true`, если хотя бы один диалог был начат.
+    pub async fn reset(&self, chat_id: ChatId) -> Result<bool, Error> {
+        let mut reset = false;
+        reset |= reset_dialogue(&self.registration, chat_id).await?;
+        reset |= reset_dialogue(&self.update, chat_id).await?;
+        reset |= reset_dialogue(&self.slots, chat_id).await?;
+        reset |= reset_dialogue(&self.admin, chat_id).await?;
+        reset |= reset_dialogue(&self.documents, chat_id).await?;
+        Ok(reset)
+    }
+
+    /// Отмечает, что от чата пришло обновление.
+    pub async fn touch(&self, chat_id: ChatId) -> Result<(), Error> {
+        self.activity.touch(chat_id.0, Utc::now()).await
+    }
+
+    /// Сбрасывает диалоги чатов, молчащих дольше `idle_timeout`, и забывает их активность.
+    /// Возвращает чаты, в которых был сброшен хотя бы один начатый диалог.
+    pub async fn expire_idle(&self) -> Result<Vec<ChatId>, Error> {
+        let idle = self.activity.take_idle(Utc::now() - self.idle_timeout).await?;
+        let mut expired = Vec::new();
+        for chat_id in idle.into_iter().map(ChatId) {
+            if self.reset(chat_id).await? {
+                expired.push(chat_id);
+            }
+        }
+        Ok(expired)
+    }
+
+    /// Описывает начатые диалоги чата в виде `имя: состояние` для уведомлений об ошибках.
+    /// Указывается только вариант состояния: поля содержат персональные данные.
+    pub async fn describe(&self, chat_id: ChatId) -> Result<Vec<String>, Error> {
+        let states = [
+            describe_dialogue("registration", &self.registration, chat_id).await?,
+            describe_dialogue("update", &self.update, chat_id).await?,
+            describe_dialogue("slots", &self.slots, chat_id).await?,
+            describe_dialogue("admin", &self.admin, chat_id).await?,
+            describe_dialogue("documents", &self.documents, chat_id).await?,
+        ];
+        Ok(states.into_iter().flatten().collect())
+    }
+}
+
+async fn describe_dialogue<D: Serialize + DeserializeOwned + Send + 'static>(
+    name: &str,
+    storage: &Arc<ErasedStorage<D>>,
+    chat_id: ChatId,
+) -> Result<Option<String>, Error> {
+    let Some(state) = storage.clone().get_dialogue(chat_id).await? else {
+        return Ok(None);
+    };
+    let state = serde_json::to_value(&state).map_err(|err| Error::Other(err.into()))?;
+    Ok(Some(format!("{}: {}", name, state_variant(&state))))
+}
+
+/// Имя варианта сериализованного состояния без значений полей.
+fn state_variant(state: &serde_json::Value) -> &str {
+    match state {
+        serde_json::Value::String(variant) => variant,
+        serde_json::Value::Object(fields) if fields.len() == 1 => {
+            fields.keys().next().map(String::as_str).unwrap_or("?")
+        }
+        _ => "?",
+    }
+}
+
+async fn reset_dialogue<D: Send + 'static>(
+    storage: &Arc<ErasedStorage<D>>,
+    chat_id: ChatId,
+) -> Result<bool, Error> {
+    if storage.clone().get_dialogue(chat_id).await?.is_none() {
+        return Ok(false);
+    }
+    storage.clone().remove_dialogue(chat_id).await?;
+    Ok(true)
+}
+
+/// Активность чатов в памяти, теряется при перезапуске вместе с диалогами.
+#[derive(Default)]
+struct InMemChatActivity {
+    chats: Mutex<HashMap<i64, DateTime<Utc>>>,
+}
+
+#[async_trait]
+impl ChatActivity for InMemChatActivity {
+    async fn touch(&self, chat_id: i64, at: DateTime<Utc>) -> Result<(), Error> {
+        self.chats.lock().unwrap().insert(chat_id, at);
+        Ok(())
+    }
+
+    async fn take_idle(&self, before: DateTime<Utc>) -> Result<Vec<i64>, Error> {
+        let mut chats = self.chats.lock().unwrap();
+        let idle: Vec<_> = chats
+            .iter()
+            .filter(|(_, at)| **at < before)
+            .map(|(chat_id, _)| *chat_id)
+            .collect();
+        for chat_id in &idle {
+            chats.remove(chat_id);
+        }
+        Ok(idle)
+    }
+}
+
 pub struct UmdDispatcher;
 
 impl UmdDispatcher {
-    pub async fn create(bot: Bot, app: App) -> Dispatcher<Bot, Error, DefaultKey> {
+    pub async fn create(
+        bot: Bot,
+        app: App,
+        storages: DialogueStorages,
+        reporter: ErrorReporter,
+    ) -> Dispatcher<Bot, Error, DefaultKey> {
         Dispatcher::builder(bot, Self::scheme())
             .dependencies(dptree::deps![
+                app.audit,
+                app.calendar_feed,
                 app.cancel_reservation,
                 app.check_admin,
                 app.check_deadline,
                 app.check_registered,
+                app.consent,
                 app.days_with_free_slots,
+                app.deadline_overrides,
+                app.delete_user,
+                app.documents,
+                app.export_user_data,
+                app.forecast,
                 app.free_slots,
                 app.get_user,
                 app.register_user,
+                app.request_deadline_override,
                 app.reserve_slot,
+                app.revoke_consent,
+                app.search_users,
                 app.slots,
+                app.staff,
+                app.stats,
+                app.status,
                 app.update_user,
-                InMemStorage::<RegistrationState>::new(),
-                InMemStorage::<UpdateState>::new(),
-                InMemStorage::<SlotsState>::new(),
-                InMemStorage::<AdminState>::new()
+                app.upload_document,
+                storages.registration.clone(),
+                storages.update.clone(),
+                storages.slots.clone(),
+                storages.admin.clone(),
+                storages.documents.clone(),
+                storages,
+                reporter
             ])
             .default_handler(|upd| async move {
                 log::warn!("Unhandled update: {:?}", upd);
@@ -41,11 +220,352 @@ impl UmdDispatcher {
     }
 
     fn scheme() -> UpdateHandler<Error> {
-        entry()
+        let handler = entry()
+            .inspect_async(track_activity)
+            .branch(session_schema())
+            // Получить и удалить свои данные можно и без действующего согласия.
+            .branch(my_data_schema())
+            // Без действующего согласия остальные команды недоступны.
+            .branch(consent_schema())
             .branch(slots_schema())
             .branch(update_schema())
             .branch(view_schema())
-            .branch(registration_schema())
+            .branch(status_schema())
+            .branch(documents_schema())
+            // Ссылки на документы имеют вид /start docs_..., поэтому обрабатываются до регистрации.
             .branch(admin_schema())
+            .branch(overrides_schema())
+            .branch(staff_schema())
+            .branch(audit_schema())
+            .branch(find_schema())
+            .branch(stats_schema())
+            .branch(registration_schema());
+
+        with_error_handling(handler)
+    }
+}
+
+#[cfg(test)]
+mod dialogue_storages_tests {
+    use super::*;
+    use crate::bot::handlers::admin::ExportFormat;
+    use crate::domain::models::{
+        Citizenship, DocumentKind, OnlyCyrillic, OnlyLatin, ReservationsFilter, Service,
+    };
+    use crate::bot::handlers::user::CONSENT_VERSION;
+    use crate::infra::PostgresRepository;
+    use crate::usecases::{ConsentUseCase, FreeSlotDTO};
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::{NaiveDate, TimeZone};
+    use std::collections::HashMap;
+    use std::ops::ControlFlow;
+    use teloxide::dispatching::dialogue::Dialogue;
+    use teloxide::types::{Me, Update};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn plaintext() -> Arc<FieldCipher> {
+        Arc::new(FieldCipher::plaintext())
+    }
+
+    /// Поддельный Bot API: на любой запрос отвечает отправленным в чат `chat_id` сообщением.
+    async fn fake_bot_api(chat_id: ChatId) -> Bot {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        let body = serde_json::json!({
+            "ok": true,
+            "result": {
+                "message_id": 1,
+                "date": 0,
+                "chat": {"id": chat_id.0, "type": "private", "first_name": "Ivan"},
+                "text": "ok",
+            },
+        })
+        .to_string();
+        tokio::spawn(async move {
+            loop {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let body = body.clone();
+                tokio::spawn(async move {
+                    // Читаем заголовки, затем тело длиной Content-Length.
+                    let mut data = Vec::new();
+                    let mut buffer = [0; 4096];
+                    loop {
+                        let n = stream.read(&mut buffer).await.unwrap();
+                        data.extend_from_slice(&buffer[..n]);
+                        let text = String::from_utf8_lossy(&data).to_string();
+                        let Some((head, rest)) = text.split_once("\r\n\r\n") else {
+                            continue;
+                        };
+                        let length = head
+                            .lines()
+                            .find_map(|line| {
+                                let line = line.to_lowercase();
+                                line.strip_prefix("content-length:").map(|v| v.trim().parse().unwrap())
+                            })
+                            .unwrap_or(0);
+                        if n == 0 || rest.len() >= length {
+                            break;
+                        }
+                    }
+                    let response = format!(
+                        "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n\
+                        Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        body.len(),
+                        body
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+        Bot::new("0:test").set_api_url(reqwest::Url::parse(&url).unwrap())
+    }
+
+    /// Запускает бота заново с новым пулом соединений и передаёт настоящей схеме обработчиков
+    /// сообщение `text` из чата `chat_id`.
+    async fn restart_and_send(bot: &Bot, chat_id: ChatId, text: &str) {
+        let pool = test_db_setup().await;
+        let storages = DialogueStorages::postgres(pool.clone(), plaintext(), Duration::minutes(60));
+        let repos = Arc::new(PostgresRepository::new(pool));
+        let consent = ConsentUseCase::new(
+            CONSENT_VERSION,
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        );
+        let me: Me = serde_json::from_value(serde_json::json!({
+            "id": 1,
+            "is_bot": true,
+            "first_name": "УМД",
+            "username": "umd_bot",
+            "can_join_groups": false,
+            "can_read_all_group_messages": false,
+            "supports_inline_queries": false,
+            "can_connect_to_business": false,
+            "has_main_web_app": false,
+        }))
+        .unwrap();
+        let update = serde_json::json!({
+            "update_id": 1,
+            "message": {
+                "message_id": 2,
+                "date": Utc::now().timestamp(),
+                "chat": {"id": chat_id.0, "type": "private", "first_name": "Ivan"},
+                "from": {"id": chat_id.0, "is_bot": false, "first_name": "Ivan"},
+                "text": text,
+            },
+        });
+        // Из serde_json::Value teloxide разбирает обновление как UpdateKind::Error.
+        let update: Update = serde_json::from_str(&update.to_string()).unwrap();
+
+        let res = UmdDispatcher::scheme()
+            .dispatch(dptree::deps![
+                bot.clone(),
+                me,
+                update,
+                consent,
+                storages.registration.clone(),
+                storages.update.clone(),
+                storages.slots.clone(),
+                storages.admin.clone(),
+                storages.documents.clone(),
+                storages,
+                ErrorReporter::new(None)
+            ])
+            .await;
+        assert!(matches!(res, ControlFlow::Break(Ok(()))));
+    }
+
+    #[tokio::test]
+    async fn test_dispatcher_continues_dialogue_after_restart() {
+        let chat_id = ChatId(Utc::now().timestamp_micros());
+        let pool = test_db_setup().await;
+        let bot = fake_bot_api(chat_id).await;
+        let storages = DialogueStorages::postgres(pool.clone(), plaintext(), Duration::minutes(60));
+
+        // GIVEN студент начал регистрацию и ввёл ФИО латиницей
+        Dialogue::new(storages.registration.clone(), chat_id)
+            .update(RegistrationState::AwaitingFullNameLat)
+            .await
+            .unwrap();
+        restart_and_send(&bot, chat_id, "Ivanov Ivan").await;
+
+        // WHEN бот перезапускается и студент вводит ФИО кириллицей
+        restart_and_send(&bot, chat_id, "Иванов Иван").await;
+
+        // THEN новый экземпляр продолжает регистрацию с сохранённого шага
+        let state = Dialogue::new(storages.registration.clone(), chat_id).get().await.unwrap();
+        assert!(matches!(
+            state,
+            Some(RegistrationState::AwaitingCitizenship(lat, cyr))
+                if lat.as_str() == "Ivanov Ivan" && cyr.as_str() == "Иванов Иван"
+        ));
+        assert!(storages.reset(chat_id).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_dialogues_survive_restart() {
+        let chat_id = ChatId(Utc::now().timestamp_micros());
+        let date = |d| NaiveDate::from_ymd_opt(2025, 7, d).unwrap();
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+        let slot = FreeSlotDTO {
+            start,
+            end: start + Duration::minutes(20),
+        };
+
+        // GIVEN студент на середине регистрации, записи, изменения анкеты и загрузки документа,
+        // а сотрудник выбирает фильтры выгрузки
+        let storages = DialogueStorages::postgres(test_db_setup().await, plaintext(), Duration::minutes(60));
+        Dialogue::new(storages.registration.clone(), chat_id)
+            .update(RegistrationState::AwaitingRegistrationExpiry(
+                OnlyLatin::new("Ivanov Ivan").unwrap(),
+                OnlyCyrillic::new("Иванов Иван").unwrap(),
+                Citizenship::Armenia,
+                date(7),
+                Some(date(30)),
+            ))
+            .await
+            .unwrap();
+        Dialogue::new(storages.slots.clone(), chat_id)
+            .update(SlotsState::AwaitingApproval(
+                Service::Visa,
+                vec![date(14), date(15)],
+                HashMap::from([("10:00".to_string(), slot.clone())]),
+                slot,
+            ))
+            .await
+            .unwrap();
+        Dialogue::new(storages.update.clone(), chat_id)
+            .update(UpdateState::AwaitingPassport)
+            .await
+            .unwrap();
+        Dialogue::new(storages.admin.clone(), chat_id)
+            .update(AdminState::AwaitingFilters {
+                from: date(1),
+                to: date(31),
+                filter: ReservationsFilter {
+                    service: Some(Service::Visa),
+                    ..Default::default()
+                },
+                format: ExportFormat::Xlsx,
+            })
+            .await
+            .unwrap();
+        Dialogue::new(storages.documents.clone(), chat_id)
+            .update(DocumentsState::AwaitingFile(DocumentKind::Passport))
+            .await
+            .unwrap();
+        let before = storages.describe(chat_id).await.unwrap();
+        drop(storages);
+
+        // WHEN бот перезапускается с новым пулом соединений
+        let storages = DialogueStorages::postgres(test_db_setup().await, plaintext(), Duration::minutes(60));
+
+        // THEN все диалоги продолжаются с тех же состояний
+        assert_eq!(before.len(), 5);
+        assert_eq!(storages.describe(chat_id).await.unwrap(), before);
+        let registration = Dialogue::new(storages.registration.clone(), chat_id).get().await.unwrap();
+        assert!(matches!(
+            registration,
+            Some(RegistrationState::AwaitingRegistrationExpiry(lat, _, Citizenship::Armenia, _, Some(_)))
+                if lat.as_str() == "Ivanov Ivan"
+        ));
+        let slots = Dialogue::new(storages.slots.clone(), chat_id).get().await.unwrap();
+        assert!(matches!(
+            slots,
+            Some(SlotsState::AwaitingApproval(Service::Visa, days, slots, slot))
+                if days.len() == 2 && slots.contains_key("10:00") && slot.start == start
+        ));
+        let admin = Dialogue::new(storages.admin.clone(), chat_id).get().await.unwrap();
+        assert!(matches!(
+            admin,
+            Some(AdminState::AwaitingFilters { format: ExportFormat::Xlsx, filter, .. })
+                if filter.service == Some(Service::Visa)
+        ));
+        // AND после сброса диалогов в базе ничего не остаётся
+        assert!(storages.reset(chat_id).await.unwrap());
+        assert!(storages.describe(chat_id).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_describe_hides_state_fields() {
+        let chat_id = ChatId(1);
+        let storages = DialogueStorages::in_memory(Duration::minutes(60));
+
+        // GIVEN студент ввёл ФИО и гражданство и ждёт вопроса о визе
+        Dialogue::new(storages.registration.clone(), chat_id)
+            .update(RegistrationState::AwaitingVisaExpiry(
+                OnlyLatin::new("Ivanov Ivan").unwrap(),
+                OnlyCyrillic::new("Иванов Иван").unwrap(),
+                Citizenship::Armenia,
+                NaiveDate::from_ymd_opt(2025, 7, 7).unwrap(),
+            ))
+            .await
+            .unwrap();
+        // AND вводит номер паспорта
+        Dialogue::new(storages.update.clone(), chat_id)
+            .update(UpdateState::AwaitingPassport)
+            .await
+            .unwrap();
+
+        // WHEN диалоги описываются для уведомления об ошибке
+        let states = storages.describe(chat_id).await.unwrap();
+
+        // THEN в описании только варианты состояний
+        assert_eq!(
+            states,
+            vec!["registration: AwaitingVisaExpiry", "update: AwaitingPassport"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_idle_dialogues_expire() {
+        let chat_id = ChatId(1);
+        let storages = DialogueStorages::in_memory(Duration::minutes(60));
+
+        // GIVEN пользователь начал запись и перестал отвечать два часа назад
+        storages
+            .slots
+            .clone()
+            .update_dialogue(chat_id, SlotsState::default())
+            .await
+            .unwrap();
+        storages
+            .activity
+            .touch(chat_id.0, Utc::now() - Duration::hours(2))
+            .await
+            .unwrap();
+        // AND другой пользователь тоже начал запись, но пишет прямо сейчас
+        let active = ChatId(2);
+        storages
+            .slots
+            .clone()
+            .update_dialogue(active, SlotsState::default())
+            .await
+            .unwrap();
+        storages.touch(active).await.unwrap();
+
+        // WHEN бот сбрасывает брошенные диалоги
+        let expired = storages.expire_idle().await.unwrap();
+
+        // THEN сброшен только брошенный диалог
+        assert_eq!(expired, vec![chat_id]);
+        assert!(storages.slots.clone().get_dialogue(chat_id).await.unwrap().is_none());
+        assert!(storages.slots.clone().get_dialogue(active).await.unwrap().is_some());
+        // AND активность сброшенного чата забыта, повторно он не находится
+        assert!(storages.expire_idle().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reset_without_dialogues() {
+        // GIVEN у пользователя нет начатых диалогов
+        let storages = DialogueStorages::in_memory(Duration::minutes(60));
+
+        // WHEN пользователь отменяет операцию
+        let reset = storages.reset(ChatId(1)).await.unwrap();
+
+        // THEN сбрасывать нечего
+        assert!(!reset);
     }
 }
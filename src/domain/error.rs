@@ -1,3 +1,5 @@
+use chrono::NaiveDate;
+
 use crate::domain::models::UserID;
 
 pub type StdError = Box<dyn std::error::Error + Send + Sync>;
@@ -22,6 +24,42 @@ pub enum Error {
     #[error("slot already reserved by user")]
     SlotAlreadyReserved(UserID),
 
+    #[error("document size exceeds {0} bytes")]
+    DocumentTooLarge(usize),
+
+    #[error("unsupported document type: {0}")]
+    UnsupportedDocumentType(String),
+
+    #[error("deadline expired on {0}")]
+    DeadlineExpired(NaiveDate),
+
+    #[error("deadline override not found: {0}")]
+    OverrideNotFound(i64),
+
+    #[error("deadline override already requested")]
+    OverrideAlreadyRequested,
+
+    #[error("deadline override already resolved: {0}")]
+    OverrideAlreadyResolved(i64),
+
+    #[error("deadline override not approved: {0}")]
+    OverrideNotApproved(i64),
+
+    #[error("cannot remove the last owner: {0}")]
+    LastOwner(UserID),
+
+    #[error("role is granted by the staff group: {0}")]
+    RoleFromGroup(UserID),
+
+    #[error("conflict: {0}")]
+    Conflict(StdError),
+
+    #[error("storage unavailable: {0}")]
+    Unavailable(StdError),
+
+    #[error("serialization failure: {0}")]
+    SerializationFailure(StdError),
+
     #[error(transparent)]
     Other(#[from] StdError),
 }
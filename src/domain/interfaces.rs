@@ -1,7 +1,11 @@
 use async_trait::async_trait;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use std::collections::HashMap;
 
 use crate::domain::Error;
-use crate::domain::models::{Slot, User, UserID};
+use crate::domain::models::{
+    AuditEntry, Consent, DayReservationStats, DeadlineOverride, ReservationBreakdown, Document, Service, Slot, StaffMember, StaffRole, User, UserID,
+};
 
 #[async_trait]
 pub trait HasAvailableSlotsProvider: Send + Sync {
@@ -25,7 +29,120 @@ pub trait ReservedSlotProvider: Send + Sync {
 
 #[async_trait]
 pub trait SlotsRepository: Send + Sync {
-    async fn save_slot(&self, slot: &Slot) -> Result<(), Error>;
+    /// Сохраняет слот и запись журнала аудита об изменении в одной транзакции.
+    async fn save_slot(&self, slot: &Slot, entry: &AuditEntry) -> Result<(), Error>;
+
+    /// Сохраняет слот, погашает одобренный запрос на запись после срока и добавляет запись
+    /// журнала аудита в одной транзакции. Если запрос уже использован или не одобрен, ничего
+    /// не сохраняется.
+    async fn save_slot_with_override(
+        &self,
+        slot: &Slot,
+        override_id: i64,
+        used_at: DateTime<Utc>,
+        entry: &AuditEntry,
+    ) -> Result<(), Error>;
+}
+
+/// CalendarMirror отражает записи в календаре сотрудников: по событию на слот.
+#[async_trait]
+pub trait CalendarMirror: Send + Sync {
+    /// Создаёт или заменяет событие слота со списком записавшихся.
+    async fn put_slot(&self, slot: &Slot) -> Result<(), Error>;
+    /// Отпечаток события слота: меняется, если меняется что-либо в событии.
+    fn event_hash(&self, slot: &Slot) -> String;
+    /// Удаляет событие слота. Отсутствие события не считается ошибкой.
+    async fn delete_slot(&self, start: DateTime<Utc>) -> Result<(), Error>;
+}
+
+/// ChatActivity помнит время последнего обновления от каждого чата, чтобы находить
+/// брошенные диалоги.
+#[async_trait]
+pub trait ChatActivity: Send + Sync {
+    /// Отмечает, что чат `chat_id` был активен в `at`.
+    async fn touch(&self, chat_id: i64, at: DateTime<Utc>) -> Result<(), Error>;
+    /// Возвращает чаты, молчащие с `before`, и забывает их активность.
+    async fn take_idle(&self, before: DateTime<Utc>) -> Result<Vec<i64>, Error>;
+}
+
+/// CalendarFeedLinks выдаёт секретные ссылки на личные календари студентов и узнаёт
+/// по токену из ссылки, чей это календарь.
+pub trait CalendarFeedLinks: Send + Sync {
+    /// Ссылка на календарь пользователя. None, если календари по ссылке не настроены.
+    fn link(&self, id: UserID) -> Option<String>;
+    /// Пользователь, которому выдан токен. None для поддельного или чужого токена.
+    fn user(&self, token: &str) -> Option<UserID>;
+}
+
+/// MirroredSlotsRepository помнит отпечатки событий, отправленных в календарь, чтобы
+/// обновлять только изменившиеся события.
+#[async_trait]
+pub trait MirroredSlotsRepository: Send + Sync {
+    /// Возвращает отпечатки событий отражённых слотов, начинающихся в `[from, to)`.
+    async fn mirrored_slots(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<HashMap<DateTime<Utc>, String>, Error>;
+    async fn save_mirrored_slot(&self, start: DateTime<Utc>, event_hash: &str) -> Result<(), Error>;
+    async fn delete_mirrored_slot(&self, start: DateTime<Utc>) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait AttendanceRepository: Send + Sync {
+    /// Отмечает приход студента на приём. Возвращает false, если такой записи нет.
+    async fn mark_attended(
+        &self,
+        slot_start: DateTime<Utc>,
+        user_id: UserID,
+        at: DateTime<Utc>,
+    ) -> Result<bool, Error>;
+}
+
+#[async_trait]
+pub trait ReservationStatsProvider: Send + Sync {
+    /// Возвращает число записей, приходов и отмен по дням с `from` по `to` включительно.
+    /// Дни без записей и отмен пропускаются.
+    async fn daily_reservation_stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DayReservationStats>, Error>;
+    /// Возвращает число неотменённых записей за период по услугам и гражданству.
+    async fn reservation_breakdown(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ReservationBreakdown>, Error>;
+    /// Возвращает среднее время от записи до приёма или None, если оно неизвестно.
+    async fn average_lead_time(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Option<Duration>, Error>;
+    /// Возвращает число неотменённых записей в каждом слоте за период. Слоты без записей
+    /// пропускаются.
+    async fn slot_reservation_counts(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<HashMap<DateTime<Utc>, usize>, Error>;
+}
+
+#[async_trait]
+pub trait UserReservationsProvider: Send + Sync {
+    /// Возвращает записи пользователя, начинающиеся не раньше `from`, в порядке возрастания.
+    async fn user_reservations(
+        &self,
+        id: UserID,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, Service)>, Error>;
+
+    /// Возвращает отменённые записи пользователя: начало слота, услугу и время отмены.
+    async fn user_cancellations(
+        &self,
+        id: UserID,
+    ) -> Result<Vec<(DateTime<Utc>, Service, DateTime<Utc>)>, Error>;
 }
 
 #[async_trait]
@@ -33,12 +150,173 @@ pub trait UserProvider: Send + Sync {
     async fn user(&self, id: UserID) -> Result<User, Error>;
 }
 
+#[async_trait]
+pub trait UserSearchProvider: Send + Sync {
+    /// Ищет пользователей по точному совпадению ФИО латиницей или кириллицей
+    /// без учёта регистра и лишних пробелов.
+    async fn find_users(&self, full_name: &str) -> Result<Vec<User>, Error>;
+}
+
+#[async_trait]
+pub trait ExpiringUsersProvider: Send + Sync {
+    /// Возвращает пользователей, у которых срок действия визы или регистрации истекает не позже
+    /// `until`.
+    async fn users_with_expiry(&self, until: NaiveDate) -> Result<Vec<User>, Error>;
+}
+
+#[async_trait]
+pub trait UnreservedUsersProvider: Send + Sync {
+    /// Возвращает пользователей, у которых после даты прибытия нет ни одной неотменённой
+    /// записи.
+    async fn unreserved_users(&self) -> Result<Vec<User>, Error>;
+}
+
+#[async_trait]
+pub trait InactiveUsersProvider: Send + Sync {
+    /// Возвращает пользователей, у которых не было ни записей, ни изменений анкеты начиная с
+    /// `before` и у которых на `today` нет действующей визы или регистрации. Обезличенные
+    /// пользователи возвращаются, только если `anonymised` истинно.
+    async fn inactive_users(
+        &self,
+        before: DateTime<Utc>,
+        today: NaiveDate,
+        anonymised: bool,
+    ) -> Result<Vec<UserID>, Error>;
+}
+
+/// ReservationsRetention обезличивает старые записи и отмены: они остаются для статистики,
+/// но больше не связаны с пользователями.
+#[async_trait]
+pub trait ReservationsRetention: Send + Sync {
+    /// Возвращает число записей и отмен слотов, начавшихся раньше `before`, которые ещё
+    /// связаны с пользователями.
+    async fn identified_reservations(&self, before: DateTime<Utc>) -> Result<usize, Error>;
+    /// Отвязывает эти записи и отмены от пользователей и возвращает их число.
+    async fn anonymise_reservations(&self, before: DateTime<Utc>) -> Result<usize, Error>;
+}
+
 #[async_trait]
 pub trait UserRepository: Send + Sync {
-    async fn save_user(&self, user: User) -> Result<(), Error>;
+    /// Сохраняет пользователя и запись журнала аудита об изменении в одной транзакции.
+    async fn save_user(&self, user: User, entry: &AuditEntry) -> Result<(), Error>;
+    /// Стирает персональные данные пользователя, оставляя его идентификатор для истории
+    /// записей, а от даты въезда - только месяц. Такой пользователь считается
+    /// незарегистрированным.
+    async fn anonymise_user(&self, id: UserID, at: DateTime<Utc>) -> Result<(), Error>;
+    /// Удаляет пользователя вместе с его записями, документами и запросами.
+    async fn delete_user(&self, id: UserID) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait ConsentProvider: Send + Sync {
+    /// Возвращает последнее данное пользователем согласие.
+    async fn consent(&self, user_id: UserID) -> Result<Option<Consent>, Error>;
+}
+
+#[async_trait]
+pub trait ConsentRepository: Send + Sync {
+    async fn save_consent(&self, consent: &Consent) -> Result<(), Error>;
 }
 
 #[async_trait]
 pub trait AdminProvider: Send + Sync {
-    async fn is_admin(&self, id: UserID) -> Result<bool, Error>;
+    /// Возвращает роль сотрудника или `None`, если пользователь не сотрудник.
+    async fn role(&self, id: UserID) -> Result<Option<StaffRole>, Error>;
+    async fn staff(&self) -> Result<Vec<StaffMember>, Error>;
+    /// Сообщает, что состав чата `chat_id` изменился, чтобы провайдер обновил данные о
+    /// пользователе.
+    async fn membership_changed(&self, _chat_id: i64, _id: UserID) {}
+}
+
+#[async_trait]
+pub trait StaffRepository: Send + Sync {
+    /// Назначает сотруднику роль, заменяя прежнюю.
+    async fn save_staff_member(
+        &self,
+        member: &StaffMember,
+        granted_by: Option<UserID>,
+        granted_at: DateTime<Utc>,
+    ) -> Result<(), Error>;
+    async fn delete_staff_member(&self, id: UserID) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait DeadlineOverrideProvider: Send + Sync {
+    async fn deadline_override(&self, id: i64) -> Result<DeadlineOverride, Error>;
+    /// Возвращает последний нерассмотренный или одобренный запрос пользователя на услугу.
+    async fn active_override(
+        &self,
+        user_id: UserID,
+        service: Service,
+    ) -> Result<Option<DeadlineOverride>, Error>;
+    async fn pending_overrides(&self) -> Result<Vec<DeadlineOverride>, Error>;
+}
+
+#[async_trait]
+pub trait DeadlineOverrideRepository: Send + Sync {
+    async fn create_override(
+        &self,
+        user_id: UserID,
+        service: Service,
+        requested_at: DateTime<Utc>,
+    ) -> Result<DeadlineOverride, Error>;
+    async fn save_override(&self, o: &DeadlineOverride) -> Result<(), Error>;
+}
+
+/// NotificationLog запоминает отправленные пользователю уведомления, чтобы не отправлять их
+/// повторно.
+#[async_trait]
+pub trait NotificationLog: Send + Sync {
+    async fn is_notified(&self, user_id: UserID, key: &str) -> Result<bool, Error>;
+    async fn mark_notified(&self, user_id: UserID, key: &str) -> Result<(), Error>;
+    /// Проверяет, отправлена ли рассылка, не относящаяся к одному пользователю, например
+    /// сводка для сотрудников.
+    async fn is_sent(&self, key: &str) -> Result<bool, Error>;
+    async fn mark_sent(&self, key: &str) -> Result<(), Error>;
+}
+
+/// DocumentStorage хранит содержимое документов по ключу [`Document::storage_key`].
+#[async_trait]
+pub trait DocumentStorage: Send + Sync {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), Error>;
+    async fn get(&self, key: &str) -> Result<Vec<u8>, Error>;
+    async fn delete(&self, key: &str) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait DocumentRepository: Send + Sync {
+    async fn save_document(&self, document: &Document) -> Result<(), Error>;
+    async fn delete_document(&self, document: &Document) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait DocumentsProvider: Send + Sync {
+    async fn documents(
+        &self,
+        user_id: UserID,
+        slot_start: DateTime<Utc>,
+    ) -> Result<Vec<Document>, Error>;
+    /// Возвращает все документы пользователя.
+    async fn user_documents(&self, user_id: UserID) -> Result<Vec<Document>, Error>;
+}
+
+#[async_trait]
+pub trait ExpiredDocumentsProvider: Send + Sync {
+    /// Возвращает документы записей, начавшихся раньше `before`, а также документы записей,
+    /// отменённых раньше `before`.
+    async fn expired_documents(&self, before: DateTime<Utc>) -> Result<Vec<Document>, Error>;
+}
+
+#[async_trait]
+pub trait AuditLog: Send + Sync {
+    async fn append(&self, entry: &AuditEntry) -> Result<(), Error>;
+}
+
+#[async_trait]
+pub trait AuditLogProvider: Send + Sync {
+    /// Возвращает последние `limit` записей, в которых пользователь действовал сам или
+    /// действия касались его данных, от новых к старым.
+    async fn user_audit_entries(&self, id: UserID, limit: usize) -> Result<Vec<AuditEntry>, Error>;
+    /// Возвращает записи за день `date` (UTC) в порядке времени.
+    async fn audit_entries_on(&self, date: NaiveDate) -> Result<Vec<AuditEntry>, Error>;
 }
@@ -0,0 +1,276 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Error;
+use crate::domain::models::{User, UserID};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditAction {
+    UserRegistered,
+    UserUpdated,
+    UserAnonymised,
+    UserDeleted,
+    UserDataExported,
+    ConsentGiven,
+    ConsentRevoked,
+    ReservationCreated,
+    ReservationCancelled,
+    ReservationAttended,
+    ReservationsExported,
+    DocumentUploaded,
+    DocumentsViewed,
+    UserLookedUp,
+    OverrideRequested,
+    OverrideApproved,
+    OverrideRejected,
+    StaffRoleGranted,
+    StaffRoleRevoked,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::UserRegistered => "user_registered",
+            Self::UserUpdated => "user_updated",
+            Self::UserAnonymised => "user_anonymised",
+            Self::UserDeleted => "user_deleted",
+            Self::UserDataExported => "user_data_exported",
+            Self::ConsentGiven => "consent_given",
+            Self::ConsentRevoked => "consent_revoked",
+            Self::ReservationCreated => "reservation_created",
+            Self::ReservationCancelled => "reservation_cancelled",
+            Self::ReservationAttended => "reservation_attended",
+            Self::ReservationsExported => "reservations_exported",
+            Self::DocumentUploaded => "document_uploaded",
+            Self::DocumentsViewed => "documents_viewed",
+            Self::UserLookedUp => "user_looked_up",
+            Self::OverrideRequested => "override_requested",
+            Self::OverrideApproved => "override_approved",
+            Self::OverrideRejected => "override_rejected",
+            Self::StaffRoleGranted => "staff_role_granted",
+            Self::StaffRoleRevoked => "staff_role_revoked",
+        }
+    }
+
+    fn all() -> &'static [AuditAction] {
+        &[
+            Self::UserRegistered,
+            Self::UserUpdated,
+            Self::UserAnonymised,
+            Self::UserDeleted,
+            Self::UserDataExported,
+            Self::ConsentGiven,
+            Self::ConsentRevoked,
+            Self::ReservationCreated,
+            Self::ReservationCancelled,
+            Self::ReservationAttended,
+            Self::ReservationsExported,
+            Self::DocumentUploaded,
+            Self::DocumentsViewed,
+            Self::UserLookedUp,
+            Self::OverrideRequested,
+            Self::OverrideApproved,
+            Self::OverrideRejected,
+            Self::StaffRoleGranted,
+            Self::StaffRoleRevoked,
+        ]
+    }
+}
+
+impl TryFrom<&str> for AuditAction {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        Self::all()
+            .iter()
+            .find(|a| a.as_str() == value)
+            .copied()
+            .ok_or_else(|| Error::InvalidValue(format!("invalid AuditAction: {}", value)))
+    }
+}
+
+/// Изменение одного поля: `before` отсутствует у созданных значений, `after` — у удалённых.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditChange {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditChange {
+    pub fn new(field: &str, before: Option<String>, after: Option<String>) -> Self {
+        Self {
+            field: field.to_string(),
+            before,
+            after,
+        }
+    }
+
+    pub fn added(field: &str, value: impl ToString) -> Self {
+        Self::new(field, None, Some(value.to_string()))
+    }
+
+    pub fn removed(field: &str, value: impl ToString) -> Self {
+        Self::new(field, Some(value.to_string()), None)
+    }
+}
+
+fn user_fields(u: &User) -> Vec<(&'static str, Option<String>)> {
+    vec![
+        ("username", Some(u.username().as_str().to_string())),
+        ("full_name_lat", Some(u.full_name_lat().as_str().to_string())),
+        ("full_name_cyr", Some(u.full_name_cyr().as_str().to_string())),
+        ("citizenship", Some(u.citizenship().as_str().to_string())),
+        ("arrival_date", Some(u.arrival_date().to_string())),
+        ("visa_expiry", u.visa_expiry().map(|d| d.to_string())),
+        ("registration_expiry", u.registration_expiry().map(|d| d.to_string())),
+        ("birth_date", u.birth_date().map(|d| d.to_string())),
+        ("sex", u.sex().map(|s| s.as_str().to_string())),
+        ("passport", u.passport().map(|p| p.to_string())),
+        ("place_of_stay", u.place_of_stay().map(|p| p.to_string())),
+    ]
+}
+
+/// Возвращает изменившиеся поля пользователя. `None` означает, что пользователя не было
+/// до изменения или не стало после него. Журнал аудита нельзя изменить, поэтому в него
+/// попадают только названия полей, но не персональные данные.
+pub fn user_changes(before: Option<&User>, after: Option<&User>) -> Vec<AuditChange> {
+    let before = before.map(user_fields);
+    let after = after.map(user_fields);
+    let fields = before.as_ref().or(after.as_ref()).cloned().unwrap_or_default();
+    fields
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (field, _))| {
+            let old = before.as_ref().and_then(|b| b[i].1.as_ref());
+            let new = after.as_ref().and_then(|a| a[i].1.as_ref());
+            (old != new).then(|| AuditChange::new(field, None, None))
+        })
+        .collect()
+}
+
+/// AuditEntry - запись журнала аудита: кто (`actor`), что сделал (`action`) и с чьими
+/// данными (`target`). Записи только добавляются и никогда не изменяются.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    at: DateTime<Utc>,
+    actor: UserID,
+    action: AuditAction,
+    target: Option<UserID>,
+    changes: Vec<AuditChange>,
+}
+
+impl AuditEntry {
+    pub fn new(actor: UserID, action: AuditAction, target: Option<UserID>) -> Self {
+        Self {
+            at: Utc::now(),
+            actor,
+            action,
+            target,
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn restore(
+        at: DateTime<Utc>,
+        actor: UserID,
+        action: AuditAction,
+        target: Option<UserID>,
+        changes: Vec<AuditChange>,
+    ) -> Self {
+        Self {
+            at,
+            actor,
+            action,
+            target,
+            changes,
+        }
+    }
+
+    pub fn with_changes(mut self, changes: Vec<AuditChange>) -> Self {
+        self.changes.extend(changes);
+        self
+    }
+
+    pub fn with_change(mut self, change: AuditChange) -> Self {
+        self.changes.push(change);
+        self
+    }
+
+    pub fn at(&self) -> DateTime<Utc> {
+        self.at
+    }
+
+    pub fn actor(&self) -> UserID {
+        self.actor
+    }
+
+    pub fn action(&self) -> AuditAction {
+        self.action
+    }
+
+    pub fn target(&self) -> Option<UserID> {
+        self.target
+    }
+
+    pub fn changes(&self) -> &[AuditChange] {
+        &self.changes
+    }
+}
+
+#[cfg(test)]
+mod audit_tests {
+    use super::*;
+    use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, Username};
+    use chrono::NaiveDate;
+
+    fn user() -> User {
+        User::new(
+            UserID::new(1),
+            Username::new("ivan"),
+            OnlyLatin::new("Ivanov Ivan").unwrap(),
+            OnlyCyrillic::new("Иванов Иван").unwrap(),
+            Citizenship::Other("Китай".to_string()),
+            NaiveDate::from_ymd_opt(2025, 7, 14).unwrap(),
+        )
+    }
+
+    #[test]
+    fn test_user_changes_contain_only_changed_fields() {
+        // GIVEN пользователь изменил ФИО и указал срок действия визы
+        let before = user();
+        let mut after = before.clone();
+        after.set_full_name_lat(OnlyLatin::new("Petrov Petr").unwrap());
+        after.set_visa_expiry(NaiveDate::from_ymd_opt(2026, 1, 1));
+
+        // WHEN вычисляются изменения
+        let changes = user_changes(Some(&before), Some(&after));
+
+        // THEN в них только названия изменившихся полей без значений
+        assert_eq!(
+            changes,
+            vec![
+                AuditChange::new("full_name_lat", None, None),
+                AuditChange::new("visa_expiry", None, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_new_user_changes_contain_all_fields() {
+        // GIVEN новый пользователь
+        let changes = user_changes(None, Some(&user()));
+
+        // THEN названы все заполненные поля, а их значений нет
+        assert_eq!(changes.len(), 5);
+        assert!(changes.iter().all(|c| c.before.is_none() && c.after.is_none()));
+    }
+
+    #[test]
+    fn test_action_from_str() {
+        for action in AuditAction::all() {
+            assert_eq!(AuditAction::try_from(action.as_str()).unwrap(), *action);
+        }
+        assert!(AuditAction::try_from("unknown").is_err());
+    }
+}
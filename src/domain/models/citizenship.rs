@@ -12,7 +12,22 @@ pub enum Citizenship {
     Other(String),
 }
 
+/// Гражданства, которые предлагаются при регистрации, без «Другое».
+const KNOWN: &[Citizenship] = &[
+    Citizenship::Tajikistan,
+    Citizenship::Uzbekistan,
+    Citizenship::Kazakhstan,
+    Citizenship::Kyrgyzstan,
+    Citizenship::Armenia,
+    Citizenship::Belarus,
+    Citizenship::Ukraine,
+];
+
 impl Citizenship {
+    pub fn known() -> &'static [Citizenship] {
+        KNOWN
+    }
+
     pub fn as_str(&self) -> &str {
         match self {
             Citizenship::Tajikistan => "Таджикистан",
@@ -0,0 +1,94 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::models::UserID;
+
+/// Consent - согласие пользователя на обработку персональных данных в редакции `version`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Consent {
+    user_id: UserID,
+    version: u32,
+    given_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Consent {
+    pub fn new(user_id: UserID, version: u32, given_at: DateTime<Utc>) -> Self {
+        Self {
+            user_id,
+            version,
+            given_at,
+            revoked_at: None,
+        }
+    }
+
+    pub fn restore(
+        user_id: UserID,
+        version: u32,
+        given_at: DateTime<Utc>,
+        revoked_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            user_id,
+            version,
+            given_at,
+            revoked_at,
+        }
+    }
+
+    /// Отзывает согласие. Повторный отзыв не меняет время первого.
+    pub fn revoke(&mut self, at: DateTime<Utc>) {
+        self.revoked_at.get_or_insert(at);
+    }
+
+    /// Проверяет, что согласие не отозвано и дано на редакцию не старше `version`.
+    pub fn covers(&self, version: u32) -> bool {
+        self.revoked_at.is_none() && self.version >= version
+    }
+
+    pub fn user_id(&self) -> UserID {
+        self.user_id
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+
+    pub fn given_at(&self) -> DateTime<Utc> {
+        self.given_at
+    }
+
+    pub fn revoked_at(&self) -> Option<DateTime<Utc>> {
+        self.revoked_at
+    }
+}
+
+#[cfg(test)]
+mod consent_tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_consent_covers_only_current_version() {
+        // GIVEN согласие на первую редакцию текста
+        let consent = Consent::new(UserID::new(1), 1, Utc::now());
+
+        // THEN оно действует для первой редакции, но не для второй
+        assert!(consent.covers(1));
+        assert!(!consent.covers(2));
+    }
+
+    #[test]
+    fn test_revoked_consent_covers_nothing() {
+        let given_at = Utc::now();
+        let mut consent = Consent::new(UserID::new(1), 2, given_at);
+
+        // WHEN пользователь дважды отзывает согласие
+        consent.revoke(given_at + Duration::days(1));
+        consent.revoke(given_at + Duration::days(2));
+
+        // THEN согласие больше не действует
+        assert!(!consent.covers(1));
+        // AND запомнено время первого отзыва
+        assert_eq!(consent.revoked_at(), Some(given_at + Duration::days(1)));
+    }
+}
@@ -0,0 +1,187 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Error;
+use crate::domain::models::{Service, UserID};
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum OverrideStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Used,
+}
+
+/// DeadlineOverride - запрос студента на запись после окончания срока подачи документов.
+/// Одобренный сотрудником запрос разрешает ровно одну запись на услугу. Запросы не удаляются,
+/// поэтому по ним всегда можно установить, кто и когда разрешил запись.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadlineOverride {
+    id: i64,
+    user_id: UserID,
+    service: Service,
+    status: OverrideStatus,
+    requested_at: DateTime<Utc>,
+    resolved_by: Option<UserID>,
+    resolved_at: Option<DateTime<Utc>>,
+    used_at: Option<DateTime<Utc>>,
+}
+
+impl DeadlineOverride {
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        id: i64,
+        user_id: UserID,
+        service: Service,
+        status: OverrideStatus,
+        requested_at: DateTime<Utc>,
+        resolved_by: Option<UserID>,
+        resolved_at: Option<DateTime<Utc>>,
+        used_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            id,
+            user_id,
+            service,
+            status,
+            requested_at,
+            resolved_by,
+            resolved_at,
+            used_at,
+        }
+    }
+
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    pub fn user_id(&self) -> UserID {
+        self.user_id
+    }
+
+    pub fn service(&self) -> Service {
+        self.service
+    }
+
+    pub fn status(&self) -> OverrideStatus {
+        self.status
+    }
+
+    pub fn requested_at(&self) -> DateTime<Utc> {
+        self.requested_at
+    }
+
+    pub fn resolved_by(&self) -> Option<UserID> {
+        self.resolved_by
+    }
+
+    pub fn resolved_at(&self) -> Option<DateTime<Utc>> {
+        self.resolved_at
+    }
+
+    pub fn used_at(&self) -> Option<DateTime<Utc>> {
+        self.used_at
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.status == OverrideStatus::Approved
+    }
+
+    pub fn approve(&mut self, admin_id: UserID, now: DateTime<Utc>) -> Result<(), Error> {
+        self.resolve(OverrideStatus::Approved, admin_id, now)
+    }
+
+    pub fn reject(&mut self, admin_id: UserID, now: DateTime<Utc>) -> Result<(), Error> {
+        self.resolve(OverrideStatus::Rejected, admin_id, now)
+    }
+
+    /// Отмечает одобренный запрос использованным при создании записи.
+    pub fn consume(&mut self, now: DateTime<Utc>) -> Result<(), Error> {
+        if !self.is_approved() {
+            return Err(Error::OverrideNotApproved(self.id));
+        }
+        self.status = OverrideStatus::Used;
+        self.used_at = Some(now);
+        Ok(())
+    }
+
+    fn resolve(
+        &mut self,
+        status: OverrideStatus,
+        admin_id: UserID,
+        now: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        if self.status != OverrideStatus::Pending {
+            return Err(Error::OverrideAlreadyResolved(self.id));
+        }
+        self.status = status;
+        self.resolved_by = Some(admin_id);
+        self.resolved_at = Some(now);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod deadline_override_tests {
+    use super::*;
+
+    fn pending() -> DeadlineOverride {
+        DeadlineOverride::restore(
+            1,
+            UserID::new(1),
+            Service::InitialRegistration,
+            OverrideStatus::Pending,
+            Utc::now(),
+            None,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn test_approve_and_consume() {
+        // GIVEN запрос на запись после срока
+        let mut o = pending();
+
+        // WHEN администратор одобряет запрос и студент записывается
+        o.approve(UserID::new(100), Utc::now()).unwrap();
+        assert!(o.is_approved());
+        o.consume(Utc::now()).unwrap();
+
+        // THEN запрос использован, повторно использовать его нельзя
+        assert_eq!(o.status(), OverrideStatus::Used);
+        assert_eq!(o.resolved_by(), Some(UserID::new(100)));
+        assert!(o.used_at().is_some());
+        assert!(matches!(
+            o.consume(Utc::now()),
+            Err(Error::OverrideNotApproved(1))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_twice() {
+        // GIVEN отклонённый запрос
+        let mut o = pending();
+        o.reject(UserID::new(100), Utc::now()).unwrap();
+
+        // WHEN другой администратор пытается его одобрить
+        let res = o.approve(UserID::new(101), Utc::now());
+
+        // THEN ошибка, решение не меняется
+        assert!(matches!(res, Err(Error::OverrideAlreadyResolved(1))));
+        assert_eq!(o.status(), OverrideStatus::Rejected);
+        assert_eq!(o.resolved_by(), Some(UserID::new(100)));
+    }
+
+    #[test]
+    fn test_consume_pending() {
+        // WHEN студент пытается использовать нерассмотренный запрос
+        let mut o = pending();
+
+        // THEN ошибка
+        assert!(matches!(
+            o.consume(Utc::now()),
+            Err(Error::OverrideNotApproved(1))
+        ));
+    }
+}
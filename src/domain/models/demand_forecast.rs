@@ -0,0 +1,78 @@
+use chrono::NaiveDate;
+
+/// День прогноза спроса на приём.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForecastDay {
+    pub date: NaiveDate,
+    /// Свободные места в слотах дня.
+    pub free: usize,
+    /// Студенты без записи, у которых в этот день истекает срок подачи документов.
+    pub due: usize,
+    /// Сколько студентов должно быть принято с начала прогноза по этот день включительно.
+    pub demand: usize,
+    /// Сколько свободных мест с начала прогноза по этот день включительно.
+    pub supply: usize,
+}
+
+impl ForecastDay {
+    /// Сколько мест не хватает, чтобы к концу дня принять всех, у кого истекает срок.
+    pub fn shortage(&self) -> usize {
+        self.demand.saturating_sub(self.supply)
+    }
+}
+
+/// Строит прогноз по дням `free` (дата и число свободных мест, по порядку) для студентов со
+/// сроками подачи документов `deadlines`. Студента можно принять в любой день до срока,
+/// поэтому спрос и свободные места копятся с первого дня прогноза. Сроки раньше первого
+/// дня прогноза не учитываются.
+pub fn forecast_demand(free: &[(NaiveDate, usize)], deadlines: &[NaiveDate]) -> Vec<ForecastDay> {
+    let Some(&(first, _)) = free.first() else {
+        return Vec::new();
+    };
+    let mut supply = 0;
+    free.iter()
+        .map(|&(date, free)| {
+            supply += free;
+            ForecastDay {
+                date,
+                free,
+                due: deadlines.iter().filter(|d| **d == date).count(),
+                demand: deadlines.iter().filter(|d| **d >= first && **d <= date).count(),
+                supply,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod demand_forecast_tests {
+    use super::*;
+
+    #[test]
+    fn test_shortage_accumulates_until_capacity_catches_up() {
+        let day = |d| NaiveDate::from_ymd_opt(2025, 9, d).unwrap();
+        // GIVEN в пятницу 5 мест, в выходные приёма нет, в понедельник 10 мест
+        let free = [(day(5), 5), (day(6), 0), (day(7), 0), (day(8), 10)];
+        // AND у троих срок в пятницу, у четверых - в воскресенье, у одного - в понедельник,
+        // у одного срок уже прошёл
+        let deadlines = [
+            day(5), day(5), day(5),
+            day(7), day(7), day(7), day(7),
+            day(8),
+            day(1),
+        ];
+
+        // WHEN строится прогноз
+        let forecast = forecast_demand(&free, &deadlines);
+
+        // THEN в пятницу мест хватает
+        assert_eq!(forecast[0].due, 3);
+        assert_eq!(forecast[0].shortage(), 0);
+        // AND к воскресенью нужно принять семерых при пяти местах
+        assert_eq!((forecast[2].demand, forecast[2].supply), (7, 5));
+        assert_eq!(forecast[2].shortage(), 2);
+        // AND к понедельнику мест снова хватает
+        assert_eq!((forecast[3].demand, forecast[3].supply), (8, 15));
+        assert_eq!(forecast[3].shortage(), 0);
+    }
+}
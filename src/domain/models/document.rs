@@ -0,0 +1,287 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Error;
+use crate::domain::models::UserID;
+
+/// Максимальный размер загружаемого документа - 10 МБ.
+pub const MAX_DOCUMENT_SIZE: usize = 10 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum DocumentKind {
+    Passport,
+    MigrationCard,
+    Visa,
+}
+
+impl DocumentKind {
+    pub fn all() -> &'static [DocumentKind] {
+        &[
+            DocumentKind::Passport,
+            DocumentKind::MigrationCard,
+            DocumentKind::Visa,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DocumentKind::Passport => "passport",
+            DocumentKind::MigrationCard => "migration_card",
+            DocumentKind::Visa => "visa",
+        }
+    }
+}
+
+/// Document описывает файл (фото или PDF), приложенный студентом к записи для предварительной
+/// проверки сотрудником УМД. Для одной записи хранится не более одного документа каждого вида.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Document {
+    user_id: UserID,
+    slot_start: DateTime<Utc>,
+    kind: DocumentKind,
+    file_name: String,
+    mime_type: String,
+    size: usize,
+    uploaded_at: DateTime<Utc>,
+    storage_key: String,
+}
+
+impl Document {
+    pub fn check(mime_type: &str, size: usize) -> Result<(), Error> {
+        if extension(mime_type).is_none() {
+            return Err(Error::UnsupportedDocumentType(mime_type.to_string()));
+        }
+        if size > MAX_DOCUMENT_SIZE {
+            return Err(Error::DocumentTooLarge(MAX_DOCUMENT_SIZE));
+        }
+        Ok(())
+    }
+
+    pub fn new(
+        user_id: UserID,
+        slot_start: DateTime<Utc>,
+        kind: DocumentKind,
+        file_name: impl Into<String>,
+        mime_type: impl Into<String>,
+        size: usize,
+        uploaded_at: DateTime<Utc>,
+    ) -> Result<Self, Error> {
+        let mime_type = mime_type.into();
+        Self::check(&mime_type, size)?;
+        let storage_key = default_storage_key(user_id, slot_start, kind, &mime_type);
+        Ok(Self {
+            user_id,
+            slot_start,
+            kind,
+            file_name: file_name.into(),
+            mime_type,
+            size,
+            uploaded_at,
+            storage_key,
+        })
+    }
+
+    /// Восстанавливает документ из хранилища. Для документов, загруженных до появления
+    /// сохраняемого ключа, ключ выводится из записи.
+    #[allow(clippy::too_many_arguments)]
+    pub fn restore(
+        user_id: UserID,
+        slot_start: DateTime<Utc>,
+        kind: DocumentKind,
+        file_name: String,
+        mime_type: String,
+        size: usize,
+        uploaded_at: DateTime<Utc>,
+        storage_key: Option<String>,
+    ) -> Self {
+        let storage_key = storage_key
+            .unwrap_or_else(|| default_storage_key(user_id, slot_start, kind, &mime_type));
+        Self {
+            user_id,
+            slot_start,
+            kind,
+            file_name,
+            mime_type,
+            size,
+            uploaded_at,
+            storage_key,
+        }
+    }
+
+    /// Прикрепляет документ к другой записи пользователя, например при переносе записи.
+    /// Файл остаётся в хранилище под прежним ключом.
+    pub fn moved_to(&self, slot_start: DateTime<Utc>) -> Self {
+        Self {
+            slot_start,
+            ..self.clone()
+        }
+    }
+
+    pub fn user_id(&self) -> UserID {
+        self.user_id
+    }
+
+    pub fn slot_start(&self) -> DateTime<Utc> {
+        self.slot_start
+    }
+
+    pub fn kind(&self) -> DocumentKind {
+        self.kind
+    }
+
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn uploaded_at(&self) -> DateTime<Utc> {
+        self.uploaded_at
+    }
+
+    /// Ключ, под которым содержимое документа лежит в хранилище. Повторная загрузка документа
+    /// того же вида и типа к той же записи перезаписывает предыдущий файл; файл другого типа
+    /// или документ, перенесённый с другой записи, лежит под другим ключом, и предыдущий
+    /// нужно удалить.
+    pub fn storage_key(&self) -> String {
+        self.storage_key.clone()
+    }
+}
+
+fn default_storage_key(
+    user_id: UserID,
+    slot_start: DateTime<Utc>,
+    kind: DocumentKind,
+    mime_type: &str,
+) -> String {
+    format!(
+        "{}/{}_{}.{}",
+        user_id,
+        slot_start.timestamp(),
+        kind.as_str(),
+        extension(mime_type).unwrap_or("bin"),
+    )
+}
+
+fn extension(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        "image/jpeg" => Some("jpg"),
+        "image/png" => Some("png"),
+        "application/pdf" => Some("pdf"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod document_tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn slot_start() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_allowed_types() {
+        // GIVEN допустимые типы файлов
+        let types = vec!["image/jpeg", "image/png", "application/pdf"];
+
+        // THEN документы таких типов создаются
+        types.into_iter().for_each(|mime_type| {
+            let doc = Document::new(
+                UserID::new(1),
+                slot_start(),
+                DocumentKind::Passport,
+                "passport",
+                mime_type,
+                1024,
+                Utc::now(),
+            );
+            assert!(doc.is_ok(), "{}", mime_type);
+        })
+    }
+
+    #[test]
+    fn test_unsupported_type() {
+        // WHEN загружается документ Word
+        let res = Document::new(
+            UserID::new(1),
+            slot_start(),
+            DocumentKind::Visa,
+            "visa.docx",
+            "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+            1024,
+            Utc::now(),
+        );
+
+        // THEN ошибка неподдерживаемого типа
+        assert!(matches!(res, Err(Error::UnsupportedDocumentType(_))));
+    }
+
+    #[test]
+    fn test_too_large() {
+        // WHEN размер документа превышает допустимый
+        let res = Document::new(
+            UserID::new(1),
+            slot_start(),
+            DocumentKind::MigrationCard,
+            "card.pdf",
+            "application/pdf",
+            MAX_DOCUMENT_SIZE + 1,
+            Utc::now(),
+        );
+
+        // THEN ошибка превышения размера
+        assert!(matches!(res, Err(Error::DocumentTooLarge(MAX_DOCUMENT_SIZE))));
+    }
+
+    #[test]
+    fn test_storage_key() {
+        // GIVEN фотография паспорта
+        let doc = Document::new(
+            UserID::new(42),
+            slot_start(),
+            DocumentKind::Passport,
+            "photo.jpg",
+            "image/jpeg",
+            1024,
+            Utc::now(),
+        )
+        .unwrap();
+
+        // THEN ключ однозначно определяется пользователем, записью и видом документа
+        assert_eq!(
+            doc.storage_key(),
+            format!("42/{}_passport.jpg", slot_start().timestamp())
+        );
+    }
+
+    #[test]
+    fn test_moved_document_keeps_storage_key() {
+        // GIVEN документ, загруженный к записи
+        let doc = Document::new(
+            UserID::new(42),
+            slot_start(),
+            DocumentKind::Visa,
+            "visa.pdf",
+            "application/pdf",
+            1024,
+            Utc::now(),
+        )
+        .unwrap();
+
+        // WHEN запись переносится на другое время
+        let new_start = slot_start() + chrono::Duration::days(2);
+        let moved = doc.moved_to(new_start);
+
+        // THEN документ прикреплён к новой записи, а файл остаётся под прежним ключом
+        assert_eq!(moved.slot_start(), new_start);
+        assert_eq!(moved.storage_key(), doc.storage_key());
+    }
+}
@@ -1,12 +1,29 @@
+mod audit;
 mod citizenship;
 mod closed_range;
+mod consent;
+mod deadline_override;
+mod demand_forecast;
+mod document;
 mod reservation;
+mod reservation_stats;
+mod reservations_filter;
 mod service;
 mod slot;
+mod staff;
 mod user;
 
+pub use audit::*;
 pub use citizenship::*;
 pub use closed_range::*;
+pub use consent::*;
+pub use deadline_override::*;
+pub use demand_forecast::*;
+pub use document::*;
+pub use reservation::{Reservation, reservation_code, reservation_id};
+pub use reservation_stats::*;
+pub use reservations_filter::*;
 pub use service::*;
 pub use slot::*;
+pub use staff::*;
 pub use user::*;
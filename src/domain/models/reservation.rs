@@ -1,4 +1,10 @@
-use crate::domain::models::{Service, User};
+use chrono::{DateTime, Utc};
+
+use crate::domain::models::{Service, User, UserID};
+
+/// Алфавит кода записи без похожих друг на друга символов (0/O, 1/I).
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+const CODE_LENGTH: usize = 6;
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct Reservation {
@@ -19,3 +25,47 @@ impl Reservation {
         &self.service
     }
 }
+
+/// Идентификатор записи во внешних системах. Запись однозначно определяется пользователем и
+/// временем начала слота.
+pub fn reservation_id(user_id: UserID, slot_start: DateTime<Utc>) -> String {
+    format!("{}-{}", user_id, slot_start.timestamp())
+}
+
+/// Короткий код записи, который студент называет сотруднику УМД на приёме. Код однозначно
+/// вычисляется по пользователю и времени начала слота, поэтому не хранится в базе.
+pub fn reservation_code(user_id: UserID, slot_start: DateTime<Utc>) -> String {
+    // FNV-1a
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let key = format!("{}:{}", user_id, slot_start.timestamp());
+    for byte in key.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    (0..CODE_LENGTH)
+        .map(|i| CODE_ALPHABET[((hash >> (i * 5)) & 0x1f) as usize] as char)
+        .collect()
+}
+
+#[cfg(test)]
+mod reservation_code_tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reservation_code() {
+        // GIVEN запись пользователя
+        let slot_start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+
+        // WHEN код вычисляется повторно
+        let code = reservation_code(UserID::new(42), slot_start);
+
+        // THEN код не меняется и состоит из символов алфавита
+        assert_eq!(code, reservation_code(UserID::new(42), slot_start));
+        assert_eq!(code.len(), CODE_LENGTH);
+        assert!(code.bytes().all(|c| CODE_ALPHABET.contains(&c)));
+
+        // AND у другого пользователя на тот же слот код другой
+        assert_ne!(code, reservation_code(UserID::new(43), slot_start));
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::NaiveDate;
+
+use crate::domain::models::{Citizenship, Service};
+
+/// Записи одного дня для статистики.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DayReservationStats {
+    pub date: NaiveDate,
+    /// Записи, которые не были отменены.
+    pub reserved: usize,
+    /// Записи, по которым сотрудники отметили приход.
+    pub attended: usize,
+    pub cancelled: usize,
+}
+
+impl DayReservationStats {
+    /// Число неявок. Считается только за прошедшие дни, в которые сотрудники отмечали приход:
+    /// если не отмечен никто, неизвестно, пришёл ли кто-нибудь.
+    pub fn no_shows(&self, today: NaiveDate) -> Option<usize> {
+        (self.date < today && self.attended > 0).then(|| self.reserved - self.attended)
+    }
+}
+
+/// Число записей с одной услугой и гражданством.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReservationBreakdown {
+    pub service: Service,
+    pub citizenship: Citizenship,
+    pub reserved: usize,
+}
+
+#[cfg(test)]
+mod reservation_stats_tests {
+    use super::*;
+
+    #[test]
+    fn test_no_shows_only_for_past_days_with_attendance() {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
+        let day = |date: NaiveDate, attended| DayReservationStats {
+            date,
+            reserved: 5,
+            attended,
+            cancelled: 1,
+        };
+
+        // GIVEN прошедший день, в который отмечали приход
+        // THEN неявившиеся - записанные без отметки
+        assert_eq!(day(today.pred_opt().unwrap(), 3).no_shows(today), Some(2));
+
+        // GIVEN прошедший день без отметок
+        // THEN неявки неизвестны
+        assert_eq!(day(today.pred_opt().unwrap(), 0).no_shows(today), None);
+
+        // GIVEN сегодняшний день: приём ещё идёт
+        // THEN неявки не считаются
+        assert_eq!(day(today, 3).no_shows(today), None);
+    }
+}
@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::domain::models::{Citizenship, Reservation, Service};
+
+/// Статус записи относительно текущего момента.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ReservationStatus {
+    Upcoming,
+    Past,
+}
+
+/// Фильтр выгрузки записей. Незаданное поле не ограничивает выборку.
+///
+/// Гражданство `Citizenship::Other` совпадает с любым гражданством не из списка.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReservationsFilter {
+    pub service: Option<Service>,
+    pub citizenship: Option<Citizenship>,
+    pub status: Option<ReservationStatus>,
+}
+
+impl ReservationsFilter {
+    /// Проверяет запись на слот `slot_start`. `now` сравнивается с началом слота, поэтому
+    /// должен быть сдвинут так же, как время слотов (см. `slot_now`).
+    pub fn matches(&self, r: &Reservation, slot_start: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        if self.service.is_some_and(|s| s != *r.service()) {
+            return false;
+        }
+        let citizenship_matches = match (&self.citizenship, r.by().citizenship()) {
+            (None, _) => true,
+            (Some(Citizenship::Other(_)), Citizenship::Other(_)) => true,
+            (Some(expected), actual) => expected == actual,
+        };
+        if !citizenship_matches {
+            return false;
+        }
+        match self.status {
+            None => true,
+            Some(ReservationStatus::Upcoming) => slot_start >= now,
+            Some(ReservationStatus::Past) => slot_start < now,
+        }
+    }
+}
+
+#[cfg(test)]
+mod reservations_filter_tests {
+    use super::*;
+    use crate::domain::models::{OnlyCyrillic, OnlyLatin, User, UserID, Username};
+    use chrono::{Duration, NaiveDate, TimeZone};
+
+    fn reservation(citizenship: Citizenship, service: Service) -> Reservation {
+        let user = User::new(
+            UserID::new(1),
+            Username::new(""),
+            OnlyLatin::new("Ivanov").unwrap(),
+            OnlyCyrillic::new("Иванов").unwrap(),
+            citizenship,
+            NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+        );
+        Reservation::new(user, service)
+    }
+
+    #[test]
+    fn test_empty_filter_matches_everything() {
+        // GIVEN пустой фильтр
+        let filter = ReservationsFilter::default();
+        let now = Utc.with_ymd_and_hms(2025, 7, 14, 12, 0, 0).unwrap();
+        let r = reservation(Citizenship::Armenia, Service::Visa);
+
+        // THEN под него подходят и прошедшие, и предстоящие записи
+        assert!(filter.matches(&r, now - Duration::days(1), now));
+        assert!(filter.matches(&r, now + Duration::days(1), now));
+    }
+
+    #[test]
+    fn test_filter_by_service_citizenship_and_status() {
+        // GIVEN фильтр по услуге, гражданству и статусу
+        let filter = ReservationsFilter {
+            service: Some(Service::Visa),
+            citizenship: Some(Citizenship::Armenia),
+            status: Some(ReservationStatus::Upcoming),
+        };
+        let now = Utc.with_ymd_and_hms(2025, 7, 14, 12, 0, 0).unwrap();
+        let tomorrow = now + Duration::days(1);
+
+        // THEN подходит только запись, совпадающая по всем полям
+        assert!(filter.matches(&reservation(Citizenship::Armenia, Service::Visa), tomorrow, now));
+        assert!(!filter.matches(&reservation(Citizenship::Belarus, Service::Visa), tomorrow, now));
+        assert!(!filter.matches(
+            &reservation(Citizenship::Armenia, Service::RenewalOfVisa),
+            tomorrow,
+            now
+        ));
+        assert!(!filter.matches(
+            &reservation(Citizenship::Armenia, Service::Visa),
+            now - Duration::minutes(20),
+            now
+        ));
+    }
+
+    #[test]
+    fn test_other_citizenship_matches_any_other() {
+        // GIVEN фильтр по гражданству «Другое»
+        let filter = ReservationsFilter {
+            citizenship: Some(Citizenship::Other(String::new())),
+            ..Default::default()
+        };
+        let now = Utc.with_ymd_and_hms(2025, 7, 14, 12, 0, 0).unwrap();
+
+        // THEN подходит любое гражданство не из списка
+        assert!(filter.matches(&reservation(Citizenship::Other("Китай".into()), Service::Visa), now, now));
+        assert!(!filter.matches(&reservation(Citizenship::Armenia, Service::Visa), now, now));
+    }
+}
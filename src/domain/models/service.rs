@@ -20,6 +20,14 @@ impl Service {
         }
     }
 
+    /// Для этих услуг УМД подаёт за студента уведомление о прибытии.
+    pub fn needs_arrival_notice(&self) -> bool {
+        matches!(
+            self,
+            Self::InitialRegistration | Self::RenewalOfRegistration | Self::All
+        )
+    }
+
     pub fn all() -> &'static [Service] {
         &[
             Service::InitialRegistration,
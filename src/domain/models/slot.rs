@@ -1,9 +1,24 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 
 use crate::domain::Error;
 use crate::domain::models::reservation::Reservation;
 use crate::domain::models::{ClosedRange, Service, User, UserID};
 
+/// Сдвиг московского времени от UTC. Время слотов хранится как московское «настенное»
+/// время в полях UTC, поэтому текущее время для сравнения с ними сдвигается так же.
+const SLOT_TIME_OFFSET: Duration = Duration::hours(3);
+
+/// Текущий момент в том же виде, что и время слотов. Сравнивать с началом слота можно только
+/// его, а не настоящее время.
+pub fn slot_now(now: DateTime<Utc>) -> DateTime<Utc> {
+    now + SLOT_TIME_OFFSET
+}
+
+/// Сегодняшняя дата в том же виде, что и даты слотов, то есть по Москве.
+pub fn slot_today(now: DateTime<Utc>) -> NaiveDate {
+    slot_now(now).date_naive()
+}
+
 #[derive(Debug, Clone)]
 pub struct Slot {
     interval: ClosedRange<DateTime<Utc>>,
@@ -244,4 +259,27 @@ mod slot_tests {
         // THEN слот всё ещё забронирован на одно место
         assert_eq!(slot.reserved(), 1);
     }
+
+    #[test]
+    fn test_slot_today_is_moscow_date() {
+        // GIVEN в UTC ещё 14 июля, а в Москве уже 15-е
+        let now = Utc.with_ymd_and_hms(2025, 7, 14, 22, 30, 0).unwrap();
+
+        // THEN сегодня - 15 июля, как у слотов этого утра
+        assert_eq!(slot_today(now), NaiveDate::from_ymd_opt(2025, 7, 15).unwrap());
+        // AND днём даты совпадают
+        let noon = Utc.with_ymd_and_hms(2025, 7, 14, 9, 0, 0).unwrap();
+        assert_eq!(slot_today(noon), NaiveDate::from_ymd_opt(2025, 7, 14).unwrap());
+    }
+
+    #[test]
+    fn test_slot_now_is_moscow_wall_clock() {
+        // GIVEN в Москве 11:30, а слот начался в 10:00 по Москве
+        let now = Utc.with_ymd_and_hms(2025, 7, 14, 8, 30, 0).unwrap();
+        let slot_start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+
+        // THEN слот уже начался, хотя в UTC ещё 8:30
+        assert!(slot_start < slot_now(now));
+        assert_eq!(slot_now(now), Utc.with_ymd_and_hms(2025, 7, 14, 11, 30, 0).unwrap());
+    }
 }
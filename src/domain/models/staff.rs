@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::Error;
+use crate::domain::models::UserID;
+
+/// Роль сотрудника УМД. Роли упорядочены по возрастанию прав: каждая следующая разрешает всё,
+/// что разрешает предыдущая.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum StaffRole {
+    /// Просмотр и выгрузка записей и документов.
+    Viewer,
+    /// Вдобавок изменение чужих записей и рассмотрение запросов студентов.
+    Operator,
+    /// Вдобавок назначение и снятие ролей.
+    Owner,
+}
+
+impl StaffRole {
+    pub fn allows(&self, required: StaffRole) -> bool {
+        *self >= required
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Viewer => "viewer",
+            Self::Operator => "operator",
+            Self::Owner => "owner",
+        }
+    }
+}
+
+impl TryFrom<&str> for StaffRole {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "viewer" => Ok(Self::Viewer),
+            "operator" => Ok(Self::Operator),
+            "owner" => Ok(Self::Owner),
+            _ => Err(Error::InvalidValue(format!(
+                "invalid StaffRole: expected one of ['viewer', 'operator', 'owner'], got {}",
+                value
+            ))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StaffMember {
+    id: UserID,
+    role: StaffRole,
+}
+
+impl StaffMember {
+    pub fn new(id: UserID, role: StaffRole) -> Self {
+        Self { id, role }
+    }
+
+    pub fn id(&self) -> UserID {
+        self.id
+    }
+
+    pub fn role(&self) -> StaffRole {
+        self.role
+    }
+}
+
+#[cfg(test)]
+mod staff_tests {
+    use super::*;
+
+    #[test]
+    fn test_roles_are_ordered_by_permissions() {
+        // GIVEN сотрудник с ролью оператора
+        let role = StaffRole::Operator;
+
+        // THEN ему доступно всё, что доступно наблюдателю и оператору, но не владельцу
+        assert!(role.allows(StaffRole::Viewer));
+        assert!(role.allows(StaffRole::Operator));
+        assert!(!role.allows(StaffRole::Owner));
+    }
+
+    #[test]
+    fn test_role_from_str() {
+        assert_eq!(StaffRole::try_from("owner").unwrap(), StaffRole::Owner);
+        assert_eq!(StaffRole::try_from(StaffRole::Viewer.as_str()).unwrap(), StaffRole::Viewer);
+        assert!(StaffRole::try_from("admin").is_err());
+    }
+}
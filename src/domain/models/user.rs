@@ -1,5 +1,5 @@
 use crate::domain::Error;
-use crate::domain::models::Citizenship;
+use crate::domain::models::{Citizenship, Service};
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use std::fmt::Display;
@@ -70,10 +70,13 @@ impl OnlyCyrillic {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct UserID(i64);
 
 impl UserID {
+    /// От имени SYSTEM в журнал аудита записываются действия, которые бот выполняет сам.
+    pub const SYSTEM: UserID = UserID(0);
+
     pub fn new(id: impl Into<i64>) -> Self {
         Self(id.into())
     }
@@ -102,6 +105,91 @@ impl Username {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Sex {
+    Male,
+    Female,
+}
+
+impl Sex {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Sex::Male => "male",
+            Sex::Female => "female",
+        }
+    }
+}
+
+impl TryFrom<&str> for Sex {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "male" => Ok(Sex::Male),
+            "female" => Ok(Sex::Female),
+            _ => Err(Error::InvalidValue(format!(
+                "invalid Sex: expected one of ['male', 'female'], got {}",
+                value
+            ))),
+        }
+    }
+}
+
+/// Паспорт иностранного гражданина. Серии у многих паспортов нет, тогда она пустая.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Passport {
+    series: String,
+    number: String,
+}
+
+impl Passport {
+    const MAX_LEN: usize = 20;
+
+    fn check(s: &str) -> bool {
+        s.len() <= Self::MAX_LEN && s.chars().all(|c| c.is_ascii_alphanumeric())
+    }
+
+    pub fn new(series: impl Into<String>, number: impl Into<String>) -> Result<Self, Error> {
+        let series = series.into().to_uppercase();
+        let number = number.into().to_uppercase();
+        if number.is_empty() || !Self::check(&series) || !Self::check(&number) {
+            return Err(Error::InvalidValue(format!(
+                "Passport: got {} {}",
+                series, number
+            )));
+        }
+        Ok(Self { series, number })
+    }
+
+    /// Разбирает «серия номер» или только номер, если серии нет.
+    pub fn parse(s: &str) -> Result<Self, Error> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        match parts.as_slice() {
+            [number] => Self::new("", *number),
+            [series, number] => Self::new(*series, *number),
+            _ => Err(Error::InvalidValue(format!("Passport: got {}", s))),
+        }
+    }
+
+    pub fn series(&self) -> &str {
+        &self.series
+    }
+
+    pub fn number(&self) -> &str {
+        &self.number
+    }
+}
+
+impl Display for Passport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.series.is_empty() {
+            write!(f, "{}", self.number)
+        } else {
+            write!(f, "{} {}", self.series, self.number)
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct User {
     id: UserID,
@@ -110,6 +198,12 @@ pub struct User {
     full_name_cyr: OnlyCyrillic,
     citizenship: Citizenship,
     arrival_date: NaiveDate,
+    visa_expiry: Option<NaiveDate>,
+    registration_expiry: Option<NaiveDate>,
+    birth_date: Option<NaiveDate>,
+    sex: Option<Sex>,
+    passport: Option<Passport>,
+    place_of_stay: Option<String>,
 }
 
 impl User {
@@ -128,6 +222,12 @@ impl User {
             full_name_cyr,
             citizenship,
             arrival_date,
+            visa_expiry: None,
+            registration_expiry: None,
+            birth_date: None,
+            sex: None,
+            passport: None,
+            place_of_stay: None,
         }
     }
 
@@ -155,6 +255,63 @@ impl User {
         &self.arrival_date
     }
 
+    pub fn visa_expiry(&self) -> Option<NaiveDate> {
+        self.visa_expiry
+    }
+
+    pub fn registration_expiry(&self) -> Option<NaiveDate> {
+        self.registration_expiry
+    }
+
+    pub fn birth_date(&self) -> Option<NaiveDate> {
+        self.birth_date
+    }
+
+    pub fn sex(&self) -> Option<Sex> {
+        self.sex
+    }
+
+    pub fn passport(&self) -> Option<&Passport> {
+        self.passport.as_ref()
+    }
+
+    /// Адрес места пребывания для уведомления о прибытии.
+    pub fn place_of_stay(&self) -> Option<&str> {
+        self.place_of_stay.as_deref()
+    }
+
+    /// Возвращает незаполненные поля, без которых уведомление о прибытии придётся дописывать
+    /// от руки.
+    pub fn missing_arrival_notice_fields(&self) -> Vec<&'static str> {
+        let mut res = Vec::new();
+        if self.birth_date.is_none() {
+            res.push("birth_date");
+        }
+        if self.sex.is_none() {
+            res.push("sex");
+        }
+        if self.passport.is_none() {
+            res.push("passport");
+        }
+        if self.place_of_stay.is_none() {
+            res.push("place_of_stay");
+        }
+        res
+    }
+
+    /// Возвращает услуги продления, доступные пользователю, вместе с датой окончания срока
+    /// действия продлеваемого документа.
+    pub fn renewals(&self) -> Vec<(Service, NaiveDate)> {
+        let mut res = Vec::new();
+        if let Some(date) = self.registration_expiry {
+            res.push((Service::RenewalOfRegistration, date));
+        }
+        if let Some(date) = self.visa_expiry {
+            res.push((Service::RenewalOfVisa, date));
+        }
+        res
+    }
+
     pub fn set_full_name_lat(&mut self, full_name_lat: OnlyLatin) {
         self.full_name_lat = full_name_lat;
     }
@@ -170,6 +327,30 @@ impl User {
     pub fn set_arrival_date(&mut self, arrival_data: NaiveDate) {
         self.arrival_date = arrival_data;
     }
+
+    pub fn set_visa_expiry(&mut self, visa_expiry: Option<NaiveDate>) {
+        self.visa_expiry = visa_expiry;
+    }
+
+    pub fn set_registration_expiry(&mut self, registration_expiry: Option<NaiveDate>) {
+        self.registration_expiry = registration_expiry;
+    }
+
+    pub fn set_birth_date(&mut self, birth_date: Option<NaiveDate>) {
+        self.birth_date = birth_date;
+    }
+
+    pub fn set_sex(&mut self, sex: Option<Sex>) {
+        self.sex = sex;
+    }
+
+    pub fn set_passport(&mut self, passport: Option<Passport>) {
+        self.passport = passport;
+    }
+
+    pub fn set_place_of_stay(&mut self, place_of_stay: Option<String>) {
+        self.place_of_stay = place_of_stay;
+    }
 }
 
 #[cfg(test)]
@@ -213,4 +394,87 @@ mod tests {
             assert!(OnlyCyrillic::new("Иван 2".to_string()).is_err());
         }
     }
+
+    mod renewals {
+        use super::*;
+
+        fn create_user() -> User {
+            User::new(
+                UserID::new(1),
+                Username::new("username"),
+                OnlyLatin::new("Ivan").unwrap(),
+                OnlyCyrillic::new("Иван").unwrap(),
+                Citizenship::Armenia,
+                NaiveDate::from_ymd_opt(2025, 7, 7).unwrap(),
+            )
+        }
+
+        #[test]
+        fn should_be_empty_without_expiry_dates() {
+            assert!(create_user().renewals().is_empty());
+        }
+
+        #[test]
+        fn should_match_expiry_dates_with_services() {
+            let visa_expiry = NaiveDate::from_ymd_opt(2026, 1, 1).unwrap();
+            let registration_expiry = NaiveDate::from_ymd_opt(2025, 10, 1).unwrap();
+            let mut user = create_user();
+            user.set_visa_expiry(Some(visa_expiry));
+            user.set_registration_expiry(Some(registration_expiry));
+
+            assert_eq!(
+                user.renewals(),
+                vec![
+                    (Service::RenewalOfRegistration, registration_expiry),
+                    (Service::RenewalOfVisa, visa_expiry),
+                ]
+            );
+        }
+    }
+
+    mod passport {
+        use super::*;
+
+        #[test]
+        fn should_parse_series_and_number() {
+            let passport = Passport::parse("ab 1234567").unwrap();
+            assert_eq!(passport.series(), "AB");
+            assert_eq!(passport.number(), "1234567");
+            assert_eq!(passport.to_string(), "AB 1234567");
+        }
+
+        #[test]
+        fn should_allow_number_without_series() {
+            let passport = Passport::parse("N01234567").unwrap();
+            assert_eq!(passport.series(), "");
+            assert_eq!(passport.to_string(), "N01234567");
+        }
+
+        #[test]
+        fn should_not_allow_invalid_input() {
+            assert!(Passport::parse("").is_err());
+            assert!(Passport::parse("AB 12 34").is_err());
+            assert!(Passport::parse("АБ 1234567").is_err());
+        }
+    }
+
+    mod arrival_notice {
+        use super::*;
+
+        #[test]
+        fn should_list_missing_fields() {
+            let mut user = User::new(
+                UserID::new(1),
+                Username::new("username"),
+                OnlyLatin::new("Ivan").unwrap(),
+                OnlyCyrillic::new("Иван").unwrap(),
+                Citizenship::Armenia,
+                NaiveDate::from_ymd_opt(2025, 7, 7).unwrap(),
+            );
+            user.set_sex(Some(Sex::Male));
+            user.set_passport(Some(Passport::parse("AB 1234567").unwrap()));
+
+            assert_eq!(user.missing_arrival_notice_fields(), vec!["birth_date", "place_of_stay"]);
+        }
+    }
 }
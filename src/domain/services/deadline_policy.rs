@@ -1,10 +1,36 @@
-use chrono::Days;
+use chrono::{Days, NaiveDate};
 
-use crate::domain::models::Citizenship;
+use crate::domain::Error;
+use crate::domain::models::{Citizenship, ClosedRange};
 
 /// DeadlinePolicy описывает сроки подачи основных документов для иностранцев.
 pub trait DeadlinePolicy: Send + Sync {
     fn deadline(&self, citizenship: &Citizenship) -> Days;
+
+    /// Последний день подачи документов для прибывшего в `arrival_date`.
+    fn deadline_date(&self, arrival_date: NaiveDate, citizenship: &Citizenship) -> NaiveDate {
+        arrival_date
+            .checked_add_days(self.deadline(citizenship))
+            .unwrap()
+    }
+
+    /// Дни, на которые можно записаться с `today` до последнего дня подачи документов.
+    /// Если срок уже истёк, возвращает `DeadlineExpired`: записаться можно только по запросу.
+    fn reservation_days(
+        &self,
+        today: NaiveDate,
+        arrival_date: NaiveDate,
+        citizenship: &Citizenship,
+    ) -> Result<ClosedRange<NaiveDate>, Error> {
+        let deadline = self.deadline_date(arrival_date, citizenship);
+        if deadline < today {
+            return Err(Error::DeadlineExpired(deadline));
+        }
+        Ok(ClosedRange {
+            start: today,
+            end: deadline,
+        })
+    }
 }
 
 /// StandardDeadlinePolicy устанавливает указанные представителем УМД сроки:
@@ -56,4 +82,53 @@ mod default_deadline_policy_tests {
             assert_eq!(policy.deadline(&citizenship), Days::new(days));
         })
     }
+
+    #[test]
+    fn test_deadline_date() {
+        // GIVEN стандартная политика сроков
+        let policy = StandardDeadlinePolicy::default();
+
+        // WHEN гражданин Таджикистана прибыл 20.07.2025
+        let arrival_date = NaiveDate::from_ymd_opt(2025, 7, 20).unwrap();
+
+        // THEN последний день подачи документов - 04.08.2025
+        assert_eq!(
+            policy.deadline_date(arrival_date, &Citizenship::Tajikistan),
+            NaiveDate::from_ymd_opt(2025, 8, 4).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_reservation_days_end_on_deadline() {
+        // GIVEN гражданин Таджикистана прибыл неделю назад
+        let policy = StandardDeadlinePolicy::default();
+        let today = NaiveDate::from_ymd_opt(2025, 7, 27).unwrap();
+        let arrival_date = NaiveDate::from_ymd_opt(2025, 7, 20).unwrap();
+
+        // WHEN запрашиваются дни для записи
+        let days = policy
+            .reservation_days(today, arrival_date, &Citizenship::Tajikistan)
+            .unwrap();
+
+        // THEN предлагаются дни с сегодняшнего до последнего дня подачи, а не 15 дней от сегодня
+        assert_eq!(days.start, today);
+        assert_eq!(days.end, NaiveDate::from_ymd_opt(2025, 8, 4).unwrap());
+    }
+
+    #[test]
+    fn test_reservation_days_after_deadline() {
+        // GIVEN гражданин другой страны прибыл 10 дней назад
+        let policy = StandardDeadlinePolicy::default();
+        let today = NaiveDate::from_ymd_opt(2025, 7, 30).unwrap();
+        let arrival_date = NaiveDate::from_ymd_opt(2025, 7, 20).unwrap();
+
+        // WHEN запрашиваются дни для записи
+        let res = policy.reservation_days(today, arrival_date, &Citizenship::Other("China".into()));
+
+        // THEN срок истёк 27.07.2025
+        assert!(matches!(
+            res,
+            Err(Error::DeadlineExpired(d)) if d == NaiveDate::from_ymd_opt(2025, 7, 27).unwrap()
+        ));
+    }
 }
@@ -1,7 +1,9 @@
 mod deadline_policy;
+mod retention_policy;
 mod slots_factory;
 mod working_hours_policy;
 
 pub use deadline_policy::*;
+pub use retention_policy::*;
 pub use slots_factory::*;
 pub use working_hours_policy::*;
@@ -0,0 +1,84 @@
+use chrono::{DateTime, Months, Utc};
+
+use crate::domain::Error;
+
+/// RetentionPolicy задаёт сроки хранения персональных данных неактивных пользователей, то есть
+/// тех, у кого не было ни записей, ни изменений анкеты:
+/// - через `anonymise_after` данные обезличиваются, история записей остаётся;
+/// - через `delete_after` пользователь удаляется вместе с записями.
+///
+/// Записи и отмены старше `anonymise_after` обезличиваются независимо от активности
+/// пользователя.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetentionPolicy {
+    anonymise_after: Months,
+    delete_after: Months,
+}
+
+impl RetentionPolicy {
+    pub fn new(anonymise_after: Months, delete_after: Months) -> Result<Self, Error> {
+        if delete_after < anonymise_after {
+            return Err(Error::InvalidValue(format!(
+                "RetentionPolicy: delete_after ({} months) is shorter than anonymise_after \
+                ({} months)",
+                delete_after.as_u32(),
+                anonymise_after.as_u32(),
+            )));
+        }
+        Ok(Self {
+            anonymise_after,
+            delete_after,
+        })
+    }
+
+    /// Пользователи, неактивные с этого момента, обезличиваются.
+    pub fn anonymise_before(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.anonymise_after
+    }
+
+    /// Пользователи, неактивные с этого момента, удаляются.
+    pub fn delete_before(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        now - self.delete_after
+    }
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            anonymise_after: Months::new(12),
+            delete_after: Months::new(24),
+        }
+    }
+}
+
+#[cfg(test)]
+mod retention_policy_tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_cutoffs() {
+        // GIVEN политика по умолчанию
+        let policy = RetentionPolicy::default();
+        let now = Utc.with_ymd_and_hms(2026, 2, 28, 12, 0, 0).unwrap();
+
+        // THEN обезличиваются неактивные год, а удаляются неактивные два года
+        assert_eq!(
+            policy.anonymise_before(now),
+            Utc.with_ymd_and_hms(2025, 2, 28, 12, 0, 0).unwrap()
+        );
+        assert_eq!(
+            policy.delete_before(now),
+            Utc.with_ymd_and_hms(2024, 2, 28, 12, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_delete_before_anonymise() {
+        // WHEN удалять предлагается раньше, чем обезличивать
+        let res = RetentionPolicy::new(Months::new(12), Months::new(6));
+
+        // THEN политика отклоняется
+        assert!(matches!(res, Err(Error::InvalidValue(_))));
+    }
+}
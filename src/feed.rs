@@ -0,0 +1,321 @@
+use chrono::{DateTime, Utc};
+use http_body_util::Full;
+use hyper::body::Bytes;
+use hyper::header::{ALLOW, CACHE_CONTROL, CONTENT_TYPE};
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{Method, Request, StatusCode};
+use hyper_util::rt::TokioIo;
+use percent_encoding::percent_decode_str;
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+
+use crate::domain::models::Service;
+use crate::infra::{IcsMethod, reservation_event, to_ics};
+use crate::usecases::{CalendarFeedUseCase, CalendarReservationDTO};
+
+/// Сколько соединений обслуживается одновременно.
+const MAX_CONNECTIONS: usize = 64;
+/// Сколько может длиться одно соединение.
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Ответ сервера календарей.
+#[derive(Debug, PartialEq)]
+enum Response {
+    Calendar(String),
+    NotFound,
+    MethodNotAllowed,
+    InternalError,
+}
+
+impl Response {
+    fn status(&self) -> StatusCode {
+        match self {
+            Response::Calendar(_) => StatusCode::OK,
+            Response::NotFound => StatusCode::NOT_FOUND,
+            Response::MethodNotAllowed => StatusCode::METHOD_NOT_ALLOWED,
+            Response::InternalError => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn into_http(self) -> hyper::Response<Full<Bytes>> {
+        let status = self.status();
+        let (content_type, body) = match self {
+            Response::Calendar(ics) => ("text/calendar; charset=utf-8", ics),
+            _ => ("text/plain; charset=utf-8", status.to_string()),
+        };
+        let mut response = hyper::Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, content_type)
+            .header(CACHE_CONTROL, "no-store");
+        if status == StatusCode::METHOD_NOT_ALLOWED {
+            response = response.header(ALLOW, "GET, HEAD");
+        }
+        response
+            .body(Full::new(Bytes::from(body)))
+            .expect("static headers are valid")
+    }
+}
+
+/// Раздаёт личные календари студентов по ссылкам вида `<адрес>/<токен>.ics`. Сервер
+/// отвечает на GET и HEAD и закрывает соединение после ответа: календари опрашивают
+/// ссылку редко, а TLS и домен обеспечивает обратный прокси.
+pub async fn serve_calendar_feed(
+    listener: TcpListener,
+    use_case: CalendarFeedUseCase,
+    service_title: fn(&Service) -> &'static str,
+) {
+    serve(listener, use_case, service_title, MAX_CONNECTIONS).await
+}
+
+async fn serve(
+    listener: TcpListener,
+    use_case: CalendarFeedUseCase,
+    service_title: fn(&Service) -> &'static str,
+    max_connections: usize,
+) {
+    let connections = Arc::new(Semaphore::new(max_connections));
+    loop {
+        // Соединение принимается, только когда есть свободное место, остальные клиенты ждут
+        // в очереди ОС и не занимают задачи и сокеты бота.
+        let permit = connections
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("semaphore is never closed");
+        let stream = match listener.accept().await {
+            Ok((stream, _)) => stream,
+            Err(err) => {
+                log::error!("Failed to accept calendar feed connection: {}", err);
+                continue;
+            }
+        };
+        let use_case = use_case.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |req| {
+                let use_case = use_case.clone();
+                async move {
+                    let response = respond(&req, &use_case, service_title).await;
+                    Ok::<_, Infallible>(response.into_http())
+                }
+            });
+            let connection = http1::Builder::new()
+                .keep_alive(false)
+                .serve_connection(TokioIo::new(stream), service);
+            match tokio::time::timeout(CONNECTION_TIMEOUT, connection).await {
+                Ok(Ok(())) => {}
+                Ok(Err(err)) => log::warn!("Calendar feed connection failed: {}", err),
+                Err(_) => log::warn!("Calendar feed connection timed out"),
+            }
+            drop(permit);
+        });
+    }
+}
+
+async fn respond<B>(
+    req: &Request<B>,
+    use_case: &CalendarFeedUseCase,
+    service_title: fn(&Service) -> &'static str,
+) -> Response {
+    let token = match feed_token(req.method(), req.uri().path()) {
+        Ok(token) => token,
+        Err(response) => return response,
+    };
+    match use_case.feed(&token).await {
+        Ok(Some(reservations)) => {
+            Response::Calendar(feed_ics(&reservations, service_title, Utc::now()))
+        }
+        Ok(None) => Response::NotFound,
+        Err(err) => {
+            log::error!("Failed to build calendar feed: {}", err);
+            Response::InternalError
+        }
+    }
+}
+
+/// Токен из пути `<путь>/<токен>.ics`. Префикс пути не проверяется, чтобы сервер работал
+/// за прокси с любым адресом. На HEAD hyper отвечает теми же заголовками без тела.
+fn feed_token(method: &Method, path: &str) -> Result<String, Response> {
+    if method != Method::GET && method != Method::HEAD {
+        return Err(Response::MethodNotAllowed);
+    }
+    let name = path.rsplit('/').next().unwrap_or_default();
+    let name = percent_decode_str(name)
+        .decode_utf8()
+        .map_err(|_| Response::NotFound)?;
+    name.strip_suffix(".ics")
+        .filter(|token| !token.is_empty())
+        .map(str::to_string)
+        .ok_or(Response::NotFound)
+}
+
+/// Календарь со всеми записями студента. UID событий такие же, как в присланных ботом
+/// файлах, поэтому события не дублируются.
+fn feed_ics(
+    reservations: &[CalendarReservationDTO],
+    service_title: fn(&Service) -> &'static str,
+    now: DateTime<Utc>,
+) -> String {
+    let events: Vec<_> = reservations
+        .iter()
+        .map(|r| {
+            reservation_event(
+                r.user_id,
+                r.slot_start,
+                r.slot_end,
+                service_title(&r.service),
+                IcsMethod::Publish,
+            )
+        })
+        .collect();
+    to_ics(IcsMethod::Publish, &events, now)
+}
+
+#[cfg(test)]
+mod feed_tests {
+    use super::*;
+    use crate::domain::interfaces::CalendarFeedLinks;
+    use crate::domain::models::{UserID, reservation_id};
+    use crate::domain::services::FixedSlotsFactory;
+    use crate::infra::{FeedLinks, PostgresRepository};
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::TimeZone;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    const KEY: &str = "ZmVlZC1saW5rcy10ZXN0LWtleQ==";
+
+    #[test]
+    fn test_feed_token_from_request() {
+        // GIVEN запросы календаря через прокси, с экранированием и без токена
+        let cases = [
+            (Method::GET, "/calendar/42.abc.ics", Ok("42.abc".to_string())),
+            (Method::HEAD, "/42.abc.ics", Ok("42.abc".to_string())),
+            (Method::GET, "/calendar/42%2Eabc.ics", Ok("42.abc".to_string())),
+            (Method::GET, "/calendar/", Err(Response::NotFound)),
+            (Method::GET, "/calendar/.ics", Err(Response::NotFound)),
+            (Method::GET, "/calendar/%FF.ics", Err(Response::NotFound)),
+            (Method::POST, "/calendar/42.abc.ics", Err(Response::MethodNotAllowed)),
+        ];
+
+        // THEN токен берётся из имени файла в пути
+        for (method, path, expected) in cases {
+            assert_eq!(feed_token(&method, path), expected, "{} {}", method, path);
+        }
+    }
+
+    #[test]
+    fn test_feed_has_same_events_as_attachments() {
+        // GIVEN две записи студента
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+        let reservation = |start: DateTime<Utc>, service| CalendarReservationDTO {
+            user_id: UserID::new(42),
+            slot_start: start,
+            slot_end: start + chrono::Duration::minutes(20),
+            service,
+        };
+        let reservations = [
+            reservation(start, Service::Visa),
+            reservation(start + chrono::Duration::days(7), Service::RenewalOfVisa),
+        ];
+
+        // WHEN строится календарь
+        let ics = feed_ics(&reservations, |_| "Виза", Utc::now());
+
+        // THEN в нём оба события с UID, как у файлов после записи
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        let uid = format!("UID:{}@umd-bot\r\n", reservation_id(UserID::new(42), start));
+        assert!(ics.contains(&uid));
+        assert!(ics.contains("VALARM"));
+        // AND ответ сервера - календарь
+        let response = Response::Calendar(ics).into_http();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], "text/calendar; charset=utf-8");
+    }
+
+    /// Запускает сервер календарей на свободном порту и возвращает его адрес.
+    async fn start_server(links: FeedLinks, max_connections: usize) -> String {
+        let use_case = CalendarFeedUseCase::new(
+            Arc::new(FixedSlotsFactory::new(3, chrono::Duration::minutes(20))),
+            Arc::new(PostgresRepository::new(test_db_setup().await)),
+            Arc::new(links),
+        );
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap().to_string();
+        tokio::spawn(serve(listener, use_case, |_| "Виза", max_connections));
+        addr
+    }
+
+    async fn request(stream: &mut TcpStream, method: &str, path: &str) -> String {
+        let request = format!("{} {} HTTP/1.1\r\nHost: umd\r\n\r\n", method, path);
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn test_server_answers_get_and_head() {
+        // GIVEN сервер и ссылка на календарь студента
+        let links = FeedLinks::new("https://umd.example.org/calendar", KEY).unwrap();
+        let link = links.link(UserID::new(Utc::now().timestamp_micros())).unwrap();
+        let path = link.strip_prefix("https://umd.example.org").unwrap().to_string();
+        let addr = start_server(links, MAX_CONNECTIONS).await;
+
+        // WHEN календарь запрашивается
+        let mut stream = TcpStream::connect(&addr).await.unwrap();
+        let get = request(&mut stream, "GET", &path).await;
+
+        // THEN сервер отдаёт календарь
+        assert!(get.starts_with("HTTP/1.1 200 OK\r\n"), "{}", get);
+        assert!(get.to_lowercase().contains("content-type: text/calendar; charset=utf-8\r\n"));
+        assert!(get.ends_with("END:VCALENDAR\r\n"));
+
+        // WHEN запрашиваются только заголовки
+        let mut stream = TcpStream::connect(&addr).await.unwrap();
+        let head = request(&mut stream, "HEAD", &path).await;
+
+        // THEN ответ такой же, но без тела
+        assert!(head.starts_with("HTTP/1.1 200 OK\r\n"), "{}", head);
+        assert!(head.ends_with("\r\n\r\n"));
+        let length = |response: &str| {
+            response
+                .lines()
+                .find_map(|line| line.to_lowercase().strip_prefix("content-length: ").map(str::to_string))
+        };
+        assert!(length(&get).is_some());
+        assert_eq!(length(&head), length(&get));
+
+        // AND на другие методы сервер сообщает допустимые
+        let mut stream = TcpStream::connect(&addr).await.unwrap();
+        let post = request(&mut stream, "POST", &path).await;
+        assert!(post.starts_with("HTTP/1.1 405 Method Not Allowed\r\n"), "{}", post);
+        assert!(post.to_lowercase().contains("allow: get, head\r\n"));
+    }
+
+    #[tokio::test]
+    async fn test_connections_are_limited() {
+        // GIVEN сервер на одно соединение, которое занял молчащий клиент
+        let addr = start_server(FeedLinks::disabled(), 1).await;
+        let idle = TcpStream::connect(&addr).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        // WHEN приходит ещё один запрос
+        let mut stream = TcpStream::connect(&addr).await.unwrap();
+        let pending = tokio::time::timeout(
+            Duration::from_millis(300),
+            request(&mut stream, "GET", "/calendar/42.abc.ics"),
+        )
+        .await;
+
+        // THEN он ждёт, пока место не освободится
+        assert!(pending.is_err());
+        drop(idle);
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"), "{}", response);
+    }
+}
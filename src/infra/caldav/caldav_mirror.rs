@@ -0,0 +1,367 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use reqwest::{Client, Method, RequestBuilder, Response, StatusCode};
+use sha2::{Digest, Sha256};
+
+use crate::domain::Error;
+use crate::domain::interfaces::CalendarMirror;
+use crate::domain::models::{Service, Slot, reservation_code};
+use crate::infra::{IcsEvent, to_calendar_object};
+
+/// CalDavMirror хранит события слотов в календаре CalDAV-сервера (Radicale, Nextcloud и т.п.).
+///
+/// Событие слота лежит по постоянному адресу, поэтому повторная отправка заменяет его, а не
+/// создаёт дубликат.
+pub struct CalDavMirror {
+    client: Client,
+    calendar_url: String,
+    credentials: Option<(String, String)>,
+    service_title: fn(&Service) -> &'static str,
+}
+
+impl CalDavMirror {
+    /// `calendar_url` - адрес коллекции календаря, `service_title` - название услуги для
+    /// описания события.
+    pub fn new(
+        calendar_url: &str,
+        credentials: Option<(String, String)>,
+        service_title: fn(&Service) -> &'static str,
+    ) -> Self {
+        Self {
+            client: Client::new(),
+            calendar_url: format!("{}/", calendar_url.trim_end_matches('/')),
+            credentials,
+            service_title,
+        }
+    }
+
+    fn event_url(&self, start: DateTime<Utc>) -> String {
+        format!("{}{}.ics", self.calendar_url, slot_uid(start))
+    }
+
+    fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        let request = self.client.request(method, url);
+        match &self.credentials {
+            Some((username, password)) => request.basic_auth(username, Some(password)),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl CalendarMirror for CalDavMirror {
+    async fn put_slot(&self, slot: &Slot) -> Result<(), Error> {
+        let event = slot_event(slot, self.service_title);
+        let response = self
+            .request(Method::PUT, &self.event_url(slot.start()))
+            .header("Content-Type", "text/calendar; charset=utf-8")
+            .body(to_calendar_object(&[event], Utc::now()))
+            .send()
+            .await
+            .map_err(http_error)?;
+        check_status(response, &[])
+    }
+
+    fn event_hash(&self, slot: &Slot) -> String {
+        // Время создания объекта не относится к содержимому события.
+        let event = slot_event(slot, self.service_title);
+        let object = to_calendar_object(&[event], DateTime::UNIX_EPOCH);
+        format!("{:x}", Sha256::digest(object.as_bytes()))
+    }
+
+    async fn delete_slot(&self, start: DateTime<Utc>) -> Result<(), Error> {
+        let response = self
+            .request(Method::DELETE, &self.event_url(start))
+            .send()
+            .await
+            .map_err(http_error)?;
+        check_status(response, &[StatusCode::NOT_FOUND])
+    }
+}
+
+fn slot_uid(start: DateTime<Utc>) -> String {
+    format!("umd-slot-{}", start.timestamp())
+}
+
+/// Событие слота: записавшиеся перечислены в описании, по строке на запись.
+fn slot_event(slot: &Slot, service_title: fn(&Service) -> &'static str) -> IcsEvent {
+    let description = slot
+        .reservations()
+        .iter()
+        .map(|r| {
+            format!(
+                "{} · {} ({}) · {}",
+                reservation_code(r.by().id(), slot.start()),
+                r.by().full_name_cyr().as_str(),
+                r.by().full_name_lat().as_str(),
+                service_title(r.service()),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    IcsEvent {
+        uid: format!("{}@umd-bot", slot_uid(slot.start())),
+        sequence: 0,
+        // Время слотов хранится как московское.
+        start: slot.start().naive_utc(),
+        end: slot.interval().end.naive_utc(),
+        summary: format!("Приём УМД: {} из {}", slot.reserved(), slot.max_size()),
+        description,
+        location: String::new(),
+        alarm: None,
+    }
+}
+
+fn http_error(err: reqwest::Error) -> Error {
+    Error::Unavailable(err.into())
+}
+
+fn check_status(response: Response, allowed: &[StatusCode]) -> Result<(), Error> {
+    let status = response.status();
+    if status.is_success() || allowed.contains(&status) {
+        return Ok(());
+    }
+    Err(Error::Other(
+        format!("CalDAV {} returned {}", response.url(), status).into(),
+    ))
+}
+
+#[cfg(test)]
+mod caldav_mirror_tests {
+    use super::*;
+    use crate::domain::models::{
+        Citizenship, ClosedRange, OnlyCyrillic, OnlyLatin, User, UserID, Username,
+    };
+    use chrono::{Duration, NaiveDate, TimeZone};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn service_title(service: &Service) -> &'static str {
+        match service {
+            Service::Visa => "Получение визы",
+            _ => "Регистрация",
+        }
+    }
+
+    fn user(id: i64, lat: &str, cyr: &str) -> User {
+        User::new(
+            UserID::new(id),
+            Username::new("username"),
+            OnlyLatin::new(lat).unwrap(),
+            OnlyCyrillic::new(cyr).unwrap(),
+            Citizenship::Armenia,
+            NaiveDate::from_ymd_opt(2025, 7, 7).unwrap(),
+        )
+    }
+
+    fn empty_slot(start: DateTime<Utc>) -> Slot {
+        Slot::empty(
+            ClosedRange {
+                start,
+                end: start + Duration::minutes(20),
+            },
+            3,
+        )
+    }
+
+    fn slot(start: DateTime<Utc>, users: &[(i64, &str, &str)]) -> Slot {
+        let mut slot = empty_slot(start);
+        for &(id, lat, cyr) in users {
+            slot.reserve(user(id, lat, cyr), Service::Visa).unwrap();
+        }
+        slot
+    }
+
+    /// Запрос, полученный поддельным CalDAV-сервером.
+    #[derive(Debug)]
+    struct Request {
+        method: String,
+        path: String,
+        body: String,
+    }
+
+    /// Поднимает поддельный CalDAV-сервер, который отвечает на запросы статусами `statuses`
+    /// по порядку. Возвращает адрес календаря и полученные запросы.
+    async fn fake_caldav(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Request>>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/umd/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        tokio::spawn(async move {
+            for status in statuses {
+                let (mut stream, _) = listener.accept().await.unwrap();
+                let mut data = Vec::new();
+                let mut buffer = [0; 4096];
+                // Читаем заголовки, затем тело длиной Content-Length.
+                let request = loop {
+                    let n = stream.read(&mut buffer).await.unwrap();
+                    data.extend_from_slice(&buffer[..n]);
+                    let text = String::from_utf8_lossy(&data).to_string();
+                    let Some((head, body)) = text.split_once("\r\n\r\n") else {
+                        continue;
+                    };
+                    let length = head
+                        .lines()
+                        .find_map(|line| {
+                            let line = line.to_lowercase();
+                            line.strip_prefix("content-length:").map(|v| v.trim().parse().unwrap())
+                        })
+                        .unwrap_or(0);
+                    if body.len() < length {
+                        continue;
+                    }
+                    let mut words = head.split_whitespace();
+                    break Request {
+                        method: words.next().unwrap().to_string(),
+                        path: words.next().unwrap().to_string(),
+                        body: body.to_string(),
+                    };
+                };
+                received.lock().unwrap().push(request);
+                let response = format!(
+                    "HTTP/1.1 {} Fake\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                    status
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[test]
+    fn test_slot_event_lists_attendees() {
+        // GIVEN слот с двумя записями
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+        let slot = slot(start, &[(1, "Ivanov Ivan", "Иванов Иван"), (2, "Petrova Anna", "Петрова Анна")]);
+
+        // WHEN строится событие
+        let event = slot_event(&slot, service_title);
+        let ics = to_calendar_object(std::slice::from_ref(&event), Utc::now());
+
+        // THEN в описании по строке на запись, UID зависит только от времени слота
+        assert_eq!(event.uid, format!("umd-slot-{}@umd-bot", start.timestamp()));
+        assert_eq!(event.summary, "Приём УМД: 2 из 3");
+        let lines: Vec<_> = event.description.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                format!("{} · Иванов Иван (Ivanov Ivan) · Получение визы", reservation_code(UserID::new(1), start)),
+                format!("{} · Петрова Анна (Petrova Anna) · Получение визы", reservation_code(UserID::new(2), start)),
+            ]
+        );
+        // AND объект календаря пригоден для CalDAV
+        assert!(!ics.contains("METHOD:"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Moscow:20250714T100000\r\n"));
+    }
+
+    #[test]
+    fn test_event_hash_follows_event_content() {
+        let mirror = CalDavMirror::new("http://localhost/umd", None, service_title);
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+        let ivanov = slot(start, &[(1, "Ivanov Ivan", "Иванов Иван")]);
+
+        // GIVEN отпечаток события слота
+        let hash = mirror.event_hash(&ivanov);
+
+        // THEN он не зависит от времени расчёта
+        assert_eq!(mirror.event_hash(&ivanov), hash);
+        // AND меняется вместе с именем записавшегося
+        let renamed = slot(start, &[(1, "Ivanov Ivan", "Иванов Иоанн")]);
+        assert_ne!(mirror.event_hash(&renamed), hash);
+        // AND вместе с услугой
+        let mut registration = empty_slot(start);
+        registration
+            .reserve(user(1, "Ivanov Ivan", "Иванов Иван"), Service::RenewalOfRegistration)
+            .unwrap();
+        assert_ne!(mirror.event_hash(&registration), hash);
+    }
+
+    #[tokio::test]
+    async fn test_mirror_slot_over_http() {
+        // GIVEN CalDAV-сервер, у которого ещё нет события, а потом случается сбой
+        let (url, requests) = fake_caldav(vec![201, 404, 500]).await;
+        let credentials = Some(("umd".to_string(), "secret".to_string()));
+        let mirror = CalDavMirror::new(url.trim_end_matches('/'), credentials, service_title);
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+        let path = format!("/umd/umd-slot-{}.ics", start.timestamp());
+
+        // WHEN событие слота отправляется
+        mirror
+            .put_slot(&slot(start, &[(1, "Ivanov Ivan", "Иванов Иван")]))
+            .await
+            .unwrap();
+        // AND удаляется событие, которого на сервере нет
+        mirror.delete_slot(start).await.unwrap();
+        // AND сервер отвечает ошибкой
+        let failed = mirror.delete_slot(start).await;
+
+        // THEN событие лежит по постоянному адресу слота
+        let requests = requests.lock().unwrap();
+        assert_eq!((requests[0].method.as_str(), requests[0].path.as_str()), ("PUT", path.as_str()));
+        assert!(requests[0].body.contains("BEGIN:VEVENT") && requests[0].body.contains("Иванов Иван"));
+        // AND удаление отсутствующего события не ошибка, а ответ 500 - ошибка
+        assert_eq!((requests[1].method.as_str(), requests[1].path.as_str()), ("DELETE", path.as_str()));
+        assert!(failed.is_err());
+    }
+
+    /// Проверка на настоящем CalDAV-сервере, например Radicale:
+    /// `docker compose --profile caldav up -d radicale`, затем
+    /// `CALDAV_TEST_URL=http://localhost:5232/umd/ cargo test -- --ignored radicale`.
+    #[tokio::test]
+    #[ignore = "нужен CalDAV-сервер в CALDAV_TEST_URL"]
+    async fn test_mirror_slot_in_radicale() {
+        let base = std::env::var("CALDAV_TEST_URL").expect("CALDAV_TEST_URL must be set");
+        let credentials = std::env::var("CALDAV_TEST_USERNAME")
+            .ok()
+            .map(|username| (username, std::env::var("CALDAV_TEST_PASSWORD").unwrap_or_default()));
+        let calendar = format!(
+            "{}/umd-test-{}/",
+            base.trim_end_matches('/'),
+            Utc::now().timestamp_micros()
+        );
+        let mirror = CalDavMirror::new(&calendar, credentials, service_title);
+        let response = mirror
+            .request(Method::from_bytes(b"MKCALENDAR").unwrap(), &calendar)
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success(), "MKCALENDAR: {}", response.status());
+        let get = async |start| {
+            let response = mirror
+                .request(Method::GET, &mirror.event_url(start))
+                .send()
+                .await
+                .unwrap();
+            match response.status() {
+                StatusCode::NOT_FOUND => None,
+                _ => Some(response.error_for_status().unwrap().text().await.unwrap()),
+            }
+        };
+        let start = Utc.with_ymd_and_hms(2025, 7, 14, 10, 0, 0).unwrap();
+
+        // GIVEN в слот записались двое, событие отправлено дважды
+        let full = slot(start, &[(1, "Ivanov Ivan", "Иванов Иван"), (2, "Petrova Anna", "Петрова Анна")]);
+        mirror.put_slot(&full).await.unwrap();
+        mirror.put_slot(&full).await.unwrap();
+
+        // THEN в календаре одно событие с обоими студентами
+        let ics = get(start).await.unwrap().replace("\r\n ", "");
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 1);
+        assert!(ics.contains("Иванов Иван") && ics.contains("Петрова Анна"));
+
+        // WHEN одна запись отменена
+        mirror.put_slot(&slot(start, &[(2, "Petrova Anna", "Петрова Анна")])).await.unwrap();
+
+        // THEN событие обновлено
+        let ics = get(start).await.unwrap().replace("\r\n ", "");
+        assert!(!ics.contains("Иванов Иван") && ics.contains("Петрова Анна"));
+
+        // WHEN отменена последняя запись, событие удаляется, в том числе повторно
+        mirror.delete_slot(start).await.unwrap();
+        mirror.delete_slot(start).await.unwrap();
+
+        // THEN события больше нет
+        assert!(get(start).await.is_none());
+    }
+}
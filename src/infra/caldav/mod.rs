@@ -0,0 +1,3 @@
+mod caldav_mirror;
+
+pub use caldav_mirror::*;
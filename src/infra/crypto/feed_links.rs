@@ -0,0 +1,124 @@
+use base64::Engine;
+use base64::engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::domain::Error;
+use crate::domain::interfaces::CalendarFeedLinks;
+use crate::domain::models::UserID;
+
+/// Сколько байт HMAC попадает в токен.
+const SIGNATURE_LEN: usize = 16;
+
+/// FeedLinks подписывает ссылки на личные календари: токен `<id>.<base64url(HMAC(id))>`
+/// нельзя подобрать для чужого id, не зная ключа. Ссылки бессрочные, при смене ключа
+/// все выданные ссылки перестают работать.
+pub struct FeedLinks {
+    base_url: String,
+    key: Option<Vec<u8>>,
+}
+
+impl FeedLinks {
+    /// Календари по ссылке выключены.
+    pub fn disabled() -> Self {
+        Self {
+            base_url: String::new(),
+            key: None,
+        }
+    }
+
+    /// `base_url` - адрес, по которому снаружи доступен сервер календарей,
+    /// `key` - ключ подписи в base64.
+    pub fn new(base_url: &str, key: &str) -> Result<Self, Error> {
+        let key = BASE64
+            .decode(key)
+            .map_err(|err| Error::InvalidValue(format!("invalid base64 key: {}", err)))?;
+        if key.is_empty() {
+            return Err(Error::InvalidValue("empty calendar feed key".to_string()));
+        }
+        Ok(Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            key: Some(key),
+        })
+    }
+
+    fn mac(key: &[u8], id: i64) -> Hmac<Sha256> {
+        let mut mac =
+            <Hmac<Sha256> as Mac>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(format!("calendar:{}", id).as_bytes());
+        mac
+    }
+}
+
+impl CalendarFeedLinks for FeedLinks {
+    fn link(&self, id: UserID) -> Option<String> {
+        let key = self.key.as_ref()?;
+        let signature = Self::mac(key, id.as_i64()).finalize().into_bytes();
+        Some(format!(
+            "{}/{}.{}.ics",
+            self.base_url,
+            id.as_i64(),
+            URL_SAFE_NO_PAD.encode(&signature[..SIGNATURE_LEN]),
+        ))
+    }
+
+    fn user(&self, token: &str) -> Option<UserID> {
+        let key = self.key.as_ref()?;
+        let (id, signature) = token.split_once('.')?;
+        let id: i64 = id.parse().ok()?;
+        let signature = URL_SAFE_NO_PAD.decode(signature).ok()?;
+        if signature.len() != SIGNATURE_LEN {
+            return None;
+        }
+        Self::mac(key, id)
+            .verify_truncated_left(&signature)
+            .ok()
+            .map(|_| UserID::new(id))
+    }
+}
+
+#[cfg(test)]
+mod feed_links_tests {
+    use super::*;
+
+    const KEY: &str = "c2VjcmV0LWtleS1mb3ItY2FsZW5kYXItZmVlZHM=";
+
+    fn token(link: &str) -> &str {
+        link.rsplit('/').next().unwrap().strip_suffix(".ics").unwrap()
+    }
+
+    #[test]
+    fn test_link_resolves_to_its_user() {
+        // GIVEN ссылка на календарь пользователя
+        let links = FeedLinks::new("https://umd.example.org/calendar/", KEY).unwrap();
+        let link = links.link(UserID::new(42)).unwrap();
+
+        // THEN она ведёт на сервер календарей, а токен из неё указывает на пользователя
+        assert!(link.starts_with("https://umd.example.org/calendar/42."));
+        assert_eq!(links.user(token(&link)), Some(UserID::new(42)));
+    }
+
+    #[test]
+    fn test_forged_tokens_are_rejected() {
+        let links = FeedLinks::new("https://umd.example.org/calendar", KEY).unwrap();
+        let link = links.link(UserID::new(42)).unwrap();
+        let (_, signature) = token(&link).split_once('.').unwrap();
+
+        // GIVEN подпись от чужого id, мусор и подпись другим ключом
+        let other = FeedLinks::new("https://umd.example.org/calendar", "b3RoZXIta2V5").unwrap();
+        let forged = [
+            format!("43.{}", signature),
+            "42.abc".to_string(),
+            "42".to_string(),
+            token(&other.link(UserID::new(42)).unwrap()).to_string(),
+        ];
+
+        // THEN пользователь по ним не находится
+        for token in forged {
+            assert_eq!(links.user(&token), None, "{}", token);
+        }
+        // AND выключенные ссылки не выдаются и не проверяются
+        assert_eq!(FeedLinks::disabled().link(UserID::new(42)), None);
+        assert_eq!(FeedLinks::disabled().user(token(&link)), None);
+    }
+}
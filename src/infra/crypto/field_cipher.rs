@@ -0,0 +1,328 @@
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::domain::Error;
+
+/// Префикс зашифрованного значения: `enc:v1:<id ключа>:<base64(nonce || ciphertext)>`.
+const PREFIX: &str = "enc:v1:";
+const NONCE_LEN: usize = 24;
+const KEY_LEN: usize = 32;
+
+struct FieldKey {
+    id: String,
+    aead: XChaCha20Poly1305,
+}
+
+/// FieldCipher шифрует поля с персональными данными перед записью в базу.
+///
+/// Используется XChaCha20-Poly1305, в качестве associated data выступают имя столбца и
+/// id строки, поэтому зашифрованное значение нельзя незаметно перенести в другое поле
+/// или другому пользователю. Первый ключ в списке текущий, остальные нужны только для
+/// чтения данных, зашифрованных до ротации.
+///
+/// Значения без префикса считаются открытым текстом и читаются как есть - так база
+/// продолжает работать, пока существующие строки не перешифрованы.
+pub struct FieldCipher {
+    keys: Vec<FieldKey>,
+    index_key: Option<Vec<u8>>,
+}
+
+impl FieldCipher {
+    /// Шифрование выключено, значения хранятся открытым текстом.
+    pub fn plaintext() -> Self {
+        Self {
+            keys: Vec::new(),
+            index_key: None,
+        }
+    }
+
+    /// Создаёт шифр из конфигурации.
+    ///
+    /// `keys` - список `id:base64` через запятую, первый ключ текущий.
+    /// `index_key` - ключ blind index в base64.
+    pub fn new(keys: &str, index_key: &str) -> Result<Self, Error> {
+        let keys = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(parse_key)
+            .collect::<Result<Vec<_>, _>>()?;
+        if keys.is_empty() {
+            return Err(Error::InvalidValue("no encryption keys".to_string()));
+        }
+        for (i, key) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|k| k.id == key.id) {
+                return Err(Error::InvalidValue(format!("duplicate key id: {}", key.id)));
+            }
+        }
+
+        let index_key = decode_key(index_key.trim())?;
+        Ok(Self {
+            keys,
+            index_key: Some(index_key),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.keys.is_empty()
+    }
+
+    /// Шифрует значение столбца `column` строки `id` текущим ключом.
+    /// Пустые значения (например, у обезличенных пользователей) не шифруются.
+    pub fn encrypt(&self, column: &str, id: i64, value: &str) -> Result<String, Error> {
+        let Some(key) = self.keys.first() else {
+            return Ok(value.to_string());
+        };
+        if value.is_empty() {
+            return Ok(String::new());
+        }
+
+        let aad = associated_data(column, id);
+        let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = key
+            .aead
+            .encrypt(&nonce, Payload { msg: value.as_bytes(), aad: aad.as_bytes() })
+            .map_err(|_| Error::Other("unable to encrypt field".into()))?;
+
+        let mut sealed = nonce.to_vec();
+        sealed.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}:{}", PREFIX, key.id, BASE64.encode(sealed)))
+    }
+
+    /// Расшифровывает значение столбца `column` строки `id`. Открытый текст
+    /// возвращается без изменений.
+    pub fn decrypt(&self, column: &str, id: i64, value: &str) -> Result<String, Error> {
+        let Some(rest) = value.strip_prefix(PREFIX) else {
+            return Ok(value.to_string());
+        };
+        let (key_id, data) = rest
+            .split_once(':')
+            .ok_or_else(|| Error::Other("malformed encrypted field".into()))?;
+        let key = self
+            .keys
+            .iter()
+            .find(|k| k.id == key_id)
+            .ok_or_else(|| Error::Other(format!("unknown encryption key: {}", key_id).into()))?;
+        let data = BASE64
+            .decode(data)
+            .map_err(|err| Error::Other(err.into()))?;
+        if data.len() < NONCE_LEN {
+            return Err(Error::Other("malformed encrypted field".into()));
+        }
+
+        let (nonce, ciphertext) = data.split_at(NONCE_LEN);
+        let aad = associated_data(column, id);
+        let plaintext = key
+            .aead
+            .decrypt(
+                XNonce::from_slice(nonce),
+                Payload { msg: ciphertext, aad: aad.as_bytes() },
+            )
+            .map_err(|_| Error::Other(format!("unable to decrypt {} of {}", column, id).into()))?;
+        String::from_utf8(plaintext).map_err(|err| Error::Other(err.into()))
+    }
+
+    /// Нужно ли перешифровать значение: оно хранится открытым текстом или зашифровано
+    /// не текущим ключом.
+    pub fn needs_reencryption(&self, value: &str) -> bool {
+        let Some(current) = self.keys.first() else {
+            return false;
+        };
+        if value.is_empty() {
+            return false;
+        }
+        match value.strip_prefix(PREFIX).and_then(|rest| rest.split_once(':')) {
+            Some((key_id, _)) => key_id != current.id,
+            None => true,
+        }
+    }
+
+    /// Возвращает blind index для поиска по точному совпадению: HMAC-SHA256 от
+    /// нормализованного значения. При выключенном шифровании индексом служит само
+    /// нормализованное значение. Для пустых значений индекса нет.
+    pub fn blind_index(&self, value: &str) -> Option<String> {
+        let normalized = normalize(value);
+        if normalized.is_empty() {
+            return None;
+        }
+        let Some(index_key) = &self.index_key else {
+            return Some(normalized);
+        };
+
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(index_key)
+            .expect("HMAC accepts keys of any length");
+        mac.update(normalized.as_bytes());
+        Some(BASE64.encode(mac.finalize().into_bytes()))
+    }
+}
+
+fn parse_key(s: &str) -> Result<FieldKey, Error> {
+    let (id, key) = s
+        .split_once(':')
+        .ok_or_else(|| Error::InvalidValue(format!("expected id:key, got: {}", s)))?;
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(Error::InvalidValue(format!("invalid key id: {}", id)));
+    }
+    let key = decode_key(key)?;
+    let aead = XChaCha20Poly1305::new_from_slice(&key)
+        .map_err(|_| Error::InvalidValue(format!("key {} must be {} bytes", id, KEY_LEN)))?;
+    Ok(FieldKey {
+        id: id.to_string(),
+        aead,
+    })
+}
+
+fn decode_key(s: &str) -> Result<Vec<u8>, Error> {
+    let key = BASE64
+        .decode(s)
+        .map_err(|err| Error::InvalidValue(format!("invalid base64 key: {}", err)))?;
+    if key.len() != KEY_LEN {
+        return Err(Error::InvalidValue(format!("key must be {} bytes", KEY_LEN)));
+    }
+    Ok(key)
+}
+
+fn associated_data(column: &str, id: i64) -> String {
+    format!("users.{}:{}", column, id)
+}
+
+/// Приводит ФИО к виду, в котором его ищут: без лишних пробелов и без учёта регистра.
+fn normalize(value: &str) -> String {
+    value
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+        .replace('ё', "е")
+}
+
+#[cfg(test)]
+mod field_cipher_tests {
+    use super::*;
+
+    fn key(byte: u8) -> String {
+        BASE64.encode([byte; KEY_LEN])
+    }
+
+    fn cipher(keys: &[(&str, u8)]) -> FieldCipher {
+        let keys = keys
+            .iter()
+            .map(|(id, byte)| format!("{}:{}", id, key(*byte)))
+            .collect::<Vec<_>>()
+            .join(",");
+        FieldCipher::new(&keys, &key(0xAA)).unwrap()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        // GIVEN шифр с одним ключом
+        let cipher = cipher(&[("1", 1)]);
+
+        // WHEN значение шифруется и расшифровывается
+        let sealed = cipher.encrypt("full_name_lat", 42, "Ivanov Ivan").unwrap();
+        let opened = cipher.decrypt("full_name_lat", 42, &sealed).unwrap();
+
+        // THEN в базе нет открытого текста, а расшифрованное значение совпадает с исходным
+        assert!(sealed.starts_with("enc:v1:1:"));
+        assert!(!sealed.contains("Ivanov"));
+        assert_eq!(opened, "Ivanov Ivan");
+    }
+
+    #[test]
+    fn test_plaintext_is_read_as_is() {
+        // GIVEN шифр и значение, записанное до включения шифрования
+        let cipher = cipher(&[("1", 1)]);
+
+        // WHEN значение читается
+        // THEN оно возвращается без изменений и требует перешифрования
+        assert_eq!(cipher.decrypt("citizenship", 1, "Armenia").unwrap(), "Armenia");
+        assert!(cipher.needs_reencryption("Armenia"));
+        assert!(!cipher.needs_reencryption(""));
+    }
+
+    #[test]
+    fn test_tampered_value_is_rejected() {
+        // GIVEN зашифрованное значение пользователя 1
+        let cipher = cipher(&[("1", 1)]);
+        let sealed = cipher.encrypt("full_name_cyr", 1, "Иванов").unwrap();
+
+        // WHEN его подставляют другому пользователю или в другое поле
+        // THEN расшифровать его не получается
+        assert!(cipher.decrypt("full_name_cyr", 2, &sealed).is_err());
+        assert!(cipher.decrypt("full_name_lat", 1, &sealed).is_err());
+
+        // WHEN шифротекст изменён
+        let mut data = BASE64.decode(sealed.rsplit(':').next().unwrap()).unwrap();
+        let last = data.len() - 1;
+        data[last] ^= 1;
+        let tampered = format!("enc:v1:1:{}", BASE64.encode(data));
+
+        // THEN расшифровать его не получается
+        assert!(cipher.decrypt("full_name_cyr", 1, &tampered).is_err());
+    }
+
+    #[test]
+    fn test_key_rotation() {
+        // GIVEN значение, зашифрованное старым ключом
+        let old = cipher(&[("1", 1)]);
+        let sealed = old.encrypt("arrival_date", 7, "2025-07-01").unwrap();
+
+        // WHEN текущим становится новый ключ, а старый остаётся в списке
+        let rotated = cipher(&[("2", 2), ("1", 1)]);
+
+        // THEN старое значение читается и помечается для перешифрования
+        assert_eq!(rotated.decrypt("arrival_date", 7, &sealed).unwrap(), "2025-07-01");
+        assert!(rotated.needs_reencryption(&sealed));
+        let resealed = rotated.encrypt("arrival_date", 7, "2025-07-01").unwrap();
+        assert!(!rotated.needs_reencryption(&resealed));
+
+        // THEN без старого ключа значение не читается
+        let only_new = cipher(&[("2", 2)]);
+        assert!(only_new.decrypt("arrival_date", 7, &sealed).is_err());
+    }
+
+    #[test]
+    fn test_blind_index() {
+        // GIVEN шифр с ключом индекса
+        let cipher = cipher(&[("1", 1)]);
+
+        // WHEN строится индекс для одного ФИО, записанного по-разному
+        let a = cipher.blind_index("Иванов  Пётр").unwrap();
+        let b = cipher.blind_index(" иванов петр ").unwrap();
+
+        // THEN индексы совпадают, не содержат ФИО и отличаются для другого ФИО
+        assert_eq!(a, b);
+        assert!(!a.to_lowercase().contains("иванов"));
+        assert_ne!(a, cipher.blind_index("Петров Пётр").unwrap());
+        assert_eq!(cipher.blind_index("  "), None);
+    }
+
+    #[test]
+    fn test_plaintext_cipher() {
+        // GIVEN выключенное шифрование
+        let cipher = FieldCipher::plaintext();
+
+        // WHEN значение шифруется
+        // THEN оно не меняется, а индексом служит нормализованное значение
+        assert!(!cipher.is_enabled());
+        assert_eq!(cipher.encrypt("full_name_lat", 1, "Ivanov").unwrap(), "Ivanov");
+        assert!(!cipher.needs_reencryption("Ivanov"));
+        assert_eq!(cipher.blind_index("Ivanov  Ivan").unwrap(), "ivanov ivan");
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        // GIVEN неверные настройки ключей
+        // WHEN создаётся шифр
+        // THEN возвращается ошибка
+        assert!(FieldCipher::new("", &key(1)).is_err());
+        assert!(FieldCipher::new(&format!("1:{}", BASE64.encode([1u8; 16])), &key(1)).is_err());
+        assert!(FieldCipher::new(&format!("1:{},1:{}", key(1), key(2)), &key(3)).is_err());
+        assert!(FieldCipher::new(&format!("a:b:{}", key(1)), &key(3)).is_err());
+        assert!(FieldCipher::new(&format!("1:{}", key(1)), "").is_err());
+    }
+}
@@ -0,0 +1,5 @@
+mod feed_links;
+mod field_cipher;
+
+pub use feed_links::FeedLinks;
+pub use field_cipher::FieldCipher;
@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::domain::Error;
+use crate::domain::interfaces::DocumentStorage;
+
+/// FsDocumentStorage хранит документы в локальной директории, ключ документа является
+/// относительным путём внутри неё.
+pub struct FsDocumentStorage {
+    root: PathBuf,
+}
+
+impl FsDocumentStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn path(&self, key: &str) -> Result<PathBuf, Error> {
+        let relative = Path::new(key);
+        if relative.is_absolute() || relative.components().any(|c| c.as_os_str() == "..") {
+            return Err(Error::InvalidValue(format!("invalid storage key: {}", key)));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl DocumentStorage for FsDocumentStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), Error> {
+        let path = self.path(key)?;
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|err| Error::Other(err.into()))?;
+        }
+        tokio::fs::write(&path, data)
+            .await
+            .map_err(|err| Error::Other(err.into()))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, Error> {
+        let path = self.path(key)?;
+        tokio::fs::read(&path)
+            .await
+            .map_err(|err| Error::Other(err.into()))
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), Error> {
+        let path = self.path(key)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(Error::Other(err.into())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod fs_document_storage_tests {
+    use super::*;
+
+    fn temp_storage(name: &str) -> FsDocumentStorage {
+        let root = std::env::temp_dir().join(format!(
+            "umd-bot-{}-{}",
+            name,
+            std::process::id()
+        ));
+        FsDocumentStorage::new(root)
+    }
+
+    #[tokio::test]
+    async fn test_put_get_delete() {
+        // GIVEN пустое хранилище
+        let storage = temp_storage("put-get-delete");
+
+        // WHEN документ сохраняется во вложенную директорию
+        storage.put("1/1752487200_passport.jpg", b"data").await.unwrap();
+
+        // THEN его можно прочитать
+        let data = storage.get("1/1752487200_passport.jpg").await.unwrap();
+        assert_eq!(data, b"data");
+
+        // WHEN документ удаляется дважды
+        storage.delete("1/1752487200_passport.jpg").await.unwrap();
+        let res = storage.delete("1/1752487200_passport.jpg").await;
+
+        // THEN повторное удаление не является ошибкой, а документ больше не читается
+        assert!(res.is_ok());
+        assert!(storage.get("1/1752487200_passport.jpg").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_key_outside_root() {
+        // GIVEN хранилище
+        let storage = temp_storage("outside-root");
+
+        // WHEN ключ указывает за пределы директории хранилища
+        let res = storage.put("../escape.jpg", b"data").await;
+
+        // THEN ошибка
+        assert!(matches!(res, Err(Error::InvalidValue(_))));
+    }
+}
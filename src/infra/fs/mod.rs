@@ -0,0 +1,3 @@
+mod fs_document_storage;
+
+pub use fs_document_storage::FsDocumentStorage;
@@ -0,0 +1,218 @@
+use chrono::{DateTime, Duration, NaiveDateTime, Utc};
+
+/// Часовой пояс УМД. Время слотов хранится как московское «настенное» время.
+const TZID: &str = "Europe/Moscow";
+const PRODID: &str = "-//umd-bot//RU";
+/// Строки длиннее 75 байт переносятся (RFC 5545, 3.1).
+const MAX_LINE_LEN: usize = 75;
+
+/// Метод календаря: добавить или обновить события либо отменить их.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IcsMethod {
+    Publish,
+    Cancel,
+}
+
+impl IcsMethod {
+    fn as_str(&self) -> &'static str {
+        match self {
+            IcsMethod::Publish => "PUBLISH",
+            IcsMethod::Cancel => "CANCEL",
+        }
+    }
+}
+
+/// Событие календаря. Время начала и конца - московское.
+///
+/// Календарь сопоставляет события по `uid`: событие с тем же `uid` и большим `sequence`
+/// заменяет ранее добавленное.
+#[derive(Debug, Clone)]
+pub struct IcsEvent {
+    pub uid: String,
+    pub sequence: u32,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    pub summary: String,
+    pub description: String,
+    /// Пустое место не указывается.
+    pub location: String,
+    /// За сколько до начала напомнить о событии.
+    pub alarm: Option<Duration>,
+}
+
+/// Сериализует события в iCalendar (RFC 5545) для отправки файлом. При `IcsMethod::Cancel`
+/// события помечаются отменёнными.
+pub fn to_ics(method: IcsMethod, events: &[IcsEvent], now: DateTime<Utc>) -> String {
+    serialize(Some(method), events, now)
+}
+
+/// Сериализует события для хранения на CalDAV-сервере: такие объекты не должны
+/// содержать METHOD (RFC 4791, 4.1).
+pub fn to_calendar_object(events: &[IcsEvent], now: DateTime<Utc>) -> String {
+    serialize(None, events, now)
+}
+
+fn serialize(method: Option<IcsMethod>, events: &[IcsEvent], now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!("PRODID:{}", PRODID),
+        "CALSCALE:GREGORIAN".to_string(),
+    ];
+    if let Some(method) = method {
+        lines.push(format!("METHOD:{}", method.as_str()));
+    }
+    lines.extend([
+        // В Москве нет перехода на летнее время, поэтому достаточно одного правила.
+        "BEGIN:VTIMEZONE".to_string(),
+        format!("TZID:{}", TZID),
+        "BEGIN:STANDARD".to_string(),
+        "DTSTART:19700101T000000".to_string(),
+        "TZOFFSETFROM:+0300".to_string(),
+        "TZOFFSETTO:+0300".to_string(),
+        "TZNAME:MSK".to_string(),
+        "END:STANDARD".to_string(),
+        "END:VTIMEZONE".to_string(),
+    ]);
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("SEQUENCE:{}", event.sequence));
+        lines.push(format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")));
+        lines.push(format!("DTSTART;TZID={}:{}", TZID, local_time(event.start)));
+        lines.push(format!("DTEND;TZID={}:{}", TZID, local_time(event.end)));
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        lines.push(format!("DESCRIPTION:{}", escape(&event.description)));
+        if !event.location.is_empty() {
+            lines.push(format!("LOCATION:{}", escape(&event.location)));
+        }
+        if method == Some(IcsMethod::Cancel) {
+            lines.push("STATUS:CANCELLED".to_string());
+        } else {
+            lines.push("STATUS:CONFIRMED".to_string());
+            if let Some(alarm) = event.alarm {
+                lines.push("BEGIN:VALARM".to_string());
+                lines.push("ACTION:DISPLAY".to_string());
+                lines.push(format!("DESCRIPTION:{}", escape(&event.summary)));
+                lines.push(format!("TRIGGER:-PT{}M", alarm.num_minutes()));
+                lines.push("END:VALARM".to_string());
+            }
+        }
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+fn local_time(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// Экранирует спецсимволы текстового значения (RFC 5545, 3.3.11).
+fn escape(value: &str) -> String {
+    let mut res = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => res.push_str("\\\\"),
+            ';' => res.push_str("\\;"),
+            ',' => res.push_str("\\,"),
+            '\n' => res.push_str("\\n"),
+            '\r' => {}
+            c => res.push(c),
+        }
+    }
+    res
+}
+
+/// Переносит длинную строку, не разрывая многобайтовые символы.
+fn fold(line: &str) -> String {
+    let mut res = String::with_capacity(line.len());
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > MAX_LINE_LEN {
+            res.push_str("\r\n ");
+            // Пробел в начале продолжения тоже считается.
+            len = 1;
+        }
+        res.push(c);
+        len += c.len_utf8();
+    }
+    res
+}
+
+#[cfg(test)]
+mod calendar_tests {
+    use super::*;
+    use chrono::{NaiveDate, TimeZone};
+
+    fn event() -> IcsEvent {
+        let start = NaiveDate::from_ymd_opt(2025, 7, 14)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap();
+        IcsEvent {
+            uid: "1-1752487200@umd-bot".to_string(),
+            sequence: 0,
+            start,
+            end: start + Duration::minutes(20),
+            summary: "Запись в УМД: Получение визы".to_string(),
+            description: "Код записи: ABCD\nВозьмите паспорт; миграционную карту, визу".to_string(),
+            location: "УМД МГТУ им. Н.Э. Баумана".to_string(),
+            alarm: Some(Duration::hours(1)),
+        }
+    }
+
+    #[test]
+    fn test_publish_event_with_alarm() {
+        // GIVEN запись на 10:00 по Москве
+        let now = Utc.with_ymd_and_hms(2025, 7, 10, 9, 30, 0).unwrap();
+
+        // WHEN она сериализуется в календарь
+        let ics = to_ics(IcsMethod::Publish, &[event()], now);
+
+        // THEN время указано в московском поясе, есть напоминание, текст экранирован
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("METHOD:PUBLISH\r\n"));
+        assert!(ics.contains("DTSTART;TZID=Europe/Moscow:20250714T100000\r\n"));
+        assert!(ics.contains("DTEND;TZID=Europe/Moscow:20250714T102000\r\n"));
+        assert!(ics.contains("DTSTAMP:20250710T093000Z\r\n"));
+        assert!(ics.contains("TRIGGER:-PT60M\r\n"));
+        assert!(ics.contains("STATUS:CONFIRMED\r\n"));
+        let unfolded = ics.replace("\r\n ", "");
+        assert!(unfolded.contains("ABCD\\nВозьмите паспорт\\; миграционную карту\\, визу"));
+    }
+
+    #[test]
+    fn test_cancel_event() {
+        // GIVEN отменённая запись
+        let mut event = event();
+        event.sequence = 1;
+        let now = Utc.with_ymd_and_hms(2025, 7, 11, 9, 30, 0).unwrap();
+
+        // WHEN она сериализуется в календарь
+        let ics = to_ics(IcsMethod::Cancel, &[event], now);
+
+        // THEN событие с тем же UID отменено и без напоминания
+        assert!(ics.contains("METHOD:CANCEL\r\n"));
+        assert!(ics.contains("UID:1-1752487200@umd-bot\r\n"));
+        assert!(ics.contains("SEQUENCE:1\r\n"));
+        assert!(ics.contains("STATUS:CANCELLED\r\n"));
+        assert!(!ics.contains("VALARM"));
+    }
+
+    #[test]
+    fn test_long_lines_are_folded() {
+        // GIVEN длинное описание кириллицей
+        let mut event = event();
+        event.description = "Очень длинное описание записи ".repeat(10);
+
+        // WHEN событие сериализуется
+        let ics = to_ics(IcsMethod::Publish, &[event], Utc::now());
+
+        // THEN ни одна строка не длиннее 75 байт, а после склейки текст не меняется
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_LEN));
+        assert!(ics.replace("\r\n ", "").contains(&"Очень длинное описание записи ".repeat(10)));
+    }
+}
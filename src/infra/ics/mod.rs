@@ -0,0 +1,5 @@
+mod calendar;
+mod reservation;
+
+pub use calendar::{IcsEvent, IcsMethod, to_calendar_object, to_ics};
+pub use reservation::reservation_event;
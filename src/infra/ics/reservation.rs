@@ -0,0 +1,40 @@
+use chrono::{DateTime, Duration, Utc};
+
+use super::{IcsEvent, IcsMethod};
+use crate::domain::models::{UserID, reservation_code, reservation_id};
+
+/// Где проходит приём: подставляется в событие календаря.
+const RESERVATION_LOCATION: &str =
+    "Кабинет 401аю ГУК, МГТУ им. Н.Э. Баумана, 2-я Бауманская ул., 5, стр. 1, Москва";
+/// За сколько до приёма календарь напомнит о записи.
+const RESERVATION_ALARM: Duration = Duration::hours(1);
+
+/// Событие записи студента в слот `[start, end)` на услугу `service_title`. Событие записи
+/// и её отмены имеют один UID, поэтому календарь заменяет добавленное ранее событие
+/// отменённым, а файл и личный календарь по ссылке не дублируют друг друга.
+pub fn reservation_event(
+    user_id: UserID,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+    service_title: &str,
+    method: IcsMethod,
+) -> IcsEvent {
+    IcsEvent {
+        uid: format!("{}@umd-bot", reservation_id(user_id, start)),
+        sequence: match method {
+            IcsMethod::Publish => 0,
+            IcsMethod::Cancel => 1,
+        },
+        // Время слотов хранится как московское.
+        start: start.naive_utc(),
+        end: end.naive_utc(),
+        summary: format!("УМД: {}", service_title),
+        description: format!(
+            "Услуга: «{}»\nКод записи: {}\nВозьмите паспорт и документы для услуги.",
+            service_title,
+            reservation_code(user_id, start),
+        ),
+        location: RESERVATION_LOCATION.to_string(),
+        alarm: Some(RESERVATION_ALARM),
+    }
+}
@@ -1,3 +0,0 @@
-mod mock_admin_provider;
-
-pub use mock_admin_provider::MockAdminProvider;
@@ -1,5 +1,13 @@
+mod caldav;
+mod crypto;
+mod fs;
+mod ics;
 mod postgres;
-mod mock;
+mod telegram;
 
+pub use caldav::*;
+pub use crypto::*;
+pub use fs::*;
+pub use ics::*;
 pub use postgres::*;
-pub use mock::*;
+pub use telegram::*;
@@ -0,0 +1,64 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::Pool;
+use tokio_postgres::{Client, GenericClient};
+
+use crate::domain::Error;
+use crate::domain::interfaces::ChatActivity;
+use crate::infra::postgres::db::{delete_idle_chats, upsert_chat_activity};
+use crate::with_client;
+
+/// PostgresChatActivity хранит время последней активности чатов в таблице `chat_activity`.
+pub struct PostgresChatActivity {
+    pool: Pool,
+}
+
+impl PostgresChatActivity {
+    pub fn new(pool: Pool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl ChatActivity for PostgresChatActivity {
+    async fn touch(&self, chat_id: i64, at: DateTime<Utc>) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            upsert_chat_activity(client, chat_id, at).await
+        })
+    }
+
+    async fn take_idle(&self, before: DateTime<Utc>) -> Result<Vec<i64>, Error> {
+        with_client!(self.pool, async |client| {
+            delete_idle_chats(client, before).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod chat_activity_tests {
+    use super::*;
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_idle_chats_are_taken_once() {
+        let activity = PostgresChatActivity::new(test_db_setup().await);
+        let now = Utc::now();
+        let (idle, active) = (now.timestamp_micros(), now.timestamp_micros() + 1);
+
+        // GIVEN один чат молчит два часа, другой писал только что
+        activity.touch(idle, now - Duration::hours(3)).await.unwrap();
+        activity.touch(idle, now - Duration::hours(2)).await.unwrap();
+        activity.touch(active, now).await.unwrap();
+
+        // WHEN ищутся чаты, молчащие больше часа
+        let taken = activity.take_idle(now - Duration::hours(1)).await.unwrap();
+
+        // THEN находится только молчащий чат
+        assert!(taken.contains(&idle));
+        assert!(!taken.contains(&active));
+        // AND его отметка удалена, поэтому второй раз он не находится
+        let taken = activity.take_idle(now - Duration::hours(1)).await.unwrap();
+        assert!(!taken.contains(&idle));
+    }
+}
@@ -1,10 +1,14 @@
-use chrono::{DateTime, NaiveDate, Utc};
-use postgres_types::{FromSql, ToSql};
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use postgres_types::{FromSql, Json, ToSql};
 use tokio_postgres::{GenericClient, Row};
 
 use crate::domain::Error;
+use crate::infra::FieldCipher;
+use crate::infra::postgres::errors::pg_error;
 use crate::domain::models::{
-    Citizenship, OnlyCyrillic, OnlyLatin, Service as DomainService, Slot, User, UserID, Username,
+    AuditAction, AuditChange, AuditEntry, Citizenship, Consent, DeadlineOverride, Document, DocumentKind as DomainDocumentKind, OnlyCyrillic,
+    OnlyLatin, OverrideStatus as DomainOverrideStatus, Passport, Service as DomainService, Sex, Slot, StaffMember,
+    StaffRole as DomainStaffRole, User, UserID, Username,
 };
 
 pub struct RawUser {
@@ -13,34 +17,142 @@ pub struct RawUser {
     full_name_lat: String,
     full_name_cyr: String,
     citizenship: String,
-    arrival_date: NaiveDate,
+    arrival_date: String,
+    visa_expiry: Option<NaiveDate>,
+    registration_expiry: Option<NaiveDate>,
+    birth_date: Option<String>,
+    sex: Option<String>,
+    passport: Option<String>,
+    place_of_stay: Option<String>,
 }
 
-impl From<&User> for RawUser {
-    fn from(u: &User) -> Self {
+/// Blind index ФИО пользователя, по которому ищут без расшифровки всей таблицы.
+#[derive(PartialEq)]
+pub struct RawUserIndex {
+    full_name_lat: Option<String>,
+    full_name_cyr: Option<String>,
+}
+
+impl RawUserIndex {
+    fn new(full_name_lat: &str, full_name_cyr: &str, cipher: &FieldCipher) -> Self {
         Self {
+            full_name_lat: cipher.blind_index(full_name_lat),
+            full_name_cyr: cipher.blind_index(full_name_cyr),
+        }
+    }
+}
+
+const ARRIVAL_DATE_FORMAT: &str = "%Y-%m-%d";
+
+impl RawUser {
+    pub fn id(&self) -> i64 {
+        self.id
+    }
+
+    /// Готовит пользователя к записи: шифрует персональные данные и строит blind index.
+    pub fn seal(u: &User, cipher: &FieldCipher) -> Result<(Self, RawUserIndex), Error> {
+        let index = RawUserIndex::new(u.full_name_lat().as_str(), u.full_name_cyr().as_str(), cipher);
+        let raw = Self {
             id: u.id().as_i64(),
             username: u.username().as_str().to_string(),
             full_name_lat: u.full_name_lat().as_str().to_string(),
             full_name_cyr: u.full_name_cyr().as_str().to_string(),
             citizenship: u.citizenship().as_str().to_string(),
-            arrival_date: u.arrival_date().clone(),
-        }
+            arrival_date: u.arrival_date().format(ARRIVAL_DATE_FORMAT).to_string(),
+            visa_expiry: u.visa_expiry(),
+            registration_expiry: u.registration_expiry(),
+            birth_date: u.birth_date().map(|d| d.format(ARRIVAL_DATE_FORMAT).to_string()),
+            sex: u.sex().map(|s| s.as_str().to_string()),
+            passport: u.passport().map(|p| p.to_string()),
+            place_of_stay: u.place_of_stay().map(|p| p.to_string()),
+        };
+        Ok((raw.map_personal_data(|column, id, value| cipher.encrypt(column, id, value))?, index))
     }
-}
 
-impl TryInto<User> for RawUser {
-    type Error = Error;
+    /// Дата въезда для обезличенного пользователя: первое число месяца въезда, зашифрованное.
+    /// Точная дата вместе с историей записей может указать на человека, а месяц нужен
+    /// статистике.
+    pub fn anonymised_arrival_date(&self, cipher: &FieldCipher) -> Result<String, Error> {
+        let plain = cipher.decrypt("arrival_date", self.id, &self.arrival_date)?;
+        let date = NaiveDate::parse_from_str(&plain, ARRIVAL_DATE_FORMAT)
+            .map_err(|err| Error::Other(err.into()))?;
+        let month = date.with_day(1).unwrap_or(date);
+        cipher.encrypt("arrival_date", self.id, &month.format(ARRIVAL_DATE_FORMAT).to_string())
+    }
 
-    fn try_into(self) -> Result<User, Self::Error> {
-        Ok(User::new(
-            UserID::new(self.id),
-            Username::new(self.username),
-            OnlyLatin::new(self.full_name_lat)?,
-            OnlyCyrillic::new(self.full_name_cyr)?,
-            Citizenship::from(self.citizenship.as_str()),
-            self.arrival_date,
-        ))
+    /// Расшифровывает персональные данные и восстанавливает пользователя.
+    pub fn open(self, cipher: &FieldCipher) -> Result<User, Error> {
+        let raw = self.map_personal_data(|column, id, value| cipher.decrypt(column, id, value))?;
+        let parse_date = |s: &str| {
+            NaiveDate::parse_from_str(s, ARRIVAL_DATE_FORMAT).map_err(|err| Error::Other(err.into()))
+        };
+        let arrival_date = parse_date(&raw.arrival_date)?;
+        let mut user = User::new(
+            UserID::new(raw.id),
+            Username::new(raw.username),
+            OnlyLatin::new(raw.full_name_lat)?,
+            OnlyCyrillic::new(raw.full_name_cyr)?,
+            Citizenship::from(raw.citizenship.as_str()),
+            arrival_date,
+        );
+        user.set_visa_expiry(raw.visa_expiry);
+        user.set_registration_expiry(raw.registration_expiry);
+        user.set_birth_date(raw.birth_date.as_deref().map(parse_date).transpose()?);
+        user.set_sex(raw.sex.as_deref().map(Sex::try_from).transpose()?);
+        user.set_passport(raw.passport.as_deref().map(Passport::parse).transpose()?);
+        user.set_place_of_stay(raw.place_of_stay);
+        Ok(user)
+    }
+
+    /// Перешифровывает персональные данные текущим ключом и пересчитывает blind index.
+    /// Возвращает None, если строка уже в актуальном виде. Работает и с обезличенными
+    /// пользователями, поэтому не восстанавливает доменную модель.
+    pub fn reseal(
+        self,
+        index: &RawUserIndex,
+        cipher: &FieldCipher,
+    ) -> Result<Option<(Self, RawUserIndex)>, Error> {
+        let stale = [
+            &self.full_name_lat,
+            &self.full_name_cyr,
+            &self.citizenship,
+            &self.arrival_date,
+        ]
+        .into_iter()
+        .chain(
+            [&self.birth_date, &self.sex, &self.passport, &self.place_of_stay]
+                .into_iter()
+                .flatten(),
+        )
+        .any(|value| cipher.needs_reencryption(value));
+
+        let plain = self.map_personal_data(|column, id, value| cipher.decrypt(column, id, value))?;
+        let new_index = RawUserIndex::new(&plain.full_name_lat, &plain.full_name_cyr, cipher);
+        if !stale && new_index == *index {
+            return Ok(None);
+        }
+        let raw = plain.map_personal_data(|column, id, value| cipher.encrypt(column, id, value))?;
+        Ok(Some((raw, new_index)))
+    }
+
+    fn map_personal_data(
+        self,
+        f: impl Fn(&str, i64, &str) -> Result<String, Error>,
+    ) -> Result<Self, Error> {
+        let optional = |column: &str, value: &Option<String>| {
+            value.as_deref().map(|value| f(column, self.id, value)).transpose()
+        };
+        Ok(Self {
+            full_name_lat: f("full_name_lat", self.id, &self.full_name_lat)?,
+            full_name_cyr: f("full_name_cyr", self.id, &self.full_name_cyr)?,
+            citizenship: f("citizenship", self.id, &self.citizenship)?,
+            arrival_date: f("arrival_date", self.id, &self.arrival_date)?,
+            birth_date: optional("birth_date", &self.birth_date)?,
+            sex: optional("sex", &self.sex)?,
+            passport: optional("passport", &self.passport)?,
+            place_of_stay: optional("place_of_stay", &self.place_of_stay)?,
+            ..self
+        })
     }
 }
 
@@ -78,6 +190,226 @@ impl From<DomainService> for Service {
     }
 }
 
+#[derive(Debug, ToSql, FromSql)]
+#[postgres(name = "document_kind", rename_all = "snake_case")]
+enum DocumentKind {
+    Passport,
+    MigrationCard,
+    Visa,
+}
+
+impl From<DocumentKind> for DomainDocumentKind {
+    fn from(k: DocumentKind) -> Self {
+        match k {
+            DocumentKind::Passport => DomainDocumentKind::Passport,
+            DocumentKind::MigrationCard => DomainDocumentKind::MigrationCard,
+            DocumentKind::Visa => DomainDocumentKind::Visa,
+        }
+    }
+}
+
+impl From<DomainDocumentKind> for DocumentKind {
+    fn from(k: DomainDocumentKind) -> Self {
+        match k {
+            DomainDocumentKind::Passport => DocumentKind::Passport,
+            DomainDocumentKind::MigrationCard => DocumentKind::MigrationCard,
+            DomainDocumentKind::Visa => DocumentKind::Visa,
+        }
+    }
+}
+
+pub struct RawDocument {
+    user_id: i64,
+    slot_start: DateTime<Utc>,
+    kind: DocumentKind,
+    file_name: String,
+    mime_type: String,
+    size: i64,
+    uploaded_at: DateTime<Utc>,
+    storage_key: Option<String>,
+}
+
+impl From<&Document> for RawDocument {
+    fn from(d: &Document) -> Self {
+        Self {
+            user_id: d.user_id().as_i64(),
+            slot_start: d.slot_start(),
+            kind: d.kind().into(),
+            file_name: d.file_name().to_string(),
+            mime_type: d.mime_type().to_string(),
+            size: d.size() as i64,
+            uploaded_at: d.uploaded_at(),
+            storage_key: Some(d.storage_key()),
+        }
+    }
+}
+
+impl From<RawDocument> for Document {
+    fn from(r: RawDocument) -> Self {
+        Document::restore(
+            UserID::new(r.user_id),
+            r.slot_start,
+            r.kind.into(),
+            r.file_name,
+            r.mime_type,
+            r.size as usize,
+            r.uploaded_at,
+            r.storage_key,
+        )
+    }
+}
+
+#[derive(Debug, ToSql, FromSql)]
+#[postgres(name = "override_status", rename_all = "snake_case")]
+enum OverrideStatus {
+    Pending,
+    Approved,
+    Rejected,
+    Used,
+}
+
+impl From<OverrideStatus> for DomainOverrideStatus {
+    fn from(s: OverrideStatus) -> Self {
+        match s {
+            OverrideStatus::Pending => DomainOverrideStatus::Pending,
+            OverrideStatus::Approved => DomainOverrideStatus::Approved,
+            OverrideStatus::Rejected => DomainOverrideStatus::Rejected,
+            OverrideStatus::Used => DomainOverrideStatus::Used,
+        }
+    }
+}
+
+impl From<DomainOverrideStatus> for OverrideStatus {
+    fn from(s: DomainOverrideStatus) -> Self {
+        match s {
+            DomainOverrideStatus::Pending => OverrideStatus::Pending,
+            DomainOverrideStatus::Approved => OverrideStatus::Approved,
+            DomainOverrideStatus::Rejected => OverrideStatus::Rejected,
+            DomainOverrideStatus::Used => OverrideStatus::Used,
+        }
+    }
+}
+
+pub struct RawDeadlineOverride {
+    id: i64,
+    user_id: i64,
+    service: Service,
+    status: OverrideStatus,
+    requested_at: DateTime<Utc>,
+    resolved_by: Option<i64>,
+    resolved_at: Option<DateTime<Utc>>,
+    used_at: Option<DateTime<Utc>>,
+}
+
+impl From<&DeadlineOverride> for RawDeadlineOverride {
+    fn from(o: &DeadlineOverride) -> Self {
+        Self {
+            id: o.id(),
+            user_id: o.user_id().as_i64(),
+            service: o.service().into(),
+            status: o.status().into(),
+            requested_at: o.requested_at(),
+            resolved_by: o.resolved_by().map(|id| id.as_i64()),
+            resolved_at: o.resolved_at(),
+            used_at: o.used_at(),
+        }
+    }
+}
+
+impl From<RawDeadlineOverride> for DeadlineOverride {
+    fn from(r: RawDeadlineOverride) -> Self {
+        DeadlineOverride::restore(
+            r.id,
+            UserID::new(r.user_id),
+            r.service.into(),
+            r.status.into(),
+            r.requested_at,
+            r.resolved_by.map(UserID::new),
+            r.resolved_at,
+            r.used_at,
+        )
+    }
+}
+
+#[derive(Debug, ToSql, FromSql)]
+#[postgres(name = "staff_role", rename_all = "snake_case")]
+enum StaffRole {
+    Viewer,
+    Operator,
+    Owner,
+}
+
+impl From<StaffRole> for DomainStaffRole {
+    fn from(r: StaffRole) -> Self {
+        match r {
+            StaffRole::Viewer => DomainStaffRole::Viewer,
+            StaffRole::Operator => DomainStaffRole::Operator,
+            StaffRole::Owner => DomainStaffRole::Owner,
+        }
+    }
+}
+
+impl From<DomainStaffRole> for StaffRole {
+    fn from(r: DomainStaffRole) -> Self {
+        match r {
+            DomainStaffRole::Viewer => StaffRole::Viewer,
+            DomainStaffRole::Operator => StaffRole::Operator,
+            DomainStaffRole::Owner => StaffRole::Owner,
+        }
+    }
+}
+
+pub struct RawStaffMember {
+    user_id: i64,
+    role: StaffRole,
+}
+
+impl From<RawStaffMember> for StaffMember {
+    fn from(r: RawStaffMember) -> Self {
+        StaffMember::new(UserID::new(r.user_id), r.role.into())
+    }
+}
+
+pub struct RawAuditEntry {
+    at: DateTime<Utc>,
+    actor: i64,
+    action: String,
+    target: Option<i64>,
+    changes: Json<Vec<AuditChange>>,
+}
+
+impl TryFrom<RawAuditEntry> for AuditEntry {
+    type Error = Error;
+
+    fn try_from(r: RawAuditEntry) -> Result<Self, Self::Error> {
+        Ok(AuditEntry::restore(
+            r.at,
+            UserID::new(r.actor),
+            AuditAction::try_from(r.action.as_str())?,
+            r.target.map(UserID::new),
+            r.changes.0,
+        ))
+    }
+}
+
+pub struct RawConsent {
+    user_id: i64,
+    version: i32,
+    given_at: DateTime<Utc>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<RawConsent> for Consent {
+    fn from(r: RawConsent) -> Self {
+        Consent::restore(
+            UserID::new(r.user_id),
+            r.version as u32,
+            r.given_at,
+            r.revoked_at,
+        )
+    }
+}
+
 pub struct RawReservation {
     slot_start: DateTime<Utc>,
     service: Service,
@@ -91,8 +423,11 @@ pub struct RawReservationWithUser {
 }
 
 impl RawReservationWithUser {
-    pub fn try_unpack(self) -> Result<(DateTime<Utc>, DomainService, User), Error> {
-        Ok((self.slot_start, self.service.into(), self.user.try_into()?))
+    pub fn try_unpack(
+        self,
+        cipher: &FieldCipher,
+    ) -> Result<(DateTime<Utc>, DomainService, User), Error> {
+        Ok((self.slot_start, self.service.into(), self.user.open(cipher)?))
     }
 }
 
@@ -104,26 +439,372 @@ pub async fn get_raw_user<C: GenericClient>(client: &C, id: UserID) -> Result<Ra
             full_name_lat,
             full_name_cyr,
             citizenship,
-            arrival_date
+            arrival_date,
+            visa_expiry,
+            registration_expiry,
+            birth_date,
+            sex,
+            passport,
+            place_of_stay
         FROM users
-        WHERE id = $1
+        WHERE
+            id = $1
+            AND anonymised_at IS NULL
     "#;
 
     let row_opt = client
         .query_opt(query, &[&id.as_i64()])
         .await
-        .map_err(|err| Error::Other(err.into()))?;
+        .map_err(pg_error)?;
 
     match row_opt {
         Some(row) => {
-            let raw_user = fetch_raw_user(&row).map_err(|err| Error::Other(err.into()))?;
+            let raw_user = fetch_raw_user(&row).map_err(pg_error)?;
             Ok(raw_user)
         }
         None => Err(Error::UserNotFound(id)),
     }
 }
 
-pub async fn upsert_raw_user<C: GenericClient>(client: &C, user: RawUser) -> Result<(), Error> {
+pub async fn select_raw_users_with_expiry<C: GenericClient>(
+    client: &C,
+    until: NaiveDate,
+) -> Result<Vec<RawUser>, Error> {
+    let query = r#"
+        SELECT *
+        FROM users
+        WHERE
+            visa_expiry <= $1
+            OR registration_expiry <= $1
+    "#;
+
+    let rows = client
+        .query(query, &[&until])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(fetch_raw_user)
+        .collect::<Result<Vec<RawUser>, _>>()
+        .map_err(pg_error)
+}
+
+/// Возвращает пользователей вместе с началом их последней записи. Отменённые записи
+/// переносятся в `cancelled_reservations` и не учитываются, а прошедшие учитываются и без
+/// отметки о приходе: `/checkin` необязателен. Сравнить запись с датой въезда можно только
+/// после расшифровки, поэтому фильтрация выполняется в репозитории.
+pub async fn select_raw_users_with_last_reservation<C: GenericClient>(
+    client: &C,
+) -> Result<Vec<(RawUser, Option<DateTime<Utc>>)>, Error> {
+    let query = r#"
+        SELECT
+            u.*,
+            (
+                SELECT max(r.slot_start)
+                FROM reservations AS r
+                WHERE r.user_id = u.id
+            ) AS last_reservation
+        FROM users AS u
+        WHERE u.anonymised_at IS NULL
+    "#;
+
+    let rows = client
+        .query(query, &[])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(|row| Ok((fetch_raw_user(row)?, row.try_get("last_reservation")?)))
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+pub async fn select_raw_users_by_blind_index<C: GenericClient>(
+    client: &C,
+    index: &str,
+) -> Result<Vec<RawUser>, Error> {
+    let query = r#"
+        SELECT *
+        FROM users
+        WHERE
+            anonymised_at IS NULL
+            AND (full_name_lat_idx = $1 OR full_name_cyr_idx = $1)
+        ORDER BY id
+    "#;
+
+    let rows = client
+        .query(query, &[&index])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(fetch_raw_user)
+        .collect::<Result<Vec<RawUser>, _>>()
+        .map_err(pg_error)
+}
+
+/// Блокирует и возвращает очередную страницу пользователей, включая обезличенных,
+/// для перешифрования.
+pub async fn select_raw_users_for_update<C: GenericClient>(
+    client: &C,
+    after_id: i64,
+    limit: i64,
+) -> Result<Vec<(RawUser, RawUserIndex)>, Error> {
+    let query = r#"
+        SELECT *
+        FROM users
+        WHERE id > $1
+        ORDER BY id
+        LIMIT $2
+        FOR UPDATE
+    "#;
+
+    let rows = client
+        .query(query, &[&after_id, &limit])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(|row| Ok((fetch_raw_user(row)?, fetch_raw_user_index(row)?)))
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+pub async fn update_raw_user_personal_data<C: GenericClient>(
+    client: &C,
+    user: &RawUser,
+    index: &RawUserIndex,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            UPDATE users
+            SET
+                full_name_lat     = $2,
+                full_name_cyr     = $3,
+                citizenship       = $4,
+                arrival_date      = $5,
+                full_name_lat_idx = $6,
+                full_name_cyr_idx = $7,
+                birth_date        = $8,
+                sex               = $9,
+                passport          = $10,
+                place_of_stay     = $11
+            WHERE id = $1"#,
+            &[
+                &user.id,
+                &user.full_name_lat,
+                &user.full_name_cyr,
+                &user.citizenship,
+                &user.arrival_date,
+                &index.full_name_lat,
+                &index.full_name_cyr,
+                &user.birth_date,
+                &user.sex,
+                &user.passport,
+                &user.place_of_stay,
+            ],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn select_raw_dialogue<C: GenericClient>(
+    client: &C,
+    chat_id: i64,
+    name: &str,
+) -> Result<Option<Vec<u8>>, Error> {
+    let row = client
+        .query_opt(
+            "SELECT state FROM dialogues WHERE chat_id = $1 AND name = $2",
+            &[&chat_id, &name],
+        )
+        .await
+        .map_err(pg_error)?;
+
+    row.map(|row| row.try_get("state"))
+        .transpose()
+        .map_err(pg_error)
+}
+
+pub async fn upsert_raw_dialogue<C: GenericClient>(
+    client: &C,
+    chat_id: i64,
+    name: &str,
+    state: &[u8],
+    updated_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO dialogues (chat_id, name, state, updated_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (chat_id, name)
+            DO UPDATE SET
+                state      = EXCLUDED.state,
+                updated_at = EXCLUDED.updated_at"#,
+            &[&chat_id, &name, &state, &updated_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn delete_raw_dialogue<C: GenericClient>(
+    client: &C,
+    chat_id: i64,
+    name: &str,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "DELETE FROM dialogues WHERE chat_id = $1 AND name = $2",
+            &[&chat_id, &name],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn upsert_chat_activity<C: GenericClient>(
+    client: &C,
+    chat_id: i64,
+    active_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO chat_activity (chat_id, active_at)
+            VALUES ($1, $2)
+            ON CONFLICT (chat_id) DO UPDATE SET active_at = EXCLUDED.active_at"#,
+            &[&chat_id, &active_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+/// Удаляет отметки активности чатов, молчащих с `before`, и возвращает эти чаты.
+pub async fn delete_idle_chats<C: GenericClient>(
+    client: &C,
+    before: DateTime<Utc>,
+) -> Result<Vec<i64>, Error> {
+    let rows = client
+        .query(
+            "DELETE FROM chat_activity WHERE active_at < $1 RETURNING chat_id",
+            &[&before],
+        )
+        .await
+        .map_err(pg_error)?;
+    rows.iter()
+        .map(|row| row.try_get("chat_id"))
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+/// Проверяет, отправлено ли уведомление `key`. Уведомления без `user_id` не относятся к
+/// одному пользователю.
+pub async fn is_notified<C: GenericClient>(
+    client: &C,
+    user_id: Option<UserID>,
+    key: &str,
+) -> Result<bool, Error> {
+    let row = client
+        .query_one(
+            r#"
+            SELECT EXISTS (
+                SELECT 1
+                FROM notifications
+                WHERE user_id IS NOT DISTINCT FROM $1 AND key = $2
+            )"#,
+            &[&user_id.map(|id| id.as_i64()), &key],
+        )
+        .await
+        .map_err(pg_error)?;
+    let exists: bool = row.get(0);
+    Ok(exists)
+}
+
+pub async fn insert_notification<C: GenericClient>(
+    client: &C,
+    user_id: Option<UserID>,
+    key: &str,
+    sent_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO notifications (user_id, key, sent_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (user_id, key) DO NOTHING"#,
+            &[&user_id.map(|id| id.as_i64()), &key, &sent_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn select_mirrored_slots<C: GenericClient>(
+    client: &C,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, String)>, Error> {
+    let rows = client
+        .query(
+            r#"
+            SELECT slot_start, event_hash
+            FROM calendar_events
+            WHERE slot_start >= $1 AND slot_start < $2"#,
+            &[&from, &to],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(rows
+        .iter()
+        .map(|row| (row.get("slot_start"), row.get("event_hash")))
+        .collect())
+}
+
+pub async fn upsert_mirrored_slot<C: GenericClient>(
+    client: &C,
+    slot_start: DateTime<Utc>,
+    event_hash: &str,
+    synced_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO calendar_events (slot_start, event_hash, synced_at)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (slot_start) DO UPDATE SET
+                event_hash = EXCLUDED.event_hash,
+                synced_at = EXCLUDED.synced_at"#,
+            &[&slot_start, &event_hash, &synced_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn delete_mirrored_slot<C: GenericClient>(
+    client: &C,
+    slot_start: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "DELETE FROM calendar_events WHERE slot_start = $1",
+            &[&slot_start],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn upsert_raw_user<C: GenericClient>(
+    client: &C,
+    user: RawUser,
+    index: RawUserIndex,
+) -> Result<(), Error> {
     client
         .execute(
             r#"
@@ -133,17 +814,35 @@ pub async fn upsert_raw_user<C: GenericClient>(client: &C, user: RawUser) -> Res
                 full_name_lat,
                 full_name_cyr,
                 citizenship,
-                arrival_date
+                arrival_date,
+                visa_expiry,
+                registration_expiry,
+                full_name_lat_idx,
+                full_name_cyr_idx,
+                birth_date,
+                sex,
+                passport,
+                place_of_stay
             )
             VALUES
-                ($1, $2, $3, $4, $5, $6)
+                ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
             ON CONFLICT (id) 
             DO UPDATE SET
-                username      = EXCLUDED.username,
-                full_name_lat = EXCLUDED.full_name_lat,
-                full_name_cyr = EXCLUDED.full_name_cyr,
-                citizenship   = EXCLUDED.citizenship,
-                arrival_date  = EXCLUDED.arrival_date"#,
+                username            = EXCLUDED.username,
+                full_name_lat       = EXCLUDED.full_name_lat,
+                full_name_cyr       = EXCLUDED.full_name_cyr,
+                citizenship         = EXCLUDED.citizenship,
+                arrival_date        = EXCLUDED.arrival_date,
+                visa_expiry         = EXCLUDED.visa_expiry,
+                registration_expiry = EXCLUDED.registration_expiry,
+                full_name_lat_idx   = EXCLUDED.full_name_lat_idx,
+                full_name_cyr_idx   = EXCLUDED.full_name_cyr_idx,
+                birth_date          = EXCLUDED.birth_date,
+                sex                 = EXCLUDED.sex,
+                passport            = EXCLUDED.passport,
+                place_of_stay       = EXCLUDED.place_of_stay,
+                anonymised_at       = NULL,
+                updated_at          = now()"#,
             &[
                 &user.id,
                 &user.username.as_str(),
@@ -151,42 +850,391 @@ pub async fn upsert_raw_user<C: GenericClient>(client: &C, user: RawUser) -> Res
                 &user.full_name_cyr.as_str(),
                 &user.citizenship.as_str(),
                 &user.arrival_date,
+                &user.visa_expiry,
+                &user.registration_expiry,
+                &index.full_name_lat,
+                &index.full_name_cyr,
+                &user.birth_date,
+                &user.sex,
+                &user.passport,
+                &user.place_of_stay,
             ],
         )
         .await
-        .map_err(|err| Error::Other(err.into()))?;
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+/// Стирает персональные данные пользователя. Дата въезда заменяется на `arrival_date`,
+/// см. `RawUser::anonymised_arrival_date`.
+pub async fn anonymise_raw_user<C: GenericClient>(
+    client: &C,
+    id: UserID,
+    arrival_date: &str,
+    at: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            UPDATE users
+            SET
+                username            = '',
+                full_name_lat       = '',
+                full_name_cyr       = '',
+                citizenship         = '',
+                arrival_date        = $3,
+                full_name_lat_idx   = NULL,
+                full_name_cyr_idx   = NULL,
+                visa_expiry         = NULL,
+                registration_expiry = NULL,
+                birth_date          = NULL,
+                sex                 = NULL,
+                passport            = NULL,
+                place_of_stay       = NULL,
+                anonymised_at       = $2
+            WHERE id = $1"#,
+            &[&id.as_i64(), &at, &arrival_date],
+        )
+        .await
+        .map_err(pg_error)?;
+    client
+        .execute("DELETE FROM notifications WHERE user_id = $1", &[&id.as_i64()])
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+/// Возвращает пользователей без записей и изменений анкеты начиная с `before`. Пользователи
+/// с действующей на `today` визой или регистрацией не возвращаются: им ещё придут
+/// напоминания о продлении.
+pub async fn select_inactive_user_ids<C: GenericClient>(
+    client: &C,
+    before: DateTime<Utc>,
+    today: NaiveDate,
+    anonymised: bool,
+) -> Result<Vec<UserID>, Error> {
+    let query = r#"
+        SELECT u.id
+        FROM users AS u
+        WHERE
+            u.updated_at < $1
+            AND ($3 OR u.anonymised_at IS NULL)
+            AND (u.visa_expiry IS NULL OR u.visa_expiry < $2)
+            AND (u.registration_expiry IS NULL OR u.registration_expiry < $2)
+            AND NOT EXISTS (
+                SELECT 1
+                FROM reservations AS r
+                WHERE
+                    r.user_id = u.id
+                    AND r.slot_start >= $1
+            )
+        ORDER BY u.id ASC
+    "#;
+
+    let rows = client
+        .query(query, &[&before, &today, &anonymised])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(|row| row.try_get::<_, i64>("id").map(UserID::new))
+        .collect::<Result<Vec<UserID>, _>>()
+        .map_err(pg_error)
+}
+
+pub async fn delete_raw_user<C: GenericClient>(client: &C, id: UserID) -> Result<(), Error> {
+    client
+        .execute("DELETE FROM users WHERE id = $1", &[&id.as_i64()])
+        .await
+        .map_err(pg_error)?;
     Ok(())
 }
 
-pub async fn delete_reservations<C: GenericClient>(
+pub async fn select_last_raw_consent<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+) -> Result<Option<RawConsent>, Error> {
+    let row = client
+        .query_opt(
+            "SELECT * FROM consents WHERE user_id = $1 ORDER BY given_at DESC LIMIT 1",
+            &[&user_id.as_i64()],
+        )
+        .await
+        .map_err(pg_error)?;
+
+    row.as_ref()
+        .map(fetch_raw_consent)
+        .transpose()
+        .map_err(pg_error)
+}
+
+pub async fn upsert_raw_consent<C: GenericClient>(
+    client: &C,
+    consent: &Consent,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO consents (user_id, version, given_at, revoked_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, version)
+            DO UPDATE SET
+                given_at   = EXCLUDED.given_at,
+                revoked_at = EXCLUDED.revoked_at"#,
+            &[
+                &consent.user_id().as_i64(),
+                &(consent.version() as i32),
+                &consent.given_at(),
+                &consent.revoked_at(),
+            ],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+/// Удаляет записи слота, кроме записей пользователей `kept`, и переносит их в отменённые.
+pub async fn cancel_raw_reservations<C: GenericClient>(
     client: &C,
     slot_start: DateTime<Utc>,
+    kept: &[i64],
+    cancelled_at: DateTime<Utc>,
 ) -> Result<(), Error> {
     client
         .execute(
-            "DELETE FROM reservations WHERE slot_start = $1",
-            &[&slot_start],
+            r#"
+            WITH cancelled AS (
+                DELETE FROM reservations
+                WHERE slot_start = $1 AND NOT (user_id = ANY($2))
+                RETURNING slot_start, service, user_id, reserved_at
+            )
+            INSERT INTO cancelled_reservations (slot_start, service, user_id, reserved_at, cancelled_at)
+            SELECT slot_start, service, user_id, reserved_at, $3 FROM cancelled"#,
+            &[&slot_start, &kept, &cancelled_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+/// Сохраняет записи слота. У уже существующих записей время записи и отметка о приходе
+/// не меняются.
+pub async fn batch_upsert_raw_reservations<C: GenericClient>(
+    client: &C,
+    reservations: &[RawReservation],
+) -> Result<(), Error> {
+    let stmt = client
+        .prepare(
+            r#"
+            INSERT INTO reservations (slot_start, service, user_id) VALUES ($1, $2, $3)
+            ON CONFLICT (slot_start, user_id) DO UPDATE SET service = EXCLUDED.service"#,
+        )
+        .await
+        .map_err(pg_error)?;
+    for r in reservations {
+        client
+            .execute(&stmt, &[&r.slot_start, &r.service, &r.user_id])
+            .await
+            .map_err(pg_error)?;
+    }
+    Ok(())
+}
+
+/// Число записей и отмен слотов, начавшихся раньше `before`, которые ещё связаны
+/// с пользователями.
+pub async fn count_identified_reservations<C: GenericClient>(
+    client: &C,
+    before: DateTime<Utc>,
+) -> Result<i64, Error> {
+    let row = client
+        .query_one(
+            r#"
+            SELECT
+                (SELECT COUNT(*) FROM reservations
+                 WHERE slot_start < $1 AND user_id IS NOT NULL)
+                + (SELECT COUNT(*) FROM cancelled_reservations
+                   WHERE slot_start < $1 AND user_id IS NOT NULL) AS count"#,
+            &[&before],
+        )
+        .await
+        .map_err(pg_error)?;
+    row.try_get("count").map_err(pg_error)
+}
+
+/// Отвязывает от пользователей записи и отмены слотов, начавшихся раньше `before`.
+/// Возвращает их число.
+pub async fn anonymise_raw_reservations<C: GenericClient>(
+    client: &C,
+    before: DateTime<Utc>,
+) -> Result<u64, Error> {
+    let reservations = client
+        .execute(
+            "UPDATE reservations SET user_id = NULL WHERE slot_start < $1 AND user_id IS NOT NULL",
+            &[&before],
+        )
+        .await
+        .map_err(pg_error)?;
+    let cancelled = client
+        .execute(
+            r#"
+            UPDATE cancelled_reservations SET user_id = NULL
+            WHERE slot_start < $1 AND user_id IS NOT NULL"#,
+            &[&before],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(reservations + cancelled)
+}
+
+/// Отмечает приход студента на приём. Повторная отметка не меняет время. Возвращает false,
+/// если записи нет.
+pub async fn update_reservation_attended<C: GenericClient>(
+    client: &C,
+    slot_start: DateTime<Utc>,
+    user_id: UserID,
+    attended_at: DateTime<Utc>,
+) -> Result<bool, Error> {
+    let updated = client
+        .execute(
+            r#"
+            UPDATE reservations
+            SET attended_at = COALESCE(attended_at, $3)
+            WHERE slot_start = $1 AND user_id = $2"#,
+            &[&slot_start, &user_id.as_i64(), &attended_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(updated > 0)
+}
+
+/// Число записей, отмеченных приходов и отмен по дням слотов в `[from, to)`.
+pub async fn select_daily_reservation_counts<C: GenericClient>(
+    client: &C,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(NaiveDate, i64, i64, i64)>, Error> {
+    let query = r#"
+        WITH active AS (
+            SELECT
+                (slot_start AT TIME ZONE 'UTC')::date AS day,
+                COUNT(*) AS reserved,
+                COUNT(attended_at) AS attended
+            FROM reservations
+            WHERE slot_start >= $1 AND slot_start < $2
+            GROUP BY day
+        ),
+        cancelled AS (
+            SELECT
+                (slot_start AT TIME ZONE 'UTC')::date AS day,
+                COUNT(*) AS cancelled
+            FROM cancelled_reservations
+            WHERE slot_start >= $1 AND slot_start < $2
+            GROUP BY day
+        )
+        SELECT
+            COALESCE(a.day, c.day) AS day,
+            COALESCE(a.reserved, 0) AS reserved,
+            COALESCE(a.attended, 0) AS attended,
+            COALESCE(c.cancelled, 0) AS cancelled
+        FROM active AS a
+        FULL JOIN cancelled AS c ON a.day = c.day
+        ORDER BY day
+    "#;
+    let rows = client
+        .query(query, &[&from, &to])
+        .await
+        .map_err(pg_error)?;
+    rows.iter()
+        .map(|row| {
+            Ok((
+                row.try_get("day")?,
+                row.try_get("reserved")?,
+                row.try_get("attended")?,
+                row.try_get("cancelled")?,
+            ))
+        })
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+/// Число записей в `[from, to)` по услугам и пользователям. Гражданство зашифровано,
+/// поэтому группировка по нему возможна только после расшифровки. У обезличенных записей
+/// пользователя нет.
+pub async fn select_raw_reservation_breakdown<C: GenericClient>(
+    client: &C,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DomainService, Option<i64>, Option<String>, i64)>, Error> {
+    let query = r#"
+        SELECT
+            r.service,
+            u.id,
+            u.citizenship,
+            COUNT(*) AS reserved
+        FROM reservations AS r
+        LEFT JOIN users AS u ON u.id = r.user_id
+        WHERE r.slot_start >= $1 AND r.slot_start < $2
+        GROUP BY r.service, u.id, u.citizenship
+    "#;
+    let rows = client
+        .query(query, &[&from, &to])
+        .await
+        .map_err(pg_error)?;
+    rows.iter()
+        .map(|row| {
+            let service: Service = row.try_get("service")?;
+            Ok((
+                service.into(),
+                row.try_get("id")?,
+                row.try_get("citizenship")?,
+                row.try_get("reserved")?,
+            ))
+        })
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+/// Среднее время в секундах от записи до начала слота для записей в `[from, to)`, у которых
+/// известно время записи.
+pub async fn select_average_lead_time<C: GenericClient>(
+    client: &C,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Option<f64>, Error> {
+    let row = client
+        .query_one(
+            r#"
+            SELECT EXTRACT(EPOCH FROM AVG(slot_start - reserved_at))::float8 AS lead_time
+            FROM reservations
+            WHERE slot_start >= $1 AND slot_start < $2 AND reserved_at IS NOT NULL"#,
+            &[&from, &to],
         )
         .await
-        .map_err(|err| Error::Other(err.into()))?;
-    Ok(())
+        .map_err(pg_error)?;
+    row.try_get("lead_time").map_err(pg_error)
 }
 
-pub async fn batch_insert_raw_reservations<C: GenericClient>(
+/// Число записей в каждом слоте, начинающемся в `[from, to)`.
+pub async fn select_slot_reservation_counts<C: GenericClient>(
     client: &C,
-    reservations: &[RawReservation],
-) -> Result<(), Error> {
-    let stmt = client
-        .prepare("INSERT INTO reservations (slot_start, service, user_id) VALUES ($1, $2, $3)")
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, i64)>, Error> {
+    let rows = client
+        .query(
+            r#"
+            SELECT slot_start, COUNT(*) AS reserved
+            FROM reservations
+            WHERE slot_start >= $1 AND slot_start < $2
+            GROUP BY slot_start"#,
+            &[&from, &to],
+        )
         .await
-        .map_err(|err| Error::Other(err.into()))?;
-    for r in reservations {
-        client
-            .execute(&stmt, &[&r.slot_start, &r.service, &r.user_id])
-            .await
-            .map_err(|err| Error::Other(err.into()))?;
-    }
-    Ok(())
+        .map_err(pg_error)?;
+    rows.iter()
+        .map(|row| Ok((row.try_get("slot_start")?, row.try_get("reserved")?)))
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
 }
 
 pub async fn select_slot_raw_reservations_with_user<C: GenericClient>(
@@ -203,9 +1251,15 @@ pub async fn select_slot_raw_reservations_with_user<C: GenericClient>(
             u.full_name_lat,
             u.full_name_cyr,
             u.citizenship,
-            u.arrival_date
+            u.arrival_date,
+            u.visa_expiry,
+            u.registration_expiry,
+            u.birth_date,
+            u.sex,
+            u.passport,
+            u.place_of_stay
         FROM reservations AS r
-        LEFT JOIN 
+        JOIN
             users AS u
             ON u.id = r.user_id
         WHERE
@@ -216,7 +1270,7 @@ pub async fn select_slot_raw_reservations_with_user<C: GenericClient>(
     let rows = client
         .query(query, &[&slot_start, &max_size])
         .await
-        .map_err(|err| Error::Other(err.into()))?;
+        .map_err(pg_error)?;
 
     fetch_raw_reservations_with_user(&rows)
 }
@@ -255,7 +1309,7 @@ pub async fn select_raw_reservations_with_user<C: GenericClient>(
     let rows = client
         .query(&query, &[])
         .await
-        .map_err(|err| Error::Other(err.into()))?;
+        .map_err(pg_error)?;
 
     fetch_raw_reservations_with_user(&rows)
 }
@@ -296,11 +1350,501 @@ pub async fn select_available_raw_reservations_with_user<C: GenericClient>(
     let rows = client
         .query(&query, &[])
         .await
-        .map_err(|err| Error::Other(err.into()))?;
+        .map_err(pg_error)?;
 
     fetch_raw_reservations_with_user(&rows)
 }
 
+pub async fn select_user_raw_reservations<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+    from: DateTime<Utc>,
+) -> Result<Vec<(DateTime<Utc>, DomainService)>, Error> {
+    let query = r#"
+        SELECT
+            slot_start,
+            service
+        FROM reservations
+        WHERE
+            user_id = $1
+            AND slot_start >= $2
+        ORDER BY slot_start ASC
+    "#;
+
+    let rows = client
+        .query(query, &[&user_id.as_i64(), &from])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(|row| {
+            let slot_start: DateTime<Utc> = row.try_get("slot_start")?;
+            let service: Service = row.try_get("service")?;
+            Ok((slot_start, service.into()))
+        })
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+/// Возвращает отменённые записи пользователя: начало слота, услугу и время отмены.
+pub async fn select_user_raw_cancellations<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+) -> Result<Vec<(DateTime<Utc>, DomainService, DateTime<Utc>)>, Error> {
+    let query = r#"
+        SELECT
+            slot_start,
+            service,
+            cancelled_at
+        FROM cancelled_reservations
+        WHERE user_id = $1
+        ORDER BY slot_start ASC
+    "#;
+
+    let rows = client
+        .query(query, &[&user_id.as_i64()])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(|row| {
+            let slot_start: DateTime<Utc> = row.try_get("slot_start")?;
+            let service: Service = row.try_get("service")?;
+            let cancelled_at: DateTime<Utc> = row.try_get("cancelled_at")?;
+            Ok((slot_start, service.into(), cancelled_at))
+        })
+        .collect::<Result<Vec<_>, tokio_postgres::Error>>()
+        .map_err(pg_error)
+}
+
+pub async fn upsert_raw_document<C: GenericClient>(
+    client: &C,
+    document: RawDocument,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO documents (
+                user_id,
+                slot_start,
+                kind,
+                file_name,
+                mime_type,
+                size,
+                uploaded_at,
+                storage_key
+            )
+            VALUES
+                ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (user_id, slot_start, kind)
+            DO UPDATE SET
+                file_name   = EXCLUDED.file_name,
+                mime_type   = EXCLUDED.mime_type,
+                size        = EXCLUDED.size,
+                uploaded_at = EXCLUDED.uploaded_at,
+                storage_key = EXCLUDED.storage_key"#,
+            &[
+                &document.user_id,
+                &document.slot_start,
+                &document.kind,
+                &document.file_name,
+                &document.mime_type,
+                &document.size,
+                &document.uploaded_at,
+                &document.storage_key,
+            ],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn delete_raw_document<C: GenericClient>(
+    client: &C,
+    document: RawDocument,
+) -> Result<(), Error> {
+    client
+        .execute(
+            "DELETE FROM documents WHERE user_id = $1 AND slot_start = $2 AND kind = $3",
+            &[&document.user_id, &document.slot_start, &document.kind],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn select_raw_documents<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+    slot_start: DateTime<Utc>,
+) -> Result<Vec<RawDocument>, Error> {
+    let query = r#"
+        SELECT *
+        FROM documents
+        WHERE
+            user_id = $1
+            AND slot_start = $2
+        ORDER BY kind ASC
+    "#;
+
+    let rows = client
+        .query(query, &[&user_id.as_i64(), &slot_start])
+        .await
+        .map_err(pg_error)?;
+
+    fetch_raw_documents(&rows)
+}
+
+pub async fn select_user_raw_documents<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+) -> Result<Vec<RawDocument>, Error> {
+    let rows = client
+        .query(
+            "SELECT * FROM documents WHERE user_id = $1 ORDER BY slot_start ASC, kind ASC",
+            &[&user_id.as_i64()],
+        )
+        .await
+        .map_err(pg_error)?;
+
+    fetch_raw_documents(&rows)
+}
+
+/// Документы пользователя, у которых больше нет записи: запись отменена или перенесена.
+/// Сначала идут последние загруженные.
+pub async fn select_orphaned_raw_documents<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+) -> Result<Vec<RawDocument>, Error> {
+    let query = r#"
+        SELECT d.*
+        FROM documents AS d
+        WHERE
+            d.user_id = $1
+            AND NOT EXISTS (
+                SELECT 1
+                FROM reservations AS r
+                WHERE
+                    r.slot_start = d.slot_start
+                    AND r.user_id = d.user_id
+            )
+        ORDER BY d.uploaded_at DESC
+    "#;
+
+    let rows = client
+        .query(query, &[&user_id.as_i64()])
+        .await
+        .map_err(pg_error)?;
+
+    fetch_raw_documents(&rows)
+}
+
+pub async fn insert_raw_deadline_override<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+    service: DomainService,
+    requested_at: DateTime<Utc>,
+) -> Result<RawDeadlineOverride, Error> {
+    let row = client
+        .query_one(
+            r#"
+            INSERT INTO deadline_overrides (
+                user_id,
+                service,
+                status,
+                requested_at
+            )
+            VALUES
+                ($1, $2, $3, $4)
+            RETURNING *"#,
+            &[
+                &user_id.as_i64(),
+                &Service::from(service),
+                &OverrideStatus::Pending,
+                &requested_at,
+            ],
+        )
+        .await
+        .map_err(pg_error)?;
+
+    fetch_raw_deadline_override(&row).map_err(pg_error)
+}
+
+pub async fn update_raw_deadline_override<C: GenericClient>(
+    client: &C,
+    o: RawDeadlineOverride,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            UPDATE deadline_overrides
+            SET
+                status      = $2,
+                resolved_by = $3,
+                resolved_at = $4,
+                used_at     = $5
+            WHERE id = $1"#,
+            &[&o.id, &o.status, &o.resolved_by, &o.resolved_at, &o.used_at],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn select_raw_deadline_override<C: GenericClient>(
+    client: &C,
+    id: i64,
+) -> Result<Option<RawDeadlineOverride>, Error> {
+    let row = client
+        .query_opt("SELECT * FROM deadline_overrides WHERE id = $1", &[&id])
+        .await
+        .map_err(pg_error)?;
+
+    row.as_ref()
+        .map(fetch_raw_deadline_override)
+        .transpose()
+        .map_err(pg_error)
+}
+
+/// Читает запрос на запись после срока и блокирует строку до конца транзакции.
+pub async fn select_raw_deadline_override_for_update<C: GenericClient>(
+    client: &C,
+    id: i64,
+) -> Result<Option<RawDeadlineOverride>, Error> {
+    let row = client
+        .query_opt(
+            "SELECT * FROM deadline_overrides WHERE id = $1 FOR UPDATE",
+            &[&id],
+        )
+        .await
+        .map_err(pg_error)?;
+
+    row.as_ref()
+        .map(fetch_raw_deadline_override)
+        .transpose()
+        .map_err(pg_error)
+}
+
+pub async fn select_active_raw_deadline_override<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+    service: DomainService,
+) -> Result<Option<RawDeadlineOverride>, Error> {
+    let query = r#"
+        SELECT *
+        FROM deadline_overrides
+        WHERE
+            user_id = $1
+            AND service = $2
+            AND status IN ('pending', 'approved')
+        ORDER BY requested_at DESC
+        LIMIT 1
+    "#;
+
+    let row = client
+        .query_opt(query, &[&user_id.as_i64(), &Service::from(service)])
+        .await
+        .map_err(pg_error)?;
+
+    row.as_ref()
+        .map(fetch_raw_deadline_override)
+        .transpose()
+        .map_err(pg_error)
+}
+
+pub async fn select_pending_raw_deadline_overrides<C: GenericClient>(
+    client: &C,
+) -> Result<Vec<RawDeadlineOverride>, Error> {
+    let query = r#"
+        SELECT *
+        FROM deadline_overrides
+        WHERE status = 'pending'
+        ORDER BY requested_at ASC
+    "#;
+
+    let rows = client
+        .query(query, &[])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(fetch_raw_deadline_override)
+        .collect::<Result<Vec<RawDeadlineOverride>, _>>()
+        .map_err(pg_error)
+}
+
+pub async fn select_raw_staff_member<C: GenericClient>(
+    client: &C,
+    id: UserID,
+) -> Result<Option<RawStaffMember>, Error> {
+    let row = client
+        .query_opt("SELECT * FROM staff WHERE user_id = $1", &[&id.as_i64()])
+        .await
+        .map_err(pg_error)?;
+
+    row.as_ref()
+        .map(fetch_raw_staff_member)
+        .transpose()
+        .map_err(pg_error)
+}
+
+pub async fn select_raw_staff<C: GenericClient>(client: &C) -> Result<Vec<RawStaffMember>, Error> {
+    let rows = client
+        .query("SELECT * FROM staff ORDER BY role DESC, user_id ASC", &[])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(fetch_raw_staff_member)
+        .collect::<Result<Vec<RawStaffMember>, _>>()
+        .map_err(pg_error)
+}
+
+pub async fn upsert_raw_staff_member<C: GenericClient>(
+    client: &C,
+    member: &StaffMember,
+    granted_by: Option<UserID>,
+    granted_at: DateTime<Utc>,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO staff (user_id, role, granted_by, granted_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id)
+            DO UPDATE SET
+                role       = EXCLUDED.role,
+                granted_by = EXCLUDED.granted_by,
+                granted_at = EXCLUDED.granted_at"#,
+            &[
+                &member.id().as_i64(),
+                &StaffRole::from(member.role()),
+                &granted_by.map(|id| id.as_i64()),
+                &granted_at,
+            ],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn delete_raw_staff_member<C: GenericClient>(
+    client: &C,
+    id: UserID,
+) -> Result<(), Error> {
+    client
+        .execute("DELETE FROM staff WHERE user_id = $1", &[&id.as_i64()])
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn insert_audit_entry<C: GenericClient>(
+    client: &C,
+    entry: &AuditEntry,
+) -> Result<(), Error> {
+    client
+        .execute(
+            r#"
+            INSERT INTO audit_log (at, actor, action, target, changes)
+            VALUES ($1, $2, $3, $4, $5)"#,
+            &[
+                &entry.at(),
+                &entry.actor().as_i64(),
+                &entry.action().as_str(),
+                &entry.target().map(|id| id.as_i64()),
+                &Json(entry.changes()),
+            ],
+        )
+        .await
+        .map_err(pg_error)?;
+    Ok(())
+}
+
+pub async fn select_user_raw_audit_entries<C: GenericClient>(
+    client: &C,
+    id: UserID,
+    limit: i64,
+) -> Result<Vec<RawAuditEntry>, Error> {
+    let query = r#"
+        SELECT *
+        FROM audit_log
+        WHERE actor = $1 OR target = $1
+        ORDER BY at DESC, id DESC
+        LIMIT $2
+    "#;
+
+    let rows = client
+        .query(query, &[&id.as_i64(), &limit])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(fetch_raw_audit_entry)
+        .collect::<Result<Vec<RawAuditEntry>, _>>()
+        .map_err(pg_error)
+}
+
+pub async fn select_raw_audit_entries_between<C: GenericClient>(
+    client: &C,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<RawAuditEntry>, Error> {
+    let query = r#"
+        SELECT *
+        FROM audit_log
+        WHERE at >= $1 AND at < $2
+        ORDER BY at ASC, id ASC
+    "#;
+
+    let rows = client
+        .query(query, &[&from, &to])
+        .await
+        .map_err(pg_error)?;
+
+    rows.iter()
+        .map(fetch_raw_audit_entry)
+        .collect::<Result<Vec<RawAuditEntry>, _>>()
+        .map_err(pg_error)
+}
+
+pub async fn select_expired_raw_documents<C: GenericClient>(
+    client: &C,
+    before: DateTime<Utc>,
+) -> Result<Vec<RawDocument>, Error> {
+    let query = r#"
+        SELECT d.*
+        FROM documents AS d
+        WHERE
+            d.slot_start < $1
+            OR (
+                NOT EXISTS (
+                    SELECT 1
+                    FROM reservations AS r
+                    WHERE
+                        r.slot_start = d.slot_start
+                        AND r.user_id = d.user_id
+                )
+                -- Документы отменённой записи ждут, не запишется ли студент снова
+                AND NOT EXISTS (
+                    SELECT 1
+                    FROM cancelled_reservations AS c
+                    WHERE
+                        c.slot_start = d.slot_start
+                        AND c.user_id = d.user_id
+                        AND c.cancelled_at >= $1
+                )
+            )
+    "#;
+
+    let rows = client
+        .query(query, &[&before])
+        .await
+        .map_err(pg_error)?;
+
+    fetch_raw_documents(&rows)
+}
+
 pub async fn has_available_slots<C: GenericClient>(
     client: &C,
     starts: &[DateTime<Utc>],
@@ -342,7 +1886,7 @@ pub async fn has_available_slots<C: GenericClient>(
     let row = client
         .query_one(&query, &[&max_size])
         .await
-        .map_err(|err| Error::Other(err.into()))?;
+        .map_err(pg_error)?;
     let exists: bool = row.get(0);
     Ok(exists)
 }
@@ -355,6 +1899,19 @@ pub fn fetch_raw_user(row: &Row) -> Result<RawUser, tokio_postgres::Error> {
         full_name_cyr: row.try_get("full_name_cyr")?,
         citizenship: row.try_get("citizenship")?,
         arrival_date: row.try_get("arrival_date")?,
+        visa_expiry: row.try_get("visa_expiry")?,
+        registration_expiry: row.try_get("registration_expiry")?,
+        birth_date: row.try_get("birth_date")?,
+        sex: row.try_get("sex")?,
+        passport: row.try_get("passport")?,
+        place_of_stay: row.try_get("place_of_stay")?,
+    })
+}
+
+pub fn fetch_raw_user_index(row: &Row) -> Result<RawUserIndex, tokio_postgres::Error> {
+    Ok(RawUserIndex {
+        full_name_lat: row.try_get("full_name_lat_idx")?,
+        full_name_cyr: row.try_get("full_name_cyr_idx")?,
     })
 }
 
@@ -374,7 +1931,68 @@ pub fn fetch_raw_reservations_with_user(
     rows.iter()
         .map(|row| fetch_raw_reservation_with_user(row))
         .collect::<Result<Vec<RawReservationWithUser>, _>>()
-        .map_err(|err| Error::Other(err.into()))
+        .map_err(pg_error)
+}
+
+pub fn fetch_raw_document(row: &Row) -> Result<RawDocument, tokio_postgres::Error> {
+    Ok(RawDocument {
+        user_id: row.try_get("user_id")?,
+        slot_start: row.try_get("slot_start")?,
+        kind: row.try_get("kind")?,
+        file_name: row.try_get("file_name")?,
+        mime_type: row.try_get("mime_type")?,
+        size: row.try_get("size")?,
+        uploaded_at: row.try_get("uploaded_at")?,
+        storage_key: row.try_get("storage_key")?,
+    })
+}
+
+pub fn fetch_raw_documents(rows: &[Row]) -> Result<Vec<RawDocument>, Error> {
+    rows.iter()
+        .map(fetch_raw_document)
+        .collect::<Result<Vec<RawDocument>, _>>()
+        .map_err(pg_error)
+}
+
+pub fn fetch_raw_deadline_override(
+    row: &Row,
+) -> Result<RawDeadlineOverride, tokio_postgres::Error> {
+    Ok(RawDeadlineOverride {
+        id: row.try_get("id")?,
+        user_id: row.try_get("user_id")?,
+        service: row.try_get("service")?,
+        status: row.try_get("status")?,
+        requested_at: row.try_get("requested_at")?,
+        resolved_by: row.try_get("resolved_by")?,
+        resolved_at: row.try_get("resolved_at")?,
+        used_at: row.try_get("used_at")?,
+    })
+}
+
+pub fn fetch_raw_staff_member(row: &Row) -> Result<RawStaffMember, tokio_postgres::Error> {
+    Ok(RawStaffMember {
+        user_id: row.try_get("user_id")?,
+        role: row.try_get("role")?,
+    })
+}
+
+pub fn fetch_raw_consent(row: &Row) -> Result<RawConsent, tokio_postgres::Error> {
+    Ok(RawConsent {
+        user_id: row.try_get("user_id")?,
+        version: row.try_get("version")?,
+        given_at: row.try_get("given_at")?,
+        revoked_at: row.try_get("revoked_at")?,
+    })
+}
+
+pub fn fetch_raw_audit_entry(row: &Row) -> Result<RawAuditEntry, tokio_postgres::Error> {
+    Ok(RawAuditEntry {
+        at: row.try_get("at")?,
+        actor: row.try_get("actor")?,
+        action: row.try_get("action")?,
+        target: row.try_get("target")?,
+        changes: row.try_get("changes")?,
+    })
 }
 
 pub fn slot_to_raw_reservations(slot: &Slot) -> Vec<RawReservation> {
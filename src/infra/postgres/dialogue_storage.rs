@@ -0,0 +1,202 @@
+use chrono::Utc;
+use deadpool_postgres::Pool;
+use std::fmt::Display;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use teloxide::dispatching::dialogue::{Serializer, Storage};
+use teloxide::types::ChatId;
+use tokio_postgres::{Client, GenericClient};
+
+use crate::domain::Error;
+use crate::infra::FieldCipher;
+use crate::infra::postgres::db::{delete_raw_dialogue, select_raw_dialogue, upsert_raw_dialogue};
+use crate::with_client;
+
+type BoxFuture<T> = Pin<Box<dyn Future<Output = T> + Send>>;
+
+/// PostgresDialogueStorage хранит состояния диалогов в таблице `dialogues`, чтобы они переживали
+/// перезапуск бота. Состояния разных диалогов одного чата различаются по имени `name`.
+///
+/// В состояниях есть ФИО, даты и номер паспорта из ещё не сохранённой анкеты, поэтому они
+/// шифруются тем же `FieldCipher`, что и поля пользователей.
+pub struct PostgresDialogueStorage<S> {
+    pool: Pool,
+    cipher: Arc<FieldCipher>,
+    name: &'static str,
+    serializer: S,
+}
+
+impl<S> PostgresDialogueStorage<S> {
+    pub fn new(
+        pool: Pool,
+        cipher: Arc<FieldCipher>,
+        name: &'static str,
+        serializer: S,
+    ) -> Arc<Self> {
+        Arc::new(Self {
+            pool,
+            cipher,
+            name,
+            serializer,
+        })
+    }
+
+    fn column(&self) -> String {
+        format!("dialogues.{}", self.name)
+    }
+
+    fn seal(&self, chat_id: ChatId, state: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let state = String::from_utf8(state).map_err(|err| Error::Other(err.into()))?;
+        let sealed = self.cipher.encrypt(&self.column(), chat_id.0, &state)?;
+        Ok(sealed.into_bytes())
+    }
+
+    fn open(&self, chat_id: ChatId, state: Vec<u8>) -> Result<Vec<u8>, Error> {
+        let state = String::from_utf8(state).map_err(|err| Error::Other(err.into()))?;
+        let opened = self.cipher.decrypt(&self.column(), chat_id.0, &state)?;
+        Ok(opened.into_bytes())
+    }
+}
+
+impl<D, S> Storage<D> for PostgresDialogueStorage<S>
+where
+    D: Send + 'static,
+    S: Serializer<D> + Send + Sync + 'static,
+    S::Error: Display,
+{
+    type Error = Error;
+
+    fn remove_dialogue(self: Arc<Self>, chat_id: ChatId) -> BoxFuture<Result<(), Error>> {
+        Box::pin(async move {
+            with_client!(self.pool, async |client| {
+                delete_raw_dialogue(client, chat_id.0, self.name).await
+            })
+        })
+    }
+
+    fn update_dialogue(
+        self: Arc<Self>,
+        chat_id: ChatId,
+        dialogue: D,
+    ) -> BoxFuture<Result<(), Error>> {
+        Box::pin(async move {
+            let state = self
+                .serializer
+                .serialize(&dialogue)
+                .map_err(|err| Error::Other(err.to_string().into()))?;
+            let state = self.seal(chat_id, state)?;
+            with_client!(self.pool, async |client| {
+                upsert_raw_dialogue(client, chat_id.0, self.name, &state, Utc::now()).await
+            })
+        })
+    }
+
+    fn get_dialogue(self: Arc<Self>, chat_id: ChatId) -> BoxFuture<Result<Option<D>, Error>> {
+        Box::pin(async move {
+            let state = with_client!(self.pool, async |client| {
+                select_raw_dialogue(client, chat_id.0, self.name).await
+            })?;
+            let Some(state) = state else {
+                return Ok(None);
+            };
+            // Состояние, сохранённое предыдущей версией бота, может не совпадать с текущим
+            // форматом, а ключ, которым оно зашифровано, - быть удалён. Такой диалог начинается
+            // заново, иначе пользователь не сможет продолжить.
+            let dialogue = self.open(chat_id, state).and_then(|state| {
+                self.serializer
+                    .deserialize(&state)
+                    .map_err(|err| Error::Other(err.to_string().into()))
+            });
+            match dialogue {
+                Ok(dialogue) => Ok(Some(dialogue)),
+                Err(err) => {
+                    log::warn!(
+                        "Failed to deserialize {} dialogue of {}: {}",
+                        self.name,
+                        chat_id,
+                        err
+                    );
+                    Ok(None)
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod dialogue_storage_tests {
+    use super::*;
+    use crate::infra::postgres::repository::test_utils::test_cipher;
+    use crate::utils::postgres::testing::test_db_setup;
+    use serde::{Deserialize, Serialize};
+    use teloxide::dispatching::dialogue::serializer::Json;
+
+    #[derive(Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+    enum TestState {
+        #[default]
+        Start,
+        AwaitingName,
+        AwaitingPassport(String),
+    }
+
+    fn plaintext() -> Arc<FieldCipher> {
+        Arc::new(FieldCipher::plaintext())
+    }
+
+    #[tokio::test]
+    async fn test_incompatible_state_is_reset() {
+        let chat_id = Utc::now().timestamp_micros() + 1;
+        let pool = test_db_setup().await;
+
+        // GIVEN состояние, сохранённое в другом формате
+        let old = PostgresDialogueStorage::new(pool.clone(), plaintext(), "test_old", Json);
+        old.update_dialogue(ChatId(chat_id), "Removed".to_string())
+            .await
+            .unwrap();
+
+        // WHEN состояние читается текущей версией
+        let storage = PostgresDialogueStorage::new(pool, plaintext(), "test_old", Json);
+        let state: Option<TestState> = storage.get_dialogue(ChatId(chat_id)).await.unwrap();
+
+        // THEN диалог начинается заново
+        assert_eq!(state, None);
+    }
+
+    #[tokio::test]
+    async fn test_state_is_encrypted() {
+        let chat_id = ChatId(Utc::now().timestamp_micros() + 2);
+        let pool = test_db_setup().await;
+        let storage = PostgresDialogueStorage::new(pool.clone(), Arc::new(test_cipher()), "test_enc", Json);
+
+        // GIVEN студент на середине диалога ввёл ФИО
+        let state = TestState::AwaitingPassport("Ivanov Ivan".to_string());
+        storage.clone().update_dialogue(chat_id, state.clone()).await.unwrap();
+
+        // THEN в базе ФИО не видно
+        let client = pool.get().await.unwrap();
+        let raw = select_raw_dialogue(client.client(), chat_id.0, "test_enc").await.unwrap().unwrap();
+        let raw = String::from_utf8(raw).unwrap();
+        assert!(raw.starts_with("enc:v1:"));
+        assert!(!raw.contains("Ivanov"));
+        // AND состояние читается обратно
+        assert_eq!(storage.get_dialogue(chat_id).await.unwrap(), Some(state));
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_state_is_read_after_enabling_encryption() {
+        let chat_id = ChatId(Utc::now().timestamp_micros() + 3);
+        let pool = test_db_setup().await;
+
+        // GIVEN состояние сохранено до включения шифрования
+        let old = PostgresDialogueStorage::new(pool.clone(), plaintext(), "test_enc", Json);
+        old.update_dialogue(chat_id, TestState::AwaitingName).await.unwrap();
+
+        // WHEN бот перезапускается с ключами шифрования
+        let storage = PostgresDialogueStorage::new(pool, Arc::new(test_cipher()), "test_enc", Json);
+        let state: Option<TestState> = storage.get_dialogue(chat_id).await.unwrap();
+
+        // THEN диалог продолжается
+        assert_eq!(state, Some(TestState::AwaitingName));
+    }
+}
@@ -0,0 +1,80 @@
+use deadpool_postgres::PoolError;
+use tokio_postgres::Error as PgError;
+
+use crate::domain::Error;
+use crate::utils::postgres::helpers::{
+    is_connection_failure, is_foreign_key_violation, is_serialization_failure, is_unique_violation,
+};
+
+/// Переводит ошибку PostgreSQL в ошибку предметной области, чтобы сценарии могли отличить
+/// конфликт данных от временной недоступности БД.
+pub fn pg_error(err: PgError) -> Error {
+    if is_unique_violation(&err) || is_foreign_key_violation(&err) {
+        Error::Conflict(err.into())
+    } else if is_serialization_failure(&err) {
+        Error::SerializationFailure(err.into())
+    } else if is_connection_failure(&err) {
+        Error::Unavailable(err.into())
+    } else {
+        Error::Other(err.into())
+    }
+}
+
+pub fn pool_error(err: PoolError) -> Error {
+    match err {
+        PoolError::Backend(err) => pg_error(err),
+        PoolError::Timeout(_) | PoolError::Closed => Error::Unavailable(err.into()),
+        err => Error::Other(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod errors_tests {
+    use super::*;
+    use crate::utils::postgres::testing::test_db_setup;
+
+    async fn raise(pool: &deadpool_postgres::Pool, errcode: &str) -> Error {
+        let client = pool.get().await.unwrap();
+        let query = format!(
+            "DO $$ BEGIN RAISE EXCEPTION 'injected' USING ERRCODE = '{}'; END $$",
+            errcode
+        );
+        pg_error(client.batch_execute(&query).await.unwrap_err())
+    }
+
+    #[tokio::test]
+    async fn test_pg_errors_are_typed() {
+        let pool = test_db_setup().await;
+
+        // WHEN БД возвращает ошибки с разными кодами
+        // THEN они переводятся в соответствующие варианты
+        assert!(matches!(raise(&pool, "23505").await, Error::Conflict(_)));
+        assert!(matches!(raise(&pool, "23503").await, Error::Conflict(_)));
+        assert!(matches!(
+            raise(&pool, "40001").await,
+            Error::SerializationFailure(_)
+        ));
+        assert!(matches!(
+            raise(&pool, "40P01").await,
+            Error::SerializationFailure(_)
+        ));
+        assert!(matches!(raise(&pool, "57P01").await, Error::Unavailable(_)));
+        assert!(matches!(raise(&pool, "22012").await, Error::Other(_)));
+    }
+
+    #[tokio::test]
+    async fn test_dropped_connection_is_unavailable() {
+        // GIVEN соединение, которое сервер разрывает во время запроса
+        let pool = test_db_setup().await;
+        let client = pool.get().await.unwrap();
+
+        // WHEN выполняется запрос
+        let err = client
+            .batch_execute("SELECT pg_terminate_backend(pg_backend_pid())")
+            .await
+            .unwrap_err();
+
+        // THEN ошибка считается временной недоступностью БД
+        assert!(matches!(pg_error(err), Error::Unavailable(_)));
+    }
+}
@@ -0,0 +1,35 @@
+-- 40001: неактивен с 2019 года;
+-- 40002: анкета не менялась с 2019 года, но была запись в 2021 году;
+-- 40003: неактивен с 2019 года и уже обезличен;
+-- 40004: недавно изменил анкету;
+-- 40005: неактивен с 2019 года, но виза действует до 2100 года;
+-- 40006: неактивен с 2019 года, но регистрация действует до 2100 года.
+INSERT INTO users (id, username, full_name_lat, full_name_cyr, citizenship, arrival_date, anonymised_at, updated_at)
+VALUES
+    (40001, 'username40001', 'Ivanov', 'Иванов', 'Tajikistan', '2018-09-01', NULL, TIMESTAMPTZ '2019-01-01 00:00Z'),
+    (40002, 'username40002', 'Sidorov', 'Сидоров', 'Armenia', '2018-09-01', NULL, TIMESTAMPTZ '2019-01-01 00:00Z'),
+    (40003, '', '', '', '', '2018-09-01', TIMESTAMPTZ '2019-06-01 00:00Z', TIMESTAMPTZ '2019-01-01 00:00Z'),
+    (40004, 'username40004', 'Petrov', 'Петров', 'Ukraine', '2018-09-01', NULL, now()),
+    (40005, 'username40005', 'Orlov', 'Орлов', 'Belarus', '2018-09-01', NULL, TIMESTAMPTZ '2019-01-01 00:00Z'),
+    (40006, 'username40006', 'Sokolov', 'Соколов', 'Belarus', '2018-09-01', NULL, TIMESTAMPTZ '2019-01-01 00:00Z')
+ON CONFLICT (id) DO UPDATE SET
+    anonymised_at = EXCLUDED.anonymised_at,
+    updated_at    = EXCLUDED.updated_at;
+
+UPDATE users SET visa_expiry = '2100-01-01' WHERE id = 40005;
+UPDATE users SET registration_expiry = '2100-01-01' WHERE id = 40006;
+
+INSERT INTO reservations (slot_start, service, user_id)
+VALUES
+    (TIMESTAMPTZ '2019-02-01 09:00Z', 'initial_registration', 40001),
+    (TIMESTAMPTZ '2019-02-01 09:00Z', 'initial_registration', 40002),
+    (TIMESTAMPTZ '2021-03-01 09:00Z', 'renewal_of_visa', 40002)
+ON CONFLICT (slot_start, user_id) DO NOTHING;
+
+-- Отмена 40004, давно вернувшегося в университет.
+INSERT INTO cancelled_reservations (slot_start, service, user_id, cancelled_at)
+SELECT TIMESTAMPTZ '2019-03-01 09:00Z', 'visa', 40004, TIMESTAMPTZ '2019-02-20 12:00Z'
+WHERE NOT EXISTS (
+    SELECT 1 FROM cancelled_reservations
+    WHERE slot_start = TIMESTAMPTZ '2019-03-01 09:00Z' AND user_id = 40004
+);
@@ -1,7 +1,7 @@
 #[macro_export]
 macro_rules! with_client {
     ($pool:expr, $body:expr) => {{
-        let obj = $pool.get().await.map_err(|err| Error::Other(err.into()))?;
+        let obj = $pool.get().await.map_err($crate::infra::postgres::errors::pool_error)?;
         let client: &Client = obj.client();
         $body(client).await
     }};
@@ -10,13 +10,28 @@ macro_rules! with_client {
 #[macro_export]
 macro_rules! with_transaction {
     ($pool:expr, $body:expr) => {{
-        let mut obj = $pool.get().await.map_err(|err| Error::Other(err.into()))?;
+        let mut obj = $pool
+            .get()
+            .await
+            .map_err($crate::infra::postgres::errors::pool_error)?;
         let tx = obj
             .transaction()
             .await
-            .map_err(|err| Error::Other(err.into()))?;
+            .map_err($crate::infra::postgres::errors::pg_error)?;
         let res = $body(&tx).await?;
-        tx.commit().await.map_err(|err| Error::Other(err.into()))?;
+        tx.commit().await.map_err($crate::infra::postgres::errors::pg_error)?;
         Ok(res)
     }};
 }
+
+/// Выполняет транзакцию, повторяя её целиком при временных сбоях БД.
+#[macro_export]
+macro_rules! with_retrying_transaction {
+    ($pool:expr, $body:expr) => {{
+        $crate::infra::postgres::retry::retry_transient(
+            &$crate::infra::postgres::retry::DEFAULT_RETRY_POLICY,
+            || async move { $crate::with_transaction!($pool, $body) },
+        )
+        .await
+    }};
+}
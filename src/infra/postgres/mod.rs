@@ -1,5 +1,11 @@
+mod chat_activity;
 mod db;
+mod dialogue_storage;
+mod errors;
 mod macros;
 mod repository;
+mod retry;
 
+pub use chat_activity::PostgresChatActivity;
+pub use dialogue_storage::PostgresDialogueStorage;
 pub use repository::PostgresRepository;
@@ -1,29 +1,105 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc};
 use deadpool_postgres::Pool;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tokio_postgres::{Client, GenericClient, Transaction};
 
 use crate::domain::Error;
 use crate::domain::interfaces::{
-    AvailableSlotsProvider, HasAvailableSlotsProvider, ReservedSlotProvider, ReservedSlotsProvider,
-    SlotsRepository, UserProvider, UserRepository,
+    AttendanceRepository, AvailableSlotsProvider, DeadlineOverrideProvider, MirroredSlotsRepository,
+    ReservationStatsProvider, DeadlineOverrideRepository,
+    DocumentRepository, DocumentsProvider, ExpiredDocumentsProvider,
+    ExpiringUsersProvider, HasAvailableSlotsProvider, NotificationLog, ReservedSlotProvider,
+    ReservedSlotsProvider, SlotsRepository, StaffRepository, UnreservedUsersProvider,
+    UserProvider, UserSearchProvider, AdminProvider, AuditLog, AuditLogProvider, ConsentProvider, ConsentRepository, InactiveUsersProvider,
+    ReservationsRetention, UserRepository, UserReservationsProvider,
+};
+use crate::domain::models::{
+    AuditEntry, Citizenship, Consent, DayReservationStats, ReservationBreakdown, DeadlineOverride, Document, Service, Slot, StaffMember, StaffRole, User, UserID,
 };
-use crate::domain::models::{Slot, User, UserID};
 use crate::infra::postgres::db::{
-    batch_insert_raw_reservations, delete_reservations, get_raw_user, has_available_slots,
-    select_raw_reservations_with_user, select_slot_raw_reservations_with_user,
-    slot_to_raw_reservations, upsert_raw_user,
+    batch_upsert_raw_reservations, cancel_raw_reservations, delete_raw_document, get_raw_user,
+    has_available_slots, insert_notification, insert_raw_deadline_override, is_notified,
+    select_active_raw_deadline_override, select_expired_raw_documents,
+    select_pending_raw_deadline_overrides, select_raw_deadline_override,
+    select_raw_deadline_override_for_update,
+    select_raw_documents, select_orphaned_raw_documents, select_raw_reservations_with_user, select_raw_users_with_expiry,
+    select_slot_raw_reservations_with_user, select_raw_users_with_last_reservation,
+    select_raw_users_by_blind_index, select_raw_users_for_update, update_raw_user_personal_data,
+    select_user_raw_reservations, select_user_raw_cancellations, RawUser,
+    slot_to_raw_reservations, update_raw_deadline_override, upsert_raw_document,
+    upsert_raw_user, select_raw_staff, select_raw_staff_member, upsert_raw_staff_member,
+    delete_raw_staff_member, insert_audit_entry, select_raw_audit_entries_between,
+    select_user_raw_audit_entries, anonymise_raw_user, delete_raw_user, select_inactive_user_ids, select_last_raw_consent, upsert_raw_consent,
+    select_user_raw_documents, update_reservation_attended, select_daily_reservation_counts,
+    select_raw_reservation_breakdown, select_average_lead_time, count_identified_reservations,
+    anonymise_raw_reservations, select_slot_reservation_counts, select_mirrored_slots, upsert_mirrored_slot, delete_mirrored_slot,
 };
-use crate::{with_client, with_transaction};
+use crate::infra::FieldCipher;
+use crate::{with_client, with_retrying_transaction, with_transaction};
+
+/// Сколько пользователей перешифровывается в одной транзакции.
+const REENCRYPT_BATCH_SIZE: i64 = 100;
 
 pub struct PostgresRepository {
     pool: Pool,
+    cipher: Arc<FieldCipher>,
 }
 
 impl PostgresRepository {
     pub fn new(pool: Pool) -> PostgresRepository {
-        PostgresRepository { pool }
+        PostgresRepository {
+            pool,
+            cipher: Arc::new(FieldCipher::plaintext()),
+        }
+    }
+
+    /// Включает шифрование персональных данных пользователей.
+    pub fn with_cipher(self, cipher: Arc<FieldCipher>) -> PostgresRepository {
+        PostgresRepository { cipher, ..self }
+    }
+
+    /// Перешифровывает персональные данные всех пользователей текущим ключом и
+    /// пересчитывает blind index. Нужен после включения шифрования и после ротации
+    /// ключей. Возвращает число пользователей, строки которых изменены (или были бы
+    /// изменены при `dry_run`).
+    pub async fn reencrypt_users(&self, dry_run: bool) -> Result<usize, Error> {
+        let mut after_id = i64::MIN;
+        let mut changed = 0;
+        loop {
+            let (page_size, last_id, page_changed) = self.reencrypt_page(after_id, dry_run).await?;
+            changed += page_changed;
+            match last_id {
+                Some(id) if page_size as i64 == REENCRYPT_BATCH_SIZE => after_id = id,
+                _ => return Ok(changed),
+            }
+        }
+    }
+
+    /// Перешифровывает одну страницу пользователей после `after_id`. Возвращает размер
+    /// страницы, id последнего пользователя в ней и число изменённых строк.
+    async fn reencrypt_page(
+        &self,
+        after_id: i64,
+        dry_run: bool,
+    ) -> Result<(usize, Option<i64>, usize), Error> {
+        with_transaction!(self.pool, async |tx: &Transaction| {
+            let page = select_raw_users_for_update(tx, after_id, REENCRYPT_BATCH_SIZE).await?;
+            let page_size = page.len();
+            let last_id = page.last().map(|(raw, _)| raw.id());
+            let mut changed = 0;
+            for (raw, index) in page {
+                let Some((raw, index)) = raw.reseal(&index, &self.cipher)? else {
+                    continue;
+                };
+                if !dry_run {
+                    update_raw_user_personal_data(tx, &raw, &index).await?;
+                }
+                changed += 1;
+            }
+            Ok::<_, Error>((page_size, last_id, changed))
+        })
     }
 }
 
@@ -51,7 +127,7 @@ impl AvailableSlotsProvider for PostgresRepository {
             let rs = select_raw_reservations_with_user(client, &starts).await?;
 
             for r in rs {
-                let (start, service, user) = r.try_unpack()?;
+                let (start, service, user) = r.try_unpack(&self.cipher)?;
                 let slot = slots.get_mut(&start).unwrap();
                 slot.reserve(user, service)?;
             }
@@ -79,7 +155,7 @@ impl ReservedSlotsProvider for PostgresRepository {
             let rs = select_raw_reservations_with_user(client, &starts).await?;
 
             for r in rs {
-                let (start, service, user) = r.try_unpack()?;
+                let (start, service, user) = r.try_unpack(&self.cipher)?;
                 let slot = slots.get_mut(&start).unwrap();
                 slot.reserve(user, service)?;
             }
@@ -105,7 +181,7 @@ impl ReservedSlotProvider for PostgresRepository {
             )
             .await?;
             for r in raw {
-                let (_, service, user) = r.try_unpack()?;
+                let (_, service, user) = r.try_unpack(&self.cipher)?;
                 slot.reserve(user, service)?;
             }
             Ok(slot)
@@ -115,23 +191,146 @@ impl ReservedSlotProvider for PostgresRepository {
 
 #[async_trait]
 impl SlotsRepository for PostgresRepository {
-    async fn save_slot(&self, slot: &Slot) -> Result<(), Error> {
-        with_transaction!(self.pool, async |tx: &Transaction| {
-            delete_reservations(tx, slot.start()).await?;
-            let raw_reservations = slot_to_raw_reservations(&slot);
-            batch_insert_raw_reservations(tx, &raw_reservations).await?;
-            Ok::<_, Error>(())
+    async fn save_slot(&self, slot: &Slot, entry: &AuditEntry) -> Result<(), Error> {
+        with_retrying_transaction!(self.pool, async |tx: &Transaction| {
+            write_slot(tx, slot).await?;
+            insert_audit_entry(tx, entry).await
+        })
+    }
+
+    async fn save_slot_with_override(
+        &self,
+        slot: &Slot,
+        override_id: i64,
+        used_at: DateTime<Utc>,
+        entry: &AuditEntry,
+    ) -> Result<(), Error> {
+        with_retrying_transaction!(self.pool, async |tx: &Transaction| {
+            // Блокировка строки не даст использовать один запрос для двух записей.
+            let mut o = select_raw_deadline_override_for_update(tx, override_id)
+                .await?
+                .map(DeadlineOverride::from)
+                .ok_or(Error::OverrideNotFound(override_id))?;
+            o.consume(used_at)?;
+            update_raw_deadline_override(tx, (&o).into()).await?;
+            write_slot(tx, slot).await?;
+            insert_audit_entry(tx, entry).await
         })
     }
 }
 
+/// Записывает слот: отменяет записи, которых в нём больше нет, и сохраняет остальные.
+/// Документы, оставшиеся от отменённых записей студентов слота, переходят к этому слоту.
+async fn write_slot<C: GenericClient>(client: &C, slot: &Slot) -> Result<(), Error> {
+    let raw_reservations = slot_to_raw_reservations(slot);
+    let kept: Vec<_> = slot.reservations().iter().map(|r| r.by().id().as_i64()).collect();
+    cancel_raw_reservations(client, slot.start(), &kept, Utc::now()).await?;
+    batch_upsert_raw_reservations(client, &raw_reservations).await?;
+    for reservation in slot.reservations() {
+        move_orphaned_documents(client, reservation.by().id(), slot.start()).await?;
+    }
+    Ok(())
+}
+
+/// Прикрепляет к записи документы, оставшиеся у пользователя от отменённых записей. Из
+/// нескольких документов одного вида переносится последний загруженный, и только если к записи
+/// ещё не загружен документ этого вида.
+async fn move_orphaned_documents<C: GenericClient>(
+    client: &C,
+    user_id: UserID,
+    slot_start: DateTime<Utc>,
+) -> Result<(), Error> {
+    let orphaned = select_orphaned_raw_documents(client, user_id).await?;
+    if orphaned.is_empty() {
+        return Ok(());
+    }
+    let mut kinds: Vec<_> = select_raw_documents(client, user_id, slot_start)
+        .await?
+        .into_iter()
+        .map(|raw| Document::from(raw).kind())
+        .collect();
+    for document in orphaned.into_iter().map(Document::from) {
+        if kinds.contains(&document.kind()) {
+            continue;
+        }
+        kinds.push(document.kind());
+        delete_raw_document(client, (&document).into()).await?;
+        upsert_raw_document(client, (&document.moved_to(slot_start)).into()).await?;
+    }
+    Ok(())
+}
+
 #[async_trait]
 impl UserRepository for PostgresRepository {
-    async fn save_user(&self, user: User) -> Result<(), Error> {
+    async fn save_user(&self, user: User, entry: &AuditEntry) -> Result<(), Error> {
+        with_transaction!(self.pool, async |tx: &Transaction| {
+            let (raw_user, index) = RawUser::seal(&user, &self.cipher)?;
+            upsert_raw_user(tx, raw_user, index).await?;
+            insert_audit_entry(tx, entry).await
+        })
+    }
+
+    async fn anonymise_user(&self, id: UserID, at: DateTime<Utc>) -> Result<(), Error> {
+        with_transaction!(self.pool, async |tx: &Transaction| {
+            let raw_user = get_raw_user(tx, id).await?;
+            let arrival_date = raw_user.anonymised_arrival_date(&self.cipher)?;
+            anonymise_raw_user(tx, id, &arrival_date, at).await
+        })
+    }
+
+    async fn delete_user(&self, id: UserID) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            delete_raw_user(client, id).await
+        })
+    }
+}
+
+#[async_trait]
+impl ReservationsRetention for PostgresRepository {
+    async fn identified_reservations(&self, before: DateTime<Utc>) -> Result<usize, Error> {
+        with_client!(self.pool, async |client| {
+            let count = count_identified_reservations(client, before).await?;
+            Ok(count as usize)
+        })
+    }
+
+    async fn anonymise_reservations(&self, before: DateTime<Utc>) -> Result<usize, Error> {
+        with_transaction!(self.pool, async |tx: &Transaction| {
+            let count = anonymise_raw_reservations(tx, before).await?;
+            Ok::<_, Error>(count as usize)
+        })
+    }
+}
+
+#[async_trait]
+impl InactiveUsersProvider for PostgresRepository {
+    async fn inactive_users(
+        &self,
+        before: DateTime<Utc>,
+        today: NaiveDate,
+        anonymised: bool,
+    ) -> Result<Vec<UserID>, Error> {
+        with_client!(self.pool, async |client| {
+            select_inactive_user_ids(client, before, today, anonymised).await
+        })
+    }
+}
+
+#[async_trait]
+impl ConsentProvider for PostgresRepository {
+    async fn consent(&self, user_id: UserID) -> Result<Option<Consent>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_last_raw_consent(client, user_id).await?;
+            Ok(raw.map(Consent::from))
+        })
+    }
+}
+
+#[async_trait]
+impl ConsentRepository for PostgresRepository {
+    async fn save_consent(&self, consent: &Consent) -> Result<(), Error> {
         with_client!(self.pool, async |client| {
-            let raw_user = (&user).into();
-            upsert_raw_user(client, raw_user).await?;
-            Ok(())
+            upsert_raw_consent(client, consent).await
         })
     }
 }
@@ -141,17 +340,415 @@ impl UserProvider for PostgresRepository {
     async fn user(&self, id: UserID) -> Result<User, Error> {
         with_client!(self.pool, async |client| {
             let raw_user = get_raw_user(client, id).await?;
-            raw_user.try_into()
+            raw_user.open(&self.cipher)
+        })
+    }
+}
+
+#[async_trait]
+impl UserSearchProvider for PostgresRepository {
+    async fn find_users(&self, full_name: &str) -> Result<Vec<User>, Error> {
+        let Some(index) = self.cipher.blind_index(full_name) else {
+            return Ok(Vec::new());
+        };
+        with_client!(self.pool, async |client| {
+            let raw_users = select_raw_users_by_blind_index(client, &index).await?;
+            raw_users
+                .into_iter()
+                .map(|raw_user| raw_user.open(&self.cipher))
+                .collect::<Result<Vec<User>, Error>>()
+        })
+    }
+}
+
+#[async_trait]
+impl ExpiringUsersProvider for PostgresRepository {
+    async fn users_with_expiry(&self, until: NaiveDate) -> Result<Vec<User>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw_users = select_raw_users_with_expiry(client, until).await?;
+            raw_users
+                .into_iter()
+                .map(|raw_user| raw_user.open(&self.cipher))
+                .collect::<Result<Vec<User>, Error>>()
+        })
+    }
+}
+
+#[async_trait]
+impl UnreservedUsersProvider for PostgresRepository {
+    async fn unreserved_users(&self) -> Result<Vec<User>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw_users = select_raw_users_with_last_reservation(client).await?;
+            let mut users = Vec::new();
+            for (raw_user, last_reservation) in raw_users {
+                let user = raw_user.open(&self.cipher)?;
+                let arrival = user.arrival_date().and_time(NaiveTime::MIN).and_utc();
+                // Записи до въезда не в счёт: они относятся к прошлому приезду.
+                if last_reservation.is_none_or(|start| start < arrival) {
+                    users.push(user);
+                }
+            }
+            Ok(users)
+        })
+    }
+}
+
+#[async_trait]
+impl NotificationLog for PostgresRepository {
+    async fn is_notified(&self, user_id: UserID, key: &str) -> Result<bool, Error> {
+        with_client!(self.pool, async |client| {
+            is_notified(client, Some(user_id), key).await
+        })
+    }
+
+    async fn mark_notified(&self, user_id: UserID, key: &str) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            insert_notification(client, Some(user_id), key, Utc::now()).await
+        })
+    }
+
+    async fn is_sent(&self, key: &str) -> Result<bool, Error> {
+        with_client!(self.pool, async |client| {
+            is_notified(client, None, key).await
+        })
+    }
+
+    async fn mark_sent(&self, key: &str) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            insert_notification(client, None, key, Utc::now()).await
+        })
+    }
+}
+
+/// Границы периода с `from` по `to` включительно для сравнения со временем начала слота.
+fn period_bounds(from: NaiveDate, to: NaiveDate) -> (DateTime<Utc>, DateTime<Utc>) {
+    (
+        from.and_time(NaiveTime::MIN).and_utc(),
+        (to + Duration::days(1)).and_time(NaiveTime::MIN).and_utc(),
+    )
+}
+
+#[async_trait]
+impl AttendanceRepository for PostgresRepository {
+    async fn mark_attended(
+        &self,
+        slot_start: DateTime<Utc>,
+        user_id: UserID,
+        at: DateTime<Utc>,
+    ) -> Result<bool, Error> {
+        with_client!(self.pool, async |client| {
+            update_reservation_attended(client, slot_start, user_id, at).await
+        })
+    }
+}
+
+#[async_trait]
+impl ReservationStatsProvider for PostgresRepository {
+    async fn daily_reservation_stats(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<DayReservationStats>, Error> {
+        let (from, to) = period_bounds(from, to);
+        with_client!(self.pool, async |client| {
+            let rows = select_daily_reservation_counts(client, from, to).await?;
+            Ok(rows
+                .into_iter()
+                .map(|(date, reserved, attended, cancelled)| DayReservationStats {
+                    date,
+                    reserved: reserved as usize,
+                    attended: attended as usize,
+                    cancelled: cancelled as usize,
+                })
+                .collect())
+        })
+    }
+
+    async fn reservation_breakdown(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Vec<ReservationBreakdown>, Error> {
+        let (from, to) = period_bounds(from, to);
+        with_client!(self.pool, async |client| {
+            let rows = select_raw_reservation_breakdown(client, from, to).await?;
+            let mut breakdown: Vec<ReservationBreakdown> = Vec::new();
+            for (service, id, citizenship, reserved) in rows {
+                let citizenship = match (id, citizenship) {
+                    (Some(id), Some(citizenship)) => Citizenship::from(
+                        self.cipher.decrypt("citizenship", id, &citizenship)?.as_str(),
+                    ),
+                    // Обезличенная запись
+                    _ => Citizenship::from(""),
+                };
+                match breakdown
+                    .iter_mut()
+                    .find(|b| b.service == service && b.citizenship == citizenship)
+                {
+                    Some(b) => b.reserved += reserved as usize,
+                    None => breakdown.push(ReservationBreakdown {
+                        service,
+                        citizenship,
+                        reserved: reserved as usize,
+                    }),
+                }
+            }
+            Ok(breakdown)
+        })
+    }
+
+    async fn average_lead_time(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<Option<Duration>, Error> {
+        let (from, to) = period_bounds(from, to);
+        with_client!(self.pool, async |client| {
+            let seconds = select_average_lead_time(client, from, to).await?;
+            Ok(seconds.map(|seconds| Duration::seconds(seconds.round() as i64)))
+        })
+    }
+
+    async fn slot_reservation_counts(
+        &self,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> Result<HashMap<DateTime<Utc>, usize>, Error> {
+        let (from, to) = period_bounds(from, to);
+        with_client!(self.pool, async |client| {
+            let rows = select_slot_reservation_counts(client, from, to).await?;
+            Ok(rows
+                .into_iter()
+                .map(|(start, reserved)| (start, reserved as usize))
+                .collect())
+        })
+    }
+}
+
+#[async_trait]
+impl MirroredSlotsRepository for PostgresRepository {
+    async fn mirrored_slots(
+        &self,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<HashMap<DateTime<Utc>, String>, Error> {
+        with_client!(self.pool, async |client| {
+            let rows = select_mirrored_slots(client, from, to).await?;
+            Ok(rows.into_iter().collect())
+        })
+    }
+
+    async fn save_mirrored_slot(
+        &self,
+        start: DateTime<Utc>,
+        event_hash: &str,
+    ) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            upsert_mirrored_slot(client, start, event_hash, Utc::now()).await
+        })
+    }
+
+    async fn delete_mirrored_slot(&self, start: DateTime<Utc>) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            delete_mirrored_slot(client, start).await
+        })
+    }
+}
+
+#[async_trait]
+impl UserReservationsProvider for PostgresRepository {
+    async fn user_reservations(
+        &self,
+        id: UserID,
+        from: DateTime<Utc>,
+    ) -> Result<Vec<(DateTime<Utc>, Service)>, Error> {
+        with_client!(self.pool, async |client| {
+            select_user_raw_reservations(client, id, from).await
+        })
+    }
+
+    async fn user_cancellations(
+        &self,
+        id: UserID,
+    ) -> Result<Vec<(DateTime<Utc>, Service, DateTime<Utc>)>, Error> {
+        with_client!(self.pool, async |client| {
+            select_user_raw_cancellations(client, id).await
+        })
+    }
+}
+
+#[async_trait]
+impl DocumentRepository for PostgresRepository {
+    async fn save_document(&self, document: &Document) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            upsert_raw_document(client, document.into()).await
+        })
+    }
+
+    async fn delete_document(&self, document: &Document) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            delete_raw_document(client, document.into()).await
+        })
+    }
+}
+
+#[async_trait]
+impl DeadlineOverrideProvider for PostgresRepository {
+    async fn deadline_override(&self, id: i64) -> Result<DeadlineOverride, Error> {
+        with_client!(self.pool, async |client| {
+            select_raw_deadline_override(client, id)
+                .await?
+                .map(DeadlineOverride::from)
+                .ok_or(Error::OverrideNotFound(id))
+        })
+    }
+
+    async fn active_override(
+        &self,
+        user_id: UserID,
+        service: Service,
+    ) -> Result<Option<DeadlineOverride>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_active_raw_deadline_override(client, user_id, service).await?;
+            Ok(raw.map(DeadlineOverride::from))
+        })
+    }
+
+    async fn pending_overrides(&self) -> Result<Vec<DeadlineOverride>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_pending_raw_deadline_overrides(client).await?;
+            Ok(raw.into_iter().map(DeadlineOverride::from).collect())
+        })
+    }
+}
+
+#[async_trait]
+impl DeadlineOverrideRepository for PostgresRepository {
+    async fn create_override(
+        &self,
+        user_id: UserID,
+        service: Service,
+        requested_at: DateTime<Utc>,
+    ) -> Result<DeadlineOverride, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = insert_raw_deadline_override(client, user_id, service, requested_at).await?;
+            Ok(raw.into())
+        })
+    }
+
+    async fn save_override(&self, o: &DeadlineOverride) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            update_raw_deadline_override(client, o.into()).await
+        })
+    }
+}
+
+#[async_trait]
+impl AdminProvider for PostgresRepository {
+    async fn role(&self, id: UserID) -> Result<Option<StaffRole>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_raw_staff_member(client, id).await?;
+            Ok(raw.map(|raw| StaffMember::from(raw).role()))
+        })
+    }
+
+    async fn staff(&self) -> Result<Vec<StaffMember>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_raw_staff(client).await?;
+            Ok(raw.into_iter().map(StaffMember::from).collect())
+        })
+    }
+}
+
+#[async_trait]
+impl StaffRepository for PostgresRepository {
+    async fn save_staff_member(
+        &self,
+        member: &StaffMember,
+        granted_by: Option<UserID>,
+        granted_at: DateTime<Utc>,
+    ) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            upsert_raw_staff_member(client, member, granted_by, granted_at).await
+        })
+    }
+
+    async fn delete_staff_member(&self, id: UserID) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            delete_raw_staff_member(client, id).await
+        })
+    }
+}
+
+#[async_trait]
+impl AuditLog for PostgresRepository {
+    async fn append(&self, entry: &AuditEntry) -> Result<(), Error> {
+        with_client!(self.pool, async |client| {
+            insert_audit_entry(client, entry).await
+        })
+    }
+}
+
+#[async_trait]
+impl AuditLogProvider for PostgresRepository {
+    async fn user_audit_entries(&self, id: UserID, limit: usize) -> Result<Vec<AuditEntry>, Error> {
+        with_client!(self.pool, async |client| {
+            let limit = i64::try_from(limit).unwrap_or(i64::MAX);
+            let raw = select_user_raw_audit_entries(client, id, limit).await?;
+            raw.into_iter().map(AuditEntry::try_from).collect()
+        })
+    }
+
+    async fn audit_entries_on(&self, date: NaiveDate) -> Result<Vec<AuditEntry>, Error> {
+        let from = date.and_time(NaiveTime::MIN).and_utc();
+        let to = from + Duration::days(1);
+        with_client!(self.pool, async |client| {
+            let raw = select_raw_audit_entries_between(client, from, to).await?;
+            raw.into_iter().map(AuditEntry::try_from).collect()
+        })
+    }
+}
+
+#[async_trait]
+impl DocumentsProvider for PostgresRepository {
+    async fn documents(
+        &self,
+        user_id: UserID,
+        slot_start: DateTime<Utc>,
+    ) -> Result<Vec<Document>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_raw_documents(client, user_id, slot_start).await?;
+            Ok(raw.into_iter().map(Document::from).collect())
+        })
+    }
+
+    async fn user_documents(&self, user_id: UserID) -> Result<Vec<Document>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_user_raw_documents(client, user_id).await?;
+            Ok(raw.into_iter().map(Document::from).collect())
+        })
+    }
+}
+
+#[async_trait]
+impl ExpiredDocumentsProvider for PostgresRepository {
+    async fn expired_documents(&self, before: DateTime<Utc>) -> Result<Vec<Document>, Error> {
+        with_client!(self.pool, async |client| {
+            let raw = select_expired_raw_documents(client, before).await?;
+            Ok(raw.into_iter().map(Document::from).collect())
         })
     }
 }
 
 #[cfg(test)]
-mod test_utils {
-    use crate::domain::models::Slot;
+pub(super) mod test_utils {
+    use super::PostgresRepository;
+    use crate::domain::models::{AuditAction, AuditEntry, Slot, UserID};
     use crate::domain::services::SlotsFactory;
+    use crate::infra::FieldCipher;
     use chrono::{NaiveDate, NaiveTime};
     use deadpool_postgres::Pool;
+    use std::sync::Arc;
 
     pub async fn create_slot_hm(
         factory: &impl SlotsFactory,
@@ -165,6 +762,24 @@ mod test_utils {
         factory.create(start)
     }
 
+    /// Запись журнала аудита для тестов, которым важны только сохраняемые данные.
+    pub fn test_entry() -> AuditEntry {
+        AuditEntry::new(UserID::new(0), AuditAction::UserUpdated, None)
+    }
+
+    /// Все тесты работают с одной базой, поэтому шифруют данные одним и тем же ключом.
+    pub fn test_cipher() -> FieldCipher {
+        FieldCipher::new(
+            "test:MDEyMzQ1Njc4OWFiY2RlZjAxMjM0NTY3ODlhYmNkZWY=",
+            "aW5kZXgta2V5LWluZGV4LWtleS1pbmRleC1rZXktMTI=",
+        )
+        .unwrap()
+    }
+
+    pub fn test_repository(pool: Pool) -> PostgresRepository {
+        PostgresRepository::new(pool).with_cipher(Arc::new(test_cipher()))
+    }
+
     pub async fn setup_db(pool: &Pool) -> Result<(), tokio_postgres::Error> {
         let client = pool
             .get()
@@ -191,7 +806,7 @@ mod has_available_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![
@@ -212,7 +827,7 @@ mod has_available_slots_tests {
         let factory = FixedSlotsFactory::new(2, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![
@@ -241,7 +856,7 @@ mod available_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![
@@ -263,7 +878,7 @@ mod available_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![create_slot_hm(&factory, date, 9, 0).await];
@@ -280,7 +895,7 @@ mod available_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![
@@ -313,7 +928,7 @@ mod reserved_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![
@@ -333,7 +948,7 @@ mod reserved_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![create_slot_hm(&factory, date, 9, 20).await];
@@ -352,7 +967,7 @@ mod reserved_slots_tests {
         let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
 
         let date = NaiveDate::from_ymd_opt(2025, 7, 14).unwrap();
         let slots: Vec<Slot> = vec![
@@ -372,6 +987,7 @@ mod reserved_slots_tests {
 mod users_repository_tests {
     use chrono::NaiveDate;
     use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, Username};
+    use crate::domain::services::FixedSlotsFactory;
     use super::test_utils::*;
     use super::*;
     use crate::utils::postgres::testing::test_db_setup;
@@ -380,7 +996,7 @@ mod users_repository_tests {
     async fn test_save_several_users_with_username() {
         let pool = test_db_setup().await;
         setup_db(&pool).await.unwrap();
-        let repo = PostgresRepository { pool };
+        let repo = test_repository(pool);
         
         let user1 = User::new(
             UserID::new(1), 
@@ -400,9 +1016,906 @@ mod users_repository_tests {
             NaiveDate::from_ymd_opt(2025, 7, 12).unwrap()
         );
         
-        let res = repo.save_user(user1).await;
+        let res = repo.save_user(user1, &test_entry()).await;
         assert!(res.is_ok());
-        let res = repo.save_user(user2).await;
+        let res = repo.save_user(user2, &test_entry()).await;
         assert!(res.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_users_with_expiry() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let visa_expiry = NaiveDate::from_ymd_opt(2030, 3, 1).unwrap();
+        let mut user = User::new(
+            UserID::new(4),
+            Username::new(""),
+            OnlyLatin::new("Kuznetsov").unwrap(),
+            OnlyCyrillic::new("Кузнецов").unwrap(),
+            Citizenship::Belarus,
+            NaiveDate::from_ymd_opt(2025, 7, 12).unwrap()
+        );
+        user.set_visa_expiry(Some(visa_expiry));
+        repo.save_user(user, &test_entry()).await.unwrap();
+
+        let saved = repo.user(UserID::new(4)).await.unwrap();
+        assert_eq!(saved.visa_expiry(), Some(visa_expiry));
+        assert_eq!(saved.registration_expiry(), None);
+
+        let users = repo.users_with_expiry(visa_expiry).await.unwrap();
+        assert!(users.iter().any(|u| u.id() == UserID::new(4)));
+
+        let users = repo.users_with_expiry(visa_expiry.pred_opt().unwrap()).await.unwrap();
+        assert!(!users.iter().any(|u| u.id() == UserID::new(4)));
+    }
+
+    #[tokio::test]
+    async fn test_unreserved_users() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let user = User::new(
+            UserID::new(5),
+            Username::new(""),
+            OnlyLatin::new("Smirnov").unwrap(),
+            OnlyCyrillic::new("Смирнов").unwrap(),
+            Citizenship::Uzbekistan,
+            NaiveDate::from_ymd_opt(2025, 7, 12).unwrap()
+        );
+        repo.save_user(user, &test_entry()).await.unwrap();
+
+        // GIVEN студент с предстоящей записью, студент с прошедшей записью без отметки
+        // о приходе и студент, отменивший запись
+        let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
+        let id = Utc::now().timestamp_micros();
+        let student = |id: i64| {
+            User::new(
+                UserID::new(id),
+                Username::new(""),
+                OnlyLatin::new("Zapisov").unwrap(),
+                OnlyCyrillic::new("Записов").unwrap(),
+                Citizenship::Uzbekistan,
+                NaiveDate::from_ymd_opt(1999, 1, 1).unwrap(),
+            )
+        };
+        let (upcoming, past, cancelled) = (student(id), student(id + 1), student(id + 2));
+        repo.save_user(upcoming.clone(), &test_entry()).await.unwrap();
+        repo.save_user(past.clone(), &test_entry()).await.unwrap();
+        repo.save_user(cancelled.clone(), &test_entry()).await.unwrap();
+        let offset = Duration::days(id % 20000);
+        let mut slot = create_slot_hm(&factory, NaiveDate::from_ymd_opt(2100, 1, 1).unwrap() + offset, 10, 0).await;
+        slot.reserve(upcoming.clone(), Service::InitialRegistration).unwrap();
+        slot.reserve(cancelled.clone(), Service::InitialRegistration).unwrap();
+        repo.save_slot(&slot, &test_entry()).await.unwrap();
+        slot.cancel(cancelled.id()).unwrap();
+        repo.save_slot(&slot, &test_entry()).await.unwrap();
+        let mut slot = create_slot_hm(&factory, NaiveDate::from_ymd_opt(2000, 1, 1).unwrap() + offset, 10, 0).await;
+        slot.reserve(past.clone(), Service::InitialRegistration).unwrap();
+        repo.save_slot(&slot, &test_entry()).await.unwrap();
+
+        let users = repo.unreserved_users().await.unwrap();
+
+        // THEN пользователь 5 ни разу не записывался
+        assert!(users.iter().any(|u| u.id() == UserID::new(5)));
+        // AND студент, отменивший запись, не записан
+        assert!(users.iter().any(|u| u.id() == cancelled.id()));
+        // AND студенты с предстоящей и с прошедшей записью записаны, даже без отметки о приходе
+        assert!(!users.iter().any(|u| u.id() == upcoming.id()));
+        assert!(!users.iter().any(|u| u.id() == past.id()));
+        // AND пользователь 2 записан на 14.07.2025 после прибытия 01.07.2025
+        assert!(!users.iter().any(|u| u.id() == UserID::new(2)));
+    }
+
+    #[tokio::test]
+    async fn test_notification_log() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let key = format!("test:{}", Utc::now().timestamp_nanos_opt().unwrap());
+        assert!(!repo.is_notified(UserID::new(3), &key).await.unwrap());
+
+        repo.mark_notified(UserID::new(3), &key).await.unwrap();
+        repo.mark_notified(UserID::new(3), &key).await.unwrap();
+
+        assert!(repo.is_notified(UserID::new(3), &key).await.unwrap());
+        // Уведомление пользователю не считается общей рассылкой с тем же ключом
+        assert!(!repo.is_sent(&key).await.unwrap());
+
+        repo.mark_sent(&key).await.unwrap();
+        repo.mark_sent(&key).await.unwrap();
+
+        assert!(repo.is_sent(&key).await.unwrap());
+    }
+}
+
+#[cfg(test)]
+mod documents_tests {
+    use super::test_utils::*;
+    use super::*;
+    use crate::domain::models::{DocumentKind, OnlyCyrillic, OnlyLatin, Username};
+    use crate::domain::services::FixedSlotsFactory;
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::TimeZone;
+
+    fn passport(user_id: i64, slot_start: DateTime<Utc>) -> Document {
+        Document::new(
+            UserID::new(user_id),
+            slot_start,
+            DocumentKind::Passport,
+            "passport.pdf",
+            "application/pdf",
+            1024,
+            Utc::now(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_save_and_get_documents() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let slot_start = Utc.with_ymd_and_hms(2025, 7, 14, 9, 20, 0).unwrap();
+        let document = passport(2, slot_start);
+
+        let res = repo.save_document(&document).await;
+        assert!(res.is_ok(), "{}", res.err().unwrap());
+        let res = repo.save_document(&document).await;
+        assert!(res.is_ok(), "{}", res.err().unwrap());
+
+        let documents = repo.documents(UserID::new(2), slot_start).await.unwrap();
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].storage_key(), document.storage_key());
+    }
+
+    #[tokio::test]
+    async fn test_expired_documents() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let past = Utc.with_ymd_and_hms(2025, 7, 14, 9, 20, 0).unwrap();
+        let orphan = Utc.with_ymd_and_hms(2099, 1, 1, 10, 0, 0).unwrap();
+        repo.save_document(&passport(1, past)).await.unwrap();
+        repo.save_document(&passport(3, orphan)).await.unwrap();
+
+        let before = Utc.with_ymd_and_hms(2025, 7, 15, 0, 0, 0).unwrap();
+        let expired = repo.expired_documents(before).await.unwrap();
+
+        assert!(expired.iter().any(|d| d.user_id() == UserID::new(1)));
+        assert!(expired.iter().any(|d| d.slot_start() == orphan));
+    }
+
+    #[tokio::test]
+    async fn test_documents_follow_rescheduled_reservation() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+        let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
+        let id = Utc::now().timestamp_micros();
+        // Отдельный день в будущем, чтобы не пересекаться с другими тестами
+        let date = NaiveDate::from_ymd_opt(2100, 1, 1).unwrap() + Duration::days(id % 20000);
+        let user = User::new(
+            UserID::new(id),
+            Username::new(""),
+            OnlyLatin::new("Perenosov Petr").unwrap(),
+            OnlyCyrillic::new("Переносов Пётр").unwrap(),
+            Citizenship::Kazakhstan,
+            NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+        );
+        repo.save_user(user.clone(), &test_entry()).await.unwrap();
+
+        // GIVEN студент записался и загрузил паспорт
+        let mut old_slot = create_slot_hm(&factory, date, 10, 0).await;
+        old_slot.reserve(user.clone(), Service::InitialRegistration).unwrap();
+        repo.save_slot(&old_slot, &test_entry()).await.unwrap();
+        let document = passport(id, old_slot.start());
+        repo.save_document(&document).await.unwrap();
+
+        // WHEN студент отменяет запись и записывается на другое время
+        old_slot.cancel(user.id()).unwrap();
+        repo.save_slot(&old_slot, &test_entry()).await.unwrap();
+        let before = Utc::now() - Duration::days(1);
+        assert!(
+            !repo
+                .expired_documents(before)
+                .await
+                .unwrap()
+                .iter()
+                .any(|d| d.user_id() == user.id()),
+            "документы недавно отменённой записи не удаляются"
+        );
+        let mut new_slot = create_slot_hm(&factory, date, 11, 0).await;
+        new_slot.reserve(user.clone(), Service::InitialRegistration).unwrap();
+        repo.save_slot(&new_slot, &test_entry()).await.unwrap();
+
+        // THEN паспорт прикреплён к новой записи и лежит в хранилище под прежним ключом
+        let moved = repo.documents(user.id(), new_slot.start()).await.unwrap();
+        assert_eq!(moved.len(), 1);
+        assert_eq!(moved[0].storage_key(), document.storage_key());
+        assert!(repo.documents(user.id(), old_slot.start()).await.unwrap().is_empty());
+        // AND не удаляется вместе с документами отменённых записей
+        let expired = repo.expired_documents(before).await.unwrap();
+        assert!(!expired.iter().any(|d| d.user_id() == user.id()));
+    }
+
+    #[tokio::test]
+    async fn test_user_reservations() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let from = Utc.with_ymd_and_hms(2025, 7, 14, 9, 10, 0).unwrap();
+        let reservations = repo.user_reservations(UserID::new(2), from).await.unwrap();
+
+        assert!(!reservations.is_empty());
+        assert!(reservations.iter().all(|(start, _)| *start >= from));
+        assert!(reservations.is_sorted_by_key(|(start, _)| *start));
+    }
+
+    #[tokio::test]
+    async fn test_user_cancellations() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let client = pool.get().await.unwrap();
+        let repo = test_repository(pool.clone());
+
+        // GIVEN пользователь отменил запись
+        let slot_start = DateTime::from_timestamp_micros(Utc::now().timestamp_micros()).unwrap();
+        let cancelled_at = slot_start - Duration::days(1);
+        client
+            .execute(
+                "INSERT INTO cancelled_reservations (slot_start, service, user_id, cancelled_at) \
+                VALUES ($1, 'renewal_of_visa', 2, $2)",
+                &[&slot_start, &cancelled_at],
+            )
+            .await
+            .unwrap();
+
+        // WHEN запрашиваются отмены пользователя
+        let cancellations = repo.user_cancellations(UserID::new(2)).await.unwrap();
+
+        // THEN отмена в них есть
+        assert!(cancellations.contains(&(slot_start, Service::RenewalOfVisa, cancelled_at)));
+    }
+}
+
+#[cfg(test)]
+mod deadline_overrides_tests {
+    use super::test_utils::*;
+    use super::*;
+    use crate::domain::models::OverrideStatus;
+    use crate::domain::services::FixedSlotsFactory;
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::SubsecRound;
+
+    #[tokio::test]
+    async fn test_override_lifecycle() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+
+        let user_id = UserID::new(3);
+        let service = Service::RenewalOfVisa;
+        // PostgreSQL хранит время с точностью до микросекунд
+        let now = Utc::now().trunc_subsecs(0);
+
+        let mut o = repo
+            .create_override(user_id, service, now)
+            .await
+            .unwrap();
+        assert_eq!(o.status(), OverrideStatus::Pending);
+
+        let pending = repo.pending_overrides().await.unwrap();
+        assert!(pending.iter().any(|p| p.id() == o.id()));
+
+        o.approve(UserID::new(100), now).unwrap();
+        repo.save_override(&o).await.unwrap();
+        let active = repo.active_override(user_id, service).await.unwrap();
+        assert_eq!(active, Some(o.clone()));
+
+        o.consume(now).unwrap();
+        repo.save_override(&o).await.unwrap();
+        let active = repo.active_override(user_id, service).await.unwrap();
+        assert_eq!(active, None);
+
+        let stored = repo.deadline_override(o.id()).await.unwrap();
+        assert_eq!(stored.status(), OverrideStatus::Used);
+        assert_eq!(stored.resolved_by(), Some(UserID::new(100)));
+    }
+
+    #[tokio::test]
+    async fn test_override_is_used_once() {
+        let pool = test_db_setup().await;
+        setup_db(&pool).await.unwrap();
+        let repo = test_repository(pool);
+        let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
+
+        // GIVEN одобренный запрос на запись после срока и два слота в свободный день
+        let id = Utc::now().timestamp_micros();
+        let date = NaiveDate::from_ymd_opt(2100, 1, 1).unwrap() + Duration::days(id % 20000);
+        let user_id = UserID::new(3);
+        let now = Utc::now().trunc_subsecs(0);
+        let mut o = repo
+            .create_override(user_id, Service::RenewalOfVisa, now)
+            .await
+            .unwrap();
+        o.approve(UserID::new(100), now).unwrap();
+        repo.save_override(&o).await.unwrap();
+
+        let user = repo.user(user_id).await.unwrap();
+        let mut first = create_slot_hm(&factory, date, 10, 0).await;
+        first.reserve(user.clone(), Service::RenewalOfVisa).unwrap();
+        let mut second = create_slot_hm(&factory, date, 10, 20).await;
+        second.reserve(user, Service::RenewalOfVisa).unwrap();
+
+        // WHEN по запросу записываются дважды
+        repo.save_slot_with_override(&first, o.id(), now, &test_entry())
+            .await
+            .unwrap();
+        let res = repo
+            .save_slot_with_override(&second, o.id(), now, &test_entry())
+            .await;
+
+        // THEN вторая запись отклонена и не сохранена
+        assert!(matches!(res, Err(Error::OverrideNotApproved(id)) if id == o.id()));
+        let second = repo
+            .reserved_slot(create_slot_hm(&factory, date, 10, 20).await)
+            .await
+            .unwrap();
+        assert!(second.reservations().is_empty());
+        // AND первая сохранена, а запрос погашен
+        let first = repo
+            .reserved_slot(create_slot_hm(&factory, date, 10, 0).await)
+            .await
+            .unwrap();
+        assert_eq!(first.reservations().len(), 1);
+        let stored = repo.deadline_override(o.id()).await.unwrap();
+        assert_eq!(stored.status(), OverrideStatus::Used);
+    }
+}
+
+#[cfg(test)]
+mod staff_tests {
+    use super::test_utils::test_repository;
+    use super::*;
+    use crate::utils::postgres::testing::test_db_setup;
+
+    #[tokio::test]
+    async fn test_grant_and_revoke_role() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool);
+        let id = UserID::new(Utc::now().timestamp_micros());
+        let owner = UserID::new(1);
+
+        // GIVEN сотруднику назначена роль наблюдателя
+        let viewer = StaffMember::new(id, StaffRole::Viewer);
+        repo.save_staff_member(&viewer, Some(owner), Utc::now()).await.unwrap();
+        assert_eq!(repo.role(id).await.unwrap(), Some(StaffRole::Viewer));
+
+        // WHEN роль повышается до оператора
+        let operator = StaffMember::new(id, StaffRole::Operator);
+        repo.save_staff_member(&operator, Some(owner), Utc::now()).await.unwrap();
+
+        // THEN сохраняется только новая роль
+        assert_eq!(repo.role(id).await.unwrap(), Some(StaffRole::Operator));
+        let staff = repo.staff().await.unwrap();
+        assert_eq!(staff.iter().filter(|m| m.id() == id).count(), 1);
+
+        // AND после снятия роли пользователь перестаёт быть сотрудником
+        repo.delete_staff_member(id).await.unwrap();
+        assert_eq!(repo.role(id).await.unwrap(), None);
+    }
+}
+
+#[cfg(test)]
+mod mirrored_slots_tests {
+    use super::test_utils::test_repository;
+    use super::*;
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::{SubsecRound, TimeZone};
+
+    #[tokio::test]
+    async fn test_mirrored_slots() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool);
+        // Слот в далёком будущем, чтобы не пересекаться с другими тестами
+        let start = Utc.with_ymd_and_hms(2090, 1, 1, 10, 0, 0).unwrap()
+            + Duration::seconds(Utc::now().trunc_subsecs(0).timestamp() % 86400);
+        let (from, to) = (start, start + Duration::minutes(20));
+
+        // GIVEN слот отражён в календаре
+        repo.save_mirrored_slot(start, "first").await.unwrap();
+
+        // WHEN событие слота изменилось и отражено повторно
+        repo.save_mirrored_slot(start, "second").await.unwrap();
+
+        // THEN хранится отпечаток последней версии события
+        let mirrored = repo.mirrored_slots(from, to).await.unwrap();
+        assert_eq!(mirrored, HashMap::from([(start, "second".to_string())]));
+
+        // AND после удаления события слот забывается
+        repo.delete_mirrored_slot(start).await.unwrap();
+        assert!(repo.mirrored_slots(from, to).await.unwrap().is_empty());
+    }
+}
+
+#[cfg(test)]
+mod reservation_stats_tests {
+    use super::test_utils::*;
+    use super::*;
+    use crate::domain::models::{OnlyCyrillic, OnlyLatin, Username};
+    use crate::domain::services::FixedSlotsFactory;
+    use crate::utils::postgres::testing::test_db_setup;
+
+    #[tokio::test]
+    async fn test_reservation_stats() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool);
+        let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
+        let id = Utc::now().timestamp_micros();
+        // Отдельный день в будущем, чтобы не пересекаться с другими тестами
+        let date = NaiveDate::from_ymd_opt(2100, 1, 1).unwrap() + Duration::days(id % 20000);
+        let user = |id: i64, citizenship| {
+            User::new(
+                UserID::new(id),
+                Username::new(""),
+                OnlyLatin::new("Statov Stas").unwrap(),
+                OnlyCyrillic::new("Статов Стас").unwrap(),
+                citizenship,
+                NaiveDate::from_ymd_opt(2025, 7, 1).unwrap(),
+            )
+        };
+        let (first, second) = (user(id, Citizenship::Kazakhstan), user(id + 1, Citizenship::Belarus));
+        repo.save_user(first.clone(), &test_entry()).await.unwrap();
+        repo.save_user(second.clone(), &test_entry()).await.unwrap();
+
+        // GIVEN в слот записались двое, один отменил запись, другой пришёл
+        let mut slot = create_slot_hm(&factory, date, 10, 0).await;
+        slot.reserve(first.clone(), Service::Visa).unwrap();
+        slot.reserve(second.clone(), Service::All).unwrap();
+        repo.save_slot(&slot, &test_entry()).await.unwrap();
+        slot.cancel(second.id()).unwrap();
+        repo.save_slot(&slot, &test_entry()).await.unwrap();
+        assert!(repo.mark_attended(slot.start(), first.id(), Utc::now()).await.unwrap());
+        assert!(!repo.mark_attended(slot.start(), second.id(), Utc::now()).await.unwrap());
+
+        // WHEN запрашивается статистика за день
+        let days = repo.daily_reservation_stats(date, date).await.unwrap();
+        let breakdown = repo.reservation_breakdown(date, date).await.unwrap();
+        let lead_time = repo.average_lead_time(date, date).await.unwrap();
+        let slot_counts = repo.slot_reservation_counts(date, date).await.unwrap();
+
+        // THEN учтены запись, приход и отмена
+        assert_eq!(
+            days,
+            vec![DayReservationStats {
+                date,
+                reserved: 1,
+                attended: 1,
+                cancelled: 1,
+            }]
+        );
+        // AND разбивка по расшифрованному гражданству включает только неотменённую запись
+        assert_eq!(
+            breakdown,
+            vec![ReservationBreakdown {
+                service: Service::Visa,
+                citizenship: Citizenship::Kazakhstan,
+                reserved: 1,
+            }]
+        );
+        // AND время от записи до приёма известно
+        assert!(lead_time.unwrap() > Duration::days(365));
+        // AND в слоте одна запись
+        assert_eq!(slot_counts, HashMap::from([(slot.start(), 1)]));
+    }
+}
+
+#[cfg(test)]
+mod audit_log_tests {
+    use super::test_utils::test_repository;
+    use super::*;
+    use crate::domain::models::{AuditAction, AuditChange};
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::SubsecRound;
+
+    #[tokio::test]
+    async fn test_audit_log_is_append_only() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool.clone());
+        let admin = UserID::new(Utc::now().timestamp_micros());
+        let student = UserID::new(admin.as_i64() + 1);
+
+        // GIVEN сотрудник изменил данные студента
+        let entry = AuditEntry::restore(
+            Utc::now().trunc_subsecs(0),
+            admin,
+            AuditAction::UserUpdated,
+            Some(student),
+            vec![AuditChange::new("full_name_lat", None, None)],
+        );
+        repo.append(&entry).await.unwrap();
+
+        // THEN запись находится и по сотруднику, и по студенту, и по дате
+        assert_eq!(repo.user_audit_entries(admin, 10).await.unwrap(), vec![entry.clone()]);
+        assert_eq!(repo.user_audit_entries(student, 10).await.unwrap(), vec![entry.clone()]);
+        let on_date = repo.audit_entries_on(entry.at().date_naive()).await.unwrap();
+        assert!(on_date.contains(&entry));
+
+        // AND запись нельзя удалить
+        let client = pool.get().await.unwrap();
+        let res = client
+            .execute("DELETE FROM audit_log WHERE actor = $1", &[&admin.as_i64()])
+            .await;
+        assert!(res.is_err());
+        assert_eq!(repo.user_audit_entries(admin, 10).await.unwrap().len(), 1);
+    }
+}
+
+#[cfg(test)]
+mod personal_data_tests {
+    use super::test_utils::{test_cipher, test_entry, test_repository};
+    use super::*;
+    use crate::domain::models::{
+        AuditAction, AuditChange, Citizenship, OnlyCyrillic, OnlyLatin, Passport, Username,
+        user_changes,
+    };
+    use crate::domain::services::{FixedSlotsFactory, SlotsFactory};
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::SubsecRound;
+
+    #[tokio::test]
+    async fn test_revoked_consent_and_anonymised_user() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool.clone());
+        let id = UserID::new(Utc::now().timestamp_micros());
+        let user = User::new(
+            id,
+            Username::new("ivan"),
+            OnlyLatin::new("Ivanov Ivan").unwrap(),
+            OnlyCyrillic::new("Иванов Иван").unwrap(),
+            Citizenship::Kazakhstan,
+            NaiveDate::from_ymd_opt(2025, 7, 14).unwrap(),
+        );
+        repo.save_user(user.clone(), &test_entry()).await.unwrap();
+
+        // GIVEN пользователь дал согласие на первую и вторую редакции
+        let now = Utc::now().trunc_subsecs(0);
+        repo.save_consent(&Consent::new(id, 1, now - Duration::days(30))).await.unwrap();
+        let mut consent = Consent::new(id, 2, now);
+        repo.save_consent(&consent).await.unwrap();
+        assert_eq!(repo.consent(id).await.unwrap(), Some(consent.clone()));
+
+        // WHEN пользователь отзывает согласие
+        consent.revoke(now);
+        repo.save_consent(&consent).await.unwrap();
+        repo.anonymise_user(id, now).await.unwrap();
+
+        // THEN отзыв сохранён
+        assert_eq!(repo.consent(id).await.unwrap(), Some(consent));
+        // AND пользователь больше не считается зарегистрированным
+        assert!(matches!(repo.user(id).await, Err(Error::UserNotFound(_))));
+        // AND от даты въезда остался только месяц
+        let client = pool.get().await.unwrap();
+        let row = client
+            .query_one("SELECT arrival_date FROM users WHERE id = $1", &[&id.as_i64()])
+            .await
+            .unwrap();
+        let arrival_date = test_cipher()
+            .decrypt("arrival_date", id.as_i64(), row.get(0))
+            .unwrap();
+        assert_eq!(arrival_date, "2025-07-01");
+
+        // WHEN пользователь регистрируется заново
+        repo.save_user(user.clone(), &test_entry()).await.unwrap();
+
+        // THEN его данные снова доступны
+        assert_eq!(repo.user(id).await.unwrap(), user);
+    }
+
+    #[tokio::test]
+    async fn test_delete_user_removes_reservations() {
+        let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool);
+        let id = UserID::new(Utc::now().timestamp_micros());
+        let user = User::new(
+            id,
+            Username::new("ivan"),
+            OnlyLatin::new("Ivanov Ivan").unwrap(),
+            OnlyCyrillic::new("Иванов Иван").unwrap(),
+            Citizenship::Kazakhstan,
+            NaiveDate::from_ymd_opt(2025, 7, 14).unwrap(),
+        );
+        repo.save_user(user.clone(), &test_entry()).await.unwrap();
+
+        // GIVEN пользователь записан на приём
+        let mut slot = factory.create(Utc::now().trunc_subsecs(0) + Duration::days(1));
+        slot.reserve(user, Service::Visa).unwrap();
+        repo.save_slot(&slot, &test_entry()).await.unwrap();
+        assert_eq!(repo.user_reservations(id, slot.start()).await.unwrap().len(), 1);
+
+        // WHEN пользователь удаляет свои данные
+        repo.delete_user(id).await.unwrap();
+
+        // THEN не остаётся ни анкеты, ни записей
+        assert!(matches!(repo.user(id).await, Err(Error::UserNotFound(_))));
+        assert!(repo.user_reservations(id, slot.start()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_no_personal_data_in_audit_after_deletion() {
+        let factory = FixedSlotsFactory::new(3, Duration::minutes(20));
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool);
+        let id = UserID::new(Utc::now().timestamp_micros());
+        let user = User::new(
+            id,
+            Username::new("ivan"),
+            OnlyLatin::new("Ivanov Ivan").unwrap(),
+            OnlyCyrillic::new("Иванов Иван").unwrap(),
+            Citizenship::Kazakhstan,
+            NaiveDate::from_ymd_opt(2025, 7, 14).unwrap(),
+        );
+
+        // GIVEN пользователь зарегистрировался, указал паспорт и записался на приём
+        let entry = AuditEntry::new(id, AuditAction::UserRegistered, Some(id))
+            .with_changes(user_changes(None, Some(&user)));
+        repo.save_user(user.clone(), &entry).await.unwrap();
+        let mut updated = user.clone();
+        updated.set_passport(Some(Passport::new("AB", "1234567").unwrap()));
+        let entry = AuditEntry::new(id, AuditAction::UserUpdated, Some(id))
+            .with_changes(user_changes(Some(&user), Some(&updated)));
+        repo.save_user(updated.clone(), &entry).await.unwrap();
+        let mut slot = factory.create(Utc::now().trunc_subsecs(0) + Duration::days(1));
+        slot.reserve(updated, Service::Visa).unwrap();
+        let entry = AuditEntry::new(id, AuditAction::ReservationCreated, Some(id))
+            .with_change(AuditChange::added("slot_start", slot.start().to_rfc3339()));
+        repo.save_slot(&slot, &entry).await.unwrap();
+
+        // WHEN пользователь удаляет свои данные
+        repo.delete_user(id).await.unwrap();
+
+        // THEN журнал аудита сохранился
+        let entries = repo.user_audit_entries(id, 10).await.unwrap();
+        assert_eq!(entries.len(), 3);
+        // AND в нём нет персональных данных, только названия изменённых полей
+        let values: Vec<_> = entries
+            .iter()
+            .flat_map(|e| e.changes())
+            .flat_map(|c| [c.before.clone(), c.after.clone()])
+            .flatten()
+            .collect();
+        for personal in ["ivan", "Ivanov", "Иванов", "1234567", "2025-07-14"] {
+            assert!(values.iter().all(|v| !v.contains(personal)), "{personal} in audit log");
+        }
+        let fields: Vec<_> = entries[1].changes().iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(fields, vec!["passport"]);
+    }
+}
+
+#[cfg(test)]
+mod inactive_users_tests {
+    use super::test_utils::test_repository;
+    use super::*;
+    use crate::utils::postgres::testing::test_db_setup;
+    use chrono::TimeZone;
+
+    async fn inactive_fixture_users(
+        repo: &PostgresRepository,
+        before: DateTime<Utc>,
+        anonymised: bool,
+    ) -> Vec<i64> {
+        let today = NaiveDate::from_ymd_opt(2025, 7, 1).unwrap();
+        repo.inactive_users(before, today, anonymised)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|id| id.as_i64())
+            .filter(|id| (40001..=40006).contains(id))
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_inactive_users() {
+        let pool = test_db_setup().await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(include_str!("./fixtures/retention_users.sql"))
+            .await
+            .unwrap();
+        let repo = test_repository(pool.clone());
+
+        // GIVEN пользователи, последний раз активные в разное время
+        let y2020 = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let y2022 = Utc.with_ymd_and_hms(2022, 1, 1, 0, 0, 0).unwrap();
+
+        // THEN неактивным с 2020 года считается только пользователь без поздних записей
+        assert_eq!(inactive_fixture_users(&repo, y2020, false).await, vec![40001]);
+        // AND обезличенные пользователи возвращаются, только если их запросили
+        assert_eq!(inactive_fixture_users(&repo, y2020, true).await, vec![40001, 40003]);
+        // AND с 2022 года неактивен и пользователь, записывавшийся в 2021 году
+        assert_eq!(inactive_fixture_users(&repo, y2022, false).await, vec![40001, 40002]);
+
+    }
+
+    #[tokio::test]
+    async fn test_users_with_valid_documents_are_active() {
+        let pool = test_db_setup().await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(include_str!("./fixtures/retention_users.sql"))
+            .await
+            .unwrap();
+        let repo = test_repository(pool.clone());
+
+        // GIVEN пользователи, неактивные с 2019 года, с визой или регистрацией до 2100 года
+        let y2020 = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        let inactive = inactive_fixture_users(&repo, y2020, false).await;
+
+        // THEN они не считаются неактивными: им ещё нужны напоминания о продлении
+        assert!(!inactive.contains(&40005));
+        assert!(!inactive.contains(&40006));
+
+        // WHEN документы истекли
+        let after_expiry = NaiveDate::from_ymd_opt(2100, 1, 2).unwrap();
+        let inactive = repo.inactive_users(y2020, after_expiry, false).await.unwrap();
+
+        // THEN пользователи становятся неактивными
+        assert!(inactive.contains(&UserID::new(40005)));
+        assert!(inactive.contains(&UserID::new(40006)));
+    }
+
+    #[tokio::test]
+    async fn test_anonymise_old_reservations() {
+        let pool = test_db_setup().await;
+        let client = pool.get().await.unwrap();
+        client
+            .batch_execute(include_str!("./fixtures/retention_users.sql"))
+            .await
+            .unwrap();
+        let repo = test_repository(pool.clone());
+        let linked = async |table: &str, ids: &[i64]| -> i64 {
+            let query = format!(
+                "SELECT COUNT(*) FROM {} WHERE user_id = ANY($1) AND slot_start < '2020-01-01Z'",
+                table
+            );
+            client.query_one(&query, &[&ids]).await.unwrap().get(0)
+        };
+
+        // GIVEN записи 2019 года и отмена 2019 года, связанные с пользователями
+        let y2020 = Utc.with_ymd_and_hms(2020, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(linked("reservations", &[40001, 40002]).await, 2);
+        assert_eq!(linked("cancelled_reservations", &[40004]).await, 1);
+        assert!(repo.identified_reservations(y2020).await.unwrap() >= 3);
+
+        // WHEN записи до 2020 года обезличиваются
+        let count = repo.anonymise_reservations(y2020).await.unwrap();
+
+        // THEN ни записи, ни отмена больше не связаны с пользователями
+        assert!(count >= 3);
+        assert_eq!(linked("reservations", &[40001, 40002]).await, 0);
+        assert_eq!(linked("cancelled_reservations", &[40004]).await, 0);
+        // AND поздняя запись осталась за пользователем
+        let later = repo
+            .user_reservations(UserID::new(40002), y2020)
+            .await
+            .unwrap();
+        assert_eq!(later.len(), 1);
+        // AND в статистике обезличенные записи учитываются
+        let day = NaiveDate::from_ymd_opt(2019, 2, 1).unwrap();
+        let stats = repo.daily_reservation_stats(day, day).await.unwrap();
+        assert!(stats[0].reserved >= 2);
+        let breakdown = repo.reservation_breakdown(day, day).await.unwrap();
+        assert!(breakdown.iter().any(|b| b.citizenship == Citizenship::from("")));
+    }
+}
+
+#[cfg(test)]
+mod encryption_tests {
+    use super::test_utils::{test_entry, test_repository};
+    use super::*;
+    use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, Passport, Sex, Username};
+    use crate::utils::postgres::testing::test_db_setup;
+
+    #[tokio::test]
+    async fn test_personal_data_is_encrypted() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool.clone());
+        let id = UserID::new(Utc::now().timestamp_micros());
+        let mut user = User::new(
+            id,
+            Username::new("kirill"),
+            OnlyLatin::new("Shifrovalnikov Kirill").unwrap(),
+            OnlyCyrillic::new("Шифровальников Кирилл").unwrap(),
+            Citizenship::Kazakhstan,
+            NaiveDate::from_ymd_opt(2025, 7, 14).unwrap(),
+        );
+        user.set_birth_date(NaiveDate::from_ymd_opt(2005, 3, 1));
+        user.set_sex(Some(Sex::Male));
+        user.set_passport(Some(Passport::parse("N 1234567").unwrap()));
+        user.set_place_of_stay(Some("г. Москва, ул. Бауманская, д. 1".to_string()));
+
+        // WHEN пользователь сохраняется
+        repo.save_user(user.clone(), &test_entry()).await.unwrap();
+
+        // THEN персональные данные в таблице зашифрованы
+        let client = pool.get().await.unwrap();
+        let row = client
+            .query_one(
+                "SELECT full_name_lat, full_name_cyr, citizenship, arrival_date, \
+                birth_date, sex, passport, place_of_stay FROM users WHERE id = $1",
+                &[&id.as_i64()],
+            )
+            .await
+            .unwrap();
+        for i in 0..8 {
+            let value: String = row.get(i);
+            assert!(value.starts_with("enc:v1:test:"), "{}", value);
+        }
+
+        // AND читаются в исходном виде
+        assert_eq!(repo.user(id).await.unwrap(), user);
+
+        // AND пользователя можно найти по ФИО без учёта регистра и лишних пробелов
+        let found = repo.find_users("  шифровальников   КИРИЛЛ").await.unwrap();
+        assert!(found.iter().any(|u| u.id() == id));
+        let found = repo.find_users("shifrovalnikov kirill").await.unwrap();
+        assert!(found.iter().any(|u| u.id() == id));
+        let found = repo.find_users("Shifrovalnikov").await.unwrap();
+        assert!(!found.iter().any(|u| u.id() == id));
+    }
+
+    #[tokio::test]
+    async fn test_reencrypt_plaintext_users() {
+        let pool = test_db_setup().await;
+        let repo = test_repository(pool.clone());
+        let id = Utc::now().timestamp_micros();
+
+        // GIVEN пользователь, сохранённый до включения шифрования
+        let client = pool.get().await.unwrap();
+        client
+            .execute(
+                r#"
+                INSERT INTO users (id, username, full_name_lat, full_name_cyr, citizenship, arrival_date)
+                VALUES ($1, '', 'Otkrytov Oleg', 'Открытов Олег', 'Armenia', '2025-07-01')"#,
+                &[&id],
+            )
+            .await
+            .unwrap();
+        let found = repo.find_users("Открытов Олег").await.unwrap();
+        assert!(!found.iter().any(|u| u.id() == UserID::new(id)));
+
+        // WHEN запускается пробное перешифрование
+        assert!(repo.reencrypt_users(true).await.unwrap() >= 1);
+
+        // THEN строка не меняется
+        let row = client
+            .query_one("SELECT full_name_lat FROM users WHERE id = $1", &[&id])
+            .await
+            .unwrap();
+        assert_eq!(row.get::<_, String>(0), "Otkrytov Oleg");
+
+        // WHEN запускается перешифрование
+        repo.reencrypt_users(false).await.unwrap();
+
+        // THEN данные зашифрованы, читаются и находятся поиском
+        let row = client
+            .query_one("SELECT full_name_lat, arrival_date FROM users WHERE id = $1", &[&id])
+            .await
+            .unwrap();
+        assert!(row.get::<_, String>(0).starts_with("enc:v1:test:"));
+        assert!(row.get::<_, String>(1).starts_with("enc:v1:test:"));
+        let user = repo.user(UserID::new(id)).await.unwrap();
+        assert_eq!(user.full_name_cyr().as_str(), "Открытов Олег");
+        assert_eq!(user.arrival_date(), &NaiveDate::from_ymd_opt(2025, 7, 1).unwrap());
+        let found = repo.find_users("Открытов Олег").await.unwrap();
+        assert!(found.iter().any(|u| u.id() == UserID::new(id)));
+    }
 }
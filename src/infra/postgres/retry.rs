@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use crate::domain::Error;
+
+/// Сколько раз выполнять операцию и с какой задержкой перед первым повтором.
+/// Каждая следующая задержка вдвое больше предыдущей.
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub base_delay: Duration,
+}
+
+pub const DEFAULT_RETRY_POLICY: RetryPolicy = RetryPolicy {
+    attempts: 3,
+    base_delay: Duration::from_millis(50),
+};
+
+/// Временные сбои, после которых операцию имеет смысл повторить.
+fn is_transient(err: &Error) -> bool {
+    matches!(err, Error::Unavailable(_) | Error::SerializationFailure(_))
+}
+
+/// Выполняет `f`, повторяя её при временных сбоях, пока не исчерпаны попытки `policy`.
+pub async fn retry_transient<T, F, Fut>(policy: &RetryPolicy, mut f: F) -> Result<T, Error>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, Error>>,
+{
+    let mut delay = policy.base_delay;
+    let mut attempt = 1;
+    loop {
+        match f().await {
+            Err(err) if is_transient(&err) && attempt < policy.attempts => {
+                log::warn!(
+                    "Transient database failure (attempt {}/{}): {}",
+                    attempt,
+                    policy.attempts,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+                attempt += 1;
+            }
+            res => return res,
+        }
+    }
+}
+
+#[cfg(test)]
+mod retry_tests {
+    use super::*;
+    use crate::utils::postgres::testing::test_db_setup;
+    use crate::with_transaction;
+    use tokio_postgres::Transaction;
+
+    const TEST_POLICY: RetryPolicy = RetryPolicy {
+        attempts: 3,
+        base_delay: Duration::from_millis(1),
+    };
+
+    /// Транзакция, которая на первых `failures` попытках завершается ошибкой с кодом `errcode`.
+    async fn flaky_transaction(failures: u32, errcode: &str) -> (Result<i32, Error>, u32) {
+        let pool = test_db_setup().await;
+        let mut attempts = 0;
+        let pool = &pool;
+        let res = retry_transient(&TEST_POLICY, || {
+            attempts += 1;
+            let fail = attempts <= failures;
+            async move {
+                with_transaction!(pool, async |tx: &Transaction| {
+                    if fail {
+                        let query = format!(
+                            "DO $$ BEGIN RAISE EXCEPTION 'injected' USING ERRCODE = '{}'; END $$",
+                            errcode
+                        );
+                        tx.batch_execute(&query)
+                            .await
+                            .map_err(crate::infra::postgres::errors::pg_error)?;
+                    }
+                    let row = tx
+                        .query_one("SELECT 1", &[])
+                        .await
+                        .map_err(crate::infra::postgres::errors::pg_error)?;
+                    Ok::<i32, Error>(row.get(0))
+                })
+            }
+        })
+        .await;
+        (res, attempts)
+    }
+
+    #[tokio::test]
+    async fn test_serialization_failure_is_retried() {
+        // GIVEN транзакция, которая один раз конфликтует с параллельной
+        // WHEN она выполняется с повторами
+        let (res, attempts) = flaky_transaction(1, "40001").await;
+
+        // THEN вторая попытка успешна
+        assert_eq!(res.unwrap(), 1);
+        assert_eq!(attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn test_retries_are_limited() {
+        // GIVEN БД недоступна дольше, чем длятся все попытки
+        // WHEN транзакция выполняется с повторами
+        let (res, attempts) = flaky_transaction(10, "57P03").await;
+
+        // THEN после последней попытки возвращается ошибка
+        assert!(matches!(res, Err(Error::Unavailable(_))));
+        assert_eq!(attempts, TEST_POLICY.attempts);
+    }
+
+    #[tokio::test]
+    async fn test_conflict_is_not_retried() {
+        // GIVEN транзакция нарушает ограничение уникальности
+        // WHEN она выполняется с повторами
+        let (res, attempts) = flaky_transaction(1, "23505").await;
+
+        // THEN ошибка возвращается сразу
+        assert!(matches!(res, Err(Error::Conflict(_))));
+        assert_eq!(attempts, 1);
+    }
+}
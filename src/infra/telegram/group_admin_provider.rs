@@ -0,0 +1,407 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use teloxide::prelude::*;
+use teloxide::types::{ChatMember, ChatMemberKind, UserId};
+use teloxide::RequestError;
+
+use crate::domain::Error;
+use crate::domain::interfaces::AdminProvider;
+use crate::domain::models::{StaffMember, StaffRole, UserID};
+
+/// Методы Bot API, через которые проверяется состав группы сотрудников. Выделены в трейт,
+/// чтобы провайдер можно было проверить без обращения к Telegram.
+#[async_trait]
+pub trait ChatMembersClient: Send + Sync {
+    async fn chat_member(&self, chat_id: ChatId, user_id: UserId) -> Result<ChatMember, RequestError>;
+    async fn chat_administrators(&self, chat_id: ChatId) -> Result<Vec<ChatMember>, RequestError>;
+}
+
+#[async_trait]
+impl ChatMembersClient for Bot {
+    async fn chat_member(&self, chat_id: ChatId, user_id: UserId) -> Result<ChatMember, RequestError> {
+        self.get_chat_member(chat_id, user_id).await
+    }
+
+    async fn chat_administrators(&self, chat_id: ChatId) -> Result<Vec<ChatMember>, RequestError> {
+        self.get_chat_administrators(chat_id).await
+    }
+}
+
+/// Кого из участников группы считать сотрудниками.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StaffGroupMembers {
+    /// Только администраторов группы.
+    Administrators,
+    /// Всех участников группы.
+    All,
+}
+
+impl TryFrom<&str> for StaffGroupMembers {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "administrators" => Ok(Self::Administrators),
+            "all" => Ok(Self::All),
+            _ => Err(Error::InvalidValue(format!(
+                "invalid StaffGroupMembers: expected one of ['administrators', 'all'], got {}",
+                value
+            ))),
+        }
+    }
+}
+
+struct Cached<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+/// GroupAdminProvider считает сотрудниками участников группы УМД: создатель группы получает роль
+/// владельца, администраторы — оператора, остальные участники (если их разрешено учитывать) —
+/// наблюдателя. Роли, назначенные в боте, сохраняются: берётся наибольшая из двух.
+/// Состав группы кэшируется на `ttl` и сбрасывается при изменении участника группы.
+pub struct GroupAdminProvider {
+    client: Arc<dyn ChatMembersClient>,
+    chat_id: ChatId,
+    members: StaffGroupMembers,
+    fallback: Arc<dyn AdminProvider>,
+    ttl: Duration,
+    roles: Mutex<HashMap<UserID, Cached<Option<StaffRole>>>>,
+    administrators: Mutex<Option<Cached<Vec<StaffMember>>>>,
+}
+
+impl GroupAdminProvider {
+    pub fn new(
+        client: Arc<dyn ChatMembersClient>,
+        chat_id: ChatId,
+        members: StaffGroupMembers,
+        fallback: Arc<dyn AdminProvider>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            client,
+            chat_id,
+            members,
+            fallback,
+            ttl,
+            roles: Mutex::new(HashMap::new()),
+            administrators: Mutex::new(None),
+        }
+    }
+
+    fn role_of(&self, kind: &ChatMemberKind) -> Option<StaffRole> {
+        let is_member = match kind {
+            ChatMemberKind::Owner(_) => return Some(StaffRole::Owner),
+            ChatMemberKind::Administrator(_) => return Some(StaffRole::Operator),
+            ChatMemberKind::Member => true,
+            ChatMemberKind::Restricted(r) => r.is_member,
+            ChatMemberKind::Left | ChatMemberKind::Banned(_) => false,
+        };
+        (is_member && self.members == StaffGroupMembers::All).then_some(StaffRole::Viewer)
+    }
+
+    fn is_fresh(&self, fetched_at: Instant) -> bool {
+        fetched_at.elapsed() < self.ttl
+    }
+
+    async fn group_role(&self, id: UserID) -> Result<Option<StaffRole>, Error> {
+        if let Some(cached) = self.roles.lock().unwrap().get(&id)
+            && self.is_fresh(cached.fetched_at)
+        {
+            return Ok(cached.value);
+        }
+
+        let role = match self.client.chat_member(self.chat_id, UserId(id.as_i64() as u64)).await {
+            Ok(member) => self.role_of(&member.kind),
+            // Telegram отвечает ошибкой на запрос о пользователе, которого никогда не было в группе
+            Err(RequestError::Api(err)) => {
+                log::debug!("Failed to get {} in staff group: {}", id, err);
+                None
+            }
+            Err(err) => return Err(Error::Unavailable(err.into())),
+        };
+        self.roles.lock().unwrap().insert(
+            id,
+            Cached {
+                value: role,
+                fetched_at: Instant::now(),
+            },
+        );
+        Ok(role)
+    }
+
+    async fn group_staff(&self) -> Result<Vec<StaffMember>, Error> {
+        if let Some(cached) = self.administrators.lock().unwrap().as_ref()
+            && self.is_fresh(cached.fetched_at)
+        {
+            return Ok(cached.value.clone());
+        }
+
+        let administrators = self
+            .client
+            .chat_administrators(self.chat_id)
+            .await
+            .map_err(|err| Error::Unavailable(err.into()))?;
+        let staff: Vec<StaffMember> = administrators
+            .iter()
+            .filter(|m| !m.user.is_bot)
+            .filter_map(|m| {
+                let role = self.role_of(&m.kind)?;
+                Some(StaffMember::new(UserID::new(m.user.id.0 as i64), role))
+            })
+            .collect();
+        *self.administrators.lock().unwrap() = Some(Cached {
+            value: staff.clone(),
+            fetched_at: Instant::now(),
+        });
+        Ok(staff)
+    }
+}
+
+#[async_trait]
+impl AdminProvider for GroupAdminProvider {
+    async fn role(&self, id: UserID) -> Result<Option<StaffRole>, Error> {
+        let group_role = self.group_role(id).await?;
+        let fallback_role = self.fallback.role(id).await?;
+        Ok(group_role.max(fallback_role))
+    }
+
+    /// Bot API не позволяет получить всех участников группы, поэтому кроме администраторов
+    /// возвращаются только участники, уже обращавшиеся к боту.
+    async fn staff(&self) -> Result<Vec<StaffMember>, Error> {
+        let mut roles: HashMap<UserID, StaffRole> = HashMap::new();
+        let members = self
+            .roles
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, cached)| self.is_fresh(cached.fetched_at))
+            .filter_map(|(&id, cached)| Some(StaffMember::new(id, cached.value?)))
+            .collect::<Vec<_>>();
+        let group_staff = self.group_staff().await?;
+        let fallback_staff = self.fallback.staff().await?;
+        for m in members.into_iter().chain(group_staff).chain(fallback_staff) {
+            let role = roles.entry(m.id()).or_insert(m.role());
+            *role = (*role).max(m.role());
+        }
+        let mut staff: Vec<StaffMember> = roles
+            .into_iter()
+            .map(|(id, role)| StaffMember::new(id, role))
+            .collect();
+        staff.sort_by_key(|m| (std::cmp::Reverse(m.role()), m.id().as_i64()));
+        Ok(staff)
+    }
+
+    async fn membership_changed(&self, chat_id: i64, id: UserID) {
+        if chat_id != self.chat_id.0 {
+            return;
+        }
+        self.roles.lock().unwrap().remove(&id);
+        *self.administrators.lock().unwrap() = None;
+        log::debug!("Staff group membership of {} changed", id);
+    }
+}
+
+#[cfg(test)]
+mod group_admin_provider_tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const STAFF_CHAT: ChatId = ChatId(-100);
+
+    /// FakeChatMembersClient отвечает по заранее заданному составу группы и считает запросы.
+    struct FakeChatMembersClient {
+        members: Mutex<HashMap<u64, ChatMember>>,
+        requests: AtomicUsize,
+    }
+
+    impl FakeChatMembersClient {
+        fn new(members: &[(u64, &str)]) -> Arc<Self> {
+            let client = Arc::new(Self {
+                members: Mutex::new(HashMap::new()),
+                requests: AtomicUsize::new(0),
+            });
+            for &(id, status) in members {
+                client.set(id, status);
+            }
+            client
+        }
+
+        fn set(&self, id: u64, status: &str) {
+            // ChatMember десериализуется так же, как приходит от Telegram
+            let member = serde_json::json!({
+                "user": { "id": id, "is_bot": false, "first_name": "Staff" },
+                "status": status,
+                "is_anonymous": false,
+                "can_be_edited": false,
+                "can_manage_chat": true,
+                "can_change_info": false,
+                "can_delete_messages": false,
+                "can_manage_video_chats": false,
+                "can_invite_users": false,
+                "can_restrict_members": false,
+                "can_pin_messages": false,
+                "can_promote_members": false,
+                "can_post_stories": false,
+                "can_edit_stories": false,
+                "can_delete_stories": false,
+            });
+            let member = serde_json::from_str(&member.to_string()).unwrap();
+            self.members.lock().unwrap().insert(id, member);
+        }
+
+        fn requests(&self) -> usize {
+            self.requests.load(Ordering::SeqCst)
+        }
+    }
+
+    #[async_trait]
+    impl ChatMembersClient for FakeChatMembersClient {
+        async fn chat_member(
+            &self,
+            _chat_id: ChatId,
+            user_id: UserId,
+        ) -> Result<ChatMember, RequestError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            match self.members.lock().unwrap().get(&user_id.0) {
+                Some(member) => Ok(member.clone()),
+                None => Err(RequestError::Api(teloxide::ApiError::UserNotFound)),
+            }
+        }
+
+        async fn chat_administrators(
+            &self,
+            _chat_id: ChatId,
+        ) -> Result<Vec<ChatMember>, RequestError> {
+            self.requests.fetch_add(1, Ordering::SeqCst);
+            Ok(self
+                .members
+                .lock()
+                .unwrap()
+                .values()
+                .filter(|m| m.kind.is_privileged())
+                .cloned()
+                .collect())
+        }
+    }
+
+    struct NoStaff;
+
+    #[async_trait]
+    impl AdminProvider for NoStaff {
+        async fn role(&self, _id: UserID) -> Result<Option<StaffRole>, Error> {
+            Ok(None)
+        }
+
+        async fn staff(&self) -> Result<Vec<StaffMember>, Error> {
+            Ok(vec![])
+        }
+    }
+
+    fn provider(
+        client: Arc<FakeChatMembersClient>,
+        members: StaffGroupMembers,
+        ttl: Duration,
+    ) -> GroupAdminProvider {
+        GroupAdminProvider::new(client, STAFF_CHAT, members, Arc::new(NoStaff), ttl)
+    }
+
+    #[tokio::test]
+    async fn test_roles_follow_group_status() {
+        // GIVEN группа с создателем, администратором и обычным участником
+        let client = FakeChatMembersClient::new(&[
+            (1, "creator"),
+            (2, "administrator"),
+            (3, "member"),
+        ]);
+        let ttl = Duration::from_secs(600);
+        let admins_only = provider(client.clone(), StaffGroupMembers::Administrators, ttl);
+        let everyone = provider(client, StaffGroupMembers::All, ttl);
+
+        // THEN роли соответствуют статусам в группе
+        assert_eq!(admins_only.role(UserID::new(1)).await.unwrap(), Some(StaffRole::Owner));
+        assert_eq!(admins_only.role(UserID::new(2)).await.unwrap(), Some(StaffRole::Operator));
+        // AND обычные участники считаются сотрудниками, только если это разрешено
+        assert_eq!(admins_only.role(UserID::new(3)).await.unwrap(), None);
+        assert_eq!(everyone.role(UserID::new(3)).await.unwrap(), Some(StaffRole::Viewer));
+        // AND пользователь не из группы не сотрудник
+        assert_eq!(everyone.role(UserID::new(4)).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_membership_is_cached() {
+        // GIVEN роль администратора уже проверялась
+        let client = FakeChatMembersClient::new(&[(2, "administrator")]);
+        let provider = provider(
+            client.clone(),
+            StaffGroupMembers::Administrators,
+            Duration::from_secs(600),
+        );
+        provider.role(UserID::new(2)).await.unwrap();
+
+        // WHEN роль проверяется снова до истечения TTL
+        provider.role(UserID::new(2)).await.unwrap();
+
+        // THEN Telegram не запрашивается повторно
+        assert_eq!(client.requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_cache_is_refreshed() {
+        // GIVEN кэш с нулевым TTL
+        let client = FakeChatMembersClient::new(&[(2, "administrator")]);
+        let provider = provider(client.clone(), StaffGroupMembers::Administrators, Duration::ZERO);
+        assert_eq!(provider.role(UserID::new(2)).await.unwrap(), Some(StaffRole::Operator));
+
+        // WHEN администратора исключают из группы
+        client.set(2, "left");
+
+        // THEN следующая проверка видит изменения
+        assert_eq!(provider.role(UserID::new(2)).await.unwrap(), None);
+        assert_eq!(client.requests(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_member_update_resets_cache() {
+        // GIVEN роль закэширована надолго
+        let client = FakeChatMembersClient::new(&[(3, "member")]);
+        let provider = provider(
+            client.clone(),
+            StaffGroupMembers::Administrators,
+            Duration::from_secs(600),
+        );
+        assert_eq!(provider.role(UserID::new(3)).await.unwrap(), None);
+
+        // WHEN участника назначают администратором группы
+        client.set(3, "administrator");
+        provider.membership_changed(STAFF_CHAT.0, UserID::new(3)).await;
+
+        // THEN новая роль действует сразу
+        assert_eq!(provider.role(UserID::new(3)).await.unwrap(), Some(StaffRole::Operator));
+        // AND изменения в других чатах кэш не сбрасывают
+        client.set(3, "left");
+        provider.membership_changed(-200, UserID::new(3)).await;
+        assert_eq!(provider.role(UserID::new(3)).await.unwrap(), Some(StaffRole::Operator));
+    }
+
+    #[tokio::test]
+    async fn test_staff_lists_group_administrators() {
+        // GIVEN группа с создателем и администратором
+        let client = FakeChatMembersClient::new(&[(1, "creator"), (2, "administrator")]);
+        let provider = provider(client, StaffGroupMembers::All, Duration::from_secs(600));
+
+        // WHEN запрашивается список сотрудников
+        let staff = provider.staff().await.unwrap();
+
+        // THEN в нём оба с ролями по старшинству
+        assert_eq!(
+            staff,
+            vec![
+                StaffMember::new(UserID::new(1), StaffRole::Owner),
+                StaffMember::new(UserID::new(2), StaffRole::Operator),
+            ]
+        );
+    }
+}
@@ -0,0 +1,3 @@
+mod group_admin_provider;
+
+pub use group_admin_provider::*;
@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+use crate::usecases::CalendarSyncUseCase;
+
+/// Периодически отражает записи и отмены в календаре сотрудников.
+pub async fn calendar_sync_job(use_case: CalendarSyncUseCase, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match use_case.sync().await {
+            Ok(0) => {}
+            Ok(count) => log::info!("Updated {} calendar events", count),
+            Err(err) => log::error!("Failed to sync calendar: {}", err),
+        }
+    }
+}
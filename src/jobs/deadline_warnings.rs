@@ -0,0 +1,161 @@
+use chrono::Utc;
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use crate::bot::handlers::keyboards::make_reserve_link_keyboard;
+use crate::domain::models::Service;
+use crate::usecases::{
+    CheckAdminUseCase, DeadlineWarningDTO, DeadlineWarningsUseCase, MissedDeadlineDTO,
+};
+
+/// Периодически предупреждает студентов, не записавшихся на первичную регистрацию, о скором
+/// окончании срока подачи документов. Раз в сутки отправляет администраторам сводку студентов,
+/// пропустивших срок. Отправленная сводка запоминается в базе, поэтому после перезапуска бота
+/// она не повторяется.
+pub async fn deadline_warnings_job(
+    bot: Bot,
+    use_case: DeadlineWarningsUseCase,
+    check_admin: CheckAdminUseCase,
+    period: Duration,
+) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        send_warnings(&bot, &use_case).await;
+
+        let today = Utc::now().date_naive();
+        match use_case.is_summary_sent(today).await {
+            Ok(true) => {}
+            Ok(false) => {
+                send_summary(&bot, &use_case, &check_admin).await;
+                if let Err(err) = use_case.mark_summary_sent(today).await {
+                    log::error!("Failed to mark missed deadlines summary as sent: {}", err);
+                }
+            }
+            Err(err) => log::error!("Failed to check missed deadlines summary: {}", err),
+        }
+    }
+}
+
+async fn send_warnings(bot: &Bot, use_case: &DeadlineWarningsUseCase) {
+    let warnings = match use_case.due_warnings().await {
+        Ok(warnings) => warnings,
+        Err(err) => {
+            log::error!("Failed to get deadline warnings: {}", err);
+            return;
+        }
+    };
+    if warnings.is_empty() {
+        return;
+    }
+    let me = match bot.get_me().await {
+        Ok(me) => me,
+        Err(err) => {
+            log::error!("Failed to get bot info: {}", err);
+            return;
+        }
+    };
+
+    for warning in warnings.iter() {
+        let res = bot
+            .send_message(ChatId(warning.user_id.as_i64()), warning_text(warning))
+            .parse_mode(ParseMode::Html)
+            .reply_markup(make_reserve_link_keyboard(&me, Service::InitialRegistration))
+            .await;
+        if let Err(err) = res {
+            log::warn!(
+                "Failed to send deadline warning to {}: {}",
+                warning.user_id,
+                err
+            );
+        }
+        if let Err(err) = use_case.mark_sent(warning).await {
+            log::error!("Failed to mark deadline warning as sent: {}", err);
+        }
+    }
+}
+
+fn warning_text(warning: &DeadlineWarningDTO) -> String {
+    let deadline = warning.deadline.format("%d.%m.%Y");
+    match warning.days_left {
+        0 => "🚨 <b>Сегодня последний день подачи документов на первичную регистрацию!</b>\n\
+            Вы до сих пор не записались на приём. Запишитесь прямо сейчас, иначе придётся \
+            оформлять регистрацию с нарушением срока."
+            .to_string(),
+        1 => format!(
+            "🚨 <b>Завтра, {}, истекает срок подачи документов на первичную регистрацию!</b>\n\
+            Вы до сих пор не записались на приём. Запишитесь как можно скорее.",
+            deadline
+        ),
+        days_left if days_left <= 3 => format!(
+            "❗️ <b>До окончания срока подачи документов на первичную регистрацию осталось {} дн.</b>\n\
+            Последний день - {}. Вы ещё не записались на приём.",
+            days_left, deadline
+        ),
+        days_left => format!(
+            "⚠️ <b>До окончания срока подачи документов на первичную регистрацию осталось {} дн.</b>\n\
+            Последний день - {}. Не откладывайте запись на приём.",
+            days_left, deadline
+        ),
+    }
+}
+
+async fn send_summary(bot: &Bot, use_case: &DeadlineWarningsUseCase, check_admin: &CheckAdminUseCase) {
+    let missed = match use_case.missed_deadlines().await {
+        Ok(missed) => missed,
+        Err(err) => {
+            log::error!("Failed to get missed deadlines: {}", err);
+            return;
+        }
+    };
+    if missed.is_empty() {
+        return;
+    }
+    let admins = match check_admin.admins().await {
+        Ok(admins) => admins,
+        Err(err) => {
+            log::error!("Failed to get admins: {}", err);
+            return;
+        }
+    };
+
+    let text = summary_text(&missed);
+    for admin in admins.iter() {
+        let res = bot
+            .send_message(ChatId(admin.as_i64()), text.clone())
+            .parse_mode(ParseMode::Html)
+            .await;
+        if let Err(err) = res {
+            log::warn!("Failed to send missed deadlines summary to {}: {}", admin, err);
+        }
+    }
+}
+
+fn summary_text(missed: &[MissedDeadlineDTO]) -> String {
+    let lines: Vec<String> = missed
+        .iter()
+        .map(|m| {
+            let username = m.user.username.as_str();
+            format!(
+                "• <a href=\"tg://user?id={}\">{}</a> ({}), {}, срок истёк {}{}",
+                m.user.id,
+                m.user.full_name_cyr.as_str(),
+                m.user.full_name_lat.as_str(),
+                m.user.citizenship.as_str(),
+                m.deadline.format("%d.%m.%Y"),
+                if username.is_empty() {
+                    String::new()
+                } else {
+                    format!(", @{}", username)
+                },
+            )
+        })
+        .collect();
+    format!(
+        "📋 <b>Не записались на первичную регистрацию в срок ({})</b>\n{}",
+        missed.len(),
+        lines.join("\n"),
+    )
+}
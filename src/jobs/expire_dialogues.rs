@@ -0,0 +1,36 @@
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::{KeyboardRemove, ParseMode};
+
+use crate::dispatcher::DialogueStorages;
+
+/// Периодически сбрасывает диалоги, брошенные дольше настроенного времени, и сообщает об
+/// этом пользователям.
+pub async fn expire_dialogues_job(bot: Bot, storages: DialogueStorages, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        let expired = match storages.expire_idle().await {
+            Ok(expired) => expired,
+            Err(err) => {
+                log::error!("Failed to expire idle dialogues: {}", err);
+                continue;
+            }
+        };
+        for chat_id in expired {
+            let res = bot
+                .send_message(
+                    chat_id,
+                    "⌛ <b>Незавершённая операция сброшена</b>\n\
+                    Вы долго не отвечали, поэтому начните её заново.",
+                )
+                .parse_mode(ParseMode::Html)
+                .reply_markup(KeyboardRemove::new())
+                .await;
+            if let Err(err) = res {
+                log::warn!("Failed to notify {} about expired dialogue: {}", chat_id, err);
+            }
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use std::time::Duration;
+use teloxide::prelude::*;
+use teloxide::types::ParseMode;
+
+use crate::bot::handlers::keyboards::make_reserve_link_keyboard;
+use crate::domain::models::Service;
+use crate::usecases::ExpiryRemindersUseCase;
+
+/// Периодически напоминает студентам о скором окончании срока действия визы или регистрации и
+/// предлагает записаться на соответствующую услугу продления.
+pub async fn expiry_reminders_job(bot: Bot, use_case: ExpiryRemindersUseCase, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+
+        let reminders = match use_case.due_reminders().await {
+            Ok(reminders) => reminders,
+            Err(err) => {
+                log::error!("Failed to get expiry reminders: {}", err);
+                continue;
+            }
+        };
+        if reminders.is_empty() {
+            continue;
+        }
+        let me = match bot.get_me().await {
+            Ok(me) => me,
+            Err(err) => {
+                log::error!("Failed to get bot info: {}", err);
+                continue;
+            }
+        };
+
+        for reminder in reminders.iter() {
+            let document = match reminder.service {
+                Service::RenewalOfVisa => "визы",
+                _ => "регистрации",
+            };
+            let text = format!(
+                "⏰ <b>Срок действия {} истекает {}</b>\n\
+                Не забудьте заранее записаться на продление.",
+                document,
+                reminder.expiry_date.format("%d.%m.%Y"),
+            );
+            let res = bot
+                .send_message(ChatId(reminder.user_id.as_i64()), text)
+                .parse_mode(ParseMode::Html)
+                .reply_markup(make_reserve_link_keyboard(&me, reminder.service))
+                .await;
+            if let Err(err) = res {
+                log::warn!(
+                    "Failed to send expiry reminder to {}: {}",
+                    reminder.user_id,
+                    err
+                );
+            }
+            // Напоминание отмечается отправленным даже при ошибке, например, если пользователь
+            // заблокировал бота, чтобы не повторять его при каждом запуске.
+            if let Err(err) = use_case.mark_sent(reminder).await {
+                log::error!("Failed to mark expiry reminder as sent: {}", err);
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+mod calendar_sync;
+mod deadline_warnings;
+mod expire_dialogues;
+mod expiry_reminders;
+mod purge_documents;
+mod retention;
+
+pub use calendar_sync::*;
+pub use deadline_warnings::*;
+pub use expire_dialogues::*;
+pub use expiry_reminders::*;
+pub use purge_documents::*;
+pub use retention::*;
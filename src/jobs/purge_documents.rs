@@ -0,0 +1,16 @@
+use std::time::Duration;
+
+use crate::usecases::PurgeDocumentsUseCase;
+
+/// Периодически удаляет документы прошедших и отменённых записей.
+pub async fn purge_documents_job(use_case: PurgeDocumentsUseCase, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match use_case.purge().await {
+            Ok(0) => {}
+            Ok(count) => log::info!("Purged {} expired documents", count),
+            Err(err) => log::error!("Failed to purge expired documents: {}", err),
+        }
+    }
+}
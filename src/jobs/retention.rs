@@ -0,0 +1,27 @@
+use std::time::Duration;
+
+use crate::usecases::{RetentionReportDTO, RetentionUseCase};
+
+fn log_report(report: &RetentionReportDTO) {
+    if report.anonymised.is_empty() && report.deleted.is_empty() && report.reservations == 0 {
+        return;
+    }
+    log::info!(
+        "Retention: anonymised {} users, deleted {} users, anonymised {} reservations",
+        report.anonymised.len(),
+        report.deleted.len(),
+        report.reservations,
+    );
+}
+
+/// Периодически обезличивает и удаляет данные неактивных пользователей.
+pub async fn retention_job(use_case: RetentionUseCase, period: Duration) {
+    let mut interval = tokio::time::interval(period);
+    loop {
+        interval.tick().await;
+        match use_case.apply(false).await {
+            Ok(report) => log_report(&report),
+            Err(err) => log::error!("Failed to apply retention policy: {}", err),
+        }
+    }
+}
@@ -1,30 +1,52 @@
-use chrono::{Duration, NaiveTime};
+use chrono::{Days, Duration, Months, NaiveTime};
 use dotenv::dotenv;
 use std::env;
+use std::process::ExitCode;
 use std::sync::Arc;
+use std::time::Duration as StdDuration;
 use teloxide::Bot;
+use teloxide::types::ChatId;
 
-use crate::dispatcher::UmdDispatcher;
+use crate::bot::handlers::errors::ErrorReporter;
+use crate::bot::handlers::keyboards::service_to_str;
+use crate::cli::Command;
+use crate::bot::handlers::user::CONSENT_VERSION;
+use crate::dispatcher::{DialogueStorages, UmdDispatcher};
+use crate::domain::interfaces::AdminProvider;
 use crate::domain::models::{ClosedRange, UserID};
 use crate::domain::services::{
-    FixedSlotsFactory, Mon2ThuAndFriWithLunchWorkingHoursPolicy, StandardDeadlinePolicy,
+    FixedSlotsFactory, Mon2ThuAndFriWithLunchWorkingHoursPolicy, RetentionPolicy,
+    StandardDeadlinePolicy,
 };
-use crate::infra::{MockAdminProvider, PostgresRepository};
-use crate::usecases::{App, CancelReservationUseCase, CheckDeadlineUseCase, CheckRegisteredUseCase, DaysWithFreeSlotsUseCase, FreeSlotsUseCase, GetUserUseCase, RegisterUserUseCase, ReserveSlotUseCase, ReservationsUseCase, UpdateUserUseCase, CheckAdminUseCase};
+use crate::infra::{
+    CalDavMirror, FeedLinks, FieldCipher, FsDocumentStorage, GroupAdminProvider, PostgresRepository, StaffGroupMembers,
+};
+use crate::usecases::{App, AuditUseCase, CalendarFeedUseCase, CalendarSyncUseCase, CancelReservationUseCase, CheckDeadlineUseCase, CheckRegisteredUseCase, ConsentUseCase, DaysWithFreeSlotsUseCase, DeadlineOverridesUseCase, DeadlineWarningsUseCase, DeleteUserUseCase, DocumentsUseCase, ExpiryRemindersUseCase, ExportUserDataUseCase, ForecastUseCase, FreeSlotsUseCase, GetUserUseCase, PurgeDocumentsUseCase, RegisterUserUseCase, RequestDeadlineOverrideUseCase, ReserveSlotUseCase, ReservationsUseCase, RetentionUseCase, RevokeConsentUseCase, SearchUsersUseCase, StaffUseCase, StatsUseCase, StatusUseCase, UpdateUserUseCase, UploadDocumentUseCase, CheckAdminUseCase};
 use crate::utils::postgres::pool;
 
 mod bot;
+mod cli;
 mod dispatcher;
+mod feed;
 mod domain;
 mod infra;
+mod jobs;
 mod usecases;
 mod utils;
 
 #[tokio::main]
-async fn main() {
+async fn main() -> ExitCode {
     dotenv().ok();
     pretty_env_logger::init();
 
+    let command = match Command::parse(env::args().skip(1)) {
+        Ok(command) => command,
+        Err(usage) => {
+            eprintln!("{}", usage);
+            return ExitCode::FAILURE;
+        }
+    };
+
     let uri = env::var("DATABASE_URI").expect("DATABASE_URI must be set");
     let pool =
         pool::connect(&uri).expect(format!("unable to connect to database: {}", uri).as_str());
@@ -33,11 +55,10 @@ async fn main() {
     let admin_ids_str = env::var("ADMIN_IDS").unwrap_or_default();
     let admin_ids: Vec<UserID> = admin_ids_str
         .split(",")
-        .into_iter()
+        .filter(|s| !s.is_empty())
         .map(|s| UserID::new(s.parse::<i64>().expect("unable to parse user ids")))
         .collect();
-        
-    let admin_provider = Arc::new(MockAdminProvider::new(admin_ids));
+
     let slots_factory = Arc::new(FixedSlotsFactory::new(3, Duration::minutes(20)));
     let deadline_policy = Arc::new(StandardDeadlinePolicy::default());
     let working_hours_policy = Arc::new(Mon2ThuAndFriWithLunchWorkingHoursPolicy::new(
@@ -54,23 +75,179 @@ async fn main() {
             end: NaiveTime::from_hms_opt(13, 30, 0).unwrap(),
         },
     ));
-    let repos = Arc::new(PostgresRepository::new(pool));
+    let cipher = match env::var("PD_ENCRYPTION_KEYS").ok().filter(|keys| !keys.is_empty()) {
+        Some(keys) => {
+            let index_key =
+                env::var("PD_BLIND_INDEX_KEY").expect("PD_BLIND_INDEX_KEY must be set");
+            FieldCipher::new(&keys, &index_key).expect("invalid personal data encryption keys")
+        }
+        None => FieldCipher::plaintext(),
+    };
+    if !cipher.is_enabled() {
+        log::warn!("PD_ENCRYPTION_KEYS is not set, personal data is stored unencrypted");
+    }
+    let cipher = Arc::new(cipher);
+    let repos = Arc::new(PostgresRepository::new(pool.clone()).with_cipher(cipher.clone()));
+
+    if let Command::EncryptUsers { dry_run } = command {
+        return match cli::encrypt_users(&repos, dry_run).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("failed to encrypt users: {}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let documents_dir = env::var("DOCUMENTS_DIR").unwrap_or("documents".to_string());
+    log::info!("Storing documents in: {}", documents_dir);
+    let document_storage = Arc::new(FsDocumentStorage::new(documents_dir));
+
+    let retention_months = |name: &str, default: u32| {
+        env::var(name)
+            .map(|months| Months::new(months.parse().expect("retention period must be a number")))
+            .unwrap_or(Months::new(default))
+    };
+    let retention_policy = RetentionPolicy::new(
+        retention_months("RETENTION_ANONYMISE_MONTHS", 12),
+        retention_months("RETENTION_DELETE_MONTHS", 24),
+    )
+    .expect("invalid retention policy");
+    let retention = RetentionUseCase::new(
+        retention_policy,
+        repos.clone(),
+        repos.clone(),
+        repos.clone(),
+        repos.clone(),
+        document_storage.clone(),
+        repos.clone(),
+    );
+
+    if let Command::Retention { dry_run } = command {
+        return match cli::retention(&retention, dry_run).await {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(err) => {
+                eprintln!("failed to apply retention policy: {}", err);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let bot = Bot::from_env();
+
+    let admin_provider: Arc<dyn AdminProvider> = match env::var("STAFF_CHAT_ID") {
+        Ok(chat_id) => {
+            let chat_id = ChatId(chat_id.parse().expect("STAFF_CHAT_ID must be a number"));
+            let members = env::var("STAFF_CHAT_MEMBERS").unwrap_or("administrators".to_string());
+            let members = StaffGroupMembers::try_from(members.as_str())
+                .expect("unable to parse STAFF_CHAT_MEMBERS");
+            let ttl = env::var("STAFF_CACHE_TTL_MINUTES")
+                .map(|minutes| minutes.parse().expect("STAFF_CACHE_TTL_MINUTES must be a number"))
+                .unwrap_or(10);
+            log::info!("Staff are members of chat {} ({:?})", chat_id, members);
+            Arc::new(GroupAdminProvider::new(
+                Arc::new(bot.clone()),
+                chat_id,
+                members,
+                repos.clone(),
+                StdDuration::from_secs(ttl * 60),
+            ))
+        }
+        Err(_) => repos.clone(),
+    };
+
+    let expiry_reminder_days = env::var("EXPIRY_REMINDER_DAYS")
+        .map(|s| s.parse::<u64>().expect("unable to parse EXPIRY_REMINDER_DAYS"))
+        .unwrap_or(14);
+
+    let feed_url = env::var("CALENDAR_FEED_URL").ok().filter(|url| !url.is_empty());
+    let feed_links = match &feed_url {
+        Some(url) => {
+            let key = env::var("CALENDAR_FEED_KEY").expect("CALENDAR_FEED_KEY must be set");
+            FeedLinks::new(url, &key).expect("invalid CALENDAR_FEED_KEY")
+        }
+        None => FeedLinks::disabled(),
+    };
 
     let app = App {
+        audit: AuditUseCase::new(repos.clone()),
+        calendar_feed: CalendarFeedUseCase::new(
+            slots_factory.clone(),
+            repos.clone(),
+            Arc::new(feed_links),
+        ),
         cancel_reservation: CancelReservationUseCase::new(
             slots_factory.clone(),
             repos.clone(),
             repos.clone(),
         ),
         check_admin: CheckAdminUseCase::new(admin_provider.clone()),
-        check_deadline: CheckDeadlineUseCase::new(deadline_policy.clone(), repos.clone()),
+        check_deadline: CheckDeadlineUseCase::new(
+            deadline_policy.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
         check_registered: CheckRegisteredUseCase::new(repos.clone()),
+        consent: ConsentUseCase::new(
+            CONSENT_VERSION,
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
         days_with_free_slots: DaysWithFreeSlotsUseCase::new(
             slots_factory.clone(),
             deadline_policy.clone(),
             working_hours_policy.clone(),
             repos.clone(),
             repos.clone(),
+            repos.clone(),
+        ),
+        deadline_overrides: DeadlineOverridesUseCase::new(
+            deadline_policy.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        deadline_warnings: DeadlineWarningsUseCase::new(
+            deadline_policy.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        delete_user: DeleteUserUseCase::new(
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            document_storage.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        documents: DocumentsUseCase::new(
+            repos.clone(),
+            document_storage.clone(),
+            repos.clone(),
+        ),
+        expiry_reminders: ExpiryRemindersUseCase::new(
+            repos.clone(),
+            repos.clone(),
+            Days::new(expiry_reminder_days),
+        ),
+        export_user_data: ExportUserDataUseCase::new(
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        forecast: ForecastUseCase::new(
+            slots_factory.clone(),
+            working_hours_policy.clone(),
+            deadline_policy.clone(),
+            repos.clone(),
+            repos.clone(),
         ),
         free_slots: FreeSlotsUseCase::new(
             slots_factory.clone(),
@@ -78,24 +255,163 @@ async fn main() {
             repos.clone(),
         ),
         get_user: GetUserUseCase::new(repos.clone()),
+        purge_documents: PurgeDocumentsUseCase::new(
+            repos.clone(),
+            document_storage.clone(),
+            repos.clone(),
+        ),
         register_user: RegisterUserUseCase::new(repos.clone()),
+        request_deadline_override: RequestDeadlineOverrideUseCase::new(
+            deadline_policy.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
         reserve_slot: ReserveSlotUseCase::new(
             slots_factory.clone(),
             working_hours_policy.clone(),
+            deadline_policy.clone(),
+            repos.clone(),
             repos.clone(),
             repos.clone(),
             repos.clone(),
         ),
+        retention,
+        revoke_consent: RevokeConsentUseCase::new(
+            slots_factory.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+            document_storage.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        search_users: SearchUsersUseCase::new(repos.clone(), repos.clone()),
         slots: ReservationsUseCase::new(
             slots_factory.clone(),
             working_hours_policy.clone(),
             repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        staff: StaffUseCase::new(
+            admin_provider.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
+        stats: StatsUseCase::new(
+            slots_factory.clone(),
+            working_hours_policy.clone(),
+            repos.clone(),
+        ),
+        status: StatusUseCase::new(
+            deadline_policy.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
         ),
         update_user: UpdateUserUseCase::new(repos.clone(), repos.clone()),
+        upload_document: UploadDocumentUseCase::new(
+            repos.clone(),
+            document_storage.clone(),
+            repos.clone(),
+            repos.clone(),
+            repos.clone(),
+        ),
     };
 
-    let bot = Bot::from_env();
-    let mut dispatcher = UmdDispatcher::create(bot, app).await;
+    app.staff
+        .bootstrap_owners(&admin_ids)
+        .await
+        .expect("unable to grant owner role to ADMIN_IDS");
+
+    tokio::spawn(jobs::purge_documents_job(
+        app.purge_documents.clone(),
+        StdDuration::from_secs(60 * 60),
+    ));
+    tokio::spawn(jobs::retention_job(
+        app.retention.clone(),
+        StdDuration::from_secs(24 * 60 * 60),
+    ));
+    tokio::spawn(jobs::expiry_reminders_job(
+        bot.clone(),
+        app.expiry_reminders.clone(),
+        StdDuration::from_secs(60 * 60),
+    ));
+    tokio::spawn(jobs::deadline_warnings_job(
+        bot.clone(),
+        app.deadline_warnings.clone(),
+        app.check_admin.clone(),
+        StdDuration::from_secs(60 * 60),
+    ));
+    if let Ok(calendar_url) = env::var("CALDAV_URL") {
+        let credentials = env::var("CALDAV_USERNAME")
+            .ok()
+            .map(|username| (username, env::var("CALDAV_PASSWORD").unwrap_or_default()));
+        let days = env::var("CALDAV_SYNC_DAYS")
+            .map(|days| days.parse().expect("CALDAV_SYNC_DAYS must be a number"))
+            .unwrap_or(31);
+        let interval = env::var("CALDAV_SYNC_INTERVAL_MINUTES")
+            .map(|minutes| minutes.parse().expect("CALDAV_SYNC_INTERVAL_MINUTES must be a number"))
+            .unwrap_or(1);
+        log::info!("Mirroring reservations to calendar: {}", calendar_url);
+        let calendar_sync = CalendarSyncUseCase::new(
+            slots_factory.clone(),
+            working_hours_policy.clone(),
+            repos.clone(),
+            Arc::new(CalDavMirror::new(&calendar_url, credentials, service_to_str)),
+            repos.clone(),
+            Days::new(days),
+        );
+        tokio::spawn(jobs::calendar_sync_job(
+            calendar_sync,
+            StdDuration::from_secs(interval * 60),
+        ));
+    }
+    if let Some(feed_url) = feed_url {
+        let addr = env::var("CALENDAR_FEED_ADDR").unwrap_or("0.0.0.0:8080".to_string());
+        let listener = tokio::net::TcpListener::bind(&addr)
+            .await
+            .unwrap_or_else(|err| panic!("unable to listen on {}: {}", addr, err));
+        log::info!("Serving calendar feeds on {} as {}", addr, feed_url);
+        tokio::spawn(feed::serve_calendar_feed(
+            listener,
+            app.calendar_feed.clone(),
+            service_to_str,
+        ));
+    }
+    let idle_timeout = env::var("DIALOGUE_IDLE_TIMEOUT_MINUTES")
+        .map(|minutes| minutes.parse().expect("DIALOGUE_IDLE_TIMEOUT_MINUTES must be a number"))
+        .unwrap_or(60);
+    let idle_timeout = Duration::minutes(idle_timeout);
+    let dialogue_storage = env::var("DIALOGUE_STORAGE").unwrap_or("memory".to_string());
+    log::info!("Storing dialogues in: {}", dialogue_storage);
+    let storages = match dialogue_storage.as_str() {
+        "memory" => DialogueStorages::in_memory(idle_timeout),
+        "postgres" => DialogueStorages::postgres(pool, cipher, idle_timeout),
+        other => panic!("unknown DIALOGUE_STORAGE: {}", other),
+    };
+
+    let admin_chat = env::var("ADMIN_CHAT_ID")
+        .ok()
+        .map(|id| ChatId(id.parse().expect("ADMIN_CHAT_ID must be a number")));
+    let reporter = ErrorReporter::new(admin_chat);
+
+    tokio::spawn(jobs::expire_dialogues_job(
+        bot.clone(),
+        storages.clone(),
+        StdDuration::from_secs(60),
+    ));
+
+    let mut dispatcher = UmdDispatcher::create(bot, app, storages, reporter).await;
 
     dispatcher.dispatch().await;
+    ExitCode::SUCCESS
 }
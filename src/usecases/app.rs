@@ -1,15 +1,34 @@
-use crate::usecases::{CancelReservationUseCase, CheckDeadlineUseCase, CheckRegisteredUseCase, DaysWithFreeSlotsUseCase, FreeSlotsUseCase, GetUserUseCase, RegisterUserUseCase, ReserveSlotUseCase, ReservationsUseCase, UpdateUserUseCase, CheckAdminUseCase};
+use crate::usecases::{AuditUseCase, CalendarFeedUseCase, CancelReservationUseCase, CheckDeadlineUseCase, CheckRegisteredUseCase, ConsentUseCase, RevokeConsentUseCase, SearchUsersUseCase, DaysWithFreeSlotsUseCase, DeadlineOverridesUseCase, DeadlineWarningsUseCase, DeleteUserUseCase, ExportUserDataUseCase, DocumentsUseCase, ForecastUseCase, ExpiryRemindersUseCase, FreeSlotsUseCase, GetUserUseCase, PurgeDocumentsUseCase, RegisterUserUseCase, RequestDeadlineOverrideUseCase, ReserveSlotUseCase, ReservationsUseCase, RetentionUseCase, StaffUseCase, StatsUseCase, StatusUseCase, UpdateUserUseCase, UploadDocumentUseCase, CheckAdminUseCase};
 
 pub struct App {
+    pub audit: AuditUseCase,
+    pub calendar_feed: CalendarFeedUseCase,
     pub cancel_reservation: CancelReservationUseCase,
     pub check_admin: CheckAdminUseCase,
     pub check_deadline: CheckDeadlineUseCase,
     pub check_registered: CheckRegisteredUseCase,
+    pub consent: ConsentUseCase,
     pub days_with_free_slots: DaysWithFreeSlotsUseCase,
+    pub deadline_overrides: DeadlineOverridesUseCase,
+    pub deadline_warnings: DeadlineWarningsUseCase,
+    pub delete_user: DeleteUserUseCase,
+    pub documents: DocumentsUseCase,
+    pub expiry_reminders: ExpiryRemindersUseCase,
+    pub export_user_data: ExportUserDataUseCase,
+    pub forecast: ForecastUseCase,
     pub free_slots: FreeSlotsUseCase,
     pub get_user: GetUserUseCase,
+    pub purge_documents: PurgeDocumentsUseCase,
     pub register_user: RegisterUserUseCase,
+    pub request_deadline_override: RequestDeadlineOverrideUseCase,
     pub reserve_slot: ReserveSlotUseCase,
+    pub retention: RetentionUseCase,
+    pub revoke_consent: RevokeConsentUseCase,
+    pub search_users: SearchUsersUseCase,
     pub slots: ReservationsUseCase,
+    pub staff: StaffUseCase,
+    pub stats: StatsUseCase,
+    pub status: StatusUseCase,
     pub update_user: UpdateUserUseCase,
+    pub upload_document: UploadDocumentUseCase,
 }
@@ -0,0 +1,33 @@
+use chrono::NaiveDate;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::AuditLogProvider;
+use crate::domain::models::UserID;
+use crate::usecases::AuditEntryDTO;
+
+/// Сколько последних записей журнала показывать по пользователю.
+const USER_ENTRIES_LIMIT: usize = 50;
+
+#[derive(Clone)]
+pub struct AuditUseCase {
+    provider: Arc<dyn AuditLogProvider>,
+}
+
+impl AuditUseCase {
+    pub fn new(provider: Arc<dyn AuditLogProvider>) -> Self {
+        Self { provider }
+    }
+
+    /// Возвращает последние записи журнала, касающиеся пользователя, от новых к старым.
+    pub async fn user_entries(&self, id: UserID) -> Result<Vec<AuditEntryDTO>, Error> {
+        let entries = self.provider.user_audit_entries(id, USER_ENTRIES_LIMIT).await?;
+        Ok(entries.iter().map(AuditEntryDTO::from).collect())
+    }
+
+    /// Возвращает записи журнала за день (UTC).
+    pub async fn entries_on(&self, date: NaiveDate) -> Result<Vec<AuditEntryDTO>, Error> {
+        let entries = self.provider.audit_entries_on(date).await?;
+        Ok(entries.iter().map(AuditEntryDTO::from).collect())
+    }
+}
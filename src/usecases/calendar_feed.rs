@@ -0,0 +1,62 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{CalendarFeedLinks, UserReservationsProvider};
+use crate::domain::models::UserID;
+use crate::domain::services::SlotsFactory;
+use crate::usecases::CalendarReservationDTO;
+
+/// Сколько прошедшие записи остаются в личном календаре.
+const FEED_HISTORY: Duration = Duration::days(30);
+
+/// Личный календарь студента по секретной ссылке: календарь телефона подписывается на
+/// ссылку и сам подтягивает новые записи и убирает отменённые.
+#[derive(Clone)]
+pub struct CalendarFeedUseCase {
+    factory: Arc<dyn SlotsFactory>,
+    provider: Arc<dyn UserReservationsProvider>,
+    links: Arc<dyn CalendarFeedLinks>,
+}
+
+impl CalendarFeedUseCase {
+    pub fn new(
+        factory: Arc<dyn SlotsFactory>,
+        provider: Arc<dyn UserReservationsProvider>,
+        links: Arc<dyn CalendarFeedLinks>,
+    ) -> Self {
+        Self {
+            factory,
+            provider,
+            links,
+        }
+    }
+
+    /// Ссылка на календарь пользователя. None, если календари по ссылке не настроены.
+    pub fn link(&self, id: UserID) -> Option<String> {
+        self.links.link(id)
+    }
+
+    /// Записи для календаря по токену из ссылки: предстоящие и за последние 30 дней.
+    /// None, если токен не выдавался.
+    pub async fn feed(&self, token: &str) -> Result<Option<Vec<CalendarReservationDTO>>, Error> {
+        let Some(user_id) = self.links.user(token) else {
+            return Ok(None);
+        };
+        let reservations = self
+            .provider
+            .user_reservations(user_id, Utc::now() - FEED_HISTORY)
+            .await?;
+        Ok(Some(
+            reservations
+                .into_iter()
+                .map(|(start, service)| CalendarReservationDTO {
+                    user_id,
+                    slot_start: start,
+                    slot_end: self.factory.create(start).interval().end,
+                    service,
+                })
+                .collect(),
+        ))
+    }
+}
@@ -0,0 +1,76 @@
+use chrono::{Days, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{CalendarMirror, MirroredSlotsRepository, ReservedSlotsProvider};
+use crate::domain::services::{SlotsFactory, WorkingHoursPolicy};
+
+/// Синхронизирует календарь сотрудников с записями на ближайшие дни.
+///
+/// Для каждого слота запоминается отпечаток отправленного события, поэтому повторная
+/// синхронизация ничего не меняет, изменения имён и услуг тоже попадают в календарь, а после
+/// сбоя календарь догоняет базу.
+#[derive(Clone)]
+pub struct CalendarSyncUseCase {
+    factory: Arc<dyn SlotsFactory>,
+    policy: Arc<dyn WorkingHoursPolicy>,
+    provider: Arc<dyn ReservedSlotsProvider>,
+    mirror: Arc<dyn CalendarMirror>,
+    repos: Arc<dyn MirroredSlotsRepository>,
+    days: Days,
+}
+
+impl CalendarSyncUseCase {
+    pub fn new(
+        factory: Arc<dyn SlotsFactory>,
+        policy: Arc<dyn WorkingHoursPolicy>,
+        provider: Arc<dyn ReservedSlotsProvider>,
+        mirror: Arc<dyn CalendarMirror>,
+        repos: Arc<dyn MirroredSlotsRepository>,
+        days: Days,
+    ) -> Self {
+        Self {
+            factory,
+            policy,
+            provider,
+            mirror,
+            repos,
+            days,
+        }
+    }
+
+    /// Отправляет в календарь слоты, события которых изменились с прошлой синхронизации,
+    /// и удаляет события слотов, где записей не осталось. Возвращает число изменённых событий.
+    pub async fn sync(&self) -> Result<usize, Error> {
+        let today = Utc::now().date_naive();
+        let until = today + self.days;
+        let slots: Vec<_> = today
+            .iter_days()
+            .take_while(|date| *date < until)
+            .flat_map(|date| self.factory.create_all(date, self.policy.as_ref()))
+            .collect();
+        let reserved = self.provider.reserved_slots(slots).await?;
+
+        let from = today.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let to = until.and_hms_opt(0, 0, 0).unwrap().and_utc();
+        let mut mirrored = self.repos.mirrored_slots(from, to).await?;
+
+        let mut changed = 0;
+        for slot in reserved {
+            let hash = self.mirror.event_hash(&slot);
+            if mirrored.remove(&slot.start()).as_ref() == Some(&hash) {
+                continue;
+            }
+            self.mirror.put_slot(&slot).await?;
+            self.repos.save_mirrored_slot(slot.start(), &hash).await?;
+            changed += 1;
+        }
+        // Оставшиеся слоты отражены в календаре, но записей в них больше нет.
+        for start in mirrored.into_keys() {
+            self.mirror.delete_slot(start).await?;
+            self.repos.delete_mirrored_slot(start).await?;
+            changed += 1;
+        }
+        Ok(changed)
+    }
+}
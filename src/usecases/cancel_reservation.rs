@@ -3,8 +3,9 @@ use std::sync::Arc;
 
 use crate::domain::Error;
 use crate::domain::interfaces::{ReservedSlotProvider, SlotsRepository};
-use crate::domain::models::UserID;
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, UserID};
 use crate::domain::services::SlotsFactory;
+use crate::usecases::CancelledReservationDTO;
 
 #[derive(Clone)]
 pub struct CancelReservationUseCase {
@@ -30,11 +31,25 @@ impl CancelReservationUseCase {
         &self,
         user_id: UserID,
         time: DateTime<Utc>,
-    ) -> Result<(), Error> {
+    ) -> Result<CancelledReservationDTO, Error> {
         let slot = self.factory.create(time);
         let mut slot = self.provider.reserved_slot(slot).await?;
+        let service = slot
+            .reservations()
+            .iter()
+            .find(|r| r.by().id() == user_id)
+            .map(|r| *r.service())
+            .ok_or(Error::UserNotReserved(user_id))?;
         slot.cancel(user_id)?;
-        self.repos.save_slot(&slot).await?;
-        Ok(())
+        let entry = AuditEntry::new(user_id, AuditAction::ReservationCancelled, Some(user_id))
+            .with_change(AuditChange::removed("slot_start", slot.start().to_rfc3339()))
+            .with_change(AuditChange::removed("service", Into::<String>::into(service)));
+        self.repos.save_slot(&slot, &entry).await?;
+
+        Ok(CancelledReservationDTO {
+            slot_start: slot.start(),
+            slot_end: slot.interval().end,
+            service,
+        })
     }
 }
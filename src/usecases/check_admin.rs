@@ -1,7 +1,7 @@
 use std::sync::Arc;
 use crate::domain::Error;
 use crate::domain::interfaces::AdminProvider;
-use crate::domain::models::UserID;
+use crate::domain::models::{StaffRole, UserID};
 
 #[derive(Clone)]
 pub struct CheckAdminUseCase {
@@ -14,8 +14,28 @@ impl CheckAdminUseCase {
     ) -> Self {
         Self { provider }
     }
-    
-    pub async fn is_admin(&self, id: UserID) -> Result<bool, Error> {
-        self.provider.is_admin(id).await
+
+    /// Проверяет, что у пользователя есть роль не ниже `required`.
+    pub async fn has_role(&self, id: UserID, required: StaffRole) -> Result<bool, Error> {
+        let role = self.provider.role(id).await?;
+        Ok(role.is_some_and(|role| role.allows(required)))
+    }
+
+    pub async fn admins(&self) -> Result<Vec<UserID>, Error> {
+        self.admins_with_role(StaffRole::Viewer).await
+    }
+
+    /// Возвращает сотрудников с ролью не ниже `required`.
+    pub async fn admins_with_role(&self, required: StaffRole) -> Result<Vec<UserID>, Error> {
+        let staff = self.provider.staff().await?;
+        Ok(staff
+            .into_iter()
+            .filter(|m| m.role().allows(required))
+            .map(|m| m.id())
+            .collect())
+    }
+
+    pub async fn membership_changed(&self, chat_id: i64, id: UserID) {
+        self.provider.membership_changed(chat_id, id).await
     }
 }
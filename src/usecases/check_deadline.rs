@@ -1,35 +1,58 @@
-use chrono::Utc;
+use chrono::{NaiveDate, Utc};
 use std::sync::Arc;
 
 use crate::domain::Error;
-use crate::domain::interfaces::UserProvider;
-use crate::domain::models::{Service, UserID};
+use crate::domain::interfaces::{DeadlineOverrideProvider, UserProvider};
+use crate::domain::models::{Service, UserID, slot_today};
 use crate::domain::services::DeadlinePolicy;
 
 #[derive(Clone)]
 pub struct CheckDeadlineUseCase {
     deadline_policy: Arc<dyn DeadlinePolicy>,
     provider: Arc<dyn UserProvider>,
+    override_provider: Arc<dyn DeadlineOverrideProvider>,
 }
 
 impl CheckDeadlineUseCase {
-    pub fn new(deadline_policy: Arc<dyn DeadlinePolicy>, provider: Arc<dyn UserProvider>) -> Self {
+    pub fn new(
+        deadline_policy: Arc<dyn DeadlinePolicy>,
+        provider: Arc<dyn UserProvider>,
+        override_provider: Arc<dyn DeadlineOverrideProvider>,
+    ) -> Self {
         Self {
             deadline_policy,
             provider,
+            override_provider,
         }
     }
 
+    /// Проверяет, может ли пользователь записаться на услугу: срок подачи документов не истёк
+    /// или сотрудник одобрил запись после срока.
     pub async fn check_deadline(&self, user_id: UserID, service: Service) -> Result<bool, Error> {
-        if !service.has_deadline() {
+        let deadline = match self.deadline(user_id, service).await? {
+            Some(deadline) => deadline,
+            None => return Ok(true),
+        };
+        if slot_today(Utc::now()) <= deadline {
             return Ok(true);
         }
+        let active = self.override_provider.active_override(user_id, service).await?;
+        Ok(active.is_some_and(|o| o.is_approved()))
+    }
+
+    /// Возвращает последний день подачи документов для услуги или `None`, если срок не ограничен.
+    pub async fn deadline(
+        &self,
+        user_id: UserID,
+        service: Service,
+    ) -> Result<Option<NaiveDate>, Error> {
+        if !service.has_deadline() {
+            return Ok(None);
+        }
         let user = self.provider.user(user_id).await?;
-        let today = Utc::now().date_naive();
-        let deadline = user
-            .arrival_date()
-            .checked_add_days(self.deadline_policy.deadline(user.citizenship()))
-            .unwrap();
-        Ok(today <= deadline)
+        Ok(Some(self.deadline_policy.deadline_date(
+            *user.arrival_date(),
+            user.citizenship(),
+        )))
     }
 }
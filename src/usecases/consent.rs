@@ -0,0 +1,56 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{AuditLog, ConsentProvider, ConsentRepository, UserProvider};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, Consent, UserID};
+
+#[derive(Clone)]
+pub struct ConsentUseCase {
+    /// Действующая редакция текста согласия.
+    version: u32,
+    user_provider: Arc<dyn UserProvider>,
+    provider: Arc<dyn ConsentProvider>,
+    repos: Arc<dyn ConsentRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl ConsentUseCase {
+    pub fn new(
+        version: u32,
+        user_provider: Arc<dyn UserProvider>,
+        provider: Arc<dyn ConsentProvider>,
+        repos: Arc<dyn ConsentRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            version,
+            user_provider,
+            provider,
+            repos,
+            audit,
+        }
+    }
+
+    /// Сохраняет согласие пользователя на действующую редакцию текста.
+    pub async fn give(&self, id: UserID) -> Result<(), Error> {
+        let consent = Consent::new(id, self.version, Utc::now());
+        self.repos.save_consent(&consent).await?;
+        log::info!("{} gave consent version {}", id, self.version);
+        let entry = AuditEntry::new(id, AuditAction::ConsentGiven, Some(id))
+            .with_change(AuditChange::added("version", self.version));
+        self.audit.append(&entry).await
+    }
+
+    /// Проверяет, что зарегистрированный пользователь должен заново дать согласие: он не давал
+    /// его вовсе или давал на прежнюю редакцию текста.
+    pub async fn needs_consent(&self, id: UserID) -> Result<bool, Error> {
+        match self.user_provider.user(id).await {
+            Ok(_) => {}
+            Err(Error::UserNotFound(_)) => return Ok(false),
+            Err(err) => return Err(err),
+        }
+        let consent = self.provider.consent(id).await?;
+        Ok(!consent.is_some_and(|c| c.covers(self.version)))
+    }
+}
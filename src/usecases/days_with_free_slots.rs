@@ -3,8 +3,10 @@ use std::ops::Add;
 use std::sync::Arc;
 
 use crate::domain::Error;
-use crate::domain::interfaces::{HasAvailableSlotsProvider, UserProvider};
-use crate::domain::models::{ClosedRange, Service, UserID};
+use crate::domain::interfaces::{
+    DeadlineOverrideProvider, HasAvailableSlotsProvider, UserProvider,
+};
+use crate::domain::models::{ClosedRange, Service, UserID, slot_today};
 use crate::domain::services::{DeadlinePolicy, SlotsFactory, WorkingHoursPolicy};
 
 const MAX_DAYS_BEFORE_RESERVE: Days = Days::new(14);
@@ -16,6 +18,7 @@ pub struct DaysWithFreeSlotsUseCase {
     working_hours_policy: Arc<dyn WorkingHoursPolicy>,
     user_provider: Arc<dyn UserProvider>,
     provider: Arc<dyn HasAvailableSlotsProvider>,
+    override_provider: Arc<dyn DeadlineOverrideProvider>,
 }
 
 impl DaysWithFreeSlotsUseCase {
@@ -25,6 +28,7 @@ impl DaysWithFreeSlotsUseCase {
         working_hours_policy: Arc<dyn WorkingHoursPolicy>,
         user_provider: Arc<dyn UserProvider>,
         provider: Arc<dyn HasAvailableSlotsProvider>,
+        override_provider: Arc<dyn DeadlineOverrideProvider>,
     ) -> Self {
         Self {
             factory,
@@ -32,24 +36,30 @@ impl DaysWithFreeSlotsUseCase {
             working_hours_policy,
             user_provider,
             provider,
+            override_provider,
         }
     }
 
+    /// Дни со свободными слотами. Возвращает `DeadlineExpired`, если срок подачи документов
+    /// истёк и одобренного запроса нет.
     pub async fn days_with_free_slots(
         &self,
         user_id: UserID,
         service: Service,
     ) -> Result<Vec<NaiveDate>, Error> {
         let user = self.user_provider.user(user_id).await?;
-        let start = Utc::now().naive_utc().date();
-        let end = if service.has_deadline() {
-            start.add(self.deadline_policy.deadline(user.citizenship()))
+        let start = slot_today(Utc::now());
+        // Без одобренного запроса дни ограничены тем же сроком, что проверяет запись.
+        let range = if service.has_deadline() && !self.has_approved_override(user_id, service).await? {
+            self.deadline_policy
+                .reservation_days(start, *user.arrival_date(), user.citizenship())?
         } else {
-            start.add(MAX_DAYS_BEFORE_RESERVE)
+            ClosedRange {
+                start,
+                end: start.add(MAX_DAYS_BEFORE_RESERVE),
+            }
         };
 
-        let range = ClosedRange { start, end };
-
         let mut result = Vec::new();
         for date in range.into_iter() {
             let slots = self
@@ -63,4 +73,9 @@ impl DaysWithFreeSlotsUseCase {
 
         Ok(result)
     }
+
+    async fn has_approved_override(&self, user_id: UserID, service: Service) -> Result<bool, Error> {
+        let active = self.override_provider.active_override(user_id, service).await?;
+        Ok(active.is_some_and(|o| o.is_approved()))
+    }
 }
@@ -0,0 +1,89 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, DeadlineOverrideProvider, DeadlineOverrideRepository, UserProvider,
+};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, DeadlineOverride, UserID};
+use crate::domain::services::DeadlinePolicy;
+use crate::usecases::DeadlineOverrideDTO;
+
+/// Очередь запросов на запись после срока для сотрудников УМД.
+#[derive(Clone)]
+pub struct DeadlineOverridesUseCase {
+    deadline_policy: Arc<dyn DeadlinePolicy>,
+    user_provider: Arc<dyn UserProvider>,
+    provider: Arc<dyn DeadlineOverrideProvider>,
+    repos: Arc<dyn DeadlineOverrideRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl DeadlineOverridesUseCase {
+    pub fn new(
+        deadline_policy: Arc<dyn DeadlinePolicy>,
+        user_provider: Arc<dyn UserProvider>,
+        provider: Arc<dyn DeadlineOverrideProvider>,
+        repos: Arc<dyn DeadlineOverrideRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            deadline_policy,
+            user_provider,
+            provider,
+            repos,
+            audit,
+        }
+    }
+
+    pub async fn pending(&self) -> Result<Vec<DeadlineOverrideDTO>, Error> {
+        let overrides = self.provider.pending_overrides().await?;
+        let mut res = Vec::with_capacity(overrides.len());
+        for o in overrides.iter() {
+            res.push(self.to_dto(o).await?);
+        }
+        Ok(res)
+    }
+
+    pub async fn approve(&self, id: i64, admin_id: UserID) -> Result<DeadlineOverrideDTO, Error> {
+        let mut o = self.provider.deadline_override(id).await?;
+        o.approve(admin_id, Utc::now())?;
+        self.repos.save_override(&o).await?;
+        log::info!("Deadline override {} approved by {}", id, admin_id);
+        self.audit_resolution(&o, admin_id, AuditAction::OverrideApproved).await?;
+        self.to_dto(&o).await
+    }
+
+    pub async fn reject(&self, id: i64, admin_id: UserID) -> Result<DeadlineOverrideDTO, Error> {
+        let mut o = self.provider.deadline_override(id).await?;
+        o.reject(admin_id, Utc::now())?;
+        self.repos.save_override(&o).await?;
+        log::info!("Deadline override {} rejected by {}", id, admin_id);
+        self.audit_resolution(&o, admin_id, AuditAction::OverrideRejected).await?;
+        self.to_dto(&o).await
+    }
+
+    async fn audit_resolution(
+        &self,
+        o: &DeadlineOverride,
+        admin_id: UserID,
+        action: AuditAction,
+    ) -> Result<(), Error> {
+        let entry = AuditEntry::new(admin_id, action, Some(o.user_id()))
+            .with_change(AuditChange::new(
+                "status",
+                Some("pending".to_string()),
+                Some(format!("{:?}", o.status()).to_lowercase()),
+            ))
+            .with_change(AuditChange::added("deadline_override", o.id()));
+        self.audit.append(&entry).await
+    }
+
+    async fn to_dto(&self, o: &DeadlineOverride) -> Result<DeadlineOverrideDTO, Error> {
+        let user = self.user_provider.user(o.user_id()).await?;
+        let deadline = self
+            .deadline_policy
+            .deadline_date(*user.arrival_date(), user.citizenship());
+        Ok(DeadlineOverrideDTO::new(o, &user, deadline))
+    }
+}
@@ -0,0 +1,150 @@
+use chrono::{Days, NaiveDate, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{NotificationLog, UnreservedUsersProvider};
+use crate::domain::models::UserID;
+use crate::domain::services::DeadlinePolicy;
+use crate::usecases::UserDTO;
+
+/// За сколько дней до окончания срока подачи документов отправляются предупреждения. Каждое
+/// следующее предупреждение настойчивее предыдущего, последнее - в день окончания срока.
+pub const DEADLINE_WARNING_DAYS: [u64; 4] = [7, 3, 1, 0];
+
+/// Сколько дней после окончания срока студент попадает в сводку для администраторов.
+pub const MISSED_DEADLINE_DAYS: u64 = 14;
+
+pub struct DeadlineWarningDTO {
+    pub user_id: UserID,
+    pub deadline: NaiveDate,
+    pub days_left: u64,
+    /// Порог из `DEADLINE_WARNING_DAYS`, к которому относится предупреждение.
+    pub level: u64,
+}
+
+impl DeadlineWarningDTO {
+    fn key(&self) -> String {
+        format!("deadline:{}:{}", self.level, self.deadline)
+    }
+}
+
+pub struct MissedDeadlineDTO {
+    pub user: UserDTO,
+    pub deadline: NaiveDate,
+}
+
+#[derive(Clone)]
+pub struct DeadlineWarningsUseCase {
+    deadline_policy: Arc<dyn DeadlinePolicy>,
+    provider: Arc<dyn UnreservedUsersProvider>,
+    log: Arc<dyn NotificationLog>,
+}
+
+impl DeadlineWarningsUseCase {
+    pub fn new(
+        deadline_policy: Arc<dyn DeadlinePolicy>,
+        provider: Arc<dyn UnreservedUsersProvider>,
+        log: Arc<dyn NotificationLog>,
+    ) -> Self {
+        Self {
+            deadline_policy,
+            provider,
+            log,
+        }
+    }
+
+    /// Возвращает ещё не отправленные предупреждения студентам, которые так и не записались на
+    /// первичную регистрацию, хотя срок подачи документов подходит к концу. Если студенту положено
+    /// сразу несколько предупреждений, возвращается только самое срочное.
+    pub async fn due_warnings(&self) -> Result<Vec<DeadlineWarningDTO>, Error> {
+        let today = Utc::now().date_naive();
+        let users = self.provider.unreserved_users().await?;
+
+        let mut res = Vec::new();
+        for user in users.iter() {
+            let deadline = self
+                .deadline_policy
+                .deadline_date(*user.arrival_date(), user.citizenship());
+            if deadline < today {
+                continue;
+            }
+            let days_left = (deadline - today).num_days() as u64;
+            let Some(level) = warning_level(days_left) else {
+                continue;
+            };
+            let warning = DeadlineWarningDTO {
+                user_id: user.id(),
+                deadline,
+                days_left,
+                level,
+            };
+            if !self.log.is_notified(user.id(), &warning.key()).await? {
+                res.push(warning);
+            }
+        }
+        Ok(res)
+    }
+
+    pub async fn mark_sent(&self, warning: &DeadlineWarningDTO) -> Result<(), Error> {
+        self.log.mark_notified(warning.user_id, &warning.key()).await
+    }
+
+    /// Проверяет, отправлена ли администраторам сводка о пропущенных сроках за день `date`.
+    pub async fn is_summary_sent(&self, date: NaiveDate) -> Result<bool, Error> {
+        self.log.is_sent(&summary_key(date)).await
+    }
+
+    pub async fn mark_summary_sent(&self, date: NaiveDate) -> Result<(), Error> {
+        self.log.mark_sent(&summary_key(date)).await
+    }
+
+    /// Возвращает студентов, у которых срок подачи документов на первичную регистрацию истёк за
+    /// последние `MISSED_DEADLINE_DAYS` дней, а записи на приём так и нет.
+    pub async fn missed_deadlines(&self) -> Result<Vec<MissedDeadlineDTO>, Error> {
+        let today = Utc::now().date_naive();
+        let since = today - Days::new(MISSED_DEADLINE_DAYS);
+        let users = self.provider.unreserved_users().await?;
+
+        let mut res: Vec<MissedDeadlineDTO> = users
+            .iter()
+            .filter_map(|user| {
+                let deadline = self
+                    .deadline_policy
+                    .deadline_date(*user.arrival_date(), user.citizenship());
+                (deadline >= since && deadline < today).then(|| MissedDeadlineDTO {
+                    user: user.into(),
+                    deadline,
+                })
+            })
+            .collect();
+        res.sort_by_key(|m| m.deadline);
+        Ok(res)
+    }
+}
+
+fn summary_key(date: NaiveDate) -> String {
+    format!("missed_deadlines:{}", date)
+}
+
+fn warning_level(days_left: u64) -> Option<u64> {
+    DEADLINE_WARNING_DAYS
+        .iter()
+        .copied()
+        .filter(|&level| days_left <= level)
+        .min()
+}
+
+#[cfg(test)]
+mod deadline_warnings_tests {
+    use super::*;
+
+    #[test]
+    fn test_warning_levels() {
+        // (WHEN дней до срока, THEN уровень предупреждения)
+        let cases = [(10, None), (7, Some(7)), (4, Some(7)), (3, Some(3)), (1, Some(1)), (0, Some(0))];
+
+        for (days_left, level) in cases {
+            assert_eq!(warning_level(days_left), level, "days_left = {}", days_left);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, ConsentProvider, ConsentRepository, DocumentStorage, DocumentsProvider,
+    UserProvider, UserRepository,
+};
+use crate::domain::models::{AuditAction, AuditEntry, UserID};
+
+#[derive(Clone)]
+pub struct DeleteUserUseCase {
+    user_provider: Arc<dyn UserProvider>,
+    user_repos: Arc<dyn UserRepository>,
+    documents_provider: Arc<dyn DocumentsProvider>,
+    storage: Arc<dyn DocumentStorage>,
+    consent_provider: Arc<dyn ConsentProvider>,
+    consent_repos: Arc<dyn ConsentRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl DeleteUserUseCase {
+    pub fn new(
+        user_provider: Arc<dyn UserProvider>,
+        user_repos: Arc<dyn UserRepository>,
+        documents_provider: Arc<dyn DocumentsProvider>,
+        storage: Arc<dyn DocumentStorage>,
+        consent_provider: Arc<dyn ConsentProvider>,
+        consent_repos: Arc<dyn ConsentRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            user_provider,
+            user_repos,
+            documents_provider,
+            storage,
+            consent_provider,
+            consent_repos,
+            audit,
+        }
+    }
+
+    /// Удаляет пользователя по его просьбе. Записи, отмены, документы и запросы удаляются вместе
+    /// с ним, согласие отзывается. В журнале аудита остаются его записи с Telegram ID, временем
+    /// слотов и названиями изменённых полей, но без персональных данных.
+    pub async fn delete(&self, id: UserID) -> Result<(), Error> {
+        self.user_provider.user(id).await?;
+
+        // Файлы удаляются до пользователя: после удаления строки документов их не найти.
+        for document in self.documents_provider.user_documents(id).await? {
+            self.storage.delete(&document.storage_key()).await?;
+        }
+        self.user_repos.delete_user(id).await?;
+
+        if let Some(mut consent) = self.consent_provider.consent(id).await? {
+            consent.revoke(Utc::now());
+            self.consent_repos.save_consent(&consent).await?;
+        }
+        let entry = AuditEntry::new(id, AuditAction::UserDeleted, Some(id));
+        self.audit.append(&entry).await?;
+        log::info!("User {} deleted their data", id);
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{AuditLog, DocumentStorage, DocumentsProvider};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, UserID};
+use crate::usecases::DocumentFileDTO;
+
+#[derive(Clone)]
+pub struct DocumentsUseCase {
+    provider: Arc<dyn DocumentsProvider>,
+    storage: Arc<dyn DocumentStorage>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl DocumentsUseCase {
+    pub fn new(
+        provider: Arc<dyn DocumentsProvider>,
+        storage: Arc<dyn DocumentStorage>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            provider,
+            storage,
+            audit,
+        }
+    }
+
+    /// Возвращает документы, загруженные к записи. Просмотр записывается в журнал аудита
+    /// от имени сотрудника `actor`.
+    pub async fn documents(
+        &self,
+        actor: UserID,
+        user_id: UserID,
+        slot_start: DateTime<Utc>,
+    ) -> Result<Vec<DocumentFileDTO>, Error> {
+        let documents = self.provider.documents(user_id, slot_start).await?;
+        let mut entry = AuditEntry::new(actor, AuditAction::DocumentsViewed, Some(user_id))
+            .with_change(AuditChange::added("slot_start", slot_start.to_rfc3339()));
+        for document in documents.iter() {
+            entry = entry.with_change(AuditChange::added("document", document.kind().as_str()));
+        }
+        self.audit.append(&entry).await?;
+
+        let mut res = Vec::with_capacity(documents.len());
+        for document in documents {
+            let data = self.storage.get(&document.storage_key()).await?;
+            res.push(DocumentFileDTO {
+                kind: document.kind(),
+                file_name: document.file_name().to_string(),
+                data,
+            });
+        }
+        Ok(res)
+    }
+}
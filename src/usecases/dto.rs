@@ -1,7 +1,9 @@
 use crate::domain::models::{
-    Citizenship, OnlyCyrillic, OnlyLatin, Service, Slot, User, UserID, Username,
+    AuditAction, AuditChange, AuditEntry, Citizenship, Consent, DeadlineOverride, Document,
+    DocumentKind, ForecastDay, OnlyCyrillic, OnlyLatin, Passport, Service, Sex, Slot, StaffMember,
+    StaffRole, User, UserID, Username,
 };
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, NaiveTime, Utc, Weekday};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Debug)]
@@ -12,6 +14,12 @@ pub struct UserDTO {
     pub full_name_cyr: OnlyCyrillic,
     pub citizenship: Citizenship,
     pub arrival_date: NaiveDate,
+    pub visa_expiry: Option<NaiveDate>,
+    pub registration_expiry: Option<NaiveDate>,
+    pub birth_date: Option<NaiveDate>,
+    pub sex: Option<Sex>,
+    pub passport: Option<Passport>,
+    pub place_of_stay: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -24,6 +32,7 @@ pub struct ReservationDTO {
     pub slot_start: DateTime<Utc>,
     pub slot_end: DateTime<Utc>,
     pub service: Service,
+    pub user_id: UserID,
     pub username: String,
     pub user_name_lat: String,
     pub user_name_cyr: String,
@@ -31,6 +40,184 @@ pub struct ReservationDTO {
     pub arrival_date: NaiveDate,
 }
 
+/// Отменённая запись студента.
+pub struct CancelledReservationDTO {
+    pub slot_start: DateTime<Utc>,
+    pub slot_end: DateTime<Utc>,
+    pub service: Service,
+}
+
+/// Запись в личном календаре студента.
+pub struct CalendarReservationDTO {
+    pub user_id: UserID,
+    pub slot_start: DateTime<Utc>,
+    pub slot_end: DateTime<Utc>,
+    pub service: Service,
+}
+
+/// Записи одного дня в выгрузке за период.
+pub struct DayReservationsDTO {
+    pub date: NaiveDate,
+    pub reservations: Vec<ReservationDTO>,
+}
+
+/// Слот в расписании на день: вместе со свободными местами.
+pub struct ScheduleSlotDTO {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub max_size: usize,
+    pub reservations: Vec<ReservationDTO>,
+}
+
+/// Данные для уведомления о прибытии студента, записанного на регистрацию.
+pub struct ArrivalNoticeDTO {
+    pub slot_start: DateTime<Utc>,
+    pub service: Service,
+    pub user: UserDTO,
+    /// Незаполненные студентом поля уведомления.
+    pub missing_fields: Vec<&'static str>,
+}
+
+/// Статистика записей одного дня.
+pub struct DayStatsDTO {
+    pub date: NaiveDate,
+    /// Число мест во всех слотах дня.
+    pub capacity: usize,
+    pub reserved: usize,
+    pub attended: usize,
+    pub cancelled: usize,
+    /// Неизвестно, если в этот день приход не отмечали.
+    pub no_shows: Option<usize>,
+}
+
+/// Статистика записей за период.
+pub struct StatsDTO {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Рабочие дни периода и дни, в которые были записи или отмены, по порядку.
+    pub days: Vec<DayStatsDTO>,
+    pub by_service: Vec<(Service, usize)>,
+    /// По убыванию числа записей.
+    pub by_citizenship: Vec<(Citizenship, usize)>,
+    pub average_lead_time: Option<Duration>,
+}
+
+impl StatsDTO {
+    pub fn capacity(&self) -> usize {
+        self.days.iter().map(|d| d.capacity).sum()
+    }
+
+    pub fn reserved(&self) -> usize {
+        self.days.iter().map(|d| d.reserved).sum()
+    }
+
+    pub fn cancelled(&self) -> usize {
+        self.days.iter().map(|d| d.cancelled).sum()
+    }
+
+    /// Доля занятых мест или None, если в период нет приёма.
+    pub fn utilisation(&self) -> Option<f64> {
+        let capacity = self.capacity();
+        (capacity > 0).then(|| self.reserved() as f64 / capacity as f64)
+    }
+
+    /// Число неявок и число дней, за которые они известны.
+    pub fn no_shows(&self) -> (usize, usize) {
+        self.days
+            .iter()
+            .filter_map(|d| d.no_shows)
+            .fold((0, 0), |(total, days), n| (total + n, days + 1))
+    }
+}
+
+/// Средняя заполненность слотов по дням недели и времени начала за период.
+pub struct HeatmapDTO {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Дни недели, в которые есть приём, с понедельника.
+    pub weekdays: Vec<Weekday>,
+    /// Время начала слотов по возрастанию.
+    pub times: Vec<NaiveTime>,
+    /// Доля занятых мест `fill[i][j]` в день недели `weekdays[i]` в слоте `times[j]`.
+    /// Больше 1, если записей больше мест; None, если в это время приёма нет.
+    pub fill: Vec<Vec<Option<f64>>>,
+}
+
+/// Прогноз спроса на приём на ближайшие дни.
+pub struct ForecastDTO {
+    /// Все дни прогноза по порядку, начиная с сегодняшнего.
+    pub days: Vec<ForecastDay>,
+    /// Студенты без записи, у которых срок истёк за последние `MISSED_DEADLINE_DAYS` дней.
+    pub overdue: usize,
+    /// Студенты без записи, у которых срок истекает после последнего дня прогноза.
+    pub later: usize,
+}
+
+impl ForecastDTO {
+    /// Дни, к концу которых не хватит мест для всех, у кого истекает срок.
+    pub fn shortage_days(&self) -> impl Iterator<Item = &ForecastDay> {
+        self.days.iter().filter(|day| day.shortage() > 0)
+    }
+}
+
+pub struct DocumentFileDTO {
+    pub kind: DocumentKind,
+    pub file_name: String,
+    pub data: Vec<u8>,
+}
+
+pub struct DeadlineOverrideDTO {
+    pub id: i64,
+    pub user: UserDTO,
+    pub service: Service,
+    pub deadline: NaiveDate,
+}
+
+impl DeadlineOverrideDTO {
+    pub fn new(o: &DeadlineOverride, user: &User, deadline: NaiveDate) -> Self {
+        Self {
+            id: o.id(),
+            user: user.into(),
+            service: o.service(),
+            deadline,
+        }
+    }
+}
+
+pub struct DeadlineStatusDTO {
+    pub service: Service,
+    pub deadline: NaiveDate,
+    /// Отрицательное значение означает, что срок истёк.
+    pub days_left: i64,
+}
+
+pub struct NextReservationDTO {
+    pub slot_start: DateTime<Utc>,
+    pub service: Service,
+    pub code: String,
+    /// Виды документов и признак того, что документ загружен.
+    pub documents: Vec<(DocumentKind, bool)>,
+}
+
+pub struct StatusDTO {
+    pub deadlines: Vec<DeadlineStatusDTO>,
+    pub next_reservation: Option<NextReservationDTO>,
+}
+
+pub struct StaffMemberDTO {
+    pub id: UserID,
+    pub role: StaffRole,
+}
+
+impl From<&StaffMember> for StaffMemberDTO {
+    fn from(m: &StaffMember) -> Self {
+        Self {
+            id: m.id(),
+            role: m.role(),
+        }
+    }
+}
+
 impl From<&Slot> for FreeSlotDTO {
     fn from(s: &Slot) -> Self {
         Self {
@@ -49,6 +236,87 @@ impl From<&User> for UserDTO {
             full_name_cyr: user.full_name_cyr().clone(),
             citizenship: user.citizenship().clone(),
             arrival_date: user.arrival_date().clone(),
+            visa_expiry: user.visa_expiry(),
+            registration_expiry: user.registration_expiry(),
+            birth_date: user.birth_date(),
+            sex: user.sex(),
+            passport: user.passport().cloned(),
+            place_of_stay: user.place_of_stay().map(|p| p.to_string()),
         }
     }
 }
+
+pub struct AuditEntryDTO {
+    pub at: DateTime<Utc>,
+    pub actor: UserID,
+    pub action: AuditAction,
+    pub target: Option<UserID>,
+    pub changes: Vec<AuditChange>,
+}
+
+impl From<&AuditEntry> for AuditEntryDTO {
+    fn from(e: &AuditEntry) -> Self {
+        Self {
+            at: e.at(),
+            actor: e.actor(),
+            action: e.action(),
+            target: e.target(),
+            changes: e.changes().to_vec(),
+        }
+    }
+}
+
+pub struct ConsentDTO {
+    pub version: u32,
+    pub given_at: DateTime<Utc>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<&Consent> for ConsentDTO {
+    fn from(c: &Consent) -> Self {
+        Self {
+            version: c.version(),
+            given_at: c.given_at(),
+            revoked_at: c.revoked_at(),
+        }
+    }
+}
+
+pub struct DocumentInfoDTO {
+    pub slot_start: DateTime<Utc>,
+    pub kind: DocumentKind,
+    pub file_name: String,
+    pub size: usize,
+    pub uploaded_at: DateTime<Utc>,
+}
+
+impl From<&Document> for DocumentInfoDTO {
+    fn from(d: &Document) -> Self {
+        Self {
+            slot_start: d.slot_start(),
+            kind: d.kind(),
+            file_name: d.file_name().to_string(),
+            size: d.size(),
+            uploaded_at: d.uploaded_at(),
+        }
+    }
+}
+
+/// Все данные, которые бот хранит о пользователе.
+pub struct UserDataDTO {
+    pub user: UserDTO,
+    pub reservations: Vec<(DateTime<Utc>, Service)>,
+    /// Отменённые записи: начало слота, услуга и время отмены.
+    pub cancellations: Vec<(DateTime<Utc>, Service, DateTime<Utc>)>,
+    pub documents: Vec<DocumentInfoDTO>,
+    pub consent: Option<ConsentDTO>,
+    pub audit: Vec<AuditEntryDTO>,
+}
+
+pub struct RetentionReportDTO {
+    pub anonymised: Vec<UserID>,
+    pub deleted: Vec<UserID>,
+    /// Число записей и отмен, отвязанных от пользователей.
+    pub reservations: usize,
+    pub dry_run: bool,
+}
@@ -0,0 +1,70 @@
+use chrono::{Days, NaiveDate, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{ExpiringUsersProvider, NotificationLog};
+use crate::domain::models::{Service, UserID};
+
+pub struct ExpiryReminderDTO {
+    pub user_id: UserID,
+    pub service: Service,
+    pub expiry_date: NaiveDate,
+}
+
+impl ExpiryReminderDTO {
+    fn key(&self) -> String {
+        let service: String = self.service.into();
+        format!("expiry:{}:{}", service, self.expiry_date)
+    }
+}
+
+#[derive(Clone)]
+pub struct ExpiryRemindersUseCase {
+    provider: Arc<dyn ExpiringUsersProvider>,
+    log: Arc<dyn NotificationLog>,
+    days_before: Days,
+}
+
+impl ExpiryRemindersUseCase {
+    pub fn new(
+        provider: Arc<dyn ExpiringUsersProvider>,
+        log: Arc<dyn NotificationLog>,
+        days_before: Days,
+    ) -> Self {
+        Self {
+            provider,
+            log,
+            days_before,
+        }
+    }
+
+    /// Возвращает ещё не отправленные напоминания о продлении визы или регистрации, срок действия
+    /// которых истекает в ближайшие `days_before` дней.
+    pub async fn due_reminders(&self) -> Result<Vec<ExpiryReminderDTO>, Error> {
+        let today = Utc::now().date_naive();
+        let until = today + self.days_before;
+        let users = self.provider.users_with_expiry(until).await?;
+
+        let mut res = Vec::new();
+        for user in users.iter() {
+            for (service, expiry_date) in user.renewals() {
+                if expiry_date < today || expiry_date > until {
+                    continue;
+                }
+                let reminder = ExpiryReminderDTO {
+                    user_id: user.id(),
+                    service,
+                    expiry_date,
+                };
+                if !self.log.is_notified(user.id(), &reminder.key()).await? {
+                    res.push(reminder);
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    pub async fn mark_sent(&self, reminder: &ExpiryReminderDTO) -> Result<(), Error> {
+        self.log.mark_notified(reminder.user_id, &reminder.key()).await
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, AuditLogProvider, ConsentProvider, DocumentsProvider, UserProvider,
+    UserReservationsProvider,
+};
+use crate::domain::models::{AuditAction, AuditEntry, UserID};
+use crate::usecases::{AuditEntryDTO, ConsentDTO, DocumentInfoDTO, UserDataDTO};
+
+#[derive(Clone)]
+pub struct ExportUserDataUseCase {
+    user_provider: Arc<dyn UserProvider>,
+    reservations_provider: Arc<dyn UserReservationsProvider>,
+    documents_provider: Arc<dyn DocumentsProvider>,
+    consent_provider: Arc<dyn ConsentProvider>,
+    audit_provider: Arc<dyn AuditLogProvider>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl ExportUserDataUseCase {
+    pub fn new(
+        user_provider: Arc<dyn UserProvider>,
+        reservations_provider: Arc<dyn UserReservationsProvider>,
+        documents_provider: Arc<dyn DocumentsProvider>,
+        consent_provider: Arc<dyn ConsentProvider>,
+        audit_provider: Arc<dyn AuditLogProvider>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            user_provider,
+            reservations_provider,
+            documents_provider,
+            consent_provider,
+            audit_provider,
+            audit,
+        }
+    }
+
+    /// Собирает все данные пользователя: анкету, записи, в том числе прошедшие и отменённые,
+    /// документы, согласие и журнал аудита.
+    pub async fn export(&self, id: UserID) -> Result<UserDataDTO, Error> {
+        let user = self.user_provider.user(id).await?;
+        let reservations = self
+            .reservations_provider
+            .user_reservations(id, DateTime::<Utc>::UNIX_EPOCH)
+            .await?;
+        let cancellations = self.reservations_provider.user_cancellations(id).await?;
+        let documents = self.documents_provider.user_documents(id).await?;
+        let consent = self.consent_provider.consent(id).await?;
+        let audit = self.audit_provider.user_audit_entries(id, usize::MAX).await?;
+
+        let entry = AuditEntry::new(id, AuditAction::UserDataExported, Some(id));
+        self.audit.append(&entry).await?;
+
+        Ok(UserDataDTO {
+            user: (&user).into(),
+            reservations,
+            cancellations,
+            documents: documents.iter().map(DocumentInfoDTO::from).collect(),
+            consent: consent.as_ref().map(ConsentDTO::from),
+            audit: audit.iter().map(AuditEntryDTO::from).collect(),
+        })
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{Days, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{ReservationStatsProvider, UnreservedUsersProvider};
+use crate::domain::models::{forecast_demand, slot_now, slot_today};
+use crate::domain::services::{DeadlinePolicy, SlotsFactory, WorkingHoursPolicy};
+use crate::usecases::{ForecastDTO, MISSED_DEADLINE_DAYS};
+
+/// Максимальная длина прогноза в днях.
+pub const MAX_FORECAST_DAYS: u64 = 56;
+
+/// Прогнозирует, хватит ли мест, чтобы принять до окончания срока подачи документов всех
+/// студентов, которые ещё не записались.
+#[derive(Clone)]
+pub struct ForecastUseCase {
+    factory: Arc<dyn SlotsFactory>,
+    policy: Arc<dyn WorkingHoursPolicy>,
+    deadline_policy: Arc<dyn DeadlinePolicy>,
+    users: Arc<dyn UnreservedUsersProvider>,
+    provider: Arc<dyn ReservationStatsProvider>,
+}
+
+impl ForecastUseCase {
+    pub fn new(
+        factory: Arc<dyn SlotsFactory>,
+        policy: Arc<dyn WorkingHoursPolicy>,
+        deadline_policy: Arc<dyn DeadlinePolicy>,
+        users: Arc<dyn UnreservedUsersProvider>,
+        provider: Arc<dyn ReservationStatsProvider>,
+    ) -> Self {
+        Self {
+            factory,
+            policy,
+            deadline_policy,
+            users,
+            provider,
+        }
+    }
+
+    /// Возвращает прогноз на `days` дней начиная с сегодняшнего. Свободные места считаются
+    /// по текущему графику работы в ещё не начавшихся слотах, спрос - по срокам подачи
+    /// документов студентов, не записавшихся ни на один приём. Студенты, которые ещё не
+    /// зарегистрировались в боте, в прогноз не попадают.
+    pub async fn forecast(&self, days: u64) -> Result<ForecastDTO, Error> {
+        if days == 0 || days > MAX_FORECAST_DAYS {
+            return Err(Error::InvalidValue(format!("invalid forecast length: {}", days)));
+        }
+        let now = Utc::now();
+        let today = slot_today(now);
+        let slots_now = slot_now(now);
+        let to = today + Days::new(days - 1);
+
+        let counts = self.provider.slot_reservation_counts(today, to).await?;
+        let free: Vec<_> = today
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .map(|date| {
+                let free = self
+                    .factory
+                    .create_all(date, self.policy.as_ref())
+                    .iter()
+                    .filter(|slot| slot.start() > slots_now)
+                    .map(|slot| {
+                        let reserved = counts.get(&slot.start()).copied().unwrap_or(0);
+                        slot.max_size().saturating_sub(reserved)
+                    })
+                    .sum();
+                (date, free)
+            })
+            .collect();
+
+        let deadlines: Vec<_> = self
+            .users
+            .unreserved_users()
+            .await?
+            .iter()
+            .map(|user| {
+                self.deadline_policy
+                    .deadline_date(*user.arrival_date(), user.citizenship())
+            })
+            .collect();
+        let since = today - Days::new(MISSED_DEADLINE_DAYS);
+        Ok(ForecastDTO {
+            days: forecast_demand(&free, &deadlines),
+            overdue: deadlines.iter().filter(|d| **d >= since && **d < today).count(),
+            later: deadlines.iter().filter(|d| **d > to).count(),
+        })
+    }
+}
@@ -1,27 +1,67 @@
 mod app;
+mod audit;
+mod calendar_feed;
+mod calendar_sync;
 mod cancel_reservation;
 mod check_deadline;
 mod check_registered;
+mod consent;
 mod days_with_free_slots;
+mod deadline_overrides;
+mod deadline_warnings;
+mod delete_user;
+mod documents;
 mod dto;
+mod expiry_reminders;
+mod export_user_data;
+mod forecast;
 mod free_slots;
 mod get_user;
+mod purge_documents;
 mod register_user;
+mod request_deadline_override;
 mod reserve_slot;
 mod reservations;
+mod retention;
+mod revoke_consent;
+mod search_users;
+mod staff;
+mod stats;
+mod status;
 mod update_user;
+mod upload_document;
 mod check_admin;
 
 pub use app::*;
+pub use audit::*;
+pub use calendar_feed::*;
+pub use calendar_sync::*;
 pub use cancel_reservation::*;
 pub use check_admin::*;
 pub use check_deadline::*;
 pub use check_registered::*;
+pub use consent::*;
 pub use days_with_free_slots::*;
+pub use deadline_overrides::*;
+pub use deadline_warnings::*;
+pub use delete_user::*;
+pub use documents::*;
 pub use dto::*;
+pub use expiry_reminders::*;
+pub use export_user_data::*;
+pub use forecast::*;
 pub use free_slots::*;
 pub use get_user::*;
+pub use purge_documents::*;
 pub use register_user::*;
+pub use request_deadline_override::*;
 pub use reserve_slot::*;
 pub use reservations::*;
+pub use retention::*;
+pub use revoke_consent::*;
+pub use search_users::*;
+pub use staff::*;
+pub use stats::*;
+pub use status::*;
 pub use update_user::*;
+pub use upload_document::*;
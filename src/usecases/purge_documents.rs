@@ -0,0 +1,41 @@
+use chrono::{Duration, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{DocumentRepository, DocumentStorage, ExpiredDocumentsProvider};
+
+/// Документы хранятся в течение суток после начала приёма или отмены записи: если студент за
+/// это время снова записывается, документы переходят к новой записи.
+const DOCUMENTS_TTL: Duration = Duration::days(1);
+
+#[derive(Clone)]
+pub struct PurgeDocumentsUseCase {
+    provider: Arc<dyn ExpiredDocumentsProvider>,
+    storage: Arc<dyn DocumentStorage>,
+    repos: Arc<dyn DocumentRepository>,
+}
+
+impl PurgeDocumentsUseCase {
+    pub fn new(
+        provider: Arc<dyn ExpiredDocumentsProvider>,
+        storage: Arc<dyn DocumentStorage>,
+        repos: Arc<dyn DocumentRepository>,
+    ) -> Self {
+        Self {
+            provider,
+            storage,
+            repos,
+        }
+    }
+
+    /// Удаляет документы прошедших и отменённых записей, возвращает количество удалённых.
+    pub async fn purge(&self) -> Result<usize, Error> {
+        let before = Utc::now() - DOCUMENTS_TTL;
+        let documents = self.provider.expired_documents(before).await?;
+        for document in documents.iter() {
+            self.storage.delete(&document.storage_key()).await?;
+            self.repos.delete_document(document).await?;
+        }
+        Ok(documents.len())
+    }
+}
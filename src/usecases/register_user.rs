@@ -3,7 +3,10 @@ use std::sync::Arc;
 
 use crate::domain::Error;
 use crate::domain::interfaces::UserRepository;
-use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, User, UserID, Username};
+use crate::domain::models::{
+    AuditAction, AuditEntry, Citizenship, OnlyCyrillic, OnlyLatin, User, UserID, Username,
+    user_changes,
+};
 
 pub struct RegisterUserRequest {
     pub id: UserID,
@@ -12,6 +15,8 @@ pub struct RegisterUserRequest {
     pub full_name_cyr: OnlyCyrillic,
     pub citizenship: Citizenship,
     pub arrival_date: NaiveDate,
+    pub visa_expiry: Option<NaiveDate>,
+    pub registration_expiry: Option<NaiveDate>,
 }
 
 #[derive(Clone)]
@@ -25,7 +30,7 @@ impl RegisterUserUseCase {
     }
 
     pub async fn register(&self, req: RegisterUserRequest) -> Result<(), Error> {
-        let user = User::new(
+        let mut user = User::new(
             req.id,
             req.username,
             req.full_name_lat,
@@ -33,7 +38,10 @@ impl RegisterUserUseCase {
             req.citizenship,
             req.arrival_date,
         );
-        self.repos.save_user(user).await?;
-        Ok(())
+        user.set_visa_expiry(req.visa_expiry);
+        user.set_registration_expiry(req.registration_expiry);
+        let entry = AuditEntry::new(user.id(), AuditAction::UserRegistered, Some(user.id()))
+            .with_changes(user_changes(None, Some(&user)));
+        self.repos.save_user(user, &entry).await
     }
 }
@@ -0,0 +1,69 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, DeadlineOverrideProvider, DeadlineOverrideRepository, UserProvider,
+};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, Service, UserID};
+use crate::domain::services::DeadlinePolicy;
+use crate::usecases::DeadlineOverrideDTO;
+
+#[derive(Clone)]
+pub struct RequestDeadlineOverrideUseCase {
+    deadline_policy: Arc<dyn DeadlinePolicy>,
+    user_provider: Arc<dyn UserProvider>,
+    provider: Arc<dyn DeadlineOverrideProvider>,
+    repos: Arc<dyn DeadlineOverrideRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl RequestDeadlineOverrideUseCase {
+    pub fn new(
+        deadline_policy: Arc<dyn DeadlinePolicy>,
+        user_provider: Arc<dyn UserProvider>,
+        provider: Arc<dyn DeadlineOverrideProvider>,
+        repos: Arc<dyn DeadlineOverrideRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            deadline_policy,
+            user_provider,
+            provider,
+            repos,
+            audit,
+        }
+    }
+
+    /// Создаёт запрос сотрудникам на запись после окончания срока подачи документов. Пока
+    /// предыдущий запрос не рассмотрен или не использован, новый создать нельзя.
+    pub async fn request(
+        &self,
+        user_id: UserID,
+        service: Service,
+    ) -> Result<DeadlineOverrideDTO, Error> {
+        let user = self.user_provider.user(user_id).await?;
+        if self.provider.active_override(user_id, service).await?.is_some() {
+            return Err(Error::OverrideAlreadyRequested);
+        }
+        let o = self
+            .repos
+            .create_override(user_id, service, Utc::now())
+            .await?;
+        log::info!(
+            "Deadline override {} requested by {} for {:?}",
+            o.id(),
+            user_id,
+            service
+        );
+        let entry = AuditEntry::new(user_id, AuditAction::OverrideRequested, Some(user_id))
+            .with_change(AuditChange::added("deadline_override", o.id()))
+            .with_change(AuditChange::added("service", Into::<String>::into(service)));
+        self.audit.append(&entry).await?;
+
+        let deadline = self
+            .deadline_policy
+            .deadline_date(*user.arrival_date(), user.citizenship());
+        Ok(DeadlineOverrideDTO::new(&o, &user, deadline))
+    }
+}
@@ -1,16 +1,28 @@
-use chrono::NaiveDate;
+use chrono::{NaiveDate, Utc};
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::domain::Error;
-use crate::domain::interfaces::ReservedSlotsProvider;
+use crate::domain::interfaces::{AttendanceRepository, AuditLog, ReservedSlotsProvider};
+use crate::domain::models::{
+    AuditAction, AuditChange, AuditEntry, Reservation, ReservationStatus, ReservationsFilter,
+    Slot, UserID, reservation_code, slot_now, slot_today,
+};
 use crate::domain::services::{SlotsFactory, WorkingHoursPolicy};
-use crate::usecases::ReservationDTO;
+use crate::usecases::{
+    ArrivalNoticeDTO, DayReservationsDTO, ReservationDTO, ScheduleSlotDTO, UserDTO,
+};
+
+/// Максимальная длина периода выгрузки в днях.
+pub const MAX_EXPORT_DAYS: i64 = 93;
 
 #[derive(Clone)]
 pub struct ReservationsUseCase {
     factory: Arc<dyn SlotsFactory>,
     policy: Arc<dyn WorkingHoursPolicy>,
     provider: Arc<dyn ReservedSlotsProvider>,
+    attendance: Arc<dyn AttendanceRepository>,
+    audit: Arc<dyn AuditLog>,
 }
 
 impl ReservationsUseCase {
@@ -18,32 +30,222 @@ impl ReservationsUseCase {
         factory: Arc<dyn SlotsFactory>,
         policy: Arc<dyn WorkingHoursPolicy>,
         provider: Arc<dyn ReservedSlotsProvider>,
+        attendance: Arc<dyn AttendanceRepository>,
+        audit: Arc<dyn AuditLog>,
     ) -> Self {
         Self {
             factory,
             policy,
             provider,
+            attendance,
+            audit,
         }
     }
 
-    pub async fn reservations(&self, date: NaiveDate) -> Result<Vec<ReservationDTO>, Error> {
+    /// Возвращает записи на день вместе с персональными данными записавшихся. Каждая выгрузка
+    /// записывается в журнал аудита от имени сотрудника `actor`.
+    pub async fn reservations(
+        &self,
+        actor: UserID,
+        date: NaiveDate,
+    ) -> Result<Vec<ReservationDTO>, Error> {
         let slots = self.factory.create_all(date, self.policy.as_ref());
         let slots = self.provider.reserved_slots(slots).await?;
-        let mut res = Vec::new();
-        for slot in slots.into_iter() {
+        let res: Vec<_> = slots
+            .iter()
+            .flat_map(|slot| slot.reservations().iter().map(move |r| to_dto(slot, r)))
+            .collect();
+        let entry = AuditEntry::new(actor, AuditAction::ReservationsExported, None)
+            .with_change(AuditChange::added("date", date))
+            .with_change(AuditChange::added("reservations", res.len()));
+        self.audit.append(&entry).await?;
+        Ok(res)
+    }
+
+    /// Возвращает расписание на день: все слоты по порядку, включая свободные, с записями
+    /// в каждом. Выгрузка записывается в журнал аудита от имени сотрудника `actor`.
+    pub async fn schedule(
+        &self,
+        actor: UserID,
+        date: NaiveDate,
+    ) -> Result<Vec<ScheduleSlotDTO>, Error> {
+        let grid = self.factory.create_all(date, self.policy.as_ref());
+        let reserved: HashMap<_, _> = self
+            .provider
+            .reserved_slots(grid.clone())
+            .await?
+            .into_iter()
+            .map(|slot| (slot.start(), slot))
+            .collect();
+
+        let mut total = 0;
+        let mut schedule: Vec<_> = grid
+            .iter()
+            .map(|slot| {
+                let slot = reserved.get(&slot.start()).unwrap_or(slot);
+                total += slot.reservations().len();
+                ScheduleSlotDTO {
+                    start: slot.start(),
+                    end: slot.interval().end,
+                    max_size: slot.max_size(),
+                    reservations: slot.reservations().iter().map(|r| to_dto(slot, r)).collect(),
+                }
+            })
+            .collect();
+        schedule.sort_by_key(|slot| slot.start);
+
+        let entry = AuditEntry::new(actor, AuditAction::ReservationsExported, None)
+            .with_change(AuditChange::added("date", date))
+            .with_change(AuditChange::added("format", "schedule"))
+            .with_change(AuditChange::added("reservations", total));
+        self.audit.append(&entry).await?;
+        Ok(schedule)
+    }
+
+    /// Отмечает приход студента, записанного на сегодня, по коду записи. Возвращает None,
+    /// если сегодня нет записи с таким кодом.
+    pub async fn check_in(
+        &self,
+        actor: UserID,
+        code: &str,
+    ) -> Result<Option<ReservationDTO>, Error> {
+        let code = code.trim().to_uppercase();
+        let today = slot_today(Utc::now());
+        let slots = self.factory.create_all(today, self.policy.as_ref());
+        let slots = self.provider.reserved_slots(slots).await?;
+        let found = slots.iter().find_map(|slot| {
+            slot.reservations()
+                .iter()
+                .find(|r| reservation_code(r.by().id(), slot.start()) == code)
+                .map(|r| to_dto(slot, r))
+        });
+        let Some(r) = found else {
+            return Ok(None);
+        };
+        if !self.attendance.mark_attended(r.slot_start, r.user_id, Utc::now()).await? {
+            return Ok(None);
+        }
+
+        let entry = AuditEntry::new(actor, AuditAction::ReservationAttended, Some(r.user_id))
+            .with_change(AuditChange::added("slot_start", r.slot_start.to_rfc3339()));
+        self.audit.append(&entry).await?;
+        Ok(Some(r))
+    }
+
+    /// Возвращает данные для уведомлений о прибытии студентов, записанных на день на услуги
+    /// с регистрацией, в порядке записи. Выгрузка записывается в журнал аудита от имени
+    /// сотрудника `actor`.
+    pub async fn arrival_notices(
+        &self,
+        actor: UserID,
+        date: NaiveDate,
+    ) -> Result<Vec<ArrivalNoticeDTO>, Error> {
+        let slots = self.factory.create_all(date, self.policy.as_ref());
+        let mut slots = self.provider.reserved_slots(slots).await?;
+        slots.sort_by_key(|slot| slot.start());
+        let res: Vec<_> = slots
+            .iter()
+            .flat_map(|slot| {
+                slot.reservations()
+                    .iter()
+                    .filter(|r| r.service().needs_arrival_notice())
+                    .map(move |r| ArrivalNoticeDTO {
+                        slot_start: slot.start(),
+                        service: *r.service(),
+                        user: UserDTO::from(r.by()),
+                        missing_fields: r.by().missing_arrival_notice_fields(),
+                    })
+            })
+            .collect();
+
+        let entry = AuditEntry::new(actor, AuditAction::ReservationsExported, None)
+            .with_change(AuditChange::added("date", date))
+            .with_change(AuditChange::added("format", "arrival_notices"))
+            .with_change(AuditChange::added("reservations", res.len()));
+        self.audit.append(&entry).await?;
+        Ok(res)
+    }
+
+    /// Возвращает записи за период с `from` по `to` включительно, подходящие под фильтр,
+    /// сгруппированные по дням. Дни без записей пропускаются. Выгрузка записывается
+    /// в журнал аудита от имени сотрудника `actor`.
+    pub async fn reservations_between(
+        &self,
+        actor: UserID,
+        from: NaiveDate,
+        to: NaiveDate,
+        filter: &ReservationsFilter,
+    ) -> Result<Vec<DayReservationsDTO>, Error> {
+        if from > to {
+            return Err(Error::InvalidValue(format!("empty period: {} - {}", from, to)));
+        }
+        if (to - from).num_days() >= MAX_EXPORT_DAYS {
+            return Err(Error::InvalidValue(format!(
+                "period longer than {} days: {} - {}",
+                MAX_EXPORT_DAYS, from, to
+            )));
+        }
+
+        let slots: Vec<Slot> = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .flat_map(|date| self.factory.create_all(date, self.policy.as_ref()))
+            .collect();
+        let mut slots = self.provider.reserved_slots(slots).await?;
+        slots.sort_by_key(|slot| slot.start());
+
+        let now = slot_now(Utc::now());
+        let mut days: Vec<DayReservationsDTO> = Vec::new();
+        let mut total = 0;
+        for slot in slots.iter() {
+            let date = slot.start().date_naive();
             for r in slot.reservations() {
-                res.push(ReservationDTO {
-                    slot_start: slot.start(),
-                    slot_end: slot.interval().end,
-                    service: r.service().clone(),
-                    username: r.by().username().as_str().to_string(),
-                    user_name_lat: r.by().full_name_lat().as_str().to_string(),
-                    user_name_cyr: r.by().full_name_cyr().as_str().to_string(),
-                    citizenship: r.by().citizenship().clone(),
-                    arrival_date: r.by().arrival_date().clone(),
-                })
+                if !filter.matches(r, slot.start(), now) {
+                    continue;
+                }
+                if days.last().is_none_or(|day| day.date != date) {
+                    days.push(DayReservationsDTO {
+                        date,
+                        reservations: Vec::new(),
+                    });
+                }
+                days.last_mut().unwrap().reservations.push(to_dto(slot, r));
+                total += 1;
             }
         }
-        Ok(res)
+
+        let mut entry = AuditEntry::new(actor, AuditAction::ReservationsExported, None)
+            .with_change(AuditChange::added("from", from))
+            .with_change(AuditChange::added("to", to));
+        if let Some(service) = filter.service {
+            entry = entry.with_change(AuditChange::added("service", Into::<String>::into(service)));
+        }
+        if let Some(citizenship) = &filter.citizenship {
+            entry = entry.with_change(AuditChange::added("citizenship", citizenship.as_str()));
+        }
+        if let Some(status) = filter.status {
+            let status = match status {
+                ReservationStatus::Upcoming => "upcoming",
+                ReservationStatus::Past => "past",
+            };
+            entry = entry.with_change(AuditChange::added("status", status));
+        }
+        entry = entry.with_change(AuditChange::added("reservations", total));
+        self.audit.append(&entry).await?;
+        Ok(days)
+    }
+}
+
+fn to_dto(slot: &Slot, r: &Reservation) -> ReservationDTO {
+    ReservationDTO {
+        slot_start: slot.start(),
+        slot_end: slot.interval().end,
+        service: r.service().clone(),
+        user_id: r.by().id(),
+        username: r.by().username().as_str().to_string(),
+        user_name_lat: r.by().full_name_lat().as_str().to_string(),
+        user_name_cyr: r.by().full_name_cyr().as_str().to_string(),
+        citizenship: r.by().citizenship().clone(),
+        arrival_date: r.by().arrival_date().clone(),
     }
 }
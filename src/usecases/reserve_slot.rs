@@ -2,33 +2,43 @@ use chrono::{DateTime, Utc};
 use std::sync::Arc;
 
 use crate::domain::Error;
-use crate::domain::interfaces::{AvailableSlotsProvider, SlotsRepository, UserProvider};
-use crate::domain::models::{Service, UserID};
-use crate::domain::services::{SlotsFactory, WorkingHoursPolicy};
+use crate::domain::interfaces::{
+    AvailableSlotsProvider, DeadlineOverrideProvider, SlotsRepository, UserProvider,
+};
+use crate::domain::models::{
+    AuditAction, AuditChange, AuditEntry, DeadlineOverride, Service, User, UserID,
+};
+use crate::domain::services::{DeadlinePolicy, SlotsFactory, WorkingHoursPolicy};
 
 #[derive(Clone)]
 pub struct ReserveSlotUseCase {
     factory: Arc<dyn SlotsFactory>,
     policy: Arc<dyn WorkingHoursPolicy>,
+    deadline_policy: Arc<dyn DeadlinePolicy>,
     user_provider: Arc<dyn UserProvider>,
     as_provider: Arc<dyn AvailableSlotsProvider>,
     repos: Arc<dyn SlotsRepository>,
+    override_provider: Arc<dyn DeadlineOverrideProvider>,
 }
 
 impl ReserveSlotUseCase {
     pub fn new(
         factory: Arc<dyn SlotsFactory>,
         policy: Arc<dyn WorkingHoursPolicy>,
+        deadline_policy: Arc<dyn DeadlinePolicy>,
         user_provider: Arc<dyn UserProvider>,
         as_provider: Arc<dyn AvailableSlotsProvider>,
         repos: Arc<dyn SlotsRepository>,
+        override_provider: Arc<dyn DeadlineOverrideProvider>,
     ) -> Self {
         Self {
             factory,
             policy,
+            deadline_policy,
             user_provider,
             as_provider,
             repos,
+            override_provider,
         }
     }
 
@@ -39,6 +49,7 @@ impl ReserveSlotUseCase {
         service: Service,
     ) -> Result<(), Error> {
         let user = self.user_provider.user(user_id).await?;
+        let deadline_override = self.check_deadline(&user, time, service).await?;
 
         let date = time.date_naive();
         let slots = self.factory.create_all(date, self.policy.as_ref());
@@ -51,7 +62,49 @@ impl ReserveSlotUseCase {
         };
 
         slot.reserve(user, service)?;
-        self.repos.save_slot(slot).await?;
+        let mut entry = AuditEntry::new(user_id, AuditAction::ReservationCreated, Some(user_id))
+            .with_change(AuditChange::added("slot_start", time.to_rfc3339()))
+            .with_change(AuditChange::added("service", Into::<String>::into(service)));
+        match deadline_override.as_ref() {
+            // Запрос погашается в той же транзакции, что и запись.
+            Some(o) => {
+                entry = entry.with_change(AuditChange::added("deadline_override", o.id()));
+                self.repos
+                    .save_slot_with_override(slot, o.id(), Utc::now(), &entry)
+                    .await?;
+                log::info!(
+                    "Deadline override {} used by {} for {:?} at {}",
+                    o.id(),
+                    user_id,
+                    service,
+                    time
+                );
+            }
+            None => self.repos.save_slot(slot, &entry).await?,
+        }
         Ok(())
     }
+
+    /// Возвращает одобренный запрос на запись после срока, если слот позже последнего дня подачи
+    /// документов. Без одобренного запроса такая запись запрещена.
+    async fn check_deadline(
+        &self,
+        user: &User,
+        time: DateTime<Utc>,
+        service: Service,
+    ) -> Result<Option<DeadlineOverride>, Error> {
+        if !service.has_deadline() {
+            return Ok(None);
+        }
+        let deadline = self
+            .deadline_policy
+            .deadline_date(*user.arrival_date(), user.citizenship());
+        if time.date_naive() <= deadline {
+            return Ok(None);
+        }
+        match self.override_provider.active_override(user.id(), service).await? {
+            Some(o) if o.is_approved() => Ok(Some(o)),
+            _ => Err(Error::DeadlineExpired(deadline)),
+        }
+    }
 }
@@ -0,0 +1,102 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, DocumentStorage, DocumentsProvider, InactiveUsersProvider, ReservationsRetention,
+    UserRepository,
+};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, UserID, slot_today};
+use crate::domain::services::RetentionPolicy;
+use crate::usecases::RetentionReportDTO;
+
+#[derive(Clone)]
+pub struct RetentionUseCase {
+    policy: RetentionPolicy,
+    provider: Arc<dyn InactiveUsersProvider>,
+    repos: Arc<dyn UserRepository>,
+    reservations: Arc<dyn ReservationsRetention>,
+    documents_provider: Arc<dyn DocumentsProvider>,
+    storage: Arc<dyn DocumentStorage>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl RetentionUseCase {
+    pub fn new(
+        policy: RetentionPolicy,
+        provider: Arc<dyn InactiveUsersProvider>,
+        repos: Arc<dyn UserRepository>,
+        reservations: Arc<dyn ReservationsRetention>,
+        documents_provider: Arc<dyn DocumentsProvider>,
+        storage: Arc<dyn DocumentStorage>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            policy,
+            provider,
+            repos,
+            reservations,
+            documents_provider,
+            storage,
+            audit,
+        }
+    }
+
+    /// Удаляет и обезличивает неактивных пользователей по политике хранения и отвязывает от
+    /// пользователей записи и отмены старше срока обезличивания. При `dry_run` только
+    /// возвращает, что бы это затронуло.
+    pub async fn apply(&self, dry_run: bool) -> Result<RetentionReportDTO, Error> {
+        let now = Utc::now();
+        let today = slot_today(now);
+        let anonymise_before = self.policy.anonymise_before(now);
+        let deleted = self
+            .provider
+            .inactive_users(self.policy.delete_before(now), today, true)
+            .await?;
+        let anonymised = self
+            .provider
+            .inactive_users(anonymise_before, today, false)
+            .await?
+            .into_iter()
+            .filter(|id| !deleted.contains(id))
+            .collect();
+        let mut report = RetentionReportDTO {
+            anonymised,
+            deleted,
+            reservations: 0,
+            dry_run,
+        };
+        if dry_run {
+            report.reservations = self
+                .reservations
+                .identified_reservations(anonymise_before)
+                .await?;
+            return Ok(report);
+        }
+
+        for &id in report.deleted.iter() {
+            self.delete(id).await?;
+        }
+        for &id in report.anonymised.iter() {
+            self.repos.anonymise_user(id, now).await?;
+            let entry = AuditEntry::new(UserID::SYSTEM, AuditAction::UserAnonymised, Some(id))
+                .with_change(AuditChange::added("reason", "retention"));
+            self.audit.append(&entry).await?;
+        }
+        report.reservations = self
+            .reservations
+            .anonymise_reservations(anonymise_before)
+            .await?;
+        Ok(report)
+    }
+
+    async fn delete(&self, id: UserID) -> Result<(), Error> {
+        for document in self.documents_provider.user_documents(id).await? {
+            self.storage.delete(&document.storage_key()).await?;
+        }
+        self.repos.delete_user(id).await?;
+        let entry = AuditEntry::new(UserID::SYSTEM, AuditAction::UserDeleted, Some(id))
+            .with_change(AuditChange::added("reason", "retention"));
+        self.audit.append(&entry).await
+    }
+}
@@ -0,0 +1,105 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, ConsentProvider, ConsentRepository, DocumentRepository, DocumentStorage,
+    DocumentsProvider, ReservedSlotProvider, SlotsRepository, UserProvider, UserRepository,
+    UserReservationsProvider,
+};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, UserID, user_changes};
+use crate::domain::services::SlotsFactory;
+
+#[derive(Clone)]
+pub struct RevokeConsentUseCase {
+    factory: Arc<dyn SlotsFactory>,
+    consent_provider: Arc<dyn ConsentProvider>,
+    consent_repos: Arc<dyn ConsentRepository>,
+    user_provider: Arc<dyn UserProvider>,
+    user_repos: Arc<dyn UserRepository>,
+    reservations_provider: Arc<dyn UserReservationsProvider>,
+    slot_provider: Arc<dyn ReservedSlotProvider>,
+    slots_repos: Arc<dyn SlotsRepository>,
+    documents_provider: Arc<dyn DocumentsProvider>,
+    storage: Arc<dyn DocumentStorage>,
+    documents_repos: Arc<dyn DocumentRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl RevokeConsentUseCase {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        factory: Arc<dyn SlotsFactory>,
+        consent_provider: Arc<dyn ConsentProvider>,
+        consent_repos: Arc<dyn ConsentRepository>,
+        user_provider: Arc<dyn UserProvider>,
+        user_repos: Arc<dyn UserRepository>,
+        reservations_provider: Arc<dyn UserReservationsProvider>,
+        slot_provider: Arc<dyn ReservedSlotProvider>,
+        slots_repos: Arc<dyn SlotsRepository>,
+        documents_provider: Arc<dyn DocumentsProvider>,
+        storage: Arc<dyn DocumentStorage>,
+        documents_repos: Arc<dyn DocumentRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            factory,
+            consent_provider,
+            consent_repos,
+            user_provider,
+            user_repos,
+            reservations_provider,
+            slot_provider,
+            slots_repos,
+            documents_provider,
+            storage,
+            documents_repos,
+            audit,
+        }
+    }
+
+    /// Отзывает согласие на обработку персональных данных: отменяет будущие записи, удаляет
+    /// документы и обезличивает данные пользователя. Прошедшие записи остаются в истории без
+    /// персональных данных. Возвращает количество отменённых записей.
+    pub async fn revoke(&self, id: UserID) -> Result<usize, Error> {
+        let now = Utc::now();
+        let user = match self.user_provider.user(id).await {
+            Ok(user) => Some(user),
+            Err(Error::UserNotFound(_)) => None,
+            Err(err) => return Err(err),
+        };
+
+        let mut cancelled = 0;
+        if let Some(user) = user {
+            let reservations = self.reservations_provider.user_reservations(id, now).await?;
+            for (start, service) in reservations {
+                let mut slot = self.slot_provider.reserved_slot(self.factory.create(start)).await?;
+                slot.cancel(id)?;
+                let entry = AuditEntry::new(id, AuditAction::ReservationCancelled, Some(id))
+                    .with_change(AuditChange::removed("slot_start", start.to_rfc3339()))
+                    .with_change(AuditChange::removed("service", Into::<String>::into(service)));
+                self.slots_repos.save_slot(&slot, &entry).await?;
+                cancelled += 1;
+            }
+
+            for document in self.documents_provider.user_documents(id).await? {
+                self.storage.delete(&document.storage_key()).await?;
+                self.documents_repos.delete_document(&document).await?;
+            }
+
+            self.user_repos.anonymise_user(id, now).await?;
+            let entry = AuditEntry::new(id, AuditAction::UserAnonymised, Some(id))
+                .with_changes(user_changes(Some(&user), None));
+            self.audit.append(&entry).await?;
+        }
+
+        if let Some(mut consent) = self.consent_provider.consent(id).await? {
+            consent.revoke(now);
+            self.consent_repos.save_consent(&consent).await?;
+        }
+        let entry = AuditEntry::new(id, AuditAction::ConsentRevoked, Some(id));
+        self.audit.append(&entry).await?;
+        log::info!("{} revoked consent, {} reservations cancelled", id, cancelled);
+        Ok(cancelled)
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{AuditLog, UserSearchProvider};
+use crate::domain::models::{AuditAction, AuditEntry, UserID};
+use crate::usecases::UserDTO;
+
+#[derive(Clone)]
+pub struct SearchUsersUseCase {
+    provider: Arc<dyn UserSearchProvider>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl SearchUsersUseCase {
+    pub fn new(provider: Arc<dyn UserSearchProvider>, audit: Arc<dyn AuditLog>) -> Self {
+        Self { provider, audit }
+    }
+
+    /// Ищет пользователей по ФИО. Каждый найденный пользователь записывается в журнал
+    /// аудита как просмотр его данных сотрудником `actor`.
+    pub async fn search(&self, actor: UserID, full_name: &str) -> Result<Vec<UserDTO>, Error> {
+        let users = self.provider.find_users(full_name).await?;
+        for user in users.iter() {
+            let entry = AuditEntry::new(actor, AuditAction::UserLookedUp, Some(user.id()));
+            self.audit.append(&entry).await?;
+        }
+        Ok(users.iter().map(UserDTO::from).collect())
+    }
+}
@@ -0,0 +1,112 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{AdminProvider, AuditLog, StaffRepository};
+use crate::domain::models::{
+    AuditAction, AuditChange, AuditEntry, StaffMember, StaffRole, UserID,
+};
+use crate::usecases::StaffMemberDTO;
+
+#[derive(Clone)]
+pub struct StaffUseCase {
+    /// Роли с учётом группы сотрудников.
+    provider: Arc<dyn AdminProvider>,
+    /// Только роли, назначенные через бота.
+    granted: Arc<dyn AdminProvider>,
+    repos: Arc<dyn StaffRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl StaffUseCase {
+    pub fn new(
+        provider: Arc<dyn AdminProvider>,
+        granted: Arc<dyn AdminProvider>,
+        repos: Arc<dyn StaffRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            provider,
+            granted,
+            repos,
+            audit,
+        }
+    }
+
+    pub async fn staff(&self) -> Result<Vec<StaffMemberDTO>, Error> {
+        let staff = self.provider.staff().await?;
+        Ok(staff.iter().map(StaffMemberDTO::from).collect())
+    }
+
+    /// Назначает владельцами `owners`, если через бота ещё не назначен ни один владелец. Так при
+    /// первом запуске появляется тот, кто может назначать роли остальным. Группа сотрудников
+    /// не учитывается: её администраторы не мешают назначению, а недоступность Telegram не
+    /// мешает запуску.
+    pub async fn bootstrap_owners(&self, owners: &[UserID]) -> Result<(), Error> {
+        let staff = self.granted.staff().await?;
+        if staff.iter().any(|m| m.role() == StaffRole::Owner) {
+            return Ok(());
+        }
+        for &id in owners {
+            let member = StaffMember::new(id, StaffRole::Owner);
+            self.repos.save_staff_member(&member, None, Utc::now()).await?;
+            log::info!("Granted owner role to {}", id);
+            let entry = AuditEntry::new(id, AuditAction::StaffRoleGranted, Some(id))
+                .with_change(AuditChange::added("role", StaffRole::Owner.as_str()));
+            self.audit.append(&entry).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn grant(&self, by: UserID, id: UserID, role: StaffRole) -> Result<(), Error> {
+        if role != StaffRole::Owner {
+            self.ensure_not_last_owner(id).await?;
+        }
+        let before = self.provider.role(id).await?;
+        let member = StaffMember::new(id, role);
+        self.repos.save_staff_member(&member, Some(by), Utc::now()).await?;
+        log::info!("{} granted {} role to {}", by, role.as_str(), id);
+        let entry = AuditEntry::new(by, AuditAction::StaffRoleGranted, Some(id)).with_change(
+            AuditChange::new(
+                "role",
+                before.map(|r| r.as_str().to_string()),
+                Some(role.as_str().to_string()),
+            ),
+        );
+        self.audit.append(&entry).await
+    }
+
+    /// Снимает с сотрудника роль, назначенную через бота. Возвращает `false`, если такой роли
+    /// не было. Роль из группы сотрудников снять нельзя: она пропадает, только когда
+    /// пользователь покидает группу или перестаёт быть её администратором.
+    pub async fn revoke(&self, by: UserID, id: UserID) -> Result<bool, Error> {
+        let Some(before) = self.granted.role(id).await? else {
+            if self.provider.role(id).await?.is_some() {
+                return Err(Error::RoleFromGroup(id));
+            }
+            return Ok(false);
+        };
+        self.ensure_not_last_owner(id).await?;
+        self.repos.delete_staff_member(id).await?;
+        log::info!("{} revoked role of {}", by, id);
+        let entry = AuditEntry::new(by, AuditAction::StaffRoleRevoked, Some(id))
+            .with_change(AuditChange::removed("role", before.as_str()));
+        self.audit.append(&entry).await?;
+        Ok(true)
+    }
+
+    /// Без владельцев никто не сможет назначать роли, поэтому последнего владельца
+    /// нельзя понизить или лишить роли.
+    async fn ensure_not_last_owner(&self, id: UserID) -> Result<(), Error> {
+        let staff = self.provider.staff().await?;
+        let owners: Vec<UserID> = staff
+            .iter()
+            .filter(|m| m.role() == StaffRole::Owner)
+            .map(|m| m.id())
+            .collect();
+        if owners == [id] {
+            return Err(Error::LastOwner(id));
+        }
+        Ok(())
+    }
+}
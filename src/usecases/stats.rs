@@ -0,0 +1,156 @@
+use chrono::{Datelike, NaiveDate, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::ReservationStatsProvider;
+use crate::domain::models::{Citizenship, Service, slot_today};
+use crate::domain::services::{SlotsFactory, WorkingHoursPolicy};
+use crate::usecases::{DayStatsDTO, HeatmapDTO, StatsDTO};
+
+/// Максимальная длина периода статистики в днях.
+pub const MAX_STATS_DAYS: i64 = 366;
+
+#[derive(Clone)]
+pub struct StatsUseCase {
+    factory: Arc<dyn SlotsFactory>,
+    policy: Arc<dyn WorkingHoursPolicy>,
+    provider: Arc<dyn ReservationStatsProvider>,
+}
+
+impl StatsUseCase {
+    pub fn new(
+        factory: Arc<dyn SlotsFactory>,
+        policy: Arc<dyn WorkingHoursPolicy>,
+        provider: Arc<dyn ReservationStatsProvider>,
+    ) -> Self {
+        Self {
+            factory,
+            policy,
+            provider,
+        }
+    }
+
+    /// Возвращает среднюю заполненность слотов по дням недели и времени начала за период
+    /// с `from` по `to` включительно. Сетка слотов строится по текущему графику работы,
+    /// записи в слоты вне графика не учитываются.
+    pub async fn heatmap(&self, from: NaiveDate, to: NaiveDate) -> Result<HeatmapDTO, Error> {
+        if from > to || (to - from).num_days() >= MAX_STATS_DAYS {
+            return Err(Error::InvalidValue(format!("invalid heatmap period: {} - {}", from, to)));
+        }
+
+        let counts = self.provider.slot_reservation_counts(from, to).await?;
+        // (занято, мест) по дню недели и времени начала
+        let mut cells = HashMap::new();
+        for date in from.iter_days().take_while(|date| *date <= to) {
+            for slot in self.factory.create_all(date, self.policy.as_ref()) {
+                let reserved = counts.get(&slot.start()).copied().unwrap_or(0);
+                let cell = cells
+                    .entry((date.weekday(), slot.start().time()))
+                    .or_insert((0, 0));
+                cell.0 += reserved;
+                cell.1 += slot.max_size();
+            }
+        }
+
+        let mut weekdays: Vec<_> = cells.keys().map(|(weekday, _)| *weekday).collect();
+        weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+        weekdays.dedup();
+        let mut times: Vec<_> = cells.keys().map(|(_, time)| *time).collect();
+        times.sort();
+        times.dedup();
+        let fill = weekdays
+            .iter()
+            .map(|weekday| {
+                times
+                    .iter()
+                    .map(|time| {
+                        cells
+                            .get(&(*weekday, *time))
+                            .filter(|(_, capacity)| *capacity > 0)
+                            .map(|(reserved, capacity)| *reserved as f64 / *capacity as f64)
+                    })
+                    .collect()
+            })
+            .collect();
+        Ok(HeatmapDTO {
+            from,
+            to,
+            weekdays,
+            times,
+            fill,
+        })
+    }
+
+    /// Возвращает статистику записей за период с `from` по `to` включительно. Заполненность
+    /// считается относительно числа мест в слотах по текущему графику работы.
+    pub async fn stats(&self, from: NaiveDate, to: NaiveDate) -> Result<StatsDTO, Error> {
+        if from > to || (to - from).num_days() >= MAX_STATS_DAYS {
+            return Err(Error::InvalidValue(format!("invalid stats period: {} - {}", from, to)));
+        }
+
+        let mut counts: HashMap<_, _> = self
+            .provider
+            .daily_reservation_stats(from, to)
+            .await?
+            .into_iter()
+            .map(|day| (day.date, day))
+            .collect();
+        let today = slot_today(Utc::now());
+        let days = from
+            .iter_days()
+            .take_while(|date| *date <= to)
+            .filter_map(|date| {
+                let capacity: usize = self
+                    .factory
+                    .create_all(date, self.policy.as_ref())
+                    .iter()
+                    .map(|slot| slot.max_size())
+                    .sum();
+                let day = counts.remove(&date);
+                if capacity == 0 && day.is_none() {
+                    return None;
+                }
+                Some(DayStatsDTO {
+                    date,
+                    capacity,
+                    reserved: day.as_ref().map_or(0, |d| d.reserved),
+                    attended: day.as_ref().map_or(0, |d| d.attended),
+                    cancelled: day.as_ref().map_or(0, |d| d.cancelled),
+                    no_shows: day.and_then(|d| d.no_shows(today)),
+                })
+            })
+            .collect();
+
+        let breakdown = self.provider.reservation_breakdown(from, to).await?;
+        let by_service = Service::all()
+            .iter()
+            .map(|service| {
+                let reserved = breakdown
+                    .iter()
+                    .filter(|b| b.service == *service)
+                    .map(|b| b.reserved)
+                    .sum();
+                (*service, reserved)
+            })
+            .filter(|(_, reserved)| *reserved > 0)
+            .collect();
+        let mut by_citizenship: Vec<(Citizenship, usize)> = Vec::new();
+        for b in breakdown {
+            match by_citizenship.iter_mut().find(|(c, _)| *c == b.citizenship) {
+                Some((_, reserved)) => *reserved += b.reserved,
+                None => by_citizenship.push((b.citizenship, b.reserved)),
+            }
+        }
+        by_citizenship.sort_by(|(a, x), (b, y)| y.cmp(x).then_with(|| a.as_str().cmp(b.as_str())));
+
+        Ok(StatsDTO {
+            from,
+            to,
+            days,
+            by_service,
+            by_citizenship,
+            average_lead_time: self.provider.average_lead_time(from, to).await?,
+        })
+    }
+}
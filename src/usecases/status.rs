@@ -0,0 +1,78 @@
+use chrono::Utc;
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{DocumentsProvider, UserProvider, UserReservationsProvider};
+use crate::domain::models::{DocumentKind, Service, UserID, reservation_code};
+use crate::domain::services::DeadlinePolicy;
+use crate::usecases::{DeadlineStatusDTO, NextReservationDTO, StatusDTO};
+
+#[derive(Clone)]
+pub struct StatusUseCase {
+    deadline_policy: Arc<dyn DeadlinePolicy>,
+    user_provider: Arc<dyn UserProvider>,
+    reservations_provider: Arc<dyn UserReservationsProvider>,
+    documents_provider: Arc<dyn DocumentsProvider>,
+}
+
+impl StatusUseCase {
+    pub fn new(
+        deadline_policy: Arc<dyn DeadlinePolicy>,
+        user_provider: Arc<dyn UserProvider>,
+        reservations_provider: Arc<dyn UserReservationsProvider>,
+        documents_provider: Arc<dyn DocumentsProvider>,
+    ) -> Self {
+        Self {
+            deadline_policy,
+            user_provider,
+            reservations_provider,
+            documents_provider,
+        }
+    }
+
+    /// Собирает сроки подачи документов по услугам со сроком, ближайшую запись пользователя и
+    /// список загруженных к ней документов.
+    pub async fn status(&self, user_id: UserID) -> Result<StatusDTO, Error> {
+        let user = self.user_provider.user(user_id).await?;
+        let today = Utc::now().date_naive();
+
+        let deadline = self
+            .deadline_policy
+            .deadline_date(*user.arrival_date(), user.citizenship());
+        let deadlines = Service::all()
+            .iter()
+            .filter(|service| service.has_deadline())
+            .map(|service| DeadlineStatusDTO {
+                service: *service,
+                deadline,
+                days_left: (deadline - today).num_days(),
+            })
+            .collect();
+
+        let reservations = self
+            .reservations_provider
+            .user_reservations(user_id, Utc::now())
+            .await?;
+        let next_reservation = match reservations.first() {
+            Some((slot_start, service)) => {
+                let uploaded = self.documents_provider.documents(user_id, *slot_start).await?;
+                let documents = DocumentKind::all()
+                    .iter()
+                    .map(|kind| (*kind, uploaded.iter().any(|d| d.kind() == *kind)))
+                    .collect();
+                Some(NextReservationDTO {
+                    slot_start: *slot_start,
+                    service: *service,
+                    code: reservation_code(user_id, *slot_start),
+                    documents,
+                })
+            }
+            None => None,
+        };
+
+        Ok(StatusDTO {
+            deadlines,
+            next_reservation,
+        })
+    }
+}
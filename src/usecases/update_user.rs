@@ -3,7 +3,10 @@ use std::sync::Arc;
 
 use crate::domain::Error;
 use crate::domain::interfaces::{UserProvider, UserRepository};
-use crate::domain::models::{Citizenship, OnlyCyrillic, OnlyLatin, UserID};
+use crate::domain::models::{
+    AuditAction, AuditEntry, Citizenship, OnlyCyrillic, OnlyLatin, Passport, Sex, User, UserID,
+    user_changes,
+};
 
 #[derive(Clone)]
 pub struct UpdateUserUseCase {
@@ -16,31 +19,62 @@ impl UpdateUserUseCase {
         Self { repos, provider }
     }
 
+    /// Изменяет данные пользователя и записывает изменения в журнал аудита.
+    async fn update(&self, id: i64, f: impl FnOnce(&mut User)) -> Result<(), Error> {
+        let id = UserID::new(id);
+        let before = self.provider.user(id).await?;
+        let mut user = before.clone();
+        f(&mut user);
+        let changes = user_changes(Some(&before), Some(&user));
+        let entry = AuditEntry::new(id, AuditAction::UserUpdated, Some(id)).with_changes(changes);
+        self.repos.save_user(user, &entry).await
+    }
+
     pub async fn update_name_lat(&self, id: i64, name: OnlyLatin) -> Result<(), Error> {
-        let mut user = self.provider.user(UserID::new(id)).await?;
-        user.set_full_name_lat(name);
-        self.repos.save_user(user).await?;
-        Ok(())
+        self.update(id, |user| user.set_full_name_lat(name)).await
     }
 
     pub async fn update_name_cyr(&self, id: i64, name: OnlyCyrillic) -> Result<(), Error> {
-        let mut user = self.provider.user(UserID::new(id)).await?;
-        user.set_full_name_cyr(name);
-        self.repos.save_user(user).await?;
-        Ok(())
+        self.update(id, |user| user.set_full_name_cyr(name)).await
     }
 
     pub async fn update_citizenship(&self, id: i64, citizenship: Citizenship) -> Result<(), Error> {
-        let mut user = self.provider.user(UserID::new(id)).await?;
-        user.set_citizenship(citizenship);
-        self.repos.save_user(user).await?;
-        Ok(())
+        self.update(id, |user| user.set_citizenship(citizenship)).await
     }
 
     pub async fn update_arrival_date(&self, id: i64, arrival_date: NaiveDate) -> Result<(), Error> {
-        let mut user = self.provider.user(UserID::new(id)).await?;
-        user.set_arrival_date(arrival_date);
-        self.repos.save_user(user).await?;
-        Ok(())
+        self.update(id, |user| user.set_arrival_date(arrival_date)).await
+    }
+
+    pub async fn update_visa_expiry(
+        &self,
+        id: i64,
+        visa_expiry: Option<NaiveDate>,
+    ) -> Result<(), Error> {
+        self.update(id, |user| user.set_visa_expiry(visa_expiry)).await
+    }
+
+    pub async fn update_registration_expiry(
+        &self,
+        id: i64,
+        registration_expiry: Option<NaiveDate>,
+    ) -> Result<(), Error> {
+        self.update(id, |user| user.set_registration_expiry(registration_expiry)).await
+    }
+
+    pub async fn update_birth_date(&self, id: i64, birth_date: NaiveDate) -> Result<(), Error> {
+        self.update(id, |user| user.set_birth_date(Some(birth_date))).await
+    }
+
+    pub async fn update_sex(&self, id: i64, sex: Sex) -> Result<(), Error> {
+        self.update(id, |user| user.set_sex(Some(sex))).await
+    }
+
+    pub async fn update_passport(&self, id: i64, passport: Passport) -> Result<(), Error> {
+        self.update(id, |user| user.set_passport(Some(passport))).await
+    }
+
+    pub async fn update_place_of_stay(&self, id: i64, place_of_stay: String) -> Result<(), Error> {
+        self.update(id, |user| user.set_place_of_stay(Some(place_of_stay))).await
     }
 }
@@ -0,0 +1,85 @@
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+use crate::domain::Error;
+use crate::domain::interfaces::{
+    AuditLog, DocumentRepository, DocumentStorage, DocumentsProvider, UserReservationsProvider,
+};
+use crate::domain::models::{AuditAction, AuditChange, AuditEntry, Document, DocumentKind, UserID};
+
+pub struct UploadDocumentRequest {
+    pub user_id: UserID,
+    pub kind: DocumentKind,
+    pub file_name: String,
+    pub mime_type: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct UploadDocumentUseCase {
+    provider: Arc<dyn UserReservationsProvider>,
+    storage: Arc<dyn DocumentStorage>,
+    documents: Arc<dyn DocumentsProvider>,
+    repos: Arc<dyn DocumentRepository>,
+    audit: Arc<dyn AuditLog>,
+}
+
+impl UploadDocumentUseCase {
+    pub fn new(
+        provider: Arc<dyn UserReservationsProvider>,
+        storage: Arc<dyn DocumentStorage>,
+        documents: Arc<dyn DocumentsProvider>,
+        repos: Arc<dyn DocumentRepository>,
+        audit: Arc<dyn AuditLog>,
+    ) -> Self {
+        Self {
+            provider,
+            storage,
+            documents,
+            repos,
+            audit,
+        }
+    }
+
+    /// Возвращает время начала ближайшей записи пользователя, к которой прикрепляются документы.
+    pub async fn next_reservation(&self, user_id: UserID) -> Result<Option<DateTime<Utc>>, Error> {
+        let reservations = self.provider.user_reservations(user_id, Utc::now()).await?;
+        Ok(reservations.first().map(|(slot_start, _)| *slot_start))
+    }
+
+    /// Прикрепляет документ к ближайшей записи пользователя и возвращает время начала этой записи.
+    pub async fn upload(&self, req: UploadDocumentRequest) -> Result<DateTime<Utc>, Error> {
+        let slot_start = match self.next_reservation(req.user_id).await? {
+            Some(slot_start) => slot_start,
+            None => return Err(Error::UserNotReserved(req.user_id)),
+        };
+
+        let document = Document::new(
+            req.user_id,
+            slot_start,
+            req.kind,
+            req.file_name,
+            req.mime_type,
+            req.data.len(),
+            Utc::now(),
+        )?;
+        let previous = self
+            .documents
+            .documents(req.user_id, slot_start)
+            .await?
+            .into_iter()
+            .find(|d| d.kind() == document.kind());
+        self.storage.put(&document.storage_key(), &req.data).await?;
+        self.repos.save_document(&document).await?;
+        // Ключ зависит от типа файла: если новый файл другого типа, старый больше не нужен.
+        if let Some(previous) = previous.filter(|p| p.storage_key() != document.storage_key()) {
+            self.storage.delete(&previous.storage_key()).await?;
+        }
+
+        let entry = AuditEntry::new(req.user_id, AuditAction::DocumentUploaded, Some(req.user_id))
+            .with_change(AuditChange::added("slot_start", slot_start.to_rfc3339()))
+            .with_change(AuditChange::added("document", document.kind().as_str()));
+        self.audit.append(&entry).await?;
+        Ok(slot_start)
+    }
+}
@@ -21,3 +21,29 @@ pub fn is_not_data_found(error: &PgError) -> bool {
         .map(|e| e.code() == &SqlState::NO_DATA_FOUND)
         .unwrap_or(false)
 }
+
+pub fn is_serialization_failure(error: &PgError) -> bool {
+    error
+        .as_db_error()
+        .map(|e| {
+            e.code() == &SqlState::T_R_SERIALIZATION_FAILURE
+                || e.code() == &SqlState::T_R_DEADLOCK_DETECTED
+        })
+        .unwrap_or(false)
+}
+
+/// Соединение с БД потеряно или сервер не принимает запросы: запрос можно повторить позже.
+pub fn is_connection_failure(error: &PgError) -> bool {
+    if error.is_closed() {
+        return true;
+    }
+    match error.as_db_error() {
+        Some(e) => {
+            e.code().code().starts_with("08")
+                || e.code() == &SqlState::ADMIN_SHUTDOWN
+                || e.code() == &SqlState::CRASH_SHUTDOWN
+                || e.code() == &SqlState::CANNOT_CONNECT_NOW
+        }
+        None => std::error::Error::source(error).is_some_and(|s| s.is::<std::io::Error>()),
+    }
+}